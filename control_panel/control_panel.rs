@@ -78,6 +78,17 @@ impl ControlPanel {
                 .service(endpoints::stats)
                 .service(endpoints::get_config)
                 .service(endpoints::set_config)
+                .service(endpoints::pause)
+                .service(endpoints::resume)
+                .service(endpoints::blockers)
+                .service(endpoints::kill_switch)
+                .service(endpoints::orders)
+                .service(endpoints::balances)
+                .service(endpoints::positions)
+                .service(endpoints::order_book_top)
+                .service(endpoints::cancel_order)
+                .service(endpoints::add_exchange_account)
+                .service(endpoints::remove_exchange_account)
                 .service(
                     actix_files::Files::new("/", webui_dir)
                         .use_last_modified(true)
@@ -41,3 +41,105 @@ pub(super) async fn set_config(body: web::Bytes, client: DataWebMmbRpcClient) ->
 pub(super) async fn stats(client: DataWebMmbRpcClient) -> impl Responder {
     send_request(client, |client| client.stats().boxed()).await
 }
+
+#[post("/exchanges/{exchange_account_id}/pause")]
+pub(super) async fn pause(
+    exchange_account_id: web::Path<String>,
+    client: DataWebMmbRpcClient,
+) -> impl Responder {
+    let exchange_account_id = exchange_account_id.into_inner();
+    send_request(client, move |client| {
+        client.pause(exchange_account_id.clone()).boxed()
+    })
+    .await
+}
+
+#[post("/exchanges/{exchange_account_id}/resume")]
+pub(super) async fn resume(
+    exchange_account_id: web::Path<String>,
+    client: DataWebMmbRpcClient,
+) -> impl Responder {
+    let exchange_account_id = exchange_account_id.into_inner();
+    send_request(client, move |client| {
+        client.resume(exchange_account_id.clone()).boxed()
+    })
+    .await
+}
+
+#[get("/blockers")]
+pub(super) async fn blockers(client: DataWebMmbRpcClient) -> impl Responder {
+    send_request(client, |client| client.blockers().boxed()).await
+}
+
+#[post("/kill_switch")]
+pub(super) async fn kill_switch(client: DataWebMmbRpcClient) -> impl Responder {
+    send_request(client, |client| client.kill_switch().boxed()).await
+}
+
+#[get("/orders")]
+pub(super) async fn orders(client: DataWebMmbRpcClient) -> impl Responder {
+    send_request(client, |client| client.orders().boxed()).await
+}
+
+#[get("/balances")]
+pub(super) async fn balances(client: DataWebMmbRpcClient) -> impl Responder {
+    send_request(client, |client| client.balances().boxed()).await
+}
+
+#[get("/positions")]
+pub(super) async fn positions(client: DataWebMmbRpcClient) -> impl Responder {
+    send_request(client, |client| client.positions().boxed()).await
+}
+
+#[get("/order_book_top")]
+pub(super) async fn order_book_top(client: DataWebMmbRpcClient) -> impl Responder {
+    send_request(client, |client| client.order_book_top().boxed()).await
+}
+
+#[post("/exchanges/{exchange_account_id}/orders/{client_order_id}/cancel")]
+pub(super) async fn cancel_order(
+    path: web::Path<(String, String)>,
+    client: DataWebMmbRpcClient,
+) -> impl Responder {
+    let (exchange_account_id, client_order_id) = path.into_inner();
+    send_request(client, move |client| {
+        client
+            .cancel_order(exchange_account_id.clone(), client_order_id.clone())
+            .boxed()
+    })
+    .await
+}
+
+#[post("/exchanges")]
+pub(super) async fn add_exchange_account(
+    body: web::Bytes,
+    client: DataWebMmbRpcClient,
+) -> impl Responder {
+    let settings = match String::from_utf8(body.to_vec()) {
+        Ok(settings) => settings,
+        Err(err) => {
+            return HttpResponse::BadRequest().body(format!(
+                "Failed to convert input exchange settings({body:?}) to utf8 string: {err}",
+            ))
+        }
+    };
+
+    send_request(client, move |client| {
+        client.add_exchange_account(settings.clone()).boxed()
+    })
+    .await
+}
+
+#[post("/exchanges/{exchange_account_id}/remove")]
+pub(super) async fn remove_exchange_account(
+    exchange_account_id: web::Path<String>,
+    client: DataWebMmbRpcClient,
+) -> impl Responder {
+    let exchange_account_id = exchange_account_id.into_inner();
+    send_request(client, move |client| {
+        client
+            .remove_exchange_account(exchange_account_id.clone())
+            .boxed()
+    })
+    .await
+}
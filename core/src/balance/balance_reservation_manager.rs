@@ -80,6 +80,22 @@ impl BalanceReservationManager {
         self.currency_pair_to_symbol_converter.exchanges_by_id()
     }
 
+    pub fn add_exchange(&mut self, exchange: Arc<Exchange>) {
+        let mut exchanges_by_id = self.exchanges_by_id().clone();
+        let _ = exchanges_by_id.insert(exchange.exchange_account_id, exchange);
+        self.currency_pair_to_symbol_converter =
+            CurrencyPairToSymbolConverter::new(exchanges_by_id);
+    }
+
+    pub fn remove_exchange(&mut self, exchange_account_id: ExchangeAccountId) {
+        let mut exchanges_by_id = self.exchanges_by_id().clone();
+        let _ = exchanges_by_id.remove(&exchange_account_id);
+        self.currency_pair_to_symbol_converter =
+            CurrencyPairToSymbolConverter::new(exchanges_by_id);
+        self.virtual_balance_holder
+            .remove_exchange_balances(exchange_account_id);
+    }
+
     pub fn update_reserved_balances(
         &mut self,
         reserved_balances_by_id: &HashMap<ReservationId, BalanceReservation>,
@@ -1741,4 +1757,19 @@ impl BalanceReservationManager {
                 .set_by_balance_request(&request, limit);
         }
     }
+
+    pub fn remove_target_amount_limit(
+        &mut self,
+        configuration_descriptor: ConfigurationDescriptor,
+        exchange_account_id: ExchangeAccountId,
+        currency_pair: CurrencyPair,
+    ) {
+        self.amount_limits_in_amount_currency
+            .remove_by_currency_pair(
+                configuration_descriptor.service_name,
+                configuration_descriptor.service_configuration_key,
+                exchange_account_id,
+                currency_pair,
+            );
+    }
 }
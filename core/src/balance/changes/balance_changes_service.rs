@@ -1,6 +1,9 @@
 use std::{sync::Arc, time::Duration};
 
 use crate::lifecycle::app_lifetime_manager::AppLifetimeManager;
+use crate::lifecycle::trading_engine::Service;
+use anyhow::{Context, Result};
+use futures::future::join_all;
 use mmb_domain::order::fill::OrderFill;
 use mmb_domain::order::snapshot::{ClientOrderFillId, OrderSnapshot};
 use mmb_utils::{
@@ -10,7 +13,8 @@ use mmb_utils::{
     DateTime,
 };
 use mockall_double::double;
-use tokio::sync::mpsc;
+use parking_lot::Mutex;
+use tokio::sync::{mpsc, oneshot};
 
 #[double]
 use crate::exchanges::general::currency_pair_to_symbol_converter::CurrencyPairToSymbolConverter;
@@ -64,40 +68,58 @@ pub struct BalanceChangesService {
     // TODO: fix me when DatabaseManager/DataRecorder will be implemented
     // private readonly IDatabaseManager _databaseManager;
     // private readonly IDataRecorder _dataRecorder;
-    rx_event: mpsc::Receiver<BalanceChangeServiceEvent>,
+    rx_event: Mutex<Option<mpsc::Receiver<BalanceChangeServiceEvent>>>,
     tx_event: mpsc::Sender<BalanceChangeServiceEvent>,
     balance_changes_accumulators: Vec<Arc<dyn BalanceChangeAccumulator + Send + Sync>>,
-    profit_loss_stopper_service: Arc<ProfitLossStopperService>,
+    profit_loss_stopper_services: Vec<Arc<ProfitLossStopperService>>,
     balance_changes_calculator: BalanceChangesCalculator,
     lifetime_manager: Arc<AppLifetimeManager>,
+    work_finished_receiver: Mutex<Option<oneshot::Receiver<Result<()>>>>,
+}
+
+impl Service for BalanceChangesService {
+    fn name(&self) -> &str {
+        "BalanceChangesService"
+    }
+
+    fn graceful_shutdown(self: Arc<Self>) -> Option<oneshot::Receiver<Result<()>>> {
+        let work_finished_receiver = self.work_finished_receiver.lock().take();
+        if work_finished_receiver.is_none() {
+            log::warn!("'work_finished_receiver' wasn't created when started graceful shutdown in BalanceChangesService");
+        }
+
+        work_finished_receiver
+    }
 }
 
 impl BalanceChangesService {
     pub fn new(
         currency_pair_to_symbol_converter: Arc<CurrencyPairToSymbolConverter>,
-        profit_loss_stopper_service: Arc<ProfitLossStopperService>,
+        profit_loss_stopper_services: Vec<Arc<ProfitLossStopperService>>,
         usd_converter: UsdConverter,
         lifetime_manager: Arc<AppLifetimeManager>,
         // IDatabaseManager databaseManager,
         // IDataRecorder dataRecorder,
     ) -> Arc<Self> {
         let (tx_event, rx_event) = mpsc::channel(20_000);
-        let balance_changes_accumulators =
-            vec![profit_loss_stopper_service.clone()
-                as Arc<dyn BalanceChangeAccumulator + Send + Sync>];
+        let balance_changes_accumulators = profit_loss_stopper_services
+            .iter()
+            .map(|x| x.clone() as Arc<dyn BalanceChangeAccumulator + Send + Sync>)
+            .collect();
 
         let this = Arc::new(Self {
             usd_converter,
             // _databaseManager = databaseManager;
             // _dataRecorder = dataRecorder;
-            rx_event,
+            rx_event: Mutex::new(Some(rx_event)),
             tx_event,
             balance_changes_accumulators,
-            profit_loss_stopper_service,
+            profit_loss_stopper_services,
             balance_changes_calculator: BalanceChangesCalculator::new(
                 currency_pair_to_symbol_converter,
             ),
             lifetime_manager: lifetime_manager.clone(),
+            work_finished_receiver: Default::default(),
         });
 
         let on_timer_tick = {
@@ -130,7 +152,15 @@ impl BalanceChangesService {
         this
     }
 
-    pub async fn run(&mut self, cancellation_token: CancellationToken) {
+    pub async fn run(self: Arc<Self>, cancellation_token: CancellationToken) -> Result<()> {
+        let mut rx_event = self
+            .rx_event
+            .lock()
+            .take()
+            .context("BalanceChangesService::run() is already started")?;
+        let (work_finished_sender, receiver) = oneshot::channel();
+        *self.work_finished_receiver.lock() = Some(receiver);
+
         // TODO: fix me when DatabaseManager/DataRecorder will be implemented
         //             if (_databaseManager != null)
         //             {
@@ -140,9 +170,12 @@ impl BalanceChangesService {
 
         loop {
             let new_event = tokio::select! {
-                event = self.rx_event.recv() => event,
-                _ = cancellation_token.when_cancelled() => return,
-            }.expect("BalanceChangesService::run() the event channel is closed but cancellation hasn't been requested");
+                event = rx_event.recv() => event,
+                _ = cancellation_token.when_cancelled() => {
+                    let _ = work_finished_sender.send(Ok(()));
+                    return Ok(());
+                }
+            }.context("BalanceChangesService::run() the event channel is closed but cancellation hasn't been requested")?;
 
             match new_event {
                 BalanceChangeServiceEvent::BalanceChange(event) => {
@@ -150,9 +183,7 @@ impl BalanceChangesService {
                         .await;
                 }
                 BalanceChangeServiceEvent::OnTimer => {
-                    self.profit_loss_stopper_service
-                        .check_for_limit(&self.usd_converter, cancellation_token.clone())
-                        .await;
+                    self.check_for_limit(cancellation_token.clone()).await;
                 }
             }
         }
@@ -191,9 +222,16 @@ impl BalanceChangesService {
                 accumulator.add_balance_change(&profit_loss_balance_change);
             }
         }
-        self.profit_loss_stopper_service
-            .check_for_limit(&self.usd_converter, cancellation_token)
-            .await;
+        self.check_for_limit(cancellation_token).await;
+    }
+
+    async fn check_for_limit(&self, cancellation_token: CancellationToken) {
+        join_all(
+            self.profit_loss_stopper_services
+                .iter()
+                .map(|x| x.check_for_limit(&self.usd_converter, cancellation_token.clone())),
+        )
+        .await;
     }
 
     pub fn add_balance_change(
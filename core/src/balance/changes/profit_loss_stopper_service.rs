@@ -37,14 +37,16 @@ pub struct ProfitLossStopperService {
 
 impl ProfitLossStopperService {
     pub fn new(
-        target_market_account_id: MarketAccountId,
         stopper_settings: &ProfitLossStopperSettings,
         exchange_blocker: Arc<ExchangeBlocker>,
         balance_manager: Option<Arc<Mutex<BalanceManager>>>,
         engine_api: Arc<EngineApi>,
     ) -> Self {
         let mut this = Self {
-            target_market_account_id,
+            target_market_account_id: MarketAccountId::new(
+                stopper_settings.exchange_account_id,
+                stopper_settings.currency_pair,
+            ),
             exchange_blocker,
             engine_api,
             profit_loss_stoppers: Vec::new(),
@@ -126,6 +128,11 @@ impl BalanceChangeAccumulator for ProfitLossStopperService {
     }
 
     fn add_balance_change(&self, balance_change: &ProfitLossBalanceChange) {
+        // limits are checked separately for every market
+        if balance_change.market_account_id != self.target_market_account_id {
+            return;
+        }
+
         for usd_periodic_calculator in self.usd_periodic_calculators.iter() {
             usd_periodic_calculator.add_balance_change(balance_change);
         }
@@ -136,8 +143,16 @@ impl BalanceChangeAccumulator for ProfitLossStopperService {
 mod test {
     use mmb_domain::market::CurrencyPair;
     use mmb_domain::market::{ExchangeAccountId, MarketAccountId};
+    use mmb_domain::order::snapshot::ClientOrderFillId;
     use rust_decimal_macros::dec;
 
+    #[double]
+    use crate::misc::time::time_manager;
+
+    use crate::balance::changes::profit_loss_stopper::test::{
+        create_balance_change, create_balance_change_by_market_account_id,
+    };
+    use crate::misc::time;
     use crate::settings::StopperCondition;
 
     use super::*;
@@ -153,22 +168,65 @@ mod test {
         )
     }
 
-    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
-    pub async fn settings_loading_test_empty_settings_should_not_throw() {
-        let stopper_settings = ProfitLossStopperSettings {
+    fn stopper_settings() -> ProfitLossStopperSettings {
+        ProfitLossStopperSettings {
+            exchange_account_id: exchange_account_id(),
+            currency_pair: market_account_id().currency_pair,
             conditions: vec![StopperCondition {
                 period_kind: TimePeriodKind::Day,
                 period_value: 1,
                 limit: dec!(50),
             }],
-        };
+        }
+    }
 
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    pub async fn settings_loading_test_empty_settings_should_not_throw() {
         ProfitLossStopperService::new(
-            market_account_id(),
-            &stopper_settings,
+            &stopper_settings(),
             Arc::new(ExchangeBlocker::default()),
             None,
             Arc::new(EngineApi::default()),
         );
     }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    pub async fn balance_changes_of_other_markets_are_ignored() {
+        let (_time_manager_mock, _time_manager_locker) =
+            time::tests::init_mock(Arc::new(Mutex::new(0)));
+        let (mut usd_converter, _usd_converter_locker) = UsdConverter::init_mock();
+        usd_converter
+            .expect_convert_amount()
+            .returning(|_, amount, _| Some(amount));
+
+        let service = ProfitLossStopperService::new(
+            &stopper_settings(),
+            Arc::new(ExchangeBlocker::default()),
+            None,
+            Arc::new(EngineApi::default()),
+        );
+
+        let other_market_account_id = MarketAccountId::new(
+            ExchangeAccountId::new("other_exchange_test_id", 0),
+            market_account_id().currency_pair,
+        );
+        service.add_balance_change(&create_balance_change(
+            dec!(-1),
+            time_manager::now(),
+            ClientOrderFillId::new("first_fill".into()),
+        ));
+        service.add_balance_change(&create_balance_change_by_market_account_id(
+            dec!(-100),
+            time_manager::now(),
+            ClientOrderFillId::new("second_fill".into()),
+            other_market_account_id,
+        ));
+
+        let usd_change = service.get_periodic_calculators()[0]
+            .calculate_over_market_usd_change(&usd_converter, CancellationToken::default())
+            .await;
+
+        // balance change in test data is doubled usd change
+        assert_eq!(usd_change, dec!(-2));
+    }
 }
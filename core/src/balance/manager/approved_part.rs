@@ -2,9 +2,9 @@ use mmb_utils::DateTime;
 
 use mmb_domain::order::snapshot::Amount;
 use mmb_domain::order::snapshot::ClientOrderId;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Debug, Serialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ApprovedPart {
     _approve_time: DateTime,
     _client_order_id: ClientOrderId,
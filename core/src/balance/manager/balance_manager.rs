@@ -7,6 +7,7 @@ use crate::balance::manager::balance_reservation::BalanceReservation;
 use crate::balance::manager::balances::Balances;
 use crate::balance::manager::position_change::PositionChange;
 use crate::exchanges::general::currency_pair_to_symbol_converter::CurrencyPairToSymbolConverter;
+use crate::exchanges::general::exchange::Exchange;
 use crate::explanation::Explanation;
 use crate::misc::reserve_parameters::ReserveParameters;
 use crate::misc::service_value_tree::ServiceValueTree;
@@ -50,6 +51,8 @@ pub struct BalanceManager {
     balance_changes_service: Option<Arc<BalanceChangesService>>,
     position_differs_times_in_row_by_exchange_id:
         HashMap<ExchangeAccountId, HashMap<CurrencyPair, u32>>,
+    /// Derivative positions received from exchange with the last balances update
+    last_positions_by_exchange_id: HashMap<ExchangeAccountId, Vec<DerivativePosition>>,
     event_recorder: Option<Arc<EventRecorder>>,
 }
 
@@ -66,6 +69,7 @@ impl BalanceManager {
             last_order_fills: HashMap::new(),
             balance_changes_service: None,
             position_differs_times_in_row_by_exchange_id: Default::default(),
+            last_positions_by_exchange_id: Default::default(),
             event_recorder,
         }))
     }
@@ -345,6 +349,11 @@ impl BalanceManager {
         }
 
         self.restore_fill_amount_position(exchange_account_id, &balances_and_positions.positions)?;
+        if let Some(positions) = &balances_and_positions.positions {
+            let _ = self
+                .last_positions_by_exchange_id
+                .insert(exchange_account_id, positions.clone());
+        }
 
         let reservations_by_exchange_account_id = self
             .balance_reservation_manager
@@ -471,13 +480,12 @@ impl BalanceManager {
                 continue;
             }
 
-            if order_type == OrderType::Market {
-                bail!("Clone doesn't support market orders because we need to know the price")
-            }
-
             if applied_orders.insert(client_order_id.clone()) {
                 let reservation_id = match reservation_id {
                     Some(reservation_id) => reservation_id,
+                    None if order_type == OrderType::Market => bail!(
+                        "Clone doesn't support market orders without reservation because we need to know the price"
+                    ),
                     None => continue,
                 };
 
@@ -977,10 +985,57 @@ impl BalanceManager {
         );
     }
 
+    /// Forget amount limit of strategy instance stopped at runtime
+    pub fn remove_target_amount_limit(
+        &mut self,
+        configuration_descriptor: ConfigurationDescriptor,
+        exchange_account_id: ExchangeAccountId,
+        currency_pair: CurrencyPair,
+    ) {
+        self.balance_reservation_manager.remove_target_amount_limit(
+            configuration_descriptor,
+            exchange_account_id,
+            currency_pair,
+        );
+    }
+
+    /// Register exchange account connected at runtime. Balances are received on the next balances update
+    pub fn add_exchange(&mut self, exchange: Arc<Exchange>) {
+        self.balance_reservation_manager.add_exchange(exchange);
+    }
+
+    /// Forget exchange account retired at runtime. Its orders should be finished before
+    pub fn remove_exchange(&mut self, exchange_account_id: ExchangeAccountId) {
+        self.balance_reservation_manager
+            .remove_exchange(exchange_account_id);
+        let _ = self
+            .position_differs_times_in_row_by_exchange_id
+            .remove(&exchange_account_id);
+        let _ = self
+            .last_positions_by_exchange_id
+            .remove(&exchange_account_id);
+    }
+
+    /// Derivative positions received from exchange with the last balances update.
+    /// `None` if positions of the exchange account aren't received yet
+    pub fn get_last_positions(
+        &self,
+        exchange_account_id: ExchangeAccountId,
+    ) -> Option<Vec<DerivativePosition>> {
+        self.last_positions_by_exchange_id
+            .get(&exchange_account_id)
+            .cloned()
+    }
+
     pub fn set_balance_changes_service(&mut self, service: Arc<BalanceChangesService>) {
         self.balance_changes_service = Some(service);
     }
 
+    #[cfg(test)]
+    pub(crate) fn has_balance_changes_service(&self) -> bool {
+        self.balance_changes_service.is_some()
+    }
+
     pub async fn update_balances_for_exchanges(
         this: Arc<Mutex<Self>>,
         cancellation_token: CancellationToken,
@@ -1040,6 +1095,14 @@ impl BalanceManager {
 
 #[cfg_attr(test, automock)]
 impl BalanceManager {
+    /// Balance manager of engine for services which use mock of it in unit tests
+    #[allow(unused_qualifications)]
+    pub(crate) fn shared(
+        balance_manager: &Arc<parking_lot::Mutex<BalanceManager>>,
+    ) -> Arc<parking_lot::Mutex<Self>> {
+        balance_manager.clone()
+    }
+
     pub fn get_last_position_change_before_period(
         &self,
         market_account_id: &MarketAccountId,
@@ -3,14 +3,14 @@ use std::collections::HashMap;
 use crate::balance::manager::position_change::PositionChange;
 use mmb_domain::market::{ExchangeAccountId, MarketAccountId};
 use mmb_domain::order::snapshot::ClientOrderFillId;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 use mmb_domain::market::CurrencyPair;
 use mmb_utils::DateTime;
 use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
 
-#[derive(Clone, Debug, Default, Serialize)]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct BalancePositionByFillAmount {
     /// MarketAccountId -> AmountInAmountCurrency
     position_by_fill_amount: HashMap<MarketAccountId, Decimal>,
@@ -30,6 +30,11 @@ impl BalancePositionByFillAmount {
             .cloned()
     }
 
+    /// Positions of all markets
+    pub fn positions(&self) -> &HashMap<MarketAccountId, Decimal> {
+        &self.position_by_fill_amount
+    }
+
     pub(crate) fn set(
         &mut self,
         exchange_account_id: ExchangeAccountId,
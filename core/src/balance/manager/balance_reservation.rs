@@ -10,13 +10,13 @@ use mmb_domain::order::snapshot::Amount;
 use mmb_domain::order::snapshot::ClientOrderId;
 use mmb_domain::order::snapshot::OrderSide;
 use mmb_domain::order::snapshot::Price;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 use anyhow::{bail, Result};
 use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
 
-#[derive(Clone, Debug, Serialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct BalanceReservation {
     pub configuration_descriptor: ConfigurationDescriptor,
     pub exchange_account_id: ExchangeAccountId,
@@ -10,13 +10,13 @@ use mmb_domain::market::ExchangeAccountId;
 use mmb_domain::market::MarketAccountId;
 use mmb_domain::order::fill::OrderFill;
 use mmb_domain::order::snapshot::ReservationId;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 use mmb_database::impl_event;
 use mmb_utils::DateTime;
 use rust_decimal::Decimal;
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Balances {
     pub version: usize,
     pub init_time: DateTime,
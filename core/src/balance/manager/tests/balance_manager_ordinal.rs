@@ -154,6 +154,7 @@ mod tests {
     use rust_decimal_macros::dec;
 
     use crate::balance::manager::balance_manager::BalanceManager;
+    use crate::balance::manager::balances::Balances;
     use crate::balance::manager::position_change::PositionChange;
     use crate::balance::manager::tests::balance_manager_base::BalanceManagerBase;
     use crate::exchanges::general::currency_pair_to_symbol_converter::CurrencyPairToSymbolConverter;
@@ -4468,6 +4469,46 @@ mod tests {
         );
     }
 
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    pub async fn restore_state_from_json() {
+        init_logger_file_named("log.txt");
+        let mut test_object = create_test_obj_by_currency_code(BalanceManagerBase::btc(), dec!(1));
+
+        let price = dec!(1);
+        let reserve_parameters = test_object.balance_manager_base.create_reserve_parameters(
+            OrderSide::Buy,
+            price,
+            dec!(0.1),
+        );
+        let reservation_id = test_object
+            .balance_manager()
+            .try_reserve(&reserve_parameters, &mut None)
+            .expect("in test");
+
+        let balances_json =
+            serde_json::to_value(test_object.balance_manager().get_balances()).expect("in test");
+        let restored_balances: Balances = serde_json::from_value(balances_json).expect("in test");
+
+        let (_, exchanges_by_id) = BalanceManagerOrdinal::create_balance_manager_ctor_parameters();
+        test_object
+            .balance_manager_base
+            .set_balance_manager(BalanceManager::new(
+                CurrencyPairToSymbolConverter::new(exchanges_by_id),
+                None,
+            ));
+        test_object
+            .balance_manager()
+            .restore_balance_state(&restored_balances, false);
+
+        let balance_manager = test_object.balance_manager();
+        assert_eq!(
+            balance_manager
+                .get_reservation_expected(reservation_id)
+                .unreserved_amount,
+            dec!(0.1)
+        );
+    }
+
     #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
     pub async fn update_exchange_balance_should_ignore_approved_reservations_for_canceled_orders() {
         init_logger_file_named("log.txt");
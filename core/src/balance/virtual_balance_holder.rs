@@ -65,6 +65,10 @@ impl VirtualBalanceHolder {
         }
     }
 
+    pub fn remove_exchange_balances(&mut self, exchange_account_id: ExchangeAccountId) {
+        let _ = self.balance_by_exchange_id.remove(&exchange_account_id);
+    }
+
     pub fn add_balance(&mut self, balance_request: &BalanceRequest, balance_to_add: Amount) {
         let current_diff_value = self
             .balance_diff
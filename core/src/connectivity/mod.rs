@@ -1,7 +1,14 @@
+use crate::exchanges::traffic::recorder::TrafficRecorder;
+use crate::settings::NetworkSettings;
+use serde::{Deserialize, Serialize};
 use std::fmt::{Display, Formatter};
+use std::sync::Arc;
+use std::time::Duration;
 use thiserror::Error;
 use url::Url;
 
+mod network;
+mod reconnect_backoff;
 mod websocket;
 mod websocket_connection;
 
@@ -21,7 +28,13 @@ pub enum ConnectivityError {
 
 pub type Result<T> = std::result::Result<T, ConnectivityError>;
 
-#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+/// Time interval between heartbeat pings are sent
+const DEFAULT_HEARTBEAT_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Time interval before lack of client response causes a timeout
+const DEFAULT_HEARTBEAT_FAIL_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
 pub enum WebSocketRole {
     Main,
     Secondary,
@@ -36,15 +49,46 @@ impl Display for WebSocketRole {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct WebSocketParams {
     url: Url,
+    network_settings: Option<NetworkSettings>,
+    traffic_recorder: Option<Arc<TrafficRecorder>>,
+    heartbeat_interval: Duration,
+    heartbeat_fail_timeout: Duration,
 }
 
 impl WebSocketParams {
     pub fn new(url: Url) -> Self {
-        WebSocketParams { url }
+        WebSocketParams {
+            url,
+            network_settings: None,
+            traffic_recorder: None,
+            heartbeat_interval: DEFAULT_HEARTBEAT_INTERVAL,
+            heartbeat_fail_timeout: DEFAULT_HEARTBEAT_FAIL_TIMEOUT,
+        }
+    }
+
+    /// Ping is sent after `interval` without received messages
+    /// and connection is closed after `fail_timeout` without them
+    pub fn with_heartbeat(mut self, interval: Duration, fail_timeout: Duration) -> Self {
+        self.heartbeat_interval = interval;
+        self.heartbeat_fail_timeout = fail_timeout;
+        self
+    }
+
+    pub fn with_network_settings(mut self, network_settings: Option<NetworkSettings>) -> Self {
+        self.network_settings = network_settings;
+        self
+    }
+
+    /// Received text messages are captured by recorder if it is set
+    pub fn with_traffic_recorder(mut self, traffic_recorder: Option<Arc<TrafficRecorder>>) -> Self {
+        self.traffic_recorder = traffic_recorder;
+        self
     }
 }
 
+pub use network::NetworkConnector;
+pub use reconnect_backoff::ReconnectBackoff;
 pub use websocket::{websocket_open, WsSender};
@@ -0,0 +1,84 @@
+use std::time::Duration;
+
+use rand::Rng;
+
+use crate::settings::WebSocketSettings;
+
+/// Exponential backoff with random jitter for websocket reconnection attempts
+pub struct ReconnectBackoff {
+    initial_delay: Duration,
+    max_delay: Duration,
+    jitter: Duration,
+    attempt: u32,
+}
+
+impl ReconnectBackoff {
+    pub fn new(settings: &WebSocketSettings) -> Self {
+        Self {
+            initial_delay: Duration::from_millis(settings.reconnect_initial_delay_ms),
+            max_delay: Duration::from_millis(settings.reconnect_max_delay_ms),
+            jitter: Duration::from_millis(settings.reconnect_jitter_ms),
+            attempt: 0,
+        }
+    }
+
+    /// Delay before the next reconnection attempt
+    pub fn next_delay(&mut self) -> Duration {
+        let factor = 2u32.saturating_pow(self.attempt);
+        self.attempt = self.attempt.saturating_add(1);
+
+        let delay = self
+            .initial_delay
+            .checked_mul(factor)
+            .unwrap_or(self.max_delay)
+            .min(self.max_delay);
+
+        let jitter_ms = self.jitter.as_millis() as u64;
+        match jitter_ms {
+            0 => delay,
+            _ => delay + Duration::from_millis(rand::thread_rng().gen_range(0..=jitter_ms)),
+        }
+    }
+
+    /// Called after successful connection
+    pub fn reset(&mut self) {
+        self.attempt = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings(jitter_ms: u64) -> WebSocketSettings {
+        WebSocketSettings {
+            reconnect_initial_delay_ms: 100,
+            reconnect_max_delay_ms: 1000,
+            reconnect_jitter_ms: jitter_ms,
+            ..WebSocketSettings::default()
+        }
+    }
+
+    #[test]
+    fn delay_grows_exponentially_up_to_max() {
+        let mut backoff = ReconnectBackoff::new(&settings(0));
+
+        let delays = (0..6)
+            .map(|_| backoff.next_delay().as_millis())
+            .collect::<Vec<_>>();
+        assert_eq!(delays, vec![100, 200, 400, 800, 1000, 1000]);
+
+        backoff.reset();
+        assert_eq!(backoff.next_delay(), Duration::from_millis(100));
+    }
+
+    #[test]
+    fn jitter_is_added_to_delay() {
+        let mut backoff = ReconnectBackoff::new(&settings(50));
+
+        for expected_delay in [100, 200, 400] {
+            let delay = backoff.next_delay().as_millis();
+            assert!((expected_delay..=expected_delay + 50).contains(&delay));
+        }
+    }
+}
@@ -1,23 +1,24 @@
+use super::network::connect_tcp;
 use super::{ConnectivityError, Result, WebSocketParams, WebSocketRole};
+use crate::exchanges::traffic::recorder::TrafficRecorder;
+use crate::exchanges::traffic::TrafficData;
 use crate::infrastructure::spawn_future_ok;
+use crate::metrics::engine_metrics::METRICS;
 use futures::stream::{SplitSink, SplitStream};
 use futures::{SinkExt, StreamExt};
 use mmb_domain::market::ExchangeAccountId;
 use mmb_utils::infrastructure::SpawnFutureFlags;
 use std::fmt::Formatter;
+use std::sync::Arc;
 use tokio::net::TcpStream;
 use tokio::sync::mpsc;
 use tokio::time::{timeout, timeout_at, Duration, Instant};
-use tokio_tungstenite::tungstenite::Message;
-use tokio_tungstenite::{connect_async, MaybeTlsStream, WebSocketStream};
+use tokio_tungstenite::tungstenite::error::UrlError;
+use tokio_tungstenite::tungstenite::handshake::client::Response;
+use tokio_tungstenite::tungstenite::{Error as WsError, Message};
+use tokio_tungstenite::{client_async_tls, connect_async, MaybeTlsStream, WebSocketStream};
 use tokio_util::sync::CancellationToken;
 
-/// Time interval between heartbeat pings are sent
-const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(5);
-
-/// Time interval before lack of client response causes a timeout
-const HEARTBEAT_FAIL_TIMEOUT: Duration = Duration::from_secs(10);
-
 /// Write deadline
 ///
 /// This timeout is for networking buffer overflow detection.  
@@ -160,6 +161,12 @@ struct ReaderHandle {
     meta: Meta,
     /// Channel to user
     reader_tx: mpsc::UnboundedSender<String>,
+    /// Capture of received messages
+    traffic_recorder: Option<Arc<TrafficRecorder>>,
+    /// Time interval between heartbeat pings are sent
+    heartbeat_interval: Duration,
+    /// Time interval before lack of client response causes a timeout
+    heartbeat_fail_timeout: Duration,
     /// Channel to `WriterHandle`
     internal_tx: mpsc::Sender<Message>,
     /// Cancellation token.
@@ -177,7 +184,12 @@ impl ReaderHandle {
         // receive date time point
         let mut receive_ts = Instant::now();
         // next heartbeat time point
-        let mut next_heartbeat_ts = receive_ts + HEARTBEAT_INTERVAL;
+        let mut next_heartbeat_ts = receive_ts + self.heartbeat_interval;
+        let messages_counter = METRICS.is_enabled().then(|| {
+            METRICS
+                .websocket_messages
+                .with_label_values(&[&self.meta.0.to_string(), &self.meta.1.to_string()])
+        });
 
         loop {
             let result = tokio::select! {
@@ -190,7 +202,7 @@ impl ReaderHandle {
             };
 
             // wrap receive in heartbeat timer
-            // send heartbeats only if no data was received for HEARTBEAT_INTERVAL
+            // send heartbeats only if no data was received for heartbeat interval
             let msg = match result {
                 Ok(Some(Err(e))) => {
                     log::error!("Websocket {} reader recv failure: {:?}", self.meta, e);
@@ -210,12 +222,12 @@ impl ReaderHandle {
 
                 Err(_) => {
                     // heartbeat timeout
-                    if receive_ts.elapsed() >= HEARTBEAT_FAIL_TIMEOUT {
+                    if receive_ts.elapsed() >= self.heartbeat_fail_timeout {
                         log::error!("Websocket {} reader reached heartbeat deadline", self.meta);
                         return;
                     }
-                    // will send heartbeat again after HEARTBEAT_INTERVAL
-                    next_heartbeat_ts += HEARTBEAT_INTERVAL;
+                    // will send heartbeat again after heartbeat interval
+                    next_heartbeat_ts += self.heartbeat_interval;
 
                     if (self.send_ping()).is_err() {
                         log::error!("Websocket {} reader failed to send ping", self.meta);
@@ -227,10 +239,21 @@ impl ReaderHandle {
 
             // received message processing
             receive_ts = Instant::now();
-            next_heartbeat_ts = receive_ts + HEARTBEAT_INTERVAL;
+            next_heartbeat_ts = receive_ts + self.heartbeat_interval;
 
             match msg {
                 Message::Text(text) => {
+                    if let Some(messages_counter) = &messages_counter {
+                        messages_counter.inc();
+                    }
+
+                    if let Some(traffic_recorder) = &self.traffic_recorder {
+                        traffic_recorder.record(TrafficData::WebSocketMessage {
+                            role: self.meta.1,
+                            message: text.clone(),
+                        });
+                    }
+
                     if self.forward_message(text).is_err() {
                         log::trace!(
                             "Websocket {} reader failed to forward message, exiting",
@@ -292,6 +315,27 @@ impl ReaderHandle {
     }
 }
 
+async fn connect(
+    params: &WebSocketParams,
+) -> std::result::Result<(WebSocketStream<MaybeTlsStream<TcpStream>>, Response), WsError> {
+    let network_settings = match &params.network_settings {
+        Some(network_settings) => network_settings,
+        None => return connect_async(params.url.clone()).await,
+    };
+
+    let host = params
+        .url
+        .host_str()
+        .ok_or(WsError::Url(UrlError::NoHostName))?;
+    let port = params
+        .url
+        .port_or_known_default()
+        .ok_or(WsError::Url(UrlError::UnsupportedUrlScheme))?;
+    let stream = connect_tcp(host, port, network_settings).await?;
+
+    client_async_tls(params.url.clone(), stream).await
+}
+
 /// Open WebSocket connection.
 ///
 /// Provided cancellation token can be used to shutdown service futures instantly.
@@ -307,7 +351,7 @@ pub async fn open_connection(
     mpsc::UnboundedSender<Message>,
     mpsc::UnboundedReceiver<String>,
 )> {
-    let (ws_stream, _) = connect_async(params.url.clone())
+    let (ws_stream, _) = connect(&params)
         .await
         .map_err(|e| ConnectivityError::FailedToConnect(role, params.url.to_string(), e))?;
 
@@ -331,6 +375,9 @@ pub async fn open_connection(
         meta,
         internal_tx,
         reader_tx,
+        traffic_recorder: params.traffic_recorder,
+        heartbeat_interval: params.heartbeat_interval,
+        heartbeat_fail_timeout: params.heartbeat_fail_timeout,
         cancel,
     };
 
@@ -9,13 +9,15 @@ use mmb_utils::{nothing_to_do, DateTime};
 use parking_lot::Mutex;
 use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
-use tokio::sync::{broadcast, oneshot};
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::{broadcast, mpsc, oneshot};
 
 use crate::disposition_execution::trading_context_calculation::calculate_trading_context;
 use crate::exchanges::general::exchange::Exchange;
-use crate::exchanges::general::request_type::RequestType;
+use crate::exchanges::general::request_type::{RequestPriority, RequestType};
 use crate::explanation::{Explanation, WithExplanation};
 use crate::lifecycle::trading_engine::{EngineContext, Service};
+use crate::metrics::engine_metrics::EventsReceiverMetrics;
 use crate::misc::reserve_parameters::ReserveParameters;
 use crate::order_book::local_snapshot_service::LocalSnapshotsService;
 use crate::strategies::disposition_strategy::DispositionStrategy;
@@ -37,8 +39,8 @@ use mmb_domain::order::event::OrderEventType;
 use mmb_domain::order::pool::OrderRef;
 use mmb_domain::order::snapshot::{Amount, Price};
 use mmb_domain::order::snapshot::{
-    ClientOrderId, OrderCreating, OrderExecutionType, OrderHeader, OrderSide, OrderSnapshot,
-    OrderStatus, OrderType,
+    ClientOrderId, OrderCreating, OrderExecutionType, OrderHeader, OrderRole, OrderSide,
+    OrderSnapshot, OrderStatus, OrderTimeInForce, OrderType,
 };
 use mmb_utils::cancellation_token::CancellationToken;
 
@@ -59,7 +61,10 @@ impl Display for DisplaySmallOrder {
 }
 
 pub struct DispositionExecutorService {
+    name: String,
     work_finished_receiver: Mutex<Option<oneshot::Receiver<Result<()>>>>,
+    strategy_sender: mpsc::UnboundedSender<Box<dyn DispositionStrategy>>,
+    cancellation_token: CancellationToken,
 }
 
 impl DispositionExecutorService {
@@ -75,21 +80,28 @@ impl DispositionExecutorService {
         statistics: Arc<StatisticService>,
     ) -> Arc<Self> {
         let (work_finished_sender, receiver) = oneshot::channel();
+        let (strategy_sender, strategy_receiver) = mpsc::unbounded_channel();
+
+        let name = format!("{DISPOSITION_EXECUTOR} {exchange_account_id} {currency_pair}");
+
+        let action = {
+            let cancellation_token = cancellation_token.clone();
+            async move {
+                let mut disposition_executor = DispositionExecutor::new(
+                    engine_ctx,
+                    events_receiver,
+                    local_snapshots_service,
+                    exchange_account_id,
+                    currency_pair,
+                    strategy,
+                    strategy_receiver,
+                    work_finished_sender,
+                    cancellation_token,
+                    statistics,
+                );
 
-        let action = async move {
-            let mut disposition_executor = DispositionExecutor::new(
-                engine_ctx,
-                events_receiver,
-                local_snapshots_service,
-                exchange_account_id,
-                currency_pair,
-                strategy,
-                work_finished_sender,
-                cancellation_token,
-                statistics,
-            );
-
-            disposition_executor.start().await
+                disposition_executor.start().await
+            }
         };
         spawn_future(
             "Start disposition executor",
@@ -98,14 +110,35 @@ impl DispositionExecutorService {
         );
 
         Arc::new(DispositionExecutorService {
+            name,
             work_finished_receiver: Mutex::new(Some(receiver)),
+            strategy_sender,
+            cancellation_token,
         })
     }
+
+    /// Replace strategy of running executor without canceling its orders.
+    /// New strategy is used since the next recalculation of trading context
+    pub fn replace_strategy(&self, strategy: Box<dyn DispositionStrategy>) -> Result<()> {
+        self.strategy_sender
+            .send(strategy)
+            .map_err(|_| anyhow!("{} is already stopped", self.name))
+    }
+
+    /// Stop executor without stopping trading engine. Orders of the executor aren't canceled
+    pub fn stop(&self) {
+        self.cancellation_token.cancel();
+    }
+
+    /// Executor is stopped if it was stopped explicitly or finished with error
+    pub fn is_stopped(&self) -> bool {
+        self.strategy_sender.is_closed()
+    }
 }
 
 impl Service for DispositionExecutorService {
     fn name(&self) -> &str {
-        DISPOSITION_EXECUTOR
+        &self.name
     }
 
     fn graceful_shutdown(self: Arc<Self>) -> Option<oneshot::Receiver<Result<()>>> {
@@ -126,6 +159,7 @@ struct DispositionExecutor {
     local_snapshots_service: LocalSnapshotsService,
     orders_state: OrdersState,
     strategy: Box<dyn DispositionStrategy>,
+    strategy_receiver: mpsc::UnboundedReceiver<Box<dyn DispositionStrategy>>,
     work_finished_sender: Option<oneshot::Sender<Result<()>>>,
     cancellation_token: CancellationToken,
     statistics: Arc<StatisticService>,
@@ -140,6 +174,7 @@ impl DispositionExecutor {
         exchange_account_id: ExchangeAccountId,
         currency_pair: CurrencyPair,
         strategy: Box<dyn DispositionStrategy>,
+        strategy_receiver: mpsc::UnboundedReceiver<Box<dyn DispositionStrategy>>,
         work_finished_sender: oneshot::Sender<Result<()>>,
         cancellation_token: CancellationToken,
         statistics: Arc<StatisticService>,
@@ -159,6 +194,7 @@ impl DispositionExecutor {
             symbol,
             orders_state: OrdersState::new(),
             strategy,
+            strategy_receiver,
             work_finished_sender: Some(work_finished_sender),
             cancellation_token,
             statistics,
@@ -167,15 +203,36 @@ impl DispositionExecutor {
 
     pub async fn start(&mut self) -> Result<()> {
         let mut trading_context: Option<TradingContext> = None;
+        let receiver_metrics = EventsReceiverMetrics::new(&format!(
+            "disposition_executor {} {}",
+            self.exchange_account_id,
+            self.symbol.currency_pair()
+        ));
 
         loop {
             let event = tokio::select! {
-                event_res = self.events_receiver.recv() => event_res.context("Error during receiving event in DispositionExecutor::start()")?,
+                event_res = self.events_receiver.recv() => match event_res {
+                    Ok(event) => event,
+                    Err(RecvError::Lagged(skipped_count)) => {
+                        log::warn!("DispositionExecutor for {} {} skipped {skipped_count} events", self.exchange_account_id, self.symbol.currency_pair());
+                        receiver_metrics.add_skipped(skipped_count);
+                        continue;
+                    }
+                    Err(err) => return Err(err).context("Error during receiving event in DispositionExecutor::start()"),
+                },
+                Some(strategy) = self.strategy_receiver.recv() => {
+                    log::info!("Strategy of DispositionExecutor for {} {} was replaced", self.exchange_account_id, self.symbol.currency_pair());
+                    self.strategy = strategy;
+                    // force applying of trading context calculated by the new strategy
+                    trading_context = None;
+                    continue;
+                }
                 _ = self.cancellation_token.when_cancelled() => {
                     let _ = self.work_finished_sender.take().ok_or_else(|| anyhow!("Can't take `work_finished_sender` in DispositionExecutor"))?.send(Ok(()));
                     return Ok(());
                 }
             };
+            receiver_metrics.set_lag(self.events_receiver.len());
 
             self.handle_event(event, &mut trading_context)?;
         }
@@ -187,7 +244,7 @@ impl DispositionExecutor {
         last_trading_context: &mut Option<TradingContext>,
     ) -> Result<()> {
         let now = now();
-        let need_recalculate_trading_context = self.prepare_estimate_trading_context(&event, now);
+        let need_recalculate_trading_context = self.prepare_estimate_trading_context(&event);
 
         match event {
             ExchangeEvent::OrderBookEvent(order_book_event) => {
@@ -199,8 +256,16 @@ impl DispositionExecutor {
                     return Ok(());
                 }
 
+                // orders of other strategy instances are handled by their own executors
+                if order.exchange_account_id() != self.exchange_account_id
+                    || order.currency_pair() != self.symbol.currency_pair()
+                {
+                    return Ok(());
+                }
+
                 match order_event.event_type {
-                    OrderEventType::CreateOrderSucceeded => nothing_to_do(),
+                    OrderEventType::CreateOrderSucceeded
+                    | OrderEventType::ConditionalOrderWaiting => nothing_to_do(),
                     OrderEventType::CreateOrderFailed => {
                         let client_order_id = order.client_order_id();
                         log::trace!("Started handling event CreateOrderFailed {client_order_id} in DispositionExecutor");
@@ -308,6 +373,7 @@ impl DispositionExecutor {
 
             self.synchronize_price_slots_for_list(
                 &state_by_side.slots,
+                &state_by_side.taker_slot,
                 &mut trading_context_by_side.estimating[..],
                 trading_context_by_side.max_amount,
                 now,
@@ -330,6 +396,7 @@ impl DispositionExecutor {
     fn synchronize_price_slots_for_list(
         &self,
         slots: &[PriceSlot],
+        taker_slot: &PriceSlot,
         estimating: &mut [WithExplanation<Option<TradeCycle>>],
         max_amount: Decimal,
         now: DateTime,
@@ -344,7 +411,23 @@ impl DispositionExecutor {
 
             let (trade_cycle, explanation) = with_explanation.as_mut_all();
 
-            self.synchronize_price_slot(trade_cycle, price_slot, max_amount, now, explanation)?;
+            match trade_cycle {
+                Some(trade_cycle) if trade_cycle.order_role == OrderRole::Taker => self
+                    .try_create_taker_order(
+                        trade_cycle,
+                        taker_slot,
+                        max_amount,
+                        now,
+                        explanation,
+                    )?,
+                _ => self.synchronize_price_slot(
+                    trade_cycle,
+                    price_slot,
+                    max_amount,
+                    now,
+                    explanation,
+                )?,
+            }
         }
 
         Ok(())
@@ -368,10 +451,10 @@ impl DispositionExecutor {
         if self
             .engine_ctx
             .exchange_blocker
-            .is_blocked(self.exchange_account_id)
+            .is_market_blocked(self.exchange_account_id, self.symbol.currency_pair())
         {
             self.start_cancelling_all_orders(
-                "target exchange is locked",
+                "target exchange or market is locked",
                 &mut composite_order.borrow_mut(),
                 explanation,
             );
@@ -620,12 +703,134 @@ impl DispositionExecutor {
             );
         }
 
-        let new_client_order_id = ClientOrderId::unique_id();
+        let new_order_header = self.create_order_header(
+            new_estimating,
+            new_order_amount,
+            OrderType::Limit,
+            OrderExecutionType::MakerOnly,
+            now,
+        );
+
+        self.reserve_and_create_order(new_order_header, price_slot, new_estimating, explanation)
+    }
+
+    /// Taker trade cycles are fire-and-forget: order is created once and isn't synchronized
+    /// with next trading contexts, but it stays in the taker slot until it is finished for
+    /// unreserving balance, releasing requests group and handling fills by strategy
+    fn try_create_taker_order(
+        &self,
+        new_estimating: &TradeCycle,
+        taker_slot: &PriceSlot,
+        max_amount: Decimal,
+        now: DateTime,
+        explanation: &mut Explanation,
+    ) -> Result<()> {
+        log::trace!("Begin try_create_taker_order");
+
+        if self
+            .engine_ctx
+            .exchange_blocker
+            .is_market_blocked(self.exchange_account_id, self.symbol.currency_pair())
+        {
+            return log_trace(
+                "Finished `try_create_taker_order` because target exchange or market is locked",
+                explanation,
+            );
+        }
+
+        let order_type = new_estimating.order_type;
+        if !matches!(order_type, OrderType::Limit | OrderType::Market) {
+            log::error!("Order type {order_type:?} is not supported for taker trade cycle {new_estimating:?}");
+            return log_trace(
+                format!("Finished `try_create_taker_order` because of unsupported order type {order_type:?}"),
+                explanation,
+            );
+        }
+
+        if !taker_slot.order.borrow().orders.is_empty() {
+            return log_trace(
+                "Finished `try_create_taker_order` because previous taker order isn't finished yet",
+                explanation,
+            );
+        }
+
+        let new_disposition = &new_estimating.disposition;
+        let new_order_amount = self.calculate_new_order_amount(
+            new_disposition.market_account_id(),
+            new_disposition.side(),
+            new_disposition.amount(),
+            max_amount,
+            explanation,
+        );
+
+        if let Err(reason) =
+            is_enough_amount_and_cost(new_disposition, new_order_amount, true, &self.symbol)
+        {
+            return log_trace(
+                format!("Finished `try_create_taker_order` by reason: {reason}"),
+                explanation,
+            );
+        }
+
+        let new_order_header = self.create_order_header(
+            new_estimating,
+            new_order_amount,
+            order_type,
+            OrderExecutionType::None,
+            now,
+        );
+        // limit taker order shouldn't stay in order book after crossing the spread
+        let new_order_header = match order_type {
+            OrderType::Limit => {
+                new_order_header.with_time_in_force(OrderTimeInForce::ImmediateOrCancel)
+            }
+            _ => new_order_header,
+        };
+
+        self.reserve_and_create_order(new_order_header, taker_slot, new_estimating, explanation)
+    }
+
+    fn create_order_header(
+        &self,
+        new_estimating: &TradeCycle,
+        new_order_amount: Amount,
+        order_type: OrderType,
+        execution_type: OrderExecutionType,
+        now: DateTime,
+    ) -> Arc<OrderHeader> {
+        OrderHeader::new(
+            ClientOrderId::unique_id(),
+            now,
+            self.exchange_account_id,
+            self.symbol.currency_pair(),
+            order_type,
+            new_estimating.disposition.side(),
+            new_order_amount,
+            execution_type,
+            None,
+            None,
+            new_estimating.strategy_name.clone(),
+        )
+    }
+
+    /// Reserve requests group and balance for the order and start its creation
+    fn reserve_and_create_order(
+        &self,
+        new_order_header: Arc<OrderHeader>,
+        price_slot: &PriceSlot,
+        new_estimating: &TradeCycle,
+        explanation: &mut Explanation,
+    ) -> Result<()> {
+        let new_disposition = &new_estimating.disposition;
+        let new_price = new_disposition.price();
+        let new_order_amount = new_order_header.amount;
+        let new_client_order_id = new_order_header.client_order_id.clone();
 
         let requests_group_id = self.engine_ctx.timeout_manager.try_reserve_group(
             self.exchange_account_id,
             GROUP_REQUESTS_COUNT,
             DISPOSITION_EXECUTOR_REQUESTS_GROUP.to_string(),
+            RequestPriority::Normal,
         );
 
         let requests_group_id = match requests_group_id {
@@ -702,19 +907,7 @@ impl DispositionExecutor {
 
         *price_slot.estimating.borrow_mut() = Some(Box::new(new_estimating.clone()));
 
-        let new_order_header = OrderHeader::new(
-            new_client_order_id.clone(),
-            now,
-            self.exchange_account_id,
-            self.symbol.currency_pair(),
-            OrderType::Limit,
-            new_disposition.side(),
-            new_order_amount,
-            OrderExecutionType::MakerOnly,
-            Some(reservation_id),
-            None,
-            new_estimating.strategy_name.clone(),
-        );
+        let new_order_header = new_order_header.with_reservation_id(reservation_id);
 
         let exchange = self.exchange();
 
@@ -742,10 +935,7 @@ impl DispositionExecutor {
             let action = async move {
                 log::trace!("Begin create_order {new_client_order_id}");
 
-                let order_creating = OrderCreating {
-                    header: new_order_header,
-                    price: new_price,
-                };
+                let order_creating = OrderCreating::new(new_order_header, new_price);
 
                 exchange
                     .create_order(order_creating, None, cancellation_token)
@@ -894,15 +1084,25 @@ impl DispositionExecutor {
             .clone()
     }
 
-    fn prepare_estimate_trading_context(&self, event: &ExchangeEvent, now: DateTime) -> bool {
-        let event_time = match event {
-            ExchangeEvent::OrderBookEvent(order_book_event) => order_book_event.creation_time,
-            ExchangeEvent::LiquidationPrice(liquidation_price) => {
-                liquidation_price.event_creation_time
-            }
+    fn prepare_estimate_trading_context(&self, event: &ExchangeEvent) -> bool {
+        let (event_time, exchange_account_id) = match event {
+            ExchangeEvent::OrderBookEvent(order_book_event) => (
+                order_book_event.creation_time,
+                order_book_event.exchange_account_id,
+            ),
+            ExchangeEvent::LiquidationPrice(liquidation_price) => (
+                liquidation_price.event_creation_time,
+                liquidation_price.exchange_account_id,
+            ),
             _ => return false,
         };
 
+        // events of simulated exchanges are created in simulated time
+        let now = match self.engine_ctx.exchanges.get(&exchange_account_id) {
+            Some(exchange) => exchange.exchange_client.market_data_now(),
+            None => now(),
+        };
+
         // max delay for skipping recalculation of trading context and orders synchronization
         let delay_for_skipping_event: Duration = Duration::milliseconds(50);
         if event_time + delay_for_skipping_event < now {
@@ -985,3 +1185,448 @@ fn log_trace(msg: impl AsRef<str>, explanation: &mut Explanation) -> Result<()>
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::balance::manager::balance_manager::BalanceManager;
+    use crate::database::events::recorder::EventRecorder;
+    use crate::disposition_execution::{TradeDisposition, TradingContextBySide};
+    use crate::exchanges::exchange_blocker::ExchangeBlocker;
+    use crate::exchanges::general::currency_pair_to_symbol_converter::CurrencyPairToSymbolConverter;
+    use crate::exchanges::general::test_helper::{
+        create_test_symbol, get_test_exchange_with_timeout_manager, get_test_timeout_manager,
+        TestClient,
+    };
+    use crate::exchanges::simulation::paper_trading::PaperTrading;
+    use crate::infrastructure::init_lifetime_manager;
+    use crate::lifecycle::launcher::EngineBuildConfig;
+    use crate::misc::time::tests::init_mock;
+    use crate::order_book::local_snapshot_service::LocalSnapshotsService;
+    use crate::service_configuration::configuration_descriptor::ConfigurationDescriptor;
+    use crate::settings::{CoreSettings, PaperTradingSettings};
+    use dashmap::DashMap;
+    use mmb_domain::events::{ExchangeBalance, ExchangeBalancesAndPositions, ExchangeEvents};
+    use mmb_domain::exchanges::symbol::Precision;
+    use mmb_domain::order::event::OrderEvent;
+    use mmb_domain::order_book::event::{EventType, OrderBookEvent};
+    use mmb_domain::order_book::order_book_data::OrderBookData;
+    use mmb_utils::hashmap;
+    use std::collections::HashMap;
+    use std::time::Duration as StdDuration;
+    use tokio::time::timeout;
+
+    /// Buys by a limit taker order on every recalculation of trading context
+    struct TakerStrategy {
+        market_account_id: MarketAccountId,
+    }
+
+    impl DispositionStrategy for TakerStrategy {
+        fn calculate_trading_context(
+            &mut self,
+            _now: DateTime,
+            _local_snapshots_service: &LocalSnapshotsService,
+            explanation: &mut Explanation,
+        ) -> Option<TradingContext> {
+            let trade_cycle = TradeCycle::taker(
+                OrderType::Limit,
+                "TakerStrategy".into(),
+                TradeDisposition::new(self.market_account_id, OrderSide::Buy, dec!(101), dec!(3)),
+            );
+            let buy_ctx = TradingContextBySide {
+                max_amount: dec!(10),
+                estimating: vec![WithExplanation {
+                    value: Some(trade_cycle),
+                    explanation: explanation.clone(),
+                }],
+            };
+            let sell_ctx = TradingContextBySide::empty(1, explanation.clone());
+
+            Some(TradingContext::new(buy_ctx, sell_ctx))
+        }
+
+        fn handle_order_fill(
+            &self,
+            _cloned_order: &Arc<OrderSnapshot>,
+            _price_slot: &PriceSlot,
+            _target_eai: ExchangeAccountId,
+            _cancellation_token: CancellationToken,
+        ) -> Result<()> {
+            Ok(())
+        }
+
+        fn configuration_descriptor(&self) -> ConfigurationDescriptor {
+            ConfigurationDescriptor::new("TakerStrategy".into(), "executor_test".into())
+        }
+    }
+
+    /// Records its name on every recalculation of trading context, but never trades
+    struct RecordingStrategy {
+        name: &'static str,
+        calculations: Arc<Mutex<Vec<&'static str>>>,
+    }
+
+    impl DispositionStrategy for RecordingStrategy {
+        fn calculate_trading_context(
+            &mut self,
+            _now: DateTime,
+            _local_snapshots_service: &LocalSnapshotsService,
+            _explanation: &mut Explanation,
+        ) -> Option<TradingContext> {
+            self.calculations.lock().push(self.name);
+            None
+        }
+
+        fn handle_order_fill(
+            &self,
+            _cloned_order: &Arc<OrderSnapshot>,
+            _price_slot: &PriceSlot,
+            _target_eai: ExchangeAccountId,
+            _cancellation_token: CancellationToken,
+        ) -> Result<()> {
+            Ok(())
+        }
+
+        fn configuration_descriptor(&self) -> ConfigurationDescriptor {
+            ConfigurationDescriptor::new("RecordingStrategy".into(), self.name.into())
+        }
+    }
+
+    /// Only a part of the taker order of `TakerStrategy` crosses the order book
+    fn order_book_event(market_account_id: MarketAccountId) -> ExchangeEvent {
+        ExchangeEvent::OrderBookEvent(OrderBookEvent::new(
+            Utc::now(),
+            market_account_id.exchange_account_id,
+            market_account_id.currency_pair,
+            "1".into(),
+            EventType::Snapshot,
+            Arc::new(OrderBookData::new(
+                [(dec!(101), dec!(1)), (dec!(102), dec!(5))].into(),
+                [(dec!(99), dec!(1))].into(),
+            )),
+        ))
+    }
+
+    async fn next_order_event(
+        events_receiver: &mut broadcast::Receiver<ExchangeEvent>,
+    ) -> OrderEvent {
+        loop {
+            let event = timeout(StdDuration::from_secs(1), events_receiver.recv())
+                .await
+                .expect("order event should be received in time")
+                .expect("in test");
+            if let ExchangeEvent::OrderEvent(order_event) = event {
+                return order_event;
+            }
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn limit_taker_order_is_expired_by_simulated_exchange_after_partial_fill() {
+        let lifetime_manager = init_lifetime_manager();
+        let (_time_manager_mock, _mock_locker) = init_mock(Arc::new(Mutex::new(0)));
+
+        let exchange_account_id = ExchangeAccountId::new("local_exchange_account_id", 0);
+        let symbol = Arc::new(Symbol::new(
+            false,
+            "btc".into(),
+            "btc".into(),
+            "usdt".into(),
+            "usdt".into(),
+            None,
+            None,
+            Some(dec!(0.001)),
+            None,
+            None,
+            "btc".into(),
+            None,
+            Precision::ByTick { tick: dec!(0.1) },
+            Precision::ByTick { tick: dec!(0.001) },
+        ));
+        let market_account_id = MarketAccountId::new(exchange_account_id, symbol.currency_pair());
+
+        let (market_data_sender, _) = broadcast::channel(10);
+        let paper_trading = PaperTrading::new(
+            Box::new(TestClient::default()),
+            &PaperTradingSettings {
+                maker_fee: dec!(0.1),
+                taker_fee: dec!(0.2),
+                initial_balances: hashmap!["usdt".into() => dec!(1000)],
+            },
+            market_data_sender.clone(),
+        );
+        let simulated_exchange = paper_trading.simulated_exchange().clone();
+
+        let exchange_blocker = ExchangeBlocker::new(vec![exchange_account_id]);
+        let timeout_manager = get_test_timeout_manager(exchange_account_id);
+        let (exchange, mut order_events_receiver) = get_test_exchange_with_timeout_manager(
+            symbol,
+            exchange_account_id,
+            Box::new(paper_trading),
+            timeout_manager.clone(),
+            &exchange_blocker,
+        );
+        exchange.exchange_client.initialized(exchange.clone()).await;
+
+        let balance_manager = BalanceManager::new(
+            CurrencyPairToSymbolConverter::new(hashmap![exchange_account_id => exchange.clone()]),
+            None,
+        );
+        exchange.setup_balance_manager(balance_manager.clone());
+        balance_manager
+            .lock()
+            .update_exchange_balance(
+                exchange_account_id,
+                &ExchangeBalancesAndPositions {
+                    balances: vec![ExchangeBalance {
+                        currency_code: "usdt".into(),
+                        balance: dec!(1000),
+                    }],
+                    positions: None,
+                },
+            )
+            .expect("in test");
+
+        let exchanges = DashMap::new();
+        exchanges.insert(exchange_account_id, exchange.clone());
+        let (executor_events_sender, executor_events_receiver) = broadcast::channel(10);
+        let engine_ctx = EngineContext::new(
+            CoreSettings::default(),
+            exchanges,
+            ExchangeEvents::new(executor_events_sender),
+            oneshot::channel().0,
+            exchange_blocker,
+            timeout_manager,
+            lifetime_manager,
+            balance_manager,
+            EventRecorder::start(None).await.expect("in test"),
+            EngineBuildConfig::new(vec![]),
+        );
+
+        let (_strategy_sender, strategy_receiver) = mpsc::unbounded_channel();
+        let mut executor = DispositionExecutor::new(
+            engine_ctx,
+            executor_events_receiver,
+            LocalSnapshotsService::new(HashMap::new()),
+            exchange_account_id,
+            market_account_id.currency_pair,
+            Box::new(TakerStrategy { market_account_id }),
+            strategy_receiver,
+            oneshot::channel().0,
+            CancellationToken::new(),
+            StatisticService::new(),
+        );
+
+        let market_data = order_book_event(market_account_id);
+        market_data_sender
+            .send(market_data.clone())
+            .expect("in test");
+        timeout(StdDuration::from_secs(1), async {
+            while simulated_exchange.now() == DateTime::MIN_UTC {
+                tokio::time::sleep(StdDuration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("market data should be applied by simulated exchange in time");
+
+        let mut trading_context = None;
+        executor
+            .handle_event(market_data.clone(), &mut trading_context)
+            .expect("in test");
+
+        let created_event = next_order_event(&mut order_events_receiver).await;
+        assert!(matches!(
+            created_event.event_type,
+            OrderEventType::CreateOrderSucceeded
+        ));
+        let order = created_event.order.clone();
+        assert_eq!(
+            order.fn_ref(|x| x.header.time_in_force),
+            OrderTimeInForce::ImmediateOrCancel
+        );
+        executor
+            .handle_event(
+                ExchangeEvent::OrderEvent(created_event),
+                &mut trading_context,
+            )
+            .expect("in test");
+
+        // the order is matched by the next event of market data and the rest shouldn't stay resting
+        market_data_sender.send(market_data).expect("in test");
+
+        loop {
+            let order_event = next_order_event(&mut order_events_receiver).await;
+            let is_cancelled =
+                matches!(order_event.event_type, OrderEventType::CancelOrderSucceeded);
+            executor
+                .handle_event(ExchangeEvent::OrderEvent(order_event), &mut trading_context)
+                .expect("in test");
+            if is_cancelled {
+                break;
+            }
+        }
+
+        assert_eq!(order.status(), OrderStatus::Canceled);
+        assert_eq!(order.filled_amount(), dec!(1));
+        assert!(executor.orders_state.by_side[OrderSide::Buy]
+            .taker_slot
+            .order
+            .borrow()
+            .orders
+            .is_empty());
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn replaced_strategy_is_used_for_next_recalculations() {
+        let lifetime_manager = init_lifetime_manager();
+        let (_time_manager_mock, _mock_locker) = init_mock(Arc::new(Mutex::new(0)));
+
+        let exchange_account_id = ExchangeAccountId::new("local_exchange_account_id", 0);
+        let symbol = create_test_symbol(false, "btc", "usdt", "btc");
+        let market_account_id = MarketAccountId::new(exchange_account_id, symbol.currency_pair());
+        let exchange_blocker = ExchangeBlocker::new(vec![exchange_account_id]);
+        let timeout_manager = get_test_timeout_manager(exchange_account_id);
+        let (exchange, _) = get_test_exchange_with_timeout_manager(
+            symbol,
+            exchange_account_id,
+            Box::new(TestClient::default()),
+            timeout_manager.clone(),
+            &exchange_blocker,
+        );
+
+        let (events_sender, events_receiver) = broadcast::channel(10);
+        let engine_ctx = EngineContext::new(
+            CoreSettings::default(),
+            DashMap::from_iter([(exchange_account_id, exchange.clone())]),
+            ExchangeEvents::new(events_sender.clone()),
+            oneshot::channel().0,
+            exchange_blocker,
+            timeout_manager,
+            lifetime_manager,
+            BalanceManager::new(
+                CurrencyPairToSymbolConverter::new(hashmap![exchange_account_id => exchange]),
+                None,
+            ),
+            EventRecorder::start(None).await.expect("in test"),
+            EngineBuildConfig::new(vec![]),
+        );
+
+        let calculations = Arc::new(Mutex::new(Vec::new()));
+        let recording_strategy = |name| {
+            Box::new(RecordingStrategy {
+                name,
+                calculations: calculations.clone(),
+            })
+        };
+        let executor = DispositionExecutorService::new(
+            engine_ctx,
+            events_receiver,
+            LocalSnapshotsService::new(HashMap::new()),
+            exchange_account_id,
+            market_account_id.currency_pair,
+            recording_strategy("first"),
+            CancellationToken::new(),
+            StatisticService::new(),
+        );
+
+        // every order book event forces recalculation of trading context
+        let recalculate_until = |name| {
+            let events_sender = events_sender.clone();
+            let calculations = calculations.clone();
+            timeout(StdDuration::from_secs(1), async move {
+                while calculations.lock().last() != Some(&name) {
+                    let _ = events_sender.send(order_book_event(market_account_id));
+                    tokio::time::sleep(StdDuration::from_millis(10)).await;
+                }
+            })
+        };
+        recalculate_until("first")
+            .await
+            .expect("trading context should be calculated by the first strategy");
+
+        executor
+            .replace_strategy(recording_strategy("second"))
+            .expect("in test");
+        recalculate_until("second")
+            .await
+            .expect("trading context should be calculated by the replaced strategy");
+
+        let calculations = calculations.lock().clone();
+        let first_by_second = calculations
+            .iter()
+            .position(|x| *x == "second")
+            .expect("in test");
+        assert!(calculations[first_by_second..]
+            .iter()
+            .all(|x| *x == "second"));
+
+        executor.stop();
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn skipped_events_of_lagged_receiver_do_not_stop_executor() {
+        let lifetime_manager = init_lifetime_manager();
+        let (_time_manager_mock, _mock_locker) = init_mock(Arc::new(Mutex::new(0)));
+
+        let exchange_account_id = ExchangeAccountId::new("local_exchange_account_id", 0);
+        let symbol = create_test_symbol(false, "btc", "usdt", "btc");
+        let market_account_id = MarketAccountId::new(exchange_account_id, symbol.currency_pair());
+        let exchange_blocker = ExchangeBlocker::new(vec![exchange_account_id]);
+        let timeout_manager = get_test_timeout_manager(exchange_account_id);
+        let (exchange, _) = get_test_exchange_with_timeout_manager(
+            symbol,
+            exchange_account_id,
+            Box::new(TestClient::default()),
+            timeout_manager.clone(),
+            &exchange_blocker,
+        );
+
+        let (events_sender, events_receiver) = broadcast::channel(2);
+        // receiver lags before executor starts receiving events
+        for _ in 0..5 {
+            let _ = events_sender.send(order_book_event(market_account_id));
+        }
+
+        let engine_ctx = EngineContext::new(
+            CoreSettings::default(),
+            DashMap::from_iter([(exchange_account_id, exchange.clone())]),
+            ExchangeEvents::new(events_sender.clone()),
+            oneshot::channel().0,
+            exchange_blocker,
+            timeout_manager,
+            lifetime_manager,
+            BalanceManager::new(
+                CurrencyPairToSymbolConverter::new(hashmap![exchange_account_id => exchange]),
+                None,
+            ),
+            EventRecorder::start(None).await.expect("in test"),
+            EngineBuildConfig::new(vec![]),
+        );
+
+        let calculations = Arc::new(Mutex::new(Vec::new()));
+        let executor = DispositionExecutorService::new(
+            engine_ctx,
+            events_receiver,
+            LocalSnapshotsService::new(HashMap::new()),
+            exchange_account_id,
+            market_account_id.currency_pair,
+            Box::new(RecordingStrategy {
+                name: "recording",
+                calculations: calculations.clone(),
+            }),
+            CancellationToken::new(),
+            StatisticService::new(),
+        );
+
+        timeout(StdDuration::from_secs(1), async {
+            while calculations.lock().is_empty() {
+                tokio::time::sleep(StdDuration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("events received after skipped ones should be handled");
+        assert!(!executor.is_stopped());
+
+        executor.stop();
+    }
+}
@@ -16,7 +16,7 @@ use crate::exchanges::timeouts::requests_timeout_manager::RequestGroupId;
 use crate::explanation::{Explanation, ExplanationSet, PriceLevelExplanation, WithExplanation};
 use mmb_domain::market::{CurrencyPair, ExchangeAccountId, ExchangeId, MarketAccountId, MarketId};
 use mmb_domain::order::pool::OrderRef;
-use mmb_domain::order::snapshot::{ClientOrderId, OrderRole, OrderSide};
+use mmb_domain::order::snapshot::{ClientOrderId, OrderRole, OrderSide, OrderType};
 
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
 pub struct SmallOrder {
@@ -106,11 +106,40 @@ impl TradeDisposition {
 
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct TradeCycle {
+    /// Maker cycles are placed as resting orders into price slots.
+    /// Taker cycles cross the spread and are not synchronized after creation
     pub order_role: OrderRole,
+    /// Type of taker order. Maker cycles always create limit orders
+    pub order_type: OrderType,
     pub strategy_name: String,
     pub disposition: TradeDisposition,
 }
 
+impl TradeCycle {
+    pub fn maker(strategy_name: String, disposition: TradeDisposition) -> Self {
+        TradeCycle {
+            order_role: OrderRole::Maker,
+            order_type: OrderType::Limit,
+            strategy_name,
+            disposition,
+        }
+    }
+
+    /// Taker cycle with limit (`OrderType::Limit`) or market (`OrderType::Market`) order
+    pub fn taker(
+        order_type: OrderType,
+        strategy_name: String,
+        disposition: TradeDisposition,
+    ) -> Self {
+        TradeCycle {
+            order_role: OrderRole::Taker,
+            order_type,
+            strategy_name,
+            disposition,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct TradingContextBySide {
     pub max_amount: Amount,
@@ -323,6 +352,8 @@ impl PriceSlot {
 struct OrdersStateBySide {
     _side: OrderSide,
     slots: Vec<PriceSlot>,
+    /// Orders of taker trade cycles which are tracked only until they are finished
+    taker_slot: PriceSlot,
 }
 
 impl OrdersStateBySide {
@@ -334,18 +365,18 @@ impl OrdersStateBySide {
                 PriceSlotId::new("PriceSlotId".into(), 0),
                 _side,
             )],
+            taker_slot: PriceSlot::new(PriceSlotId::new("TakerSlotId".into(), 0), _side),
         }
     }
 
     pub fn calc_total_remaining_amount(&self) -> Decimal {
-        self.slots
-            .iter()
+        self.traverse_price_slots()
             .map(|x| x.order.borrow().remaining_amount())
             .sum()
     }
 
     pub fn traverse_price_slots(&self) -> impl Iterator<Item = &PriceSlot> {
-        self.slots.iter()
+        self.slots.iter().chain(std::iter::once(&self.taker_slot))
     }
 
     pub(crate) fn find_price_slot(&self, order: &OrderRef) -> Option<&PriceSlot> {
@@ -367,3 +398,44 @@ impl OrdersState {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use mmb_domain::order::pool::OrdersPool;
+    use mmb_domain::order::snapshot::{OrderExecutionType, OrderHeader};
+
+    #[test]
+    fn taker_orders_are_tracked_in_taker_slot() {
+        let state = OrdersStateBySide::new(OrderSide::Buy);
+        let header = OrderHeader::new(
+            ClientOrderId::unique_id(),
+            Utc::now(),
+            ExchangeAccountId::new("Binance", 0),
+            CurrencyPair::from_codes("btc".into(), "usdt".into()),
+            OrderType::Market,
+            OrderSide::Buy,
+            dec!(2),
+            OrderExecutionType::None,
+            None,
+            None,
+            "test".to_owned(),
+        );
+        let order = OrdersPool::new().add_simple_initial(header, Some(dec!(10)), None);
+
+        state.taker_slot.add_order(
+            OrderSide::Buy,
+            dec!(10),
+            order.clone(),
+            RequestGroupId::generate(),
+        );
+
+        let price_slot = state.find_price_slot(&order).expect("in test");
+        assert_eq!(price_slot.id, state.taker_slot.id);
+        assert_eq!(state.calc_total_remaining_amount(), dec!(2));
+
+        price_slot.remove_order(&order);
+        assert!(state.find_price_slot(&order).is_none());
+    }
+}
@@ -12,3 +12,7 @@ impl_block_reason!(CREATE_ORDER_INSUFFICIENT_FUNDS);
 impl_block_reason!(REST_RATE_LIMIT);
 impl_block_reason!(GRACEFUL_SHUTDOWN);
 impl_block_reason!(EXCHANGE_UNAVAILABLE);
+impl_block_reason!(ORDER_BOOK_INTEGRITY_VIOLATED);
+impl_block_reason!(CLOCK_DRIFT_EXCEEDED);
+impl_block_reason!(MANUAL_PAUSE);
+impl_block_reason!(EXCHANGE_ACCOUNT_REMOVED);
@@ -20,7 +20,7 @@ use tokio::time::{sleep, sleep_until, Duration, Instant};
 
 #[cfg(test)]
 use crate::MOCK_MUTEX;
-use mmb_domain::market::ExchangeAccountId;
+use mmb_domain::market::{CurrencyPair, ExchangeAccountId};
 #[cfg(test)]
 use mockall::automock;
 
@@ -35,23 +35,45 @@ pub enum ExchangeBlockerMoment {
 }
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
-pub struct BlockReason(&'static str);
+pub struct BlockReason {
+    name: &'static str,
+    /// Market where trading is stopped by the reason. Other markets of exchange account aren't affected
+    currency_pair: Option<CurrencyPair>,
+}
 
 impl BlockReason {
     pub const fn new(value: &'static str) -> Self {
-        BlockReason(value)
+        BlockReason {
+            name: value,
+            currency_pair: None,
+        }
+    }
+
+    /// The same reason which stops trading only on the market of specified currency pair
+    pub fn for_market(self, currency_pair: CurrencyPair) -> Self {
+        BlockReason {
+            currency_pair: Some(currency_pair),
+            ..self
+        }
+    }
+
+    pub fn currency_pair(&self) -> Option<CurrencyPair> {
+        self.currency_pair
     }
 }
 
 impl From<&'static str> for BlockReason {
     fn from(value: &'static str) -> Self {
-        BlockReason(value)
+        BlockReason::new(value)
     }
 }
 
 impl Display for BlockReason {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        self.0.fmt(f)
+        match self.currency_pair {
+            None => self.name.fmt(f),
+            Some(currency_pair) => write!(f, "{} {currency_pair}", self.name),
+        }
     }
 }
 
@@ -181,6 +203,14 @@ pub struct ExchangeBlockerEvent {
     pub moment: ExchangeBlockerMoment,
 }
 
+/// Snapshot of existing blocker. `BlockType::Timed` contains remaining time of blocking
+#[derive(Debug, Clone)]
+pub struct ActiveBlocker {
+    pub exchange_account_id: ExchangeAccountId,
+    pub reason: BlockReason,
+    pub block_type: BlockType,
+}
+
 type Blockers = Arc<RwLock<HashMap<ExchangeAccountId, HashMap<BlockReason, Blocker>>>>;
 type BlockerEventHandler = Box<
     dyn Fn(Arc<ExchangeBlockerEvent>, CancellationToken) -> BoxFuture<'static, ()> + Send + Sync,
@@ -286,9 +316,16 @@ impl ExchangeBlockerEventsProcessor {
         use ProgressStatus::*;
 
         let mut write_guard = ctx.blockers.write();
-        let blockers = write_guard
-            .get_mut(&event.blocker_id.exchange_account_id)
-            .expect(EXPECTED_EAI_SHOULD_BE_CREATED);
+        let blockers = match write_guard.get_mut(&event.blocker_id.exchange_account_id) {
+            Some(blockers) => blockers,
+            None => {
+                log::trace!(
+                    "Event {:?} is skipped because exchange account is removed",
+                    event.blocker_id
+                );
+                return;
+            }
+        };
 
         let blocker = blockers.get(&event.blocker_id.reason).with_expect(|| {
             format!(
@@ -461,6 +498,28 @@ impl ExchangeBlocker {
         })
     }
 
+    /// Blocker of engine for services which use mock of it in unit tests
+    pub(crate) fn shared(exchange_blocker: &Arc<ExchangeBlocker>) -> Arc<Self> {
+        exchange_blocker.clone()
+    }
+
+    /// Register exchange account connected at runtime
+    pub fn add_exchange_account(&self, exchange_account_id: ExchangeAccountId) {
+        let _ = self
+            .blockers
+            .write()
+            .entry(exchange_account_id)
+            .or_default();
+    }
+
+    /// Forget exchange account removed at runtime. Waiters of its blockers are released
+    pub fn remove_exchange_account(&self, exchange_account_id: ExchangeAccountId) {
+        let blockers = self.blockers.write().remove(&exchange_account_id);
+        for blocker in blockers.iter().flat_map(|x| x.values()) {
+            blocker.unblocked_notify.notify_waiters();
+        }
+    }
+
     pub fn is_blocked(&self, exchange_account_id: ExchangeAccountId) -> bool {
         !self
             .blockers
@@ -470,6 +529,25 @@ impl ExchangeBlocker {
             .is_empty()
     }
 
+    /// Returns true if trading on the market is stopped by a reason of the whole exchange account
+    /// or by a reason of the market itself
+    pub fn is_market_blocked(
+        &self,
+        exchange_account_id: ExchangeAccountId,
+        currency_pair: CurrencyPair,
+    ) -> bool {
+        self.blockers
+            .read()
+            .get(&exchange_account_id)
+            .expect(EXPECTED_EAI_SHOULD_BE_CREATED)
+            .keys()
+            .any(|reason| {
+                reason
+                    .currency_pair
+                    .map_or(true, |blocked_pair| blocked_pair == currency_pair)
+            })
+    }
+
     pub fn is_blocked_by_reason(
         &self,
         exchange_account_id: ExchangeAccountId,
@@ -498,6 +576,26 @@ impl ExchangeBlocker {
         is_blocker_exists && blockers_count > 1 || !is_blocker_exists && blockers_count > 0
     }
 
+    pub fn active_blockers(&self) -> Vec<ActiveBlocker> {
+        let now = Instant::now();
+        self.blockers
+            .read()
+            .iter()
+            .flat_map(|(exchange_account_id, blockers)| {
+                blockers.values().map(move |blocker| ActiveBlocker {
+                    exchange_account_id: *exchange_account_id,
+                    reason: blocker.id.reason,
+                    block_type: match &*blocker.timeout.lock() {
+                        Timeout::ReadyUnblock => BlockType::Manual,
+                        Timeout::InProgress { in_progress } => {
+                            BlockType::Timed(in_progress.end_time.saturating_duration_since(now))
+                        }
+                    },
+                })
+            })
+            .collect()
+    }
+
     pub fn block(
         self: &Arc<Self>,
         exchange_account_id: ExchangeAccountId,
@@ -619,17 +717,17 @@ impl ExchangeBlocker {
                 Some(self_rc) => {
                     let exchange_account_id = blocker_id.exchange_account_id;
                     let reason = blocker_id.reason;
-                    match self_rc
-                        .blockers
-                        .read()
-                        .get(&exchange_account_id)
-                        .expect(EXPECTED_EAI_SHOULD_BE_CREATED)
-                        .get(&reason)
-                    {
+                    match self_rc.blockers.read().get(&exchange_account_id) {
                         None => {
-                           log::error!("Not found blocker '{}' on timer tick. If unblock forced, timer should be stopped manually.", &blocker_id)
+                            log::trace!("Exchange account of blocker '{}' is removed before timer tick", &blocker_id);
+                            return;
                         }
-                        Some(blocker) => *blocker.timeout.lock() = Timeout::ReadyUnblock,
+                        Some(blockers) => match blockers.get(&reason) {
+                            None => {
+                                log::error!("Not found blocker '{}' on timer tick. If unblock forced, timer should be stopped manually.", &blocker_id)
+                            }
+                            Some(blocker) => *blocker.timeout.lock() = Timeout::ReadyUnblock,
+                        },
                     }
                     self_rc.unblock(exchange_account_id, reason)
                 }
@@ -715,14 +813,14 @@ impl ExchangeBlocker {
         );
 
         loop {
-            let unblocked_notifies = self
-                .blockers
-                .read()
-                .get(&exchange_account_id)
-                .expect(EXPECTED_EAI_SHOULD_BE_CREATED)
-                .values()
-                .map(|blocker| (blocker.unblocked_notify.clone(), blocker.id))
-                .collect_vec();
+            let unblocked_notifies = match self.blockers.read().get(&exchange_account_id) {
+                // there is nothing to wait for removed exchange account
+                None => return,
+                Some(blockers) => blockers
+                    .values()
+                    .map(|blocker| (blocker.unblocked_notify.clone(), blocker.id))
+                    .collect_vec(),
+            };
 
             if unblocked_notifies.is_empty() {
                 return;
@@ -738,9 +836,7 @@ impl ExchangeBlocker {
                             .blockers
                             .read()
                             .get(&exchange_account_id)
-                            .expect(EXPECTED_EAI_SHOULD_BE_CREATED)
-                            .get(&id.reason)
-                            .is_some()
+                            .is_some_and(|x| x.contains_key(&id.reason))
                         {
                             std::future::pending::<()>().await;
                         }
@@ -758,7 +854,12 @@ impl ExchangeBlocker {
             }
 
             // we can reblock some reasons while waiting others
-            if !self.is_blocked(exchange_account_id) {
+            let is_blocked = self
+                .blockers
+                .read()
+                .get(&exchange_account_id)
+                .is_some_and(|x| !x.is_empty());
+            if !is_blocked {
                 break;
             }
         }
@@ -785,8 +886,7 @@ impl ExchangeBlocker {
             let read_locks = self.blockers.read();
             let blocker = read_locks
                 .get(&exchange_account_id)
-                .expect(EXPECTED_EAI_SHOULD_BE_CREATED)
-                .get(&reason);
+                .and_then(|x| x.get(&reason));
             if let Some(blocker) = blocker {
                 blocker.unblocked_notify.clone()
             } else {
@@ -795,13 +895,11 @@ impl ExchangeBlocker {
         };
 
         let another_check_and_wait_for_cancel = async move {
-            let is_already_unblocked = self
+            let is_already_unblocked = !self
                 .blockers
                 .read()
                 .get(&exchange_account_id)
-                .expect(EXPECTED_EAI_SHOULD_BE_CREATED)
-                .get(&reason)
-                .is_none();
+                .is_some_and(|x| x.contains_key(&reason));
 
             if is_already_unblocked {
                 return;
@@ -841,7 +939,7 @@ mod tests {
     use crate::infrastructure::{init_lifetime_manager, spawn_future_ok};
     use futures::future::{join, join_all};
     use futures::FutureExt;
-    use mmb_domain::market::ExchangeAccountId;
+    use mmb_domain::market::{CurrencyPair, ExchangeAccountId};
     use mmb_utils::cancellation_token::CancellationToken;
     use mmb_utils::infrastructure::{with_timeout, SpawnFutureFlags};
     use mmb_utils::nothing_to_do;
@@ -890,6 +988,70 @@ mod tests {
         assert!(!exchange_blocker.is_blocked(exchange_account_id()));
     }
 
+    #[tokio::test]
+    #[timeout(120_000)]
+    async fn market_is_blocked_by_its_own_or_exchange_reason() {
+        let _ = init_lifetime_manager();
+        let exchange_blocker = exchange_blocker();
+        let btc_usdt = CurrencyPair::from_codes("btc".into(), "usdt".into());
+        let eth_btc = CurrencyPair::from_codes("eth".into(), "btc".into());
+
+        let market_reason = BlockReason::new("market_reason").for_market(btc_usdt);
+        exchange_blocker.block(exchange_account_id(), market_reason, Manual);
+        assert!(exchange_blocker.is_market_blocked(exchange_account_id(), btc_usdt));
+        assert!(!exchange_blocker.is_market_blocked(exchange_account_id(), eth_btc));
+        assert_eq!(market_reason.to_string(), "market_reason btc/usdt");
+
+        exchange_blocker.block(exchange_account_id(), "exchange_reason".into(), Manual);
+        assert!(exchange_blocker.is_market_blocked(exchange_account_id(), eth_btc));
+    }
+
+    #[tokio::test]
+    #[timeout(120_000)]
+    async fn active_blockers() {
+        let _ = init_lifetime_manager();
+        let exchange_blocker = exchange_blocker();
+
+        let manual_reason = "manual_reason".into();
+        let timed_reason = "timed_reason".into();
+        exchange_blocker.block(exchange_account_id(), manual_reason, Manual);
+        exchange_blocker.block(
+            exchange_account_id(),
+            timed_reason,
+            Timed(Duration::from_secs(60)),
+        );
+
+        let mut active_blockers = exchange_blocker.active_blockers();
+        active_blockers.sort_by_key(|x| x.reason.to_string());
+        assert_eq!(active_blockers.len(), 2);
+        assert_eq!(active_blockers[0].reason, manual_reason);
+        assert!(matches!(active_blockers[0].block_type, Manual));
+        assert_eq!(active_blockers[1].reason, timed_reason);
+        match active_blockers[1].block_type {
+            Timed(remaining) => assert!(remaining <= Duration::from_secs(60)),
+            Manual => panic!("Expected timed blocker"),
+        }
+    }
+
+    #[tokio::test]
+    #[timeout(120_000)]
+    async fn add_exchange_account() {
+        let _ = init_lifetime_manager();
+        let exchange_blocker = exchange_blocker();
+        let new_exchange_account_id = ExchangeAccountId::new("Binance", 1);
+
+        exchange_blocker.add_exchange_account(new_exchange_account_id);
+        assert!(!exchange_blocker.is_blocked(new_exchange_account_id));
+
+        exchange_blocker.block(new_exchange_account_id, "test_reason".into(), Manual);
+        assert!(exchange_blocker.is_blocked(new_exchange_account_id));
+        assert!(!exchange_blocker.is_blocked(exchange_account_id()));
+
+        // existing blockers are kept on repeated registration
+        exchange_blocker.add_exchange_account(new_exchange_account_id);
+        assert!(exchange_blocker.is_blocked(new_exchange_account_id));
+    }
+
     #[tokio::test]
     #[timeout(120_000)]
     async fn block_unblock_future() {
@@ -0,0 +1,94 @@
+use std::sync::atomic::{AtomicI64, Ordering};
+
+use chrono::Duration;
+use mmb_utils::DateTime;
+
+use crate::misc::time::time_manager;
+
+/// Single measurement of exchange server clock
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClockMeasurement {
+    /// Exchange server time minus local time
+    pub offset: Duration,
+    pub round_trip_delay: Duration,
+}
+
+impl ClockMeasurement {
+    /// Estimate offset by local times of sending request and receiving response with server time.
+    /// Network delays of request and response are supposed to be equal
+    pub fn new(request_time: DateTime, server_time: DateTime, response_time: DateTime) -> Self {
+        let round_trip_delay = response_time - request_time;
+        let offset = server_time - (request_time + round_trip_delay / 2);
+
+        Self {
+            offset,
+            round_trip_delay,
+        }
+    }
+}
+
+/// Exchange server clock estimated relative to the local clock.
+/// It is shared between exchange client for signing requests and `Exchange` for timestamps of events
+#[derive(Debug, Default)]
+pub struct ExchangeClock {
+    offset_ms: AtomicI64,
+    round_trip_delay_ms: AtomicI64,
+}
+
+impl ExchangeClock {
+    pub fn offset(&self) -> Duration {
+        Duration::milliseconds(self.offset_ms.load(Ordering::Acquire))
+    }
+
+    pub fn round_trip_delay(&self) -> Duration {
+        Duration::milliseconds(self.round_trip_delay_ms.load(Ordering::Acquire))
+    }
+
+    /// Current time of exchange server clock
+    pub fn now(&self) -> DateTime {
+        time_manager::now() + self.offset()
+    }
+
+    pub fn apply(&self, measurement: ClockMeasurement) {
+        self.offset_ms
+            .store(measurement.offset.num_milliseconds(), Ordering::Release);
+        self.round_trip_delay_ms.store(
+            measurement.round_trip_delay.num_milliseconds(),
+            Ordering::Release,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    #[test]
+    fn offset_is_measured_relative_to_middle_of_request() {
+        let request_time = Utc::now();
+        let response_time = request_time + Duration::milliseconds(200);
+        let server_time = request_time + Duration::milliseconds(1600);
+
+        let measurement = ClockMeasurement::new(request_time, server_time, response_time);
+
+        assert_eq!(measurement.round_trip_delay, Duration::milliseconds(200));
+        assert_eq!(measurement.offset, Duration::milliseconds(1500));
+    }
+
+    #[test]
+    fn clock_is_corrected_by_offset() {
+        let clock = ExchangeClock::default();
+        clock.apply(ClockMeasurement {
+            offset: Duration::milliseconds(-3000),
+            round_trip_delay: Duration::milliseconds(100),
+        });
+
+        let local_now = Utc::now();
+        let exchange_now = clock.now();
+
+        assert_eq!(clock.round_trip_delay(), Duration::milliseconds(100));
+        assert!(exchange_now <= local_now - Duration::milliseconds(2900));
+        assert!(exchange_now >= local_now - Duration::milliseconds(3100));
+    }
+}
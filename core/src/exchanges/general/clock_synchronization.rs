@@ -0,0 +1,134 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use mmb_utils::cancellation_token::CancellationToken;
+use mmb_utils::infrastructure::SpawnFutureFlags;
+
+use crate::exchanges::block_reasons::CLOCK_DRIFT_EXCEEDED;
+use crate::exchanges::exchange_blocker::BlockType;
+use crate::exchanges::exchange_clock::ClockMeasurement;
+use crate::exchanges::general::exchange::Exchange;
+use crate::exchanges::general::request_type::RequestType;
+use crate::infrastructure::spawn_by_timer_with_token;
+use crate::misc::time::time_manager;
+use crate::settings::ClockSyncSettings;
+
+impl Exchange {
+    /// Synchronize exchange clock once and then periodically if exchange client supports it
+    pub(crate) async fn start_clock_synchronization(self: &Arc<Self>) {
+        if self.exchange_client.get_clock().is_none() {
+            return;
+        }
+
+        if let Err(err) = self.synchronize_clock().await {
+            log::error!(
+                "Failed to synchronize clock on {}: {err:?}",
+                self.exchange_account_id
+            );
+        }
+
+        let period = Duration::from_secs(self.get_clock_sync_settings().period_secs);
+        let exchange_wk = Arc::downgrade(self);
+        spawn_by_timer_with_token(
+            "Synchronize exchange clock",
+            period,
+            period,
+            SpawnFutureFlags::STOP_BY_TOKEN | SpawnFutureFlags::DENY_CANCELLATION,
+            self.stop_token.clone(),
+            move || {
+                let exchange_wk = exchange_wk.clone();
+                async move {
+                    let exchange = match exchange_wk.upgrade() {
+                        None => return,
+                        Some(v) => v,
+                    };
+
+                    if let Err(err) = exchange.synchronize_clock().await {
+                        log::warn!(
+                            "Failed to synchronize clock on {}: {err:?}",
+                            exchange.exchange_account_id
+                        );
+                    }
+                }
+            },
+        );
+    }
+
+    /// Measure offset of exchange server clock and apply it to exchange clock.
+    /// Exchange is blocked while the offset exceeds `ClockSyncSettings::max_offset_ms`
+    pub async fn synchronize_clock(&self) -> Result<()> {
+        let settings = self.get_clock_sync_settings();
+
+        self.timeout_manager
+            .reserve_when_available(
+                self.exchange_account_id,
+                RequestType::GetServerTime,
+                None,
+                CancellationToken::new(),
+            )
+            .await;
+
+        let request_time = time_manager::now();
+        let server_time = self
+            .exchange_client
+            .get_server_time()
+            .await
+            .context("Unable to get server time")?;
+        let response_time = time_manager::now();
+
+        let measurement = ClockMeasurement::new(request_time, server_time, response_time);
+        let offset_ms = measurement.offset.num_milliseconds();
+        let round_trip_delay_ms = measurement.round_trip_delay.num_milliseconds();
+        if round_trip_delay_ms.unsigned_abs() > settings.max_round_trip_delay_ms {
+            log::warn!(
+                "Clock measurement on {} is skipped because of too long round-trip delay {round_trip_delay_ms}ms",
+                self.exchange_account_id
+            );
+            return Ok(());
+        }
+
+        log::trace!(
+            "Clock offset on {} is {offset_ms}ms with round-trip delay {round_trip_delay_ms}ms",
+            self.exchange_account_id
+        );
+        self.clock.apply(measurement);
+
+        let exchange_blocker = match self.exchange_blocker.upgrade() {
+            None => return Ok(()),
+            Some(v) => v,
+        };
+
+        let is_blocked =
+            exchange_blocker.is_blocked_by_reason(self.exchange_account_id, CLOCK_DRIFT_EXCEEDED);
+        let is_drift_exceeded = offset_ms.unsigned_abs() > settings.max_offset_ms;
+        if is_drift_exceeded && !is_blocked {
+            log::error!(
+                "Clock offset {offset_ms}ms on {} exceeds {}ms",
+                self.exchange_account_id,
+                settings.max_offset_ms
+            );
+            exchange_blocker.block(
+                self.exchange_account_id,
+                CLOCK_DRIFT_EXCEEDED,
+                BlockType::Manual,
+            );
+        } else if !is_drift_exceeded && is_blocked {
+            log::info!(
+                "Clock offset {offset_ms}ms on {} is acceptable again",
+                self.exchange_account_id
+            );
+            exchange_blocker.unblock(self.exchange_account_id, CLOCK_DRIFT_EXCEEDED);
+        }
+
+        Ok(())
+    }
+
+    fn get_clock_sync_settings(&self) -> ClockSyncSettings {
+        self.exchange_client
+            .get_settings()
+            .clock_sync
+            .clone()
+            .unwrap_or_default()
+    }
+}
@@ -18,6 +18,10 @@ pub struct EngineApi {
 
 #[cfg_attr(test, automock)]
 impl EngineApi {
+    pub fn new(exchange: Arc<Exchange>) -> Arc<Self> {
+        Arc::new(Self { exchange })
+    }
+
     pub async fn close_active_positions(
         &self,
         cancellation_token: CancellationToken,
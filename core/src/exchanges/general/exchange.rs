@@ -1,22 +1,27 @@
 use super::polling_timeout_manager::PollingTimeoutManager;
 use crate::balance::manager::balance_manager::BalanceManager;
 use crate::connectivity::{
-    websocket_open, ConnectivityError, WebSocketParams, WebSocketRole, WsSender,
+    websocket_open, ConnectivityError, ReconnectBackoff, WebSocketParams, WebSocketRole, WsSender,
 };
-use crate::exchanges::block_reasons::WEBSOCKET_DISCONNECTED;
+use crate::database::events::recorder::EventRecorder;
+use crate::exchanges::block_reasons::{ORDER_BOOK_INTEGRITY_VIOLATED, WEBSOCKET_DISCONNECTED};
 use crate::exchanges::exchange_blocker::{BlockType, ExchangeBlocker};
+use crate::exchanges::exchange_clock::ExchangeClock;
 use crate::exchanges::general::features::{BalancePositionOption, ExchangeFeatures};
 use crate::exchanges::general::order::cancel::CancelOrderResult;
 use crate::exchanges::general::order::create::CreateOrderResult;
 use crate::exchanges::general::request_type::RequestType;
 use crate::exchanges::timeouts::requests_timeout_manager_factory::RequestTimeoutArguments;
 use crate::exchanges::timeouts::timeout_manager::TimeoutManager;
+use crate::exchanges::traffic::recorder::TrafficRecorder;
 use crate::exchanges::traits::{ExchangeClient, ExchangeError};
 use crate::infrastructure::spawn_future;
 use crate::lifecycle::app_lifetime_manager::AppLifetimeManager;
+use crate::metrics::engine_metrics::METRICS;
 use crate::misc::time::time_manager;
 use crate::orders::buffered_fills::buffered_canceled_orders_manager::BufferedCanceledOrdersManager;
 use crate::orders::buffered_fills::buffered_fills_manager::BufferedFillsManager;
+use crate::settings::WebSocketSettings;
 use anyhow::{bail, Context, Result};
 use dashmap::DashMap;
 use function_name::named;
@@ -37,6 +42,7 @@ use mmb_domain::order::pool::OrdersPool;
 use mmb_domain::order::snapshot::OrderSide;
 use mmb_domain::order::snapshot::{Amount, Price};
 use mmb_domain::order::snapshot::{ClientOrderId, ExchangeOrderId};
+use mmb_domain::order_book::event::{OrderBookIntegrityEvent, OrderBookIntegrityViolation};
 use mmb_domain::position::{ActivePosition, ClosedPosition, DerivativePosition};
 use mmb_utils::cancellation_token::CancellationToken;
 use mmb_utils::infrastructure::SpawnFutureFlags;
@@ -46,7 +52,7 @@ use parking_lot::Mutex;
 use rust_decimal::Decimal;
 use std::fmt::Debug;
 use std::ops::DerefMut;
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::{Arc, Weak};
 use std::time::Duration;
 use tokio::sync::{broadcast, oneshot};
@@ -88,9 +94,13 @@ pub struct Exchange {
     pub leverage_by_currency_pair: DashMap<CurrencyPair, Decimal>,
     pub order_book_top: DashMap<CurrencyPair, OrderBookTop>,
     pub exchange_client: BoxExchangeClient,
+    /// Exchange server clock. It equals local clock if exchange client doesn't support synchronization
+    pub clock: Arc<ExchangeClock>,
     pub(super) features: ExchangeFeatures,
     pub(super) events_channel: broadcast::Sender<ExchangeEvent>,
     pub(super) lifetime_manager: Arc<AppLifetimeManager>,
+    /// Stops background tasks of the exchange account when it is removed at runtime
+    pub stop_token: CancellationToken,
     pub(super) commission: Commission,
     pub(super) wait_cancel_order: DashMap<ClientOrderId, broadcast::Sender<()>>,
     pub(super) wait_finish_order: DashMap<ClientOrderId, broadcast::Sender<OrderRef>>,
@@ -98,10 +108,13 @@ pub struct Exchange {
     pub(super) polling_timeout_manager: PollingTimeoutManager,
     pub(super) orders_finish_events: DashMap<ClientOrderId, oneshot::Sender<()>>,
     pub(super) orders_created_events: DashMap<ClientOrderId, oneshot::Sender<()>>,
+    /// Cancellation tokens of trigger watchers of emulated conditional orders
+    pub(super) emulated_conditional_orders: DashMap<ClientOrderId, CancellationToken>,
     pub(super) last_trades_update_time: DashMap<MarketId, DateTime>,
     pub(super) last_trades: DashMap<MarketId, Trade>,
     pub(super) timeout_manager: Arc<TimeoutManager>,
     pub(crate) balance_manager: Mutex<Option<Weak<Mutex<BalanceManager>>>>,
+    pub(super) event_recorder: Mutex<Option<Arc<EventRecorder>>>,
     pub(super) buffered_fills_manager: Mutex<BufferedFillsManager>,
     pub(super) buffered_canceled_orders_manager: Mutex<BufferedCanceledOrdersManager>,
     // It allows to send and receive notification about event in websocket channel
@@ -122,9 +135,15 @@ pub struct Exchange {
             Option<oneshot::Receiver<CancelOrderResult>>,
         ),
     >,
-    exchange_blocker: Weak<ExchangeBlocker>,
+    pub(super) exchange_blocker: Weak<ExchangeBlocker>,
     ws_sender: Mutex<Option<WsSender>>,
     auto_reconnect: AtomicBool,
+    /// Created from settings on the first reconnection
+    reconnect_backoff: Mutex<Option<ReconnectBackoff>>,
+    /// Time of the first disconnection since events were recovered last time
+    disconnected_at: Mutex<Option<DateTime>>,
+    /// Incremented on each disconnection to discard outdated recoveries
+    connection_epoch: AtomicU64,
 
     // Temporary fix before integration ExchangeBlocker to wait_order_finish/wait_cancel_order fallbacks #641
     timeout: Duration,
@@ -147,6 +166,7 @@ impl Exchange {
         commission: Commission,
     ) -> Arc<Self> {
         let polling_timeout_manager = PollingTimeoutManager::new(timeout_arguments);
+        let clock = exchange_client.get_clock().unwrap_or_default();
 
         Arc::new_cyclic(move |e| {
             Self::setup_exchange_client(e.clone(), exchange_client.as_mut());
@@ -155,10 +175,14 @@ impl Exchange {
             Self {
                 exchange_account_id,
                 exchange_client,
+                clock,
                 orders,
                 ws_sender: Default::default(),
                 order_creation_events: DashMap::new(),
                 order_cancellation_events: DashMap::new(),
+                stop_token: lifetime_manager
+                    .futures_cancellation_token
+                    .create_linked_token(),
                 lifetime_manager,
                 features,
                 events_channel,
@@ -173,14 +197,19 @@ impl Exchange {
                 polling_timeout_manager,
                 orders_finish_events: DashMap::new(),
                 orders_created_events: DashMap::new(),
+                emulated_conditional_orders: DashMap::new(),
                 leverage_by_currency_pair: DashMap::new(),
                 last_trades_update_time: DashMap::new(),
                 last_trades: DashMap::new(),
                 balance_manager: Mutex::new(None),
+                event_recorder: Mutex::new(None),
                 buffered_fills_manager: Default::default(),
                 exchange_blocker,
                 buffered_canceled_orders_manager: Default::default(),
                 auto_reconnect: AtomicBool::new(false),
+                reconnect_backoff: Mutex::new(None),
+                disconnected_at: Mutex::new(None),
+                connection_epoch: AtomicU64::new(0),
                 timeout,
             }
         })
@@ -276,11 +305,75 @@ impl Exchange {
         }
     }
 
-    fn on_connected(&self) {
+    fn on_connected(self: &Arc<Self>) {
         log::info!("Exchange account id {} connected", self.exchange_account_id);
-        if let Some(exchange_blocker) = self.exchange_blocker.upgrade() {
-            exchange_blocker.unblock(self.exchange_account_id, WEBSOCKET_DISCONNECTED);
+        if let Some(reconnect_backoff) = self.reconnect_backoff.lock().as_mut() {
+            reconnect_backoff.reset();
         }
+
+        let disconnected_at = match *self.disconnected_at.lock() {
+            None => {
+                if let Some(exchange_blocker) = self.exchange_blocker.upgrade() {
+                    exchange_blocker.unblock(self.exchange_account_id, WEBSOCKET_DISCONNECTED);
+                }
+                return;
+            }
+            Some(v) => v,
+        };
+
+        let epoch = self.connection_epoch.load(Ordering::SeqCst);
+        let action = format!(
+            "Exchange account id {} recover missed events",
+            self.exchange_account_id
+        );
+        spawn_future(
+            &action,
+            SpawnFutureFlags::STOP_BY_TOKEN,
+            Self::recover_after_reconnect(Arc::downgrade(self), epoch, disconnected_at),
+        );
+    }
+
+    /// Exchange stays blocked until events missed while disconnected are recovered.
+    /// Recovery is dropped if websocket was disconnected again after connection of `epoch`
+    async fn recover_after_reconnect(
+        self_weak: Weak<Self>,
+        epoch: u64,
+        disconnected_at: DateTime,
+    ) -> Result<()> {
+        loop {
+            let self_strong = match self_weak.upgrade() {
+                None => return Ok(()),
+                Some(v) => v,
+            };
+            let id = self_strong.exchange_account_id;
+            if self_strong.connection_epoch.load(Ordering::SeqCst) != epoch {
+                // disconnected again, recovery will be started after next connection
+                return Ok(());
+            }
+
+            match self_strong.recover_missed_events(disconnected_at).await {
+                Ok(()) => break,
+                Err(err) => {
+                    log::error!(
+                        "Exchange account id {id} failed to recover missed events: {err:?}"
+                    );
+                    let delay = self_strong.next_reconnect_delay();
+                    drop(self_strong);
+                    sleep(delay).await;
+                }
+            }
+        }
+
+        if let Some(self_strong) = self_weak.upgrade() {
+            if self_strong.connection_epoch.load(Ordering::SeqCst) == epoch {
+                self_strong.disconnected_at.lock().take();
+                if let Some(exchange_blocker) = self_strong.exchange_blocker.upgrade() {
+                    exchange_blocker
+                        .unblock(self_strong.exchange_account_id, WEBSOCKET_DISCONNECTED);
+                }
+            }
+        }
+        Ok(())
     }
 
     fn on_disconnected(self: &Arc<Self>) {
@@ -310,10 +403,17 @@ impl Exchange {
         if !self.auto_reconnect.load(Ordering::SeqCst) {
             return;
         }
+
+        self.start_reconnection();
+
         let id = self.exchange_account_id;
+        let delay = self.next_reconnect_delay();
+        log::info!("Exchange account id {id} will reconnect in {delay:?}");
+
         let action = format!("Exchange account id {} reconnect", id);
         let self_weak = Arc::downgrade(self);
         let future = async move {
+            sleep(delay).await;
             if let Some(self_strong) = self_weak.upgrade() {
                 if let Err(e) = self_strong.connect_ws().await {
                     log::error!("Exchange account id {} failed to reconnect: {:?}", id, e)
@@ -324,6 +424,35 @@ impl Exchange {
         spawn_future(&action, SpawnFutureFlags::STOP_BY_TOKEN, future);
     }
 
+    /// New connection epoch stops recovery started after previous connection.
+    /// Time of the first disconnection is kept until missed events are recovered
+    fn start_reconnection(&self) {
+        let _ = self.connection_epoch.fetch_add(1, Ordering::SeqCst);
+        if METRICS.is_enabled() {
+            METRICS
+                .websocket_reconnects
+                .inc(&[&self.exchange_account_id.to_string()]);
+        }
+        self.disconnected_at
+            .lock()
+            .get_or_insert_with(time_manager::now);
+    }
+
+    fn next_reconnect_delay(&self) -> Duration {
+        self.reconnect_backoff
+            .lock()
+            .get_or_insert_with(|| ReconnectBackoff::new(&self.get_websocket_settings()))
+            .next_delay()
+    }
+
+    fn get_websocket_settings(&self) -> WebSocketSettings {
+        self.exchange_client
+            .get_settings()
+            .websocket
+            .clone()
+            .unwrap_or_default()
+    }
+
     fn maybe_log_websocket_message(&self, msg: &str) {
         if self.exchange_client.should_log_message(msg) {
             log::info!("Websocket message from {}: {msg}", self.exchange_account_id);
@@ -334,6 +463,10 @@ impl Exchange {
         *self.balance_manager.lock() = Some(Arc::downgrade(&balance_manager));
     }
 
+    pub fn setup_event_recorder(&self, event_recorder: Arc<EventRecorder>) {
+        *self.event_recorder.lock() = Some(event_recorder);
+    }
+
     pub async fn reconnect_ws(self: &Arc<Self>) -> Result<()> {
         self.disconnect_ws().await;
         self.connect_ws().await
@@ -465,7 +598,15 @@ impl Exchange {
         role: WebSocketRole,
     ) -> Result<WebSocketParams> {
         let ws_url = self.exchange_client.create_ws_url(role).await?;
-        Ok(WebSocketParams::new(ws_url))
+        let settings = self.exchange_client.get_settings();
+        let websocket_settings = self.get_websocket_settings();
+        Ok(WebSocketParams::new(ws_url)
+            .with_network_settings(settings.network.clone())
+            .with_traffic_recorder(TrafficRecorder::for_exchange_account(settings))
+            .with_heartbeat(
+                Duration::from_millis(websocket_settings.heartbeat_interval_ms),
+                Duration::from_millis(websocket_settings.heartbeat_fail_timeout_ms),
+            ))
     }
 
     pub(crate) fn add_event_on_order_change(
@@ -495,6 +636,8 @@ impl Exchange {
         cancellation_token: CancellationToken,
         add_missing_open_orders: bool,
     ) {
+        self.cancel_emulated_conditional_orders();
+
         match self.get_open_orders(add_missing_open_orders).await {
             Err(error) => {
                 log::error!(
@@ -686,19 +829,100 @@ impl Exchange {
         };
 
         if let Some(positions) = &balance_result.positions {
-            for position in positions {
-                if let Some(mut leverage) = self
-                    .leverage_by_currency_pair
-                    .get_mut(&position.currency_pair)
-                {
-                    *leverage.value_mut() = position.leverage;
-                }
-            }
+            self.update_leverage_by_positions(positions);
         }
 
         Ok(balance_result)
     }
 
+    /// Remember leverage of known currency pairs received with positions from the exchange
+    pub(crate) fn update_leverage_by_positions(&self, positions: &[DerivativePosition]) {
+        for position in positions {
+            if let Some(mut leverage) = self
+                .leverage_by_currency_pair
+                .get_mut(&position.currency_pair)
+            {
+                *leverage.value_mut() = position.leverage;
+            }
+        }
+    }
+
+    /// Stop trading on the market until local order book of currency pair becomes valid again
+    /// and ask exchange client for a fresh order book snapshot.
+    /// If exchange client can't resync order book, orders on the market are cancelled
+    /// because the market stays blocked until the exchange sends a fresh snapshot itself
+    pub(crate) fn order_book_integrity_violated(
+        self: &Arc<Self>,
+        currency_pair: CurrencyPair,
+        violation: OrderBookIntegrityViolation,
+    ) {
+        log::warn!(
+            "Order book integrity violated for {currency_pair} on {}: {violation:?}",
+            self.exchange_account_id
+        );
+
+        let _ = self.order_book_top.remove(&currency_pair);
+
+        if let Some(exchange_blocker) = self.exchange_blocker.upgrade() {
+            exchange_blocker.block(
+                self.exchange_account_id,
+                ORDER_BOOK_INTEGRITY_VIOLATED.for_market(currency_pair),
+                BlockType::Manual,
+            );
+        }
+
+        self.events_channel
+            .send_expected(ExchangeEvent::OrderBookIntegrityViolated(
+                OrderBookIntegrityEvent {
+                    exchange_account_id: self.exchange_account_id,
+                    currency_pair,
+                    violation,
+                    time: time_manager::now(),
+                },
+            ));
+
+        if self.exchange_client.can_resync_order_book() {
+            self.exchange_client
+                .on_order_book_integrity_violated(currency_pair)
+                .unwrap_or_else(|err| {
+                    log::error!(
+                        "Failed to request order book resync for {currency_pair} on {}: {err:?}",
+                        self.exchange_account_id
+                    )
+                });
+            return;
+        }
+
+        log::warn!(
+            "Order book for {currency_pair} on {} can't be resynced, orders are cancelled until a fresh snapshot",
+            self.exchange_account_id
+        );
+
+        let action = format!(
+            "Cancel orders of {currency_pair} on {} with broken order book",
+            self.exchange_account_id
+        );
+        let exchange = self.clone();
+        spawn_future(&action, SpawnFutureFlags::STOP_BY_TOKEN, async move {
+            exchange.cancel_all_orders(currency_pair).await
+        });
+    }
+
+    /// Called when order book of currency pair is valid again
+    pub(crate) fn order_book_integrity_restored(&self, currency_pair: CurrencyPair) {
+        log::info!(
+            "Order book integrity restored for {currency_pair} on {}",
+            self.exchange_account_id
+        );
+
+        if let Some(exchange_blocker) = self.exchange_blocker.upgrade() {
+            exchange_blocker.unblock(
+                self.exchange_account_id,
+                ORDER_BOOK_INTEGRITY_VIOLATED.for_market(currency_pair),
+            );
+        }
+    }
+
     /// Remove currency pairs that aren't supported by the current exchange
     /// if all currencies aren't supported return None
     fn remove_unknown_currency_pairs(
@@ -859,3 +1083,94 @@ fn print_warn(
 ) {
     log::warn!("Failed to {fn_name} for {exchange_account_id} on retry {retry_attempt}: {error:?}");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exchanges::general::test_helper::{get_test_exchange_with_blocker, TestClient};
+    use crate::infrastructure::init_lifetime_manager;
+
+    fn block_by_disconnection(exchange: &Exchange, exchange_blocker: &Arc<ExchangeBlocker>) {
+        exchange_blocker.block(
+            exchange.exchange_account_id,
+            WEBSOCKET_DISCONNECTED,
+            BlockType::Manual,
+        );
+        exchange.start_reconnection();
+    }
+
+    async fn wait_unblock(exchange: &Exchange, exchange_blocker: &ExchangeBlocker) {
+        tokio::time::timeout(
+            Duration::from_secs(1),
+            exchange_blocker.wait_unblock(exchange.exchange_account_id, CancellationToken::new()),
+        )
+        .await
+        .expect("exchange should be unblocked");
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn unblock_after_missed_events_recovered() {
+        let _ = init_lifetime_manager();
+        let (exchange, exchange_blocker, _rx) =
+            get_test_exchange_with_blocker(TestClient::default());
+        block_by_disconnection(&exchange, &exchange_blocker);
+        let disconnected_at = exchange.disconnected_at.lock().expect("in test");
+        let epoch = exchange.connection_epoch.load(Ordering::SeqCst);
+
+        Exchange::recover_after_reconnect(Arc::downgrade(&exchange), epoch, disconnected_at)
+            .await
+            .expect("in test");
+
+        wait_unblock(&exchange, &exchange_blocker).await;
+        assert_eq!(*exchange.disconnected_at.lock(), None);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn keep_blocked_if_disconnected_again_before_recovery() {
+        let _ = init_lifetime_manager();
+        let (exchange, exchange_blocker, _rx) =
+            get_test_exchange_with_blocker(TestClient::default());
+        block_by_disconnection(&exchange, &exchange_blocker);
+        let disconnected_at = exchange.disconnected_at.lock().expect("in test");
+        let epoch = exchange.connection_epoch.load(Ordering::SeqCst);
+        block_by_disconnection(&exchange, &exchange_blocker);
+
+        Exchange::recover_after_reconnect(Arc::downgrade(&exchange), epoch, disconnected_at)
+            .await
+            .expect("in test");
+
+        assert!(exchange_blocker.is_blocked(exchange.exchange_account_id));
+        // time of the first disconnection is kept for the next recovery
+        assert_eq!(*exchange.disconnected_at.lock(), Some(disconnected_at));
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn unblock_on_connected_without_missed_events() {
+        let _ = init_lifetime_manager();
+        let (exchange, exchange_blocker, _rx) =
+            get_test_exchange_with_blocker(TestClient::default());
+        exchange_blocker.block(
+            exchange.exchange_account_id,
+            WEBSOCKET_DISCONNECTED,
+            BlockType::Manual,
+        );
+
+        exchange.on_connected();
+
+        wait_unblock(&exchange, &exchange_blocker).await;
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn block_on_disconnected() {
+        let _ = init_lifetime_manager();
+        let (exchange, exchange_blocker, _rx) =
+            get_test_exchange_with_blocker(TestClient::default());
+
+        exchange.on_disconnected();
+
+        assert!(exchange_blocker.is_blocked(exchange.exchange_account_id));
+        // no reconnection without auto reconnect
+        assert_eq!(exchange.connection_epoch.load(Ordering::SeqCst), 0);
+        assert_eq!(*exchange.disconnected_at.lock(), None);
+    }
+}
@@ -1,6 +1,11 @@
 use std::sync::{Arc, Weak};
 
+use anyhow::{Context, Result};
+
+use crate::connectivity::WebSocketRole;
 use crate::exchanges::exchange_blocker::ExchangeBlocker;
+use crate::exchanges::general::exchange::BoxExchangeClient;
+use crate::exchanges::simulation::paper_trading::PaperTrading;
 use crate::lifecycle::app_lifetime_manager::AppLifetimeManager;
 use crate::lifecycle::launcher::EngineBuildConfig;
 use crate::settings::ExchangeSettings;
@@ -52,21 +57,61 @@ pub async fn create_exchange(
     exchange_blocker: Weak<ExchangeBlocker>,
 ) -> Arc<Exchange> {
     let exchange_account_id = user_settings.exchange_account_id;
-    let exchange_client_builder =
-        &build_settings.supported_exchange_clients[&exchange_account_id.exchange_id];
+    try_create_exchange(
+        user_settings,
+        build_settings,
+        events_channel,
+        lifetime_manager,
+        timeout_manager,
+        exchange_blocker,
+    )
+    .await
+    .with_expect(move || format!("Failed to create exchange {exchange_account_id}"))
+}
+
+/// Create exchange and connect to its websockets
+pub async fn try_create_exchange(
+    user_settings: &ExchangeSettings,
+    build_settings: &EngineBuildConfig,
+    events_channel: broadcast::Sender<ExchangeEvent>,
+    lifetime_manager: Arc<AppLifetimeManager>,
+    timeout_manager: Arc<TimeoutManager>,
+    exchange_blocker: Weak<ExchangeBlocker>,
+) -> Result<Arc<Exchange>> {
+    let exchange_account_id = user_settings.exchange_account_id;
+    let exchange_client_builder = build_settings
+        .supported_exchange_clients
+        .get(&exchange_account_id.exchange_id)
+        .with_context(|| {
+            format!(
+                "Exchange {} isn't supported",
+                exchange_account_id.exchange_id
+            )
+        })?;
     let orders = OrdersPool::new();
 
-    let exchange_client = exchange_client_builder.create_exchange_client(
-        user_settings.clone(),
-        events_channel.clone(),
-        lifetime_manager.clone(),
-        timeout_manager.clone(),
-        orders.clone(),
-    );
+    let exchange_client = exchange_client_builder
+        .create_exchange_client(
+            user_settings.clone(),
+            events_channel.clone(),
+            lifetime_manager.clone(),
+            timeout_manager.clone(),
+            orders.clone(),
+        )
+        .with_context(|| format!("Failed to create client of exchange {exchange_account_id}"))?;
+
+    let client = match &user_settings.paper_trading {
+        Some(paper_trading_settings) => Box::new(PaperTrading::new(
+            exchange_client.client,
+            paper_trading_settings,
+            events_channel.clone(),
+        )) as BoxExchangeClient,
+        None => exchange_client.client,
+    };
 
     let exchange = Exchange::new(
         exchange_account_id,
-        exchange_client.client,
+        client,
         orders,
         exchange_client.features,
         exchange_client_builder.get_timeout_arguments(),
@@ -77,14 +122,20 @@ pub async fn create_exchange(
         Commission::default(),
     );
 
+    exchange.start_clock_synchronization().await;
+
     exchange.build_symbols(&user_settings.currency_pairs).await;
 
-    exchange
-        .connect_ws()
-        .await
-        .with_expect(move || "Failed to connect to websockets on exchange {exchange_account_id}");
+    if exchange
+        .exchange_client
+        .is_websocket_enabled(WebSocketRole::Main)
+    {
+        exchange.connect_ws().await.with_context(|| {
+            format!("Failed to connect to websockets on exchange {exchange_account_id}")
+        })?;
+    }
 
     exchange.exchange_client.initialized(exchange.clone()).await;
 
-    exchange
+    Ok(exchange)
 }
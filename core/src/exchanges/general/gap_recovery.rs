@@ -0,0 +1,445 @@
+use std::collections::HashSet;
+
+use anyhow::{bail, Context, Result};
+use itertools::Itertools;
+use mmb_domain::market::ExchangeErrorType;
+use mmb_domain::order::fill::EventSourceType;
+use mmb_domain::order::pool::OrderRef;
+use mmb_domain::order::snapshot::{OrderInfo, OrderStatus};
+use mmb_utils::cancellation_token::CancellationToken;
+use mmb_utils::{nothing_to_do, DateTime};
+
+use crate::exchanges::general::exchange::{Exchange, RequestResult};
+use crate::exchanges::general::features::RestFillsType;
+use crate::exchanges::general::request_type::RequestType;
+
+impl Exchange {
+    /// Recover order events missed while websocket was disconnected:
+    /// orders without exchange order id are resolved by client order id,
+    /// fills are taken from trades since `disconnected_at` and
+    /// orders which disappeared from open orders are checked by order info.
+    /// Returns error if some order can't be recovered, so exchange should stay blocked
+    pub(crate) async fn recover_missed_events(&self, disconnected_at: DateTime) -> Result<()> {
+        let not_finished_orders = self
+            .orders
+            .not_finished
+            .iter()
+            .map(|x| x.value().clone())
+            .collect_vec();
+
+        log::info!(
+            "Recovering events of {} orders missed since {disconnected_at} on {}",
+            not_finished_orders.len(),
+            self.exchange_account_id
+        );
+
+        let open_orders = self
+            .get_open_orders(false)
+            .await
+            .context("Unable to get open orders")?;
+
+        for order in &not_finished_orders {
+            if order.exchange_order_id().is_none() {
+                self.recover_exchange_order_id(order, &open_orders).await?;
+            }
+        }
+
+        let orders = not_finished_orders
+            .into_iter()
+            .filter(|order| order.exchange_order_id().is_some())
+            .collect_vec();
+
+        match self.features.rest_fills_features.fills_type {
+            RestFillsType::None => {}
+            RestFillsType::MyTrades => self.recover_missed_trades(&orders, disconnected_at).await?,
+            RestFillsType::GetOrderInfo => {
+                for order in &orders {
+                    let symbol = match self.symbols.get(&order.currency_pair()) {
+                        None => continue,
+                        Some(v) => v.clone(),
+                    };
+                    let result = self
+                        .check_order_fills_using_request_type(
+                            order,
+                            &symbol,
+                            RequestType::GetOrderInfo,
+                            None,
+                            CancellationToken::new(),
+                        )
+                        .await?;
+                    if let Some(error) = result.get_error() {
+                        bail!(
+                            "Unable to check fills of {}: {error:?}",
+                            order.client_order_id()
+                        );
+                    }
+                }
+            }
+        }
+
+        let open_order_ids = open_orders
+            .into_iter()
+            .map(|x| x.exchange_order_id)
+            .collect::<HashSet<_>>();
+        for order in &orders {
+            let is_open = order
+                .exchange_order_id()
+                .map(|x| open_order_ids.contains(&x))
+                .unwrap_or(false);
+            if !is_open && !order.status().is_finished() {
+                self.recover_closed_order(order).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn recover_missed_trades(
+        &self,
+        orders: &[OrderRef],
+        disconnected_at: DateTime,
+    ) -> Result<()> {
+        let currency_pairs = orders
+            .iter()
+            .map(|x| x.currency_pair())
+            .unique()
+            .collect_vec();
+        for currency_pair in currency_pairs {
+            let symbol = match self.symbols.get(&currency_pair) {
+                None => continue,
+                Some(v) => v.clone(),
+            };
+
+            self.timeout_manager
+                .reserve_when_available(
+                    self.exchange_account_id,
+                    RequestType::GetMyTrades,
+                    None,
+                    CancellationToken::new(),
+                )
+                .await
+                .into_result()?;
+
+            let trades = match self
+                .exchange_client
+                .get_my_trades(&symbol, Some(disconnected_at))
+                .await
+            {
+                RequestResult::Success(trades) => trades,
+                RequestResult::Error(error) => {
+                    bail!("Unable to get trades for {currency_pair}: {error:?}")
+                }
+            };
+
+            for trade in &trades {
+                let order = orders.iter().find(|order| {
+                    order.exchange_order_id().as_ref() == Some(&trade.exchange_order_id)
+                });
+                let order = match order {
+                    None => continue,
+                    Some(v) => v,
+                };
+
+                let trade_id = Some(&trade.trade_id);
+                let is_fill_exists =
+                    order.fn_ref(|o| o.fills.fills.iter().any(|fill| fill.trade_id() == trade_id));
+                if !is_fill_exists {
+                    self.handle_order_filled_for_rest_fallback(order, trade);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Creation response of order could be missed while disconnected, so order is looked for
+    /// by client order id. Order which isn't found is left to creation fallback
+    async fn recover_exchange_order_id(
+        &self,
+        order: &OrderRef,
+        open_orders: &[OrderInfo],
+    ) -> Result<()> {
+        let client_order_id = order.client_order_id();
+        let open_order = open_orders
+            .iter()
+            .find(|x| x.client_order_id == client_order_id);
+        let exchange_order_id = match open_order {
+            Some(open_order) => open_order.exchange_order_id.clone(),
+            None => {
+                if !self
+                    .features
+                    .order_features
+                    .supports_get_order_info_by_client_order_id
+                {
+                    log::info!(
+                        "Order {client_order_id} without exchange order id isn't open on {}",
+                        self.exchange_account_id
+                    );
+                    return Ok(());
+                }
+
+                match self.request_order_info(order).await? {
+                    Some(order_info) => order_info.exchange_order_id,
+                    None => {
+                        log::info!(
+                            "Order {client_order_id} isn't created on {} yet",
+                            self.exchange_account_id
+                        );
+                        return Ok(());
+                    }
+                }
+            }
+        };
+
+        log::info!(
+            "Order {client_order_id} was created with exchange order id {exchange_order_id} while websocket was disconnected on {}",
+            self.exchange_account_id
+        );
+        self.handle_create_order_succeeded(
+            self.exchange_account_id,
+            &client_order_id,
+            &exchange_order_id,
+            EventSourceType::RestFallback,
+        )
+    }
+
+    /// Order isn't open on exchange anymore, so it was filled or canceled while disconnected
+    async fn recover_closed_order(&self, order: &OrderRef) -> Result<()> {
+        let order_info = self.request_order_info(order).await?.with_context(|| {
+            format!(
+                "Order {} isn't found on {}",
+                order.client_order_id(),
+                self.exchange_account_id
+            )
+        })?;
+
+        let filled_amount = order.fn_ref(|x| x.fills.filled_amount);
+        if order_info.filled_amount > filled_amount && !order.status().is_finished() {
+            log::info!(
+                "Order {} was filled by {} while websocket was disconnected on {}",
+                order.client_order_id(),
+                order_info.filled_amount - filled_amount,
+                self.exchange_account_id
+            );
+            self.handle_order_filled_by_order_info(order, &order_info);
+        }
+
+        match order_info.order_status {
+            OrderStatus::Canceled if !order.status().is_finished() => {
+                log::info!(
+                    "Order {} was canceled while websocket was disconnected on {}",
+                    order.client_order_id(),
+                    self.exchange_account_id
+                );
+                self.handle_cancel_order_succeeded(
+                    Some(&order.client_order_id()),
+                    &order_info.exchange_order_id,
+                    Some(order_info.filled_amount),
+                    EventSourceType::RestFallback,
+                );
+            }
+            OrderStatus::Completed if !order.status().is_finished() => bail!(
+                "Order {} was completed while websocket was disconnected but its fills weren't recovered on {}",
+                order.client_order_id(),
+                self.exchange_account_id
+            ),
+            _ => nothing_to_do(),
+        }
+
+        Ok(())
+    }
+
+    /// Returns `None` if order isn't found on exchange
+    async fn request_order_info(&self, order: &OrderRef) -> Result<Option<OrderInfo>> {
+        self.timeout_manager
+            .reserve_when_available(
+                self.exchange_account_id,
+                RequestType::GetOrderInfo,
+                None,
+                CancellationToken::new(),
+            )
+            .await
+            .into_result()?;
+
+        match self.get_order_info(order).await {
+            Ok(order_info) => Ok(Some(order_info)),
+            Err(error) if error.error_type == ExchangeErrorType::OrderNotFound => Ok(None),
+            Err(error) => bail!(
+                "Unable to get order info of {}: {error:?}",
+                order.client_order_id()
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use chrono::Utc;
+    use mmb_domain::order::snapshot::{
+        Amount, ClientOrderId, ExchangeOrderId, OrderRole, OrderSide, OrderSnapshot, OrderType,
+    };
+    use parking_lot::RwLock;
+    use rust_decimal_macros::dec;
+
+    use super::*;
+    use crate::exchanges::exchange_blocker::ExchangeBlocker;
+    use crate::exchanges::general::test_helper::{get_test_exchange_with_blocker, TestClient};
+    use crate::infrastructure::init_lifetime_manager;
+    use mmb_domain::events::ExchangeEvent;
+    use tokio::sync::broadcast;
+
+    const EXCHANGE_ORDER_ID: &str = "exchange_order_id";
+
+    fn init_exchange() -> (
+        Arc<Exchange>,
+        Arc<ExchangeBlocker>,
+        broadcast::Receiver<ExchangeEvent>,
+    ) {
+        let _ = init_lifetime_manager();
+        get_test_exchange_with_blocker(TestClient::default())
+    }
+
+    fn add_order(exchange: &Exchange, exchange_order_id: Option<&str>) -> OrderRef {
+        let mut order = OrderSnapshot::with_params(
+            ClientOrderId::unique_id(),
+            OrderType::Limit,
+            Some(OrderRole::Maker),
+            exchange.exchange_account_id,
+            exchange
+                .symbols
+                .iter()
+                .next()
+                .expect("in test")
+                .currency_pair(),
+            dec!(0.2),
+            dec!(5),
+            OrderSide::Buy,
+            None,
+            "FromTest",
+        );
+        if let Some(exchange_order_id) = exchange_order_id {
+            order.props.exchange_order_id = Some(exchange_order_id.into());
+            order.set_status(OrderStatus::Created, Utc::now());
+        }
+
+        let order_ref = exchange
+            .orders
+            .add_snapshot_initial(Arc::new(RwLock::new(order)));
+        if let Some(exchange_order_id) = exchange_order_id {
+            let _ = exchange
+                .orders
+                .cache_by_exchange_id
+                .insert(exchange_order_id.into(), order_ref.clone());
+        }
+        order_ref
+    }
+
+    fn order_info(order: &OrderRef, status: OrderStatus, filled_amount: Amount) -> OrderInfo {
+        OrderInfo::new(
+            order.currency_pair(),
+            ExchangeOrderId::from(EXCHANGE_ORDER_ID),
+            order.client_order_id(),
+            OrderSide::Buy,
+            status,
+            dec!(0.2),
+            dec!(5),
+            dec!(0.2),
+            filled_amount,
+            None,
+            None,
+            None,
+        )
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn apply_fills_of_order_completed_while_disconnected() {
+        let (exchange, _exchange_blocker, _rx) = init_exchange();
+        let order = add_order(&exchange, Some(EXCHANGE_ORDER_ID));
+        exchange_client(&exchange)
+            .order_infos
+            .lock()
+            .push(order_info(&order, OrderStatus::Completed, dec!(5)));
+
+        exchange
+            .recover_missed_events(Utc::now())
+            .await
+            .expect("in test");
+
+        assert_eq!(order.status(), OrderStatus::Completed);
+        assert_eq!(order.fn_ref(|x| x.fills.filled_amount), dec!(5));
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn fail_if_fills_of_completed_order_are_not_recovered() {
+        let (exchange, _exchange_blocker, _rx) = init_exchange();
+        let order = add_order(&exchange, Some(EXCHANGE_ORDER_ID));
+        exchange_client(&exchange)
+            .order_infos
+            .lock()
+            .push(order_info(&order, OrderStatus::Completed, dec!(0)));
+
+        let result = exchange.recover_missed_events(Utc::now()).await;
+
+        assert!(result.is_err());
+        assert_eq!(order.status(), OrderStatus::Created);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn cancel_order_canceled_while_disconnected() {
+        let (exchange, _exchange_blocker, _rx) = init_exchange();
+        let order = add_order(&exchange, Some(EXCHANGE_ORDER_ID));
+        exchange_client(&exchange)
+            .order_infos
+            .lock()
+            .push(order_info(&order, OrderStatus::Canceled, dec!(0)));
+
+        exchange
+            .recover_missed_events(Utc::now())
+            .await
+            .expect("in test");
+
+        assert_eq!(order.status(), OrderStatus::Canceled);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn resolve_order_without_exchange_order_id_by_client_order_id() {
+        let (exchange, _exchange_blocker, _rx) = init_exchange();
+        let order = add_order(&exchange, None);
+        exchange_client(&exchange)
+            .open_orders
+            .lock()
+            .push(order_info(&order, OrderStatus::Created, dec!(0)));
+
+        exchange
+            .recover_missed_events(Utc::now())
+            .await
+            .expect("in test");
+
+        assert_eq!(order.status(), OrderStatus::Created);
+        assert_eq!(order.exchange_order_id(), Some(EXCHANGE_ORDER_ID.into()));
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn keep_creating_order_which_is_not_found_on_exchange() {
+        let (exchange, _exchange_blocker, _rx) = init_exchange();
+        let order = add_order(&exchange, None);
+
+        exchange
+            .recover_missed_events(Utc::now())
+            .await
+            .expect("in test");
+
+        assert_eq!(order.status(), OrderStatus::Creating);
+        assert_eq!(order.exchange_order_id(), None);
+    }
+
+    fn exchange_client(exchange: &Exchange) -> &TestClient {
+        exchange
+            .exchange_client
+            .as_any()
+            .downcast_ref::<TestClient>()
+            .expect("in test")
+    }
+}
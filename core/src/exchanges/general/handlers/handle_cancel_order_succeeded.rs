@@ -105,7 +105,7 @@ impl Exchange {
             order.internal_props.is_canceling_from_wait_cancel_order
         });
 
-        // Here we cover the situation with MakerOnly orders
+        // Here we cover the situation with MakerOnly orders and orders with IOC/FOK time in force
         // As soon as we created an order, it was automatically canceled
         // Usually we raise CancelOrderSucceeded in WaitCancelOrder after a check for fills via fallback
         // but in this particular case the cancellation is triggered by exchange itself, so WaitCancelOrder was never called
@@ -1,4 +1,6 @@
 use crate::exchanges::general::handlers::should_ignore_event;
+use crate::misc::time::time_manager;
+use crate::orders::events::OrderFillRecord;
 use crate::{exchanges::general::exchange::Exchange, math::ConvertPercentToRate};
 use chrono::Utc;
 use function_name::named;
@@ -109,6 +111,10 @@ impl Exchange {
             panic!("Received HandleOrderFilled with an empty exchangeOrderId {args_to_log:?}",);
         }
 
+        if fill_event.fill_date.is_none() {
+            fill_event.fill_date = Some(self.clock.now());
+        }
+
         self.add_special_order_if_need(fill_event, &args_to_log);
 
         match self
@@ -522,7 +528,7 @@ impl Exchange {
         let order_fill = OrderFill::new(
             Uuid::new_v4(),
             Some(ClientOrderFillId::unique_id()),
-            Utc::now(),
+            time_manager::now(),
             fill_type,
             trade_id.clone(),
             rounded_fill_price,
@@ -545,7 +551,18 @@ impl Exchange {
             self.exchange_account_id
         );
 
-        order_ref.fn_mut(move |order| order.add_fill(order_fill));
+        order_ref.fn_mut(|order| {
+            self.save_order_fill(order, &order_fill);
+            order.add_fill(order_fill)
+        });
+    }
+
+    fn save_order_fill(&self, order: &OrderSnapshot, order_fill: &OrderFill) {
+        if let Some(event_recorder) = self.event_recorder.lock().as_ref() {
+            event_recorder
+                .save(OrderFillRecord::new(order, order_fill))
+                .unwrap_or_else(|err| log::error!("unable save order fill: {err:?}"));
+        }
     }
 
     fn create_and_add_order_fill(&self, fill_event: &mut FillEvent, order_ref: &OrderRef) {
@@ -645,8 +662,6 @@ impl Exchange {
         }
 
         self.react_if_order_completed(order_filled_amount, order_ref);
-
-        // TODO DataRecorder.save(order)
     }
 
     fn add_special_order_if_need(&self, fill_event: &mut FillEvent, args_to_log: &ArgsToLog) {
@@ -745,6 +760,8 @@ mod test {
     use uuid::Uuid;
 
     use super::*;
+    use crate::exchanges::exchange_clock::ClockMeasurement;
+    use crate::infrastructure::init_lifetime_manager;
     use crate::{
         exchanges::general::exchange::OrderBookTop, exchanges::general::exchange::PriceLevel,
         exchanges::general::test_helper, exchanges::general::test_helper::create_order_ref,
@@ -2477,6 +2494,62 @@ mod test {
         assert_eq!(first_fill.commission_amount(), commission_amount);
     }
 
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn receive_time_of_fill_is_local_time() {
+        let _ = init_lifetime_manager();
+        let (exchange, _event_receiver) = get_test_exchange(false);
+        exchange.clock.apply(ClockMeasurement {
+            offset: chrono::Duration::hours(-1),
+            round_trip_delay: chrono::Duration::zero(),
+        });
+
+        let currency_pair = CurrencyPair::from_codes("PHB".into(), "BTC".into());
+        let mut fill_event = FillEvent {
+            source_type: EventSourceType::WebSocket,
+            trade_id: Some(trade_id_from_str("test_trade_id")),
+            client_order_id: None,
+            exchange_order_id: ExchangeOrderId::new("".into()),
+            fill_price: dec!(0.8),
+            fill_amount: FillAmount::Incremental {
+                fill_amount: dec!(5),
+                total_filled_amount: None,
+            },
+            order_role: None,
+            commission_currency_code: None,
+            commission_rate: None,
+            commission_amount: Some(dec!(0.001)),
+            fill_type: OrderFillType::Liquidation,
+            special_order_data: Some(SpecialOrderData {
+                currency_pair,
+                order_side: OrderSide::Buy,
+                order_amount: dec!(0),
+            }),
+            fill_date: None,
+        };
+
+        let order = OrderSnapshot::with_params(
+            ClientOrderId::unique_id(),
+            OrderType::Liquidation,
+            Some(OrderRole::Maker),
+            exchange.exchange_account_id,
+            currency_pair,
+            dec!(0.2),
+            dec!(12),
+            OrderSide::Sell,
+            None,
+            "FromTest",
+        );
+        let order_pool = OrdersPool::new();
+        let order_ref = order_pool.add_snapshot_initial(Arc::new(RwLock::new(order)));
+
+        let local_time = Utc::now();
+        exchange.create_and_add_order_fill(&mut fill_event, &order_ref);
+
+        let (fills, _) = order_ref.get_fills();
+        assert_eq!(fills.len(), 1);
+        assert!(fills[0].receive_time() >= local_time);
+    }
+
     #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
     async fn use_commission_rate_if_specified() {
         let (exchange, _event_receiver) = get_test_exchange(false);
@@ -1,9 +1,11 @@
+pub mod clock_synchronization;
 pub mod currency_pair_to_symbol_converter;
 pub mod engine_api;
 pub mod exchange;
 pub mod exchange_creation;
 pub mod exchange_symbol;
 pub mod features;
+pub mod gap_recovery;
 pub mod handlers;
 pub mod order;
 pub mod polling_timeout_manager;
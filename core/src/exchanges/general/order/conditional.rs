@@ -0,0 +1,619 @@
+use crate::exchanges::general::exchange::{Exchange, OrderBookTop};
+use crate::exchanges::timeouts::requests_timeout_manager::RequestGroupId;
+use crate::misc::time::time_manager;
+use anyhow::{bail, ensure, Result};
+use itertools::Itertools;
+use mmb_domain::order::event::OrderEventType;
+use mmb_domain::order::pool::OrderRef;
+use mmb_domain::order::snapshot::{
+    OrderCreating, OrderExecutionType, OrderHeader, OrderSide, OrderStatus, OrderType, Price,
+};
+use mmb_utils::cancellation_token::CancellationToken;
+use mmb_utils::{nothing_to_do, OPERATION_CANCELED_MSG};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use std::time::Duration;
+use tokio::time::sleep;
+
+/// Period of checking order book top for emulated conditional orders
+const CHECK_TRIGGER_PERIOD: Duration = Duration::from_millis(100);
+
+/// Trigger condition of conditional order which is emulated by the engine
+/// for exchanges that can't place such orders natively
+#[derive(Debug, Clone)]
+pub(crate) struct ConditionalOrderTrigger {
+    order_type: OrderType,
+    side: OrderSide,
+    stop_price: Price,
+    trailing_stop_delta: Decimal,
+    // best market price since activation of trailing stop
+    extremum: Option<Price>,
+}
+
+impl ConditionalOrderTrigger {
+    pub(crate) fn new(order_to_create: &OrderCreating) -> Result<Self> {
+        let header = &order_to_create.header;
+        match header.order_type {
+            OrderType::StopLoss | OrderType::TakeProfit => ensure!(
+                order_to_create.stop_loss_price > dec!(0),
+                "Stop price should be positive for {:?} order {}",
+                header.order_type,
+                header.client_order_id
+            ),
+            OrderType::TrailingStop => ensure!(
+                order_to_create.trailing_stop_delta > dec!(0),
+                "Trailing stop delta should be positive for order {}",
+                header.client_order_id
+            ),
+            order_type => bail!(
+                "Order {} of type {order_type:?} is not conditional",
+                header.client_order_id
+            ),
+        }
+
+        Ok(Self {
+            order_type: header.order_type,
+            side: header.side,
+            stop_price: order_to_create.stop_loss_price,
+            trailing_stop_delta: order_to_create.trailing_stop_delta,
+            extremum: None,
+        })
+    }
+
+    /// Returns true when order should be placed to the market
+    pub(crate) fn is_triggered(&mut self, order_book_top: &OrderBookTop) -> bool {
+        // price at which triggered order will be executed
+        let price_level = match self.side {
+            OrderSide::Buy => &order_book_top.ask,
+            OrderSide::Sell => &order_book_top.bid,
+        };
+        let price = match price_level {
+            Some(price_level) => price_level.price,
+            None => return false,
+        };
+
+        match (self.order_type, self.side) {
+            (OrderType::StopLoss, OrderSide::Sell) => price <= self.stop_price,
+            (OrderType::StopLoss, OrderSide::Buy) => price >= self.stop_price,
+            (OrderType::TakeProfit, OrderSide::Sell) => price >= self.stop_price,
+            (OrderType::TakeProfit, OrderSide::Buy) => price <= self.stop_price,
+            (OrderType::TrailingStop, _) => self.is_trailing_stop_triggered(price),
+            _ => false,
+        }
+    }
+
+    fn is_trailing_stop_triggered(&mut self, price: Price) -> bool {
+        let extremum = match self.extremum {
+            Some(extremum) => extremum,
+            None => {
+                let is_activated = self.stop_price.is_zero()
+                    || match self.side {
+                        OrderSide::Sell => price >= self.stop_price,
+                        OrderSide::Buy => price <= self.stop_price,
+                    };
+                if !is_activated {
+                    return false;
+                }
+
+                price
+            }
+        };
+
+        let callback_rate = self.trailing_stop_delta / dec!(100);
+        match self.side {
+            OrderSide::Sell => {
+                let extremum = extremum.max(price);
+                self.extremum = Some(extremum);
+                price <= extremum * (dec!(1) - callback_rate)
+            }
+            OrderSide::Buy => {
+                let extremum = extremum.min(price);
+                self.extremum = Some(extremum);
+                price >= extremum * (dec!(1) + callback_rate)
+            }
+        }
+    }
+}
+
+/// Order which is placed to the market when conditional order is triggered.
+/// Stop loss and take profit orders with price become limit orders, others become market orders
+pub(crate) fn triggered_order(order_to_create: &OrderCreating) -> OrderCreating {
+    let header = &order_to_create.header;
+    let (order_type, execution_type) = match header.order_type {
+        OrderType::StopLoss | OrderType::TakeProfit if !order_to_create.price.is_zero() => {
+            (OrderType::Limit, header.execution_type)
+        }
+        _ => (OrderType::Market, OrderExecutionType::None),
+    };
+
+    let triggered_header = OrderHeader::new(
+        header.client_order_id.clone(),
+        header.init_time,
+        header.exchange_account_id,
+        header.currency_pair,
+        order_type,
+        header.side,
+        header.amount,
+        execution_type,
+        header.reservation_id,
+        header.signal_id.clone(),
+        header.strategy_name.clone(),
+    );
+    let triggered_header = match order_type {
+        OrderType::Limit => triggered_header.with_time_in_force(header.time_in_force),
+        _ => triggered_header,
+    };
+
+    OrderCreating::new(triggered_header, order_to_create.price)
+}
+
+impl Exchange {
+    /// Register conditional order in orders pool, wait until its trigger condition is reached
+    /// on order book top and then create market or limit order instead of it.
+    /// Waiting is stopped when order is canceled
+    pub(crate) async fn create_emulated_conditional_order(
+        &self,
+        order_to_create: OrderCreating,
+        pre_reservation_group_id: Option<RequestGroupId>,
+        cancellation_token: CancellationToken,
+    ) -> Result<OrderRef> {
+        let mut trigger = ConditionalOrderTrigger::new(&order_to_create)?;
+        let header = order_to_create.header.clone();
+        let client_order_id = header.client_order_id.clone();
+
+        log::info!("Waiting for trigger of emulated conditional order {order_to_create:?}");
+
+        let order = self.orders.add_simple_initial(
+            header.clone(),
+            Some(order_to_create.price),
+            self.exchange_client.get_initial_extension_data(),
+        );
+        order.fn_mut(|x| {
+            x.props.stop_loss_price = order_to_create.stop_loss_price;
+            x.props.trailing_stop_delta = order_to_create.trailing_stop_delta;
+            x.set_status(OrderStatus::WaitingForTrigger, time_manager::now());
+        });
+
+        let trigger_token = cancellation_token.create_linked_token();
+        let _ = self
+            .emulated_conditional_orders
+            .insert(client_order_id.clone(), trigger_token.clone());
+        // remove watcher however waiting is finished
+        let watcher_guard = scopeguard::guard(client_order_id, |client_order_id| {
+            let _ = self.emulated_conditional_orders.remove(&client_order_id);
+        });
+
+        self.add_event_on_order_change(&order, OrderEventType::ConditionalOrderWaiting)?;
+
+        loop {
+            let is_triggered = self
+                .order_book_top
+                .get(&header.currency_pair)
+                .map(|order_book_top| trigger.is_triggered(&order_book_top))
+                .unwrap_or(false);
+            if is_triggered {
+                break;
+            }
+
+            tokio::select! {
+                _ = sleep(CHECK_TRIGGER_PERIOD) => nothing_to_do(),
+                _ = trigger_token.when_cancelled() => {
+                    self.cancel_emulated_conditional_order(&order)?;
+                    if cancellation_token.is_cancellation_requested() {
+                        bail!(OPERATION_CANCELED_MSG);
+                    }
+
+                    return Ok(order);
+                }
+            }
+        }
+
+        drop(watcher_guard);
+
+        let triggered_order = triggered_order(&order_to_create);
+        if !activate_triggered_order(&order, &triggered_order) {
+            log::info!(
+                "Emulated conditional order {} was canceled before trigger",
+                header.client_order_id
+            );
+            return Ok(order);
+        }
+
+        log::info!(
+            "Emulated conditional order {} is triggered",
+            header.client_order_id
+        );
+
+        Box::pin(self.create_order(
+            triggered_order,
+            pre_reservation_group_id,
+            cancellation_token,
+        ))
+        .await
+    }
+
+    /// Stop waiting for trigger of emulated conditional order and mark it canceled.
+    /// Returns false if order isn't waiting for trigger anymore, e.g. it is already placed to the exchange
+    pub(crate) fn cancel_emulated_conditional_order(&self, order: &OrderRef) -> Result<bool> {
+        let client_order_id = order.client_order_id();
+        if let Some((_, trigger_token)) = self.emulated_conditional_orders.remove(&client_order_id)
+        {
+            trigger_token.cancel();
+        }
+
+        let is_canceled = order.fn_mut(|x| {
+            let is_waiting = x.status() == OrderStatus::WaitingForTrigger;
+            if is_waiting {
+                x.set_status(OrderStatus::Canceled, time_manager::now());
+            }
+            is_waiting
+        });
+        if !is_canceled {
+            return Ok(false);
+        }
+
+        log::info!(
+            "Emulated conditional order {client_order_id} is canceled on {}",
+            self.exchange_account_id
+        );
+        self.add_event_on_order_change(order, OrderEventType::CancelOrderSucceeded)?;
+
+        Ok(true)
+    }
+
+    /// Cancel all emulated conditional orders which are still waiting for trigger
+    pub(crate) fn cancel_emulated_conditional_orders(&self) {
+        let orders = self
+            .emulated_conditional_orders
+            .iter()
+            .filter_map(|x| {
+                self.orders
+                    .cache_by_client_id
+                    .get(x.key())
+                    .map(|x| x.clone())
+            })
+            .collect_vec();
+
+        for order in orders {
+            if let Err(err) = self.cancel_emulated_conditional_order(&order) {
+                log::error!(
+                    "Failed to cancel emulated conditional order {}: {err:?}",
+                    order.client_order_id()
+                );
+            }
+        }
+    }
+}
+
+/// Replace header of emulated conditional order by header of triggered order
+/// so the same order is placed to the exchange. Returns false if order isn't waiting for trigger anymore
+fn activate_triggered_order(order: &OrderRef, triggered_order: &OrderCreating) -> bool {
+    order.fn_mut(|x| {
+        if x.status() != OrderStatus::WaitingForTrigger {
+            return false;
+        }
+
+        x.header = triggered_order.header.clone();
+        x.set_status(OrderStatus::Creating, time_manager::now());
+        true
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exchanges::general::exchange::PriceLevel;
+    use crate::exchanges::general::test_helper::{get_test_exchange_with_blocker, TestClient};
+    use crate::infrastructure::init_lifetime_manager;
+    use chrono::Utc;
+    use mmb_domain::events::ExchangeEvent;
+    use mmb_domain::market::{CurrencyPair, ExchangeAccountId};
+    use mmb_domain::order::pool::OrdersPool;
+    use tokio::sync::broadcast;
+    use tokio::time::timeout;
+
+    fn order_creating(
+        order_type: OrderType,
+        side: OrderSide,
+        price: Price,
+        stop_loss_price: Price,
+        trailing_stop_delta: Decimal,
+    ) -> OrderCreating {
+        let header = OrderHeader::new(
+            "test".into(),
+            Utc::now(),
+            ExchangeAccountId::new("Binance", 0),
+            CurrencyPair::from_codes("btc".into(), "usdt".into()),
+            order_type,
+            side,
+            dec!(1),
+            OrderExecutionType::None,
+            None,
+            None,
+            "test".to_owned(),
+        );
+
+        OrderCreating {
+            header,
+            price,
+            stop_loss_price,
+            trailing_stop_delta,
+        }
+    }
+
+    fn top(bid: Price, ask: Price) -> OrderBookTop {
+        OrderBookTop {
+            ask: Some(PriceLevel {
+                price: ask,
+                amount: dec!(1),
+            }),
+            bid: Some(PriceLevel {
+                price: bid,
+                amount: dec!(1),
+            }),
+        }
+    }
+
+    #[test]
+    fn stop_loss_sell_is_triggered_by_bid() {
+        let order = order_creating(
+            OrderType::StopLoss,
+            OrderSide::Sell,
+            dec!(0),
+            dec!(90),
+            dec!(0),
+        );
+        let mut trigger = ConditionalOrderTrigger::new(&order).expect("in test");
+
+        assert!(!trigger.is_triggered(&top(dec!(95), dec!(96))));
+        assert!(!trigger.is_triggered(&top(dec!(91), dec!(89))));
+        assert!(trigger.is_triggered(&top(dec!(90), dec!(91))));
+    }
+
+    #[test]
+    fn take_profit_buy_is_triggered_by_ask() {
+        let order = order_creating(
+            OrderType::TakeProfit,
+            OrderSide::Buy,
+            dec!(0),
+            dec!(90),
+            dec!(0),
+        );
+        let mut trigger = ConditionalOrderTrigger::new(&order).expect("in test");
+
+        assert!(!trigger.is_triggered(&top(dec!(89), dec!(91))));
+        assert!(trigger.is_triggered(&top(dec!(88), dec!(90))));
+    }
+
+    #[test]
+    fn trailing_stop_sell_follows_max_price() {
+        let order = order_creating(
+            OrderType::TrailingStop,
+            OrderSide::Sell,
+            dec!(0),
+            dec!(100),
+            dec!(10),
+        );
+        let mut trigger = ConditionalOrderTrigger::new(&order).expect("in test");
+
+        // not activated yet
+        assert!(!trigger.is_triggered(&top(dec!(80), dec!(81))));
+        assert!(!trigger.is_triggered(&top(dec!(100), dec!(101))));
+        assert!(!trigger.is_triggered(&top(dec!(120), dec!(121))));
+        assert!(!trigger.is_triggered(&top(dec!(109), dec!(110))));
+        assert!(trigger.is_triggered(&top(dec!(108), dec!(109))));
+    }
+
+    #[test]
+    fn trigger_without_stop_price_is_error() {
+        let order = order_creating(
+            OrderType::StopLoss,
+            OrderSide::Sell,
+            dec!(0),
+            dec!(0),
+            dec!(0),
+        );
+
+        assert!(ConditionalOrderTrigger::new(&order).is_err());
+    }
+
+    #[test]
+    fn triggered_order_type() {
+        let stop_limit = order_creating(
+            OrderType::StopLoss,
+            OrderSide::Sell,
+            dec!(89),
+            dec!(90),
+            dec!(0),
+        );
+        let triggered = triggered_order(&stop_limit);
+        assert_eq!(triggered.header.order_type, OrderType::Limit);
+        assert_eq!(triggered.price, dec!(89));
+        assert_eq!(
+            triggered.header.client_order_id,
+            stop_limit.header.client_order_id
+        );
+
+        let trailing_stop = order_creating(
+            OrderType::TrailingStop,
+            OrderSide::Sell,
+            dec!(0),
+            dec!(0),
+            dec!(1),
+        );
+        let triggered = triggered_order(&trailing_stop);
+        assert_eq!(triggered.header.order_type, OrderType::Market);
+    }
+
+    #[test]
+    fn triggered_order_replaces_waiting_order() {
+        let stop_limit = order_creating(
+            OrderType::StopLoss,
+            OrderSide::Sell,
+            dec!(89),
+            dec!(90),
+            dec!(0),
+        );
+        let order = OrdersPool::new().add_simple_initial(
+            stop_limit.header.clone(),
+            Some(stop_limit.price),
+            None,
+        );
+        order.fn_mut(|x| x.set_status(OrderStatus::WaitingForTrigger, Utc::now()));
+
+        assert!(activate_triggered_order(
+            &order,
+            &triggered_order(&stop_limit)
+        ));
+        assert_eq!(order.order_type(), OrderType::Limit);
+        assert_eq!(order.status(), OrderStatus::Creating);
+
+        // order is already placed
+        assert!(!activate_triggered_order(
+            &order,
+            &triggered_order(&stop_limit)
+        ));
+    }
+
+    async fn next_order_event(
+        events_receiver: &mut broadcast::Receiver<ExchangeEvent>,
+    ) -> (OrderRef, OrderEventType) {
+        loop {
+            let event = timeout(Duration::from_secs(1), events_receiver.recv())
+                .await
+                .expect("in test")
+                .expect("in test");
+            if let ExchangeEvent::OrderEvent(order_event) = event {
+                return (order_event.order, order_event.event_type);
+            }
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn emulated_conditional_order_is_canceled_while_waiting_for_trigger() {
+        let _ = init_lifetime_manager();
+        let (exchange, _exchange_blocker, mut events_receiver) =
+            get_test_exchange_with_blocker(TestClient::default());
+        let stop_loss = order_creating(
+            OrderType::StopLoss,
+            OrderSide::Sell,
+            dec!(0),
+            dec!(90),
+            dec!(0),
+        );
+
+        let create_order_handle = tokio::spawn({
+            let exchange = exchange.clone();
+            async move {
+                exchange
+                    .create_emulated_conditional_order(stop_loss, None, CancellationToken::new())
+                    .await
+            }
+        });
+
+        let (order, event_type) = next_order_event(&mut events_receiver).await;
+        assert!(matches!(
+            event_type,
+            OrderEventType::ConditionalOrderWaiting
+        ));
+        assert_eq!(order.status(), OrderStatus::WaitingForTrigger);
+        assert!(exchange
+            .orders
+            .not_finished
+            .contains_key(&order.client_order_id()));
+
+        exchange
+            .wait_cancel_order(order.clone(), None, false, CancellationToken::new())
+            .await
+            .expect("in test");
+
+        let (_, event_type) = next_order_event(&mut events_receiver).await;
+        assert!(matches!(event_type, OrderEventType::CancelOrderSucceeded));
+
+        let created_order = timeout(Duration::from_secs(1), create_order_handle)
+            .await
+            .expect("in test")
+            .expect("in test")
+            .expect("in test");
+        assert_eq!(created_order.status(), OrderStatus::Canceled);
+        assert!(exchange.emulated_conditional_orders.is_empty());
+        assert!(exchange.orders.not_finished.is_empty());
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn emulated_conditional_orders_are_canceled_with_opened_orders() {
+        let _ = init_lifetime_manager();
+        let (exchange, _exchange_blocker, mut events_receiver) =
+            get_test_exchange_with_blocker(TestClient::default());
+        let trailing_stop = order_creating(
+            OrderType::TrailingStop,
+            OrderSide::Sell,
+            dec!(0),
+            dec!(0),
+            dec!(1),
+        );
+
+        let cancellation_token = CancellationToken::new();
+        let create_order_handle = tokio::spawn({
+            let exchange = exchange.clone();
+            let cancellation_token = cancellation_token.clone();
+            async move {
+                exchange
+                    .create_emulated_conditional_order(trailing_stop, None, cancellation_token)
+                    .await
+            }
+        });
+
+        let (order, _) = next_order_event(&mut events_receiver).await;
+        exchange
+            .clone()
+            .cancel_opened_orders(cancellation_token, false)
+            .await;
+
+        let (_, event_type) = next_order_event(&mut events_receiver).await;
+        assert!(matches!(event_type, OrderEventType::CancelOrderSucceeded));
+        assert_eq!(order.status(), OrderStatus::Canceled);
+        let created_order = timeout(Duration::from_secs(1), create_order_handle)
+            .await
+            .expect("in test")
+            .expect("in test")
+            .expect("in test");
+        assert_eq!(created_order.status(), OrderStatus::Canceled);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn emulated_conditional_order_is_canceled_by_cancellation_token() {
+        let _ = init_lifetime_manager();
+        let (exchange, _exchange_blocker, mut events_receiver) =
+            get_test_exchange_with_blocker(TestClient::default());
+        let stop_loss = order_creating(
+            OrderType::StopLoss,
+            OrderSide::Buy,
+            dec!(0),
+            dec!(110),
+            dec!(0),
+        );
+
+        let cancellation_token = CancellationToken::new();
+        let create_order_handle = tokio::spawn({
+            let exchange = exchange.clone();
+            let cancellation_token = cancellation_token.clone();
+            async move {
+                exchange
+                    .create_emulated_conditional_order(stop_loss, None, cancellation_token)
+                    .await
+            }
+        });
+
+        let (order, _) = next_order_event(&mut events_receiver).await;
+        cancellation_token.cancel();
+
+        let result = timeout(Duration::from_secs(1), create_order_handle)
+            .await
+            .expect("in test")
+            .expect("in test");
+        assert!(result.is_err());
+        assert_eq!(order.status(), OrderStatus::Canceled);
+        assert!(exchange.emulated_conditional_orders.is_empty());
+    }
+}
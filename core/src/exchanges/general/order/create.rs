@@ -56,6 +56,18 @@ impl Exchange {
     ) -> Result<OrderRef> {
         use AllowedEventSourceType::*;
 
+        if order_to_create.header.order_type.is_conditional()
+            && !self.features.order_features.supports_stop_loss_order
+        {
+            return self
+                .create_emulated_conditional_order(
+                    order_to_create,
+                    pre_reservation_group_id,
+                    cancellation_token,
+                )
+                .await;
+        }
+
         log::info!("Submitting order {order_to_create:?}");
 
         let order = self.orders.add_simple_initial(
@@ -63,6 +75,12 @@ impl Exchange {
             Some(order_to_create.price),
             self.exchange_client.get_initial_extension_data(),
         );
+        if order_to_create.header.order_type.is_conditional() {
+            order.fn_mut(|x| {
+                x.props.stop_loss_price = order_to_create.stop_loss_price;
+                x.props.trailing_stop_delta = order_to_create.trailing_stop_delta;
+            });
+        }
 
         let linked_ct = cancellation_token.create_linked_token();
 
@@ -443,12 +461,11 @@ impl Exchange {
             OrderStatus::Canceled => {
                 log_status(self, status, client_order_id, exchange_order_id);
 
-                let exchange_order_id = exchange_order_id
-                    .as_ref()
-                    .expect("exchange_order_id should be known when order status is `Canceled`");
+                // order in status Creating has no exchange_order_id yet, e.g. when it was
+                // expired immediately by time in force, so take it from the order info
                 self.handle_cancel_order_succeeded(
                     Some(client_order_id),
-                    exchange_order_id,
+                    &order_info.exchange_order_id,
                     Some(order_info.filled_amount),
                     EventSourceType::RestFallback,
                 )
@@ -564,7 +581,8 @@ impl Exchange {
             | OrderStatus::Canceling
             | OrderStatus::Canceled
             | OrderStatus::Completed
-            | OrderStatus::FailedToCancel => {
+            | OrderStatus::FailedToCancel
+            | OrderStatus::WaitingForTrigger => {
                 let error_msg = format!(
                     "CreateOrderFailed was received for a {status:?} order {args_to_log:?}"
                 );
@@ -661,6 +679,11 @@ impl Exchange {
                 log::error!("{error_msg}");
                 bail!(error_msg)
             }
+            OrderStatus::WaitingForTrigger => {
+                let error_msg = format!("CreateOrderSucceeded was received for an emulated conditional order which isn't placed yet {args_to_log:?}");
+                log::error!("{error_msg}");
+                bail!(error_msg)
+            }
             OrderStatus::Created
             | OrderStatus::Canceling
             | OrderStatus::Canceled
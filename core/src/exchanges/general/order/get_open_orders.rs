@@ -92,7 +92,7 @@ impl Exchange {
         Ok(open_orders)
     }
 
-    fn add_missing_open_orders(&self, open_orders: &[OrderInfo]) {
+    pub(crate) fn add_missing_open_orders(&self, open_orders: &[OrderInfo]) {
         for order in open_orders {
             if order.client_order_id.as_str().is_empty()
                 && self
@@ -1,4 +1,5 @@
 pub mod cancel;
+pub mod conditional;
 pub mod create;
 pub mod create_websocket_based;
 pub mod get_info;
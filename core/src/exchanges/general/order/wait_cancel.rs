@@ -81,6 +81,13 @@ impl Exchange {
         check_order_fills: bool,
         cancellation_token: CancellationToken,
     ) -> Result<()> {
+        // emulated conditional order isn't placed to the exchange until it is triggered
+        if order.status() == OrderStatus::WaitingForTrigger
+            && self.cancel_emulated_conditional_order(order)?
+        {
+            return Ok(());
+        }
+
         if order.status() == OrderStatus::Creating {
             self.create_order_created_fut(order, cancellation_token.clone())
                 .await?;
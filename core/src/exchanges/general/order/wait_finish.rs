@@ -22,7 +22,7 @@ use mmb_domain::exchanges::symbol::Symbol;
 use mmb_domain::market::ExchangeErrorType;
 use mmb_domain::order::fill::{EventSourceType, OrderFillType};
 use mmb_domain::order::pool::OrderRef;
-use mmb_domain::order::snapshot::{OrderExecutionType, OrderInfo, OrderStatus, OrderType};
+use mmb_domain::order::snapshot::{OrderExecutionType, OrderInfo, OrderStatus, OrderTimeInForce};
 use mmb_utils::time::ToStdExpected;
 
 use super::get_order_trades::OrderTrade;
@@ -141,7 +141,7 @@ impl Exchange {
                 const ORDER_TRADES_FALLBACK_REQUEST_PERIOD_FOR_STOP_LOSS: Duration =
                     Duration::from_secs(30);
                 const ORDER_TRADES_FALLBACK_REQUEST_PERIOD: Duration = Duration::from_secs(300);
-                let fallback_request_period = if order.order_type() == OrderType::StopLoss {
+                let fallback_request_period = if order.order_type().is_conditional() {
                     ORDER_TRADES_FALLBACK_REQUEST_PERIOD_FOR_STOP_LOSS
                 } else {
                     ORDER_TRADES_FALLBACK_REQUEST_PERIOD
@@ -207,7 +207,7 @@ impl Exchange {
                 .await?;
 
             // If a maker only order was cancelled here, it is likely happened because we missed
-            // a refusal/cancellation notification due to crossing a market.
+            // a refusal/cancellation notification due to crossing a market (or expiration by time in force).
             // But there is a chance this order was created and properly cancelled, so we need to make sure
             // to retrieve the fills which we could have missed
             let exit_on_order_is_finished_even_if_fills_didnt_received =
@@ -235,10 +235,13 @@ impl Exchange {
         pre_reservation_group_id: Option<RequestGroupId>,
         cancellation_token: CancellationToken,
     ) -> Result<bool> {
-        let order_execution_type = order.fn_ref(|order| order.header.execution_type);
-        if !self.features.order_features.maker_only
-            || order_execution_type != OrderExecutionType::MakerOnly
-        {
+        let (order_execution_type, time_in_force) =
+            order.fn_ref(|order| (order.header.execution_type, order.header.time_in_force));
+        let is_maker_only = self.features.order_features.maker_only
+            && order_execution_type == OrderExecutionType::MakerOnly;
+        // orders with IOC/FOK/GTD time in force are expired by exchange itself like refused maker only orders
+        let can_be_expired = time_in_force != OrderTimeInForce::GoodTillCancelled;
+        if !is_maker_only && !can_be_expired {
             return Ok(false);
         }
 
@@ -261,16 +264,21 @@ impl Exchange {
             .await;
 
         let order_info_result = self.get_order_info(order).await;
-        match order_info_result {
+        let order_info = match order_info_result {
             Err(_) => return Ok(false),
             Ok(order_info) => {
                 if order_info.order_status != OrderStatus::Canceled {
                     return Ok(false);
                 }
+
+                order_info
             }
-        }
+        };
 
-        match order.exchange_order_id() {
+        let exchange_order_id = order.exchange_order_id().or_else(|| {
+            (!order_info.exchange_order_id.is_empty()).then_some(order_info.exchange_order_id)
+        });
+        match exchange_order_id {
             None => {
                 log::error!("check_maker_only_order_status was called for an order with no exchange_order_id with exchange_account_id: {} and client order_id: {}",
                     exchange_account_id,
@@ -420,34 +428,7 @@ impl Exchange {
             RequestType::GetOrderInfo => {
                 let order_info = match self.get_order_info(order).await {
                     Ok(order_info) => {
-                        let exchange_order_id = order.exchange_order_id().with_context(|| {
-                        "No exchange_order_id in order while handle_order_filled_for_restfallback"
-                    })?;
-
-                        let commission_currency_code = order_info
-                            .commission_currency_code
-                            .clone()
-                            .map(|currency_code| CurrencyCode::new(&currency_code));
-
-                        let mut fill_event = FillEvent {
-                            source_type: EventSourceType::RestFallback,
-                            trade_id: None,
-                            client_order_id: Some(order.client_order_id()),
-                            exchange_order_id,
-                            fill_price: order_info.average_fill_price,
-                            fill_amount: FillAmount::Total {
-                                total_filled_amount: order_info.filled_amount,
-                            },
-                            order_role: None,
-                            commission_currency_code,
-                            commission_rate: order_info.commission_rate,
-                            commission_amount: order_info.commission_amount,
-                            fill_type: OrderFillType::UserTrade,
-                            special_order_data: None,
-                            fill_date: None,
-                        };
-                        self.handle_order_filled(&mut fill_event);
-
+                        self.handle_order_filled_by_order_info(order, &order_info);
                         RequestResult::Success(order_info)
                     }
                     Err(exchange_error) => RequestResult::Error::<OrderInfo>(exchange_error),
@@ -462,6 +443,37 @@ impl Exchange {
         }
     }
 
+    /// Apply total filled amount from order info as a fill
+    pub(crate) fn handle_order_filled_by_order_info(
+        &self,
+        order: &OrderRef,
+        order_info: &OrderInfo,
+    ) {
+        let commission_currency_code = order_info
+            .commission_currency_code
+            .clone()
+            .map(|currency_code| CurrencyCode::new(&currency_code));
+
+        let mut fill_event = FillEvent {
+            source_type: EventSourceType::RestFallback,
+            trade_id: None,
+            client_order_id: Some(order.client_order_id()),
+            exchange_order_id: order_info.exchange_order_id.clone(),
+            fill_price: order_info.average_fill_price,
+            fill_amount: FillAmount::Total {
+                total_filled_amount: order_info.filled_amount,
+            },
+            order_role: None,
+            commission_currency_code,
+            commission_rate: order_info.commission_rate,
+            commission_amount: order_info.commission_amount,
+            fill_type: OrderFillType::UserTrade,
+            special_order_data: None,
+            fill_date: None,
+        };
+        self.handle_order_filled(&mut fill_event);
+    }
+
     pub(crate) fn handle_order_filled_for_rest_fallback(
         &self,
         order: &OrderRef,
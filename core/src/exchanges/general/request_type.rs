@@ -1,4 +1,4 @@
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
 pub enum RequestType {
     CreateOrder,
     CancelOrder,
@@ -22,4 +22,25 @@ pub enum RequestType {
     GetProfileId,
     GetMyTrades,
     SetLeverage,
+    GetServerTime,
+}
+
+impl RequestType {
+    pub fn priority(&self) -> RequestPriority {
+        match self {
+            RequestType::CancelOrder => RequestPriority::High,
+            RequestType::CreateOrder => RequestPriority::Normal,
+            _ => RequestPriority::Low,
+        }
+    }
+}
+
+/// Requests with higher priority are granted first when requests limit is exhausted
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum RequestPriority {
+    /// Getting info and polling
+    #[default]
+    Low,
+    Normal,
+    High,
 }
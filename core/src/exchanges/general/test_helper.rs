@@ -18,11 +18,14 @@ use crate::{
             requests_timeout_manager_factory::RequestTimeoutArguments,
             timeout_manager::TimeoutManager,
         },
-        traits::{ExchangeClient, HandleTradeCb, OrderCancelledCb, OrderCreatedCb, Support},
+        traits::{
+            ExchangeClient, ExchangeClientBuilder, ExchangeClientBuilderResult, HandleTradeCb,
+            OrderCancelledCb, OrderCreatedCb, Support,
+        },
     },
     settings::ExchangeSettings,
 };
-use anyhow::Result;
+use anyhow::{bail, Result};
 use async_trait::async_trait;
 use chrono::Duration;
 use dashmap::DashMap;
@@ -30,7 +33,8 @@ use mmb_domain::events::{AllowedEventSourceType, ExchangeBalancesAndPositions, E
 use mmb_domain::exchanges::commission::{Commission, CommissionForType};
 use mmb_domain::exchanges::symbol::{BeforeAfter, Precision, Symbol};
 use mmb_domain::market::{
-    CurrencyCode, CurrencyId, CurrencyPair, ExchangeAccountId, SpecificCurrencyPair,
+    CurrencyCode, CurrencyId, CurrencyPair, ExchangeAccountId, ExchangeErrorType, ExchangeId,
+    SpecificCurrencyPair,
 };
 use mmb_domain::order::pool::{OrderRef, OrdersPool};
 use mmb_domain::order::snapshot::{Amount, Price};
@@ -38,13 +42,13 @@ use mmb_domain::order::snapshot::{
     ClientOrderId, OrderCancelling, OrderInfo, OrderRole, OrderSide, OrderSnapshot, OrderType,
 };
 use mmb_domain::position::{ActivePosition, ClosedPosition};
-use parking_lot::RwLock;
+use parking_lot::{Mutex, RwLock};
 use rust_decimal_macros::dec;
-use tokio::sync::broadcast;
+use tokio::sync::{broadcast, Notify};
 use url::Url;
 
 use crate::exchanges::exchange_blocker::ExchangeBlocker;
-use crate::exchanges::general::exchange::RequestResult;
+use crate::exchanges::general::exchange::{BoxExchangeClient, RequestResult};
 use crate::exchanges::general::order::cancel::CancelOrderResult;
 use crate::exchanges::general::order::create::CreateOrderResult;
 use crate::exchanges::timeouts::requests_timeout_manager_factory::RequestsTimeoutManagerFactory;
@@ -53,7 +57,28 @@ use mmb_utils::{cancellation_token::CancellationToken, hashmap, DateTime};
 
 use super::order::get_order_trades::OrderTrade;
 
-pub struct TestClient;
+pub(crate) const TEST_EXCHANGE_ID: &str = "local_exchange_account_id";
+
+/// Exchange client for unit tests. Open orders and order infos are returned from the configured lists
+#[derive(Default)]
+pub struct TestClient {
+    pub open_orders: Mutex<Vec<OrderInfo>>,
+    pub order_infos: Mutex<Vec<OrderInfo>>,
+    pub can_resync_order_book: bool,
+    /// Currency pairs for which order book resync was requested
+    pub resynced_order_books: Mutex<Vec<CurrencyPair>>,
+    /// Currency pairs for which all orders were cancelled
+    pub cancelled_markets: Mutex<Vec<CurrencyPair>>,
+    pub settings: ExchangeSettings,
+    pub symbols: Vec<Arc<Symbol>>,
+    /// Building of symbols waits for notification if it is set
+    pub symbols_ready: Option<Arc<Notify>>,
+    /// Websocket connection always fails if it is enabled
+    pub is_websocket_enabled: bool,
+    /// Requesting of open orders always fails if it is set
+    pub is_get_open_orders_failed: bool,
+    pub supported_currencies: DashMap<CurrencyId, CurrencyCode>,
+}
 
 #[async_trait]
 impl ExchangeClient for TestClient {
@@ -65,12 +90,17 @@ impl ExchangeClient for TestClient {
         unimplemented!("doesn't need in UT")
     }
 
-    async fn cancel_all_orders(&self, _currency_pair: CurrencyPair) -> Result<()> {
-        unimplemented!("doesn't need in UT")
+    async fn cancel_all_orders(&self, currency_pair: CurrencyPair) -> Result<()> {
+        self.cancelled_markets.lock().push(currency_pair);
+        Ok(())
     }
 
     async fn get_open_orders(&self) -> Result<Vec<OrderInfo>> {
-        unimplemented!("doesn't need in UT")
+        if self.is_get_open_orders_failed {
+            bail!("Open orders aren't available");
+        }
+
+        Ok(self.open_orders.lock().clone())
     }
 
     async fn get_open_orders_by_currency_pair(
@@ -80,8 +110,20 @@ impl ExchangeClient for TestClient {
         unimplemented!("doesn't need in UT")
     }
 
-    async fn get_order_info(&self, _order: &OrderRef) -> Result<OrderInfo, ExchangeError> {
-        unimplemented!("doesn't need in UT")
+    async fn get_order_info(&self, order: &OrderRef) -> Result<OrderInfo, ExchangeError> {
+        let client_order_id = order.client_order_id();
+        self.order_infos
+            .lock()
+            .iter()
+            .find(|x| x.client_order_id == client_order_id)
+            .cloned()
+            .ok_or_else(|| {
+                ExchangeError::new(
+                    ExchangeErrorType::OrderNotFound,
+                    format!("Order {client_order_id} isn't found"),
+                    None,
+                )
+            })
     }
 
     async fn close_position(
@@ -93,15 +135,18 @@ impl ExchangeClient for TestClient {
     }
 
     async fn get_active_positions(&self) -> Result<Vec<ActivePosition>> {
-        unimplemented!("doesn't need in UT")
+        Ok(vec![])
     }
 
     async fn get_balance(&self) -> Result<ExchangeBalancesAndPositions> {
-        unimplemented!("doesn't need in UT")
+        Ok(ExchangeBalancesAndPositions {
+            balances: vec![],
+            positions: None,
+        })
     }
 
     async fn get_balance_and_positions(&self) -> Result<ExchangeBalancesAndPositions> {
-        unimplemented!("doesn't need in UT")
+        self.get_balance().await
     }
 
     async fn get_my_trades(
@@ -113,7 +158,10 @@ impl ExchangeClient for TestClient {
     }
 
     async fn build_all_symbols(&self) -> Result<Vec<Arc<Symbol>>> {
-        unimplemented!("doesn't need in UT")
+        if let Some(symbols_ready) = &self.symbols_ready {
+            symbols_ready.notified().await;
+        }
+        Ok(self.symbols.clone())
     }
 }
 
@@ -127,11 +175,20 @@ impl Support for TestClient {
         unimplemented!("doesn't need in UT")
     }
     fn on_connecting(&self) -> Result<()> {
-        unimplemented!("doesn't need in UT")
+        Ok(())
     }
 
     fn on_disconnected(&self) -> Result<()> {
-        unimplemented!("doesn't need in UT")
+        Ok(())
+    }
+
+    fn can_resync_order_book(&self) -> bool {
+        self.can_resync_order_book
+    }
+
+    fn on_order_book_integrity_violated(&self, currency_pair: CurrencyPair) -> Result<()> {
+        self.resynced_order_books.lock().push(currency_pair);
+        Ok(())
     }
 
     fn set_send_websocket_message_callback(&self, _callback: SendWebsocketMessageCb) {}
@@ -147,19 +204,19 @@ impl Support for TestClient {
     fn set_traded_specific_currencies(&self, _currencies: Vec<SpecificCurrencyPair>) {}
 
     fn is_websocket_enabled(&self, _role: WebSocketRole) -> bool {
-        unimplemented!("doesn't need in UT")
+        self.is_websocket_enabled
     }
 
     async fn create_ws_url(&self, _role: WebSocketRole) -> Result<Url> {
-        unimplemented!("doesn't need in UT")
+        bail!("Websocket isn't available in UT")
     }
 
-    fn get_specific_currency_pair(&self, _currency_pair: CurrencyPair) -> SpecificCurrencyPair {
-        unimplemented!("doesn't need in UT")
+    fn get_specific_currency_pair(&self, currency_pair: CurrencyPair) -> SpecificCurrencyPair {
+        currency_pair.as_str().into()
     }
 
     fn get_supported_currencies(&self) -> &DashMap<CurrencyId, CurrencyCode> {
-        unimplemented!("doesn't need in UT")
+        &self.supported_currencies
     }
 
     fn should_log_message(&self, _message: &str) -> bool {
@@ -179,7 +236,62 @@ impl Support for TestClient {
     }
 
     fn get_settings(&self) -> &ExchangeSettings {
-        unimplemented!("doesn't need in UT")
+        &self.settings
+    }
+}
+
+fn test_exchange_features() -> ExchangeFeatures {
+    ExchangeFeatures::new(
+        OpenOrdersType::AllCurrencyPair,
+        RestFillsFeatures::default(),
+        OrderFeatures {
+            supports_get_order_info_by_client_order_id: true,
+            ..OrderFeatures::default()
+        },
+        OrderTradeOption::default(),
+        WebSocketOptions::default(),
+        false,
+        AllowedEventSourceType::default(),
+        AllowedEventSourceType::default(),
+        AllowedEventSourceType::default(),
+    )
+}
+
+/// Builds `TestClient` for exchange accounts which are connected by engine in unit tests
+#[derive(Default)]
+pub struct TestClientBuilder {
+    pub symbols: Vec<Arc<Symbol>>,
+    pub symbols_ready: Option<Arc<Notify>>,
+    pub is_websocket_enabled: bool,
+}
+
+impl ExchangeClientBuilder for TestClientBuilder {
+    fn create_exchange_client(
+        &self,
+        exchange_settings: ExchangeSettings,
+        _events_channel: broadcast::Sender<ExchangeEvent>,
+        _lifetime_manager: Arc<AppLifetimeManager>,
+        _timeout_manager: Arc<TimeoutManager>,
+        _orders: Arc<OrdersPool>,
+    ) -> Result<ExchangeClientBuilderResult> {
+        Ok(ExchangeClientBuilderResult {
+            client: Box::new(TestClient {
+                settings: exchange_settings,
+                symbols: self.symbols.clone(),
+                symbols_ready: self.symbols_ready.clone(),
+                is_websocket_enabled: self.is_websocket_enabled,
+                ..Default::default()
+            }),
+            features: test_exchange_features(),
+        })
+    }
+
+    fn get_timeout_arguments(&self) -> RequestTimeoutArguments {
+        RequestTimeoutArguments::from_requests_per_minute(1200)
+    }
+
+    fn get_exchange_id(&self) -> ExchangeId {
+        TEST_EXCHANGE_ID.into()
     }
 }
 
@@ -197,8 +309,23 @@ pub(crate) fn get_test_exchange_by_currency_codes_and_amount_code(
     quote_currency_code: &str,
     amount_currency_code: &str,
 ) -> (Arc<Exchange>, broadcast::Receiver<ExchangeEvent>) {
+    let symbol = create_test_symbol(
+        is_derivative,
+        base_currency_code,
+        quote_currency_code,
+        amount_currency_code,
+    );
+    get_test_exchange_with_symbol(symbol)
+}
+
+pub(crate) fn create_test_symbol(
+    is_derivative: bool,
+    base_currency_code: &str,
+    quote_currency_code: &str,
+    amount_currency_code: &str,
+) -> Arc<Symbol> {
     let price_tick = dec!(0.1);
-    let symbol = Arc::new(Symbol::new(
+    Arc::new(Symbol::new(
         is_derivative,
         base_currency_code.into(),
         base_currency_code.into(),
@@ -213,8 +340,7 @@ pub(crate) fn get_test_exchange_by_currency_codes_and_amount_code(
         None,
         Precision::ByTick { tick: price_tick },
         Precision::ByTick { tick: dec!(0) },
-    ));
-    get_test_exchange_with_symbol(symbol)
+    ))
 }
 
 pub(crate) fn get_test_exchange_by_currency_codes(
@@ -238,55 +364,93 @@ pub(crate) fn get_test_exchange_by_currency_codes(
 pub(crate) fn get_test_exchange_with_symbol(
     symbol: Arc<Symbol>,
 ) -> (Arc<Exchange>, broadcast::Receiver<ExchangeEvent>) {
-    let exchange_account_id = ExchangeAccountId::new("local_exchange_account_id", 0);
+    let exchange_account_id = ExchangeAccountId::new(TEST_EXCHANGE_ID, 0);
     get_test_exchange_with_symbol_and_id(symbol, exchange_account_id)
 }
 pub(crate) fn get_test_exchange_with_symbol_and_id(
     symbol: Arc<Symbol>,
     exchange_account_id: ExchangeAccountId,
 ) -> (Arc<Exchange>, broadcast::Receiver<ExchangeEvent>) {
-    let lifetime_manager = AppLifetimeManager::new(CancellationToken::new());
-    let (tx, rx) = broadcast::channel(10);
+    let exchange_blocker = ExchangeBlocker::new(vec![exchange_account_id]);
+    get_test_exchange_with_client(
+        symbol,
+        exchange_account_id,
+        TestClient::default(),
+        &exchange_blocker,
+    )
+}
 
-    let exchange_client = Box::new(TestClient);
-    let referral_reward = dec!(40);
-    let commission = Commission::new(
-        CommissionForType::new(dec!(0.1), referral_reward),
-        CommissionForType::new(dec!(0.2), referral_reward),
+/// Test exchange with blocker which is kept alive by caller
+pub(crate) fn get_test_exchange_with_blocker(
+    exchange_client: TestClient,
+) -> (
+    Arc<Exchange>,
+    Arc<ExchangeBlocker>,
+    broadcast::Receiver<ExchangeEvent>,
+) {
+    let exchange_account_id = ExchangeAccountId::new(TEST_EXCHANGE_ID, 0);
+    let exchange_blocker = ExchangeBlocker::new(vec![exchange_account_id]);
+    let (exchange, rx) = get_test_exchange_with_client(
+        create_test_symbol(false, "PHB", "BTC", "PHB"),
+        exchange_account_id,
+        exchange_client,
+        &exchange_blocker,
     );
+    (exchange, exchange_blocker, rx)
+}
 
-    let exchange_blocker = ExchangeBlocker::new(vec![exchange_account_id]);
+fn get_test_exchange_with_client(
+    symbol: Arc<Symbol>,
+    exchange_account_id: ExchangeAccountId,
+    exchange_client: TestClient,
+    exchange_blocker: &Arc<ExchangeBlocker>,
+) -> (Arc<Exchange>, broadcast::Receiver<ExchangeEvent>) {
+    get_test_exchange_with_timeout_manager(
+        symbol,
+        exchange_account_id,
+        Box::new(exchange_client),
+        get_test_timeout_manager(exchange_account_id),
+        exchange_blocker,
+    )
+}
 
+pub(crate) fn get_test_timeout_manager(
+    exchange_account_id: ExchangeAccountId,
+) -> Arc<TimeoutManager> {
     let request_timeout_manager = RequestsTimeoutManagerFactory::from_requests_per_period(
         RequestTimeoutArguments::new(100, Duration::minutes(1)),
         exchange_account_id,
     );
-    let timeout_managers = hashmap![exchange_account_id => request_timeout_manager];
-    let timeout_manager = TimeoutManager::new(timeout_managers);
+    TimeoutManager::new(hashmap![exchange_account_id => request_timeout_manager])
+}
+
+/// Test exchange with timeout manager which can be shared with engine context
+pub(crate) fn get_test_exchange_with_timeout_manager(
+    symbol: Arc<Symbol>,
+    exchange_account_id: ExchangeAccountId,
+    exchange_client: BoxExchangeClient,
+    timeout_manager: Arc<TimeoutManager>,
+    exchange_blocker: &Arc<ExchangeBlocker>,
+) -> (Arc<Exchange>, broadcast::Receiver<ExchangeEvent>) {
+    let lifetime_manager = AppLifetimeManager::new(CancellationToken::new());
+    let (tx, rx) = broadcast::channel(10);
+
+    let referral_reward = dec!(40);
+    let commission = Commission::new(
+        CommissionForType::new(dec!(0.1), referral_reward),
+        CommissionForType::new(dec!(0.2), referral_reward),
+    );
 
     let exchange = Exchange::new(
         exchange_account_id,
         exchange_client,
         OrdersPool::new(),
-        ExchangeFeatures::new(
-            OpenOrdersType::AllCurrencyPair,
-            RestFillsFeatures::default(),
-            OrderFeatures {
-                supports_get_order_info_by_client_order_id: true,
-                ..OrderFeatures::default()
-            },
-            OrderTradeOption::default(),
-            WebSocketOptions::default(),
-            false,
-            AllowedEventSourceType::default(),
-            AllowedEventSourceType::default(),
-            AllowedEventSourceType::default(),
-        ),
+        test_exchange_features(),
         RequestTimeoutArguments::from_requests_per_minute(1200),
         tx,
         lifetime_manager,
         timeout_manager,
-        Arc::downgrade(&exchange_blocker),
+        Arc::downgrade(exchange_blocker),
         commission,
     );
 
@@ -1,13 +1,209 @@
-#[derive(Clone)]
+use anyhow::{bail, Context, Result};
+
+use crate::settings::{ExchangeEnvironment, ExchangeSettings, HostsSettings};
+
+const DEFAULT_REST_SCHEME: &str = "https://";
+const HTTP_SCHEME: &str = "http://";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Hosts {
-    pub web_socket_host: &'static str,
+    pub web_socket_host: String,
     // Some exchanges have two websockets, for public and private data
-    pub web_socket2_host: &'static str,
-    pub rest_host: &'static str,
+    pub web_socket2_host: String,
+    pub rest_host: String,
 }
 
 impl Hosts {
+    pub fn new(
+        web_socket_host: impl Into<String>,
+        web_socket2_host: impl Into<String>,
+        rest_host: impl Into<String>,
+    ) -> Self {
+        Self {
+            web_socket_host: web_socket_host.into(),
+            web_socket2_host: web_socket2_host.into(),
+            rest_host: rest_host.into(),
+        }
+    }
+
+    /// Host for building REST uri. Default `https` scheme is omitted, other schemes are kept
     pub fn rest_uri_host(&self) -> &str {
-        &self.rest_host[8..]
+        self.rest_host
+            .strip_prefix(DEFAULT_REST_SCHEME)
+            .unwrap_or(&self.rest_host)
+    }
+
+    /// REST host is configured without TLS, e.g. for local mock server
+    pub fn is_rest_over_http(&self) -> bool {
+        self.rest_host.starts_with(HTTP_SCHEME)
+    }
+
+    /// Select hosts of exchange for environment from settings and apply host overrides to them.
+    /// `testnet` is `None` if exchange doesn't have testnet
+    pub fn resolve(
+        settings: &ExchangeSettings,
+        mainnet: impl FnOnce() -> Hosts,
+        testnet: impl FnOnce() -> Option<Hosts>,
+    ) -> Result<Hosts> {
+        let environment = settings.environment.unwrap_or_default();
+        let overrides = settings.hosts.as_ref();
+
+        let hosts = match environment {
+            ExchangeEnvironment::Mainnet => mainnet(),
+            ExchangeEnvironment::Testnet => testnet().with_context(|| {
+                format!(
+                    "Exchange {} doesn't have testnet environment",
+                    settings.exchange_account_id
+                )
+            })?,
+            ExchangeEnvironment::Custom => {
+                return Self::from_overrides(overrides).with_context(|| {
+                    format!(
+                        "Unable to get custom hosts for {}",
+                        settings.exchange_account_id
+                    )
+                })
+            }
+        };
+
+        Ok(hosts.with_overrides(overrides))
+    }
+
+    fn from_overrides(overrides: Option<&HostsSettings>) -> Result<Hosts> {
+        let (Some(rest_host), Some(web_socket_host)) = (
+            overrides.and_then(|x| x.rest_host.clone()),
+            overrides.and_then(|x| x.web_socket_host.clone()),
+        ) else {
+            bail!("Both rest_host and web_socket_host should be specified for custom environment");
+        };
+
+        let web_socket2_host = overrides
+            .and_then(|x| x.web_socket2_host.clone())
+            .unwrap_or_else(|| web_socket_host.clone());
+
+        Ok(Hosts {
+            web_socket_host,
+            web_socket2_host,
+            rest_host,
+        })
+    }
+
+    fn with_overrides(mut self, overrides: Option<&HostsSettings>) -> Self {
+        let Some(overrides) = overrides else {
+            return self;
+        };
+
+        if let Some(rest_host) = &overrides.rest_host {
+            self.rest_host = rest_host.clone();
+        }
+        if let Some(web_socket_host) = &overrides.web_socket_host {
+            self.web_socket_host = web_socket_host.clone();
+        }
+        if let Some(web_socket2_host) = &overrides.web_socket2_host {
+            self.web_socket2_host = web_socket2_host.clone();
+        }
+
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mainnet() -> Hosts {
+        Hosts::new("wss://main.ws", "wss://main.ws2", "https://main.rest")
+    }
+
+    fn testnet() -> Option<Hosts> {
+        Some(Hosts::new(
+            "wss://test.ws",
+            "wss://test.ws2",
+            "https://test.rest",
+        ))
+    }
+
+    fn settings(
+        environment: Option<ExchangeEnvironment>,
+        hosts: Option<HostsSettings>,
+    ) -> ExchangeSettings {
+        ExchangeSettings {
+            environment,
+            hosts,
+            ..ExchangeSettings::default()
+        }
+    }
+
+    #[test]
+    fn select_hosts_by_environment() {
+        let hosts = Hosts::resolve(&settings(None, None), mainnet, testnet).expect("in test");
+        assert_eq!(hosts, mainnet());
+
+        let hosts = Hosts::resolve(
+            &settings(Some(ExchangeEnvironment::Testnet), None),
+            mainnet,
+            testnet,
+        )
+        .expect("in test");
+        assert_eq!(hosts, testnet().expect("in test"));
+
+        let result = Hosts::resolve(
+            &settings(Some(ExchangeEnvironment::Testnet), None),
+            mainnet,
+            || None,
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn apply_host_overrides() {
+        let overrides = HostsSettings {
+            rest_host: Some("http://127.0.0.1:8080".to_owned()),
+            web_socket_host: None,
+            web_socket2_host: Some("ws://127.0.0.1:8081".to_owned()),
+        };
+
+        let hosts = Hosts::resolve(
+            &settings(Some(ExchangeEnvironment::Testnet), Some(overrides)),
+            mainnet,
+            testnet,
+        )
+        .expect("in test");
+
+        assert_eq!(
+            hosts,
+            Hosts::new(
+                "wss://test.ws",
+                "ws://127.0.0.1:8081",
+                "http://127.0.0.1:8080"
+            )
+        );
+        assert_eq!(hosts.rest_uri_host(), "http://127.0.0.1:8080");
+        assert!(hosts.is_rest_over_http());
+    }
+
+    #[test]
+    fn custom_environment_requires_hosts() {
+        let custom = Some(ExchangeEnvironment::Custom);
+        assert!(Hosts::resolve(&settings(custom, None), mainnet, testnet).is_err());
+
+        let overrides = HostsSettings {
+            rest_host: Some("https://localhost:8080".to_owned()),
+            web_socket_host: Some("ws://localhost:8081".to_owned()),
+            web_socket2_host: None,
+        };
+        let hosts =
+            Hosts::resolve(&settings(custom, Some(overrides)), mainnet, testnet).expect("in test");
+
+        assert_eq!(
+            hosts,
+            Hosts::new(
+                "ws://localhost:8081",
+                "ws://localhost:8081",
+                "https://localhost:8080"
+            )
+        );
+        assert_eq!(hosts.rest_uri_host(), "localhost:8080");
+        assert!(!hosts.is_rest_over_http());
     }
 }
@@ -1,22 +1,31 @@
-use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Duration;
 
 use anyhow::{Context, Result};
+use dashmap::DashMap;
 use mmb_utils::cancellation_token::CancellationToken;
-use mmb_utils::infrastructure::WithExpect;
 use mmb_utils::nothing_to_do;
 use parking_lot::Mutex;
+use tokio::sync::broadcast::error::RecvError;
 use tokio::sync::{broadcast, oneshot};
 
+use crate::database::events::recorder::EventRecorder;
 use crate::exchanges::general::exchange::{Exchange, OrderBookTop, PriceLevel};
-use crate::lifecycle::trading_engine::Service;
+use crate::lifecycle::trading_engine::{EngineContext, Service};
+use crate::metrics::engine_metrics::EventsReceiverMetrics;
+use crate::misc::time::time_manager;
+use crate::order_book::integrity::OrderBookIntegrityTracker;
 use crate::order_book::local_snapshot_service::LocalSnapshotsService;
+use crate::orders::events::OrderEventRecord;
 use mmb_domain::events::ExchangeEvent;
 use mmb_domain::market::ExchangeAccountId;
-use mmb_domain::order::event::OrderEventType;
+use mmb_domain::order::event::{OrderEvent, OrderEventType};
 use mmb_domain::order::snapshot::OrderType;
 use mmb_domain::order_book::event::OrderBookEvent;
 
+/// Period of checking local order books for staleness
+const CHECK_STALENESS_PERIOD: Duration = Duration::from_secs(1);
+
 pub(crate) struct InternalEventsLoop {
     work_finished_receiver: Mutex<Option<oneshot::Receiver<Result<()>>>>,
 }
@@ -31,35 +40,65 @@ impl InternalEventsLoop {
     pub async fn start(
         self: Arc<Self>,
         mut events_receiver: broadcast::Receiver<ExchangeEvent>,
-        exchanges_map: HashMap<ExchangeAccountId, Arc<Exchange>>,
+        engine_context: Arc<EngineContext>,
+        event_recorder: Arc<EventRecorder>,
         cancellation_token: CancellationToken,
     ) -> Result<()> {
+        // exchange accounts can be added and removed at runtime
+        let exchanges_map = &engine_context.exchanges;
         let mut local_snapshots_service = LocalSnapshotsService::default();
+        let mut integrity_tracker = OrderBookIntegrityTracker::default();
+        let mut check_staleness_interval = tokio::time::interval(CHECK_STALENESS_PERIOD);
+        let receiver_metrics = EventsReceiverMetrics::new("internal_events_loop");
         let (work_finished_sender, receiver) = oneshot::channel();
         *self.work_finished_receiver.lock() = Some(receiver);
 
         loop {
             let event = tokio::select! {
-                event_res = events_receiver.recv() => event_res.context("Error during receiving event in InternalEventsLoop::start()")?,
+                event_res = events_receiver.recv() => match event_res {
+                    Ok(event) => event,
+                    Err(RecvError::Lagged(skipped_count)) => {
+                        log::warn!("InternalEventsLoop skipped {skipped_count} events");
+                        receiver_metrics.add_skipped(skipped_count);
+                        continue;
+                    }
+                    Err(err) => return Err(err).context("Error during receiving event in InternalEventsLoop::start()"),
+                },
+                _ = check_staleness_interval.tick() => {
+                    check_order_books_staleness(
+                        &mut local_snapshots_service,
+                        &mut integrity_tracker,
+                        exchanges_map,
+                    );
+                    continue;
+                }
                 _ = cancellation_token.when_cancelled() => {
                     let _ = work_finished_sender.send(Ok(()));
                     return Ok(());
                 }
             };
+            receiver_metrics.set_lag(events_receiver.len());
 
             match event {
                 ExchangeEvent::OrderBookEvent(order_book_event) => {
                     update_order_book_top_for_exchange(
                         order_book_event,
                         &mut local_snapshots_service,
-                        &exchanges_map,
+                        &mut integrity_tracker,
+                        exchanges_map,
                     )
                 }
                 ExchangeEvent::OrderEvent(order_event) => {
+                    save_order_event(&event_recorder, &order_event);
+
                     let target_eai = order_event.order.exchange_account_id();
-                    let exchange = exchanges_map
-                        .get(&target_eai)
-                        .with_expect(|| format!("Failed to get Exchange for {}", target_eai));
+                    let exchange = match exchanges_map.get(&target_eai) {
+                        Some(exchange) => exchange,
+                        None => {
+                            log::warn!("Failed to get Exchange for {target_eai}");
+                            continue;
+                        }
+                    };
 
                     match order_event.event_type {
                         OrderEventType::CreateOrderSucceeded => {
@@ -79,39 +118,113 @@ impl InternalEventsLoop {
                         // TODO react on order liquidation
                     }
                 }
-                ExchangeEvent::BalanceUpdate(_) => {}
+                ExchangeEvent::BalanceUpdate(balance_update) => {
+                    if let Some(positions) = &balance_update.balances_and_positions.positions {
+                        let target_eai = balance_update.exchange_account_id;
+                        match exchanges_map.get(&target_eai) {
+                            Some(exchange) => exchange.update_leverage_by_positions(positions),
+                            None => log::warn!("Failed to get Exchange for {target_eai}"),
+                        }
+                    }
+                }
                 ExchangeEvent::LiquidationPrice(_) => {}
                 ExchangeEvent::Trades(_) => {}
+                ExchangeEvent::OrderBookIntegrityViolated(_) => {}
             }
         }
     }
 }
 
+fn save_order_event(event_recorder: &EventRecorder, order_event: &OrderEvent) {
+    let save_result = match &order_event.event_type {
+        OrderEventType::OrderFilled { cloned_order }
+        | OrderEventType::OrderCompleted { cloned_order } => {
+            event_recorder.save(OrderEventRecord::new(&order_event.event_type, cloned_order))
+        }
+        event_type => order_event
+            .order
+            .fn_ref(|order| event_recorder.save(OrderEventRecord::new(event_type, order))),
+    };
+
+    save_result.unwrap_or_else(|err| {
+        log::error!(
+            "unable save order event for order {}: {err:?}",
+            order_event.order.client_order_id()
+        )
+    });
+}
+
 fn update_order_book_top_for_exchange(
     order_book_event: OrderBookEvent,
     local_snapshots_service: &mut LocalSnapshotsService,
-    exchanges_map: &HashMap<ExchangeAccountId, Arc<Exchange>>,
+    integrity_tracker: &mut OrderBookIntegrityTracker,
+    exchanges_map: &DashMap<ExchangeAccountId, Arc<Exchange>>,
 ) {
-    let market_account_id = local_snapshots_service.update(order_book_event);
-    if let Some(market_account_id) = &market_account_id {
-        let snapshot = local_snapshots_service.get_snapshot_expected(market_account_id.market_id());
-
-        let order_book_top = OrderBookTop {
-            ask: snapshot
-                .get_top_ask()
-                .map(|(price, amount)| PriceLevel { price, amount }),
-            bid: snapshot
-                .get_top_bid()
-                .map(|(price, amount)| PriceLevel { price, amount }),
-        };
-
-        exchanges_map
-            .get(&market_account_id.exchange_account_id)
-            .map(|exchange| {
-                exchange
-                    .order_book_top
-                    .insert(market_account_id.currency_pair, order_book_top)
-            });
+    let event_market_account_id = order_book_event.market_account_id();
+    let market_account_id = match local_snapshots_service
+        .update_with_integrity_check(order_book_event)
+    {
+        Ok(market_account_id) => market_account_id,
+        Err(violation) => {
+            // following updates are rejected until a fresh snapshot arrives
+            local_snapshots_service.remove_snapshot(event_market_account_id.market_id());
+
+            if let Some(exchange) = exchanges_map.get(&event_market_account_id.exchange_account_id)
+            {
+                if integrity_tracker.on_violation(event_market_account_id, violation) {
+                    exchange.order_book_integrity_violated(
+                        event_market_account_id.currency_pair,
+                        violation,
+                    );
+                }
+            }
+            return;
+        }
+    };
+
+    let snapshot = local_snapshots_service.get_snapshot_expected(market_account_id.market_id());
+
+    let order_book_top = OrderBookTop {
+        ask: snapshot
+            .get_top_ask()
+            .map(|(price, amount)| PriceLevel { price, amount }),
+        bid: snapshot
+            .get_top_bid()
+            .map(|(price, amount)| PriceLevel { price, amount }),
+    };
+
+    let exchange_account_id = market_account_id.exchange_account_id;
+    if let Some(exchange) = exchanges_map.get(&exchange_account_id) {
+        exchange
+            .order_book_top
+            .insert(market_account_id.currency_pair, order_book_top);
+
+        if integrity_tracker.on_valid_event(market_account_id, time_manager::now()) {
+            exchange.order_book_integrity_restored(market_account_id.currency_pair);
+        }
+    }
+}
+
+fn check_order_books_staleness(
+    local_snapshots_service: &mut LocalSnapshotsService,
+    integrity_tracker: &mut OrderBookIntegrityTracker,
+    exchanges_map: &DashMap<ExchangeAccountId, Arc<Exchange>>,
+) {
+    let stale_markets =
+        integrity_tracker.check_staleness(time_manager::now(), |exchange_account_id| {
+            exchanges_map
+                .get(&exchange_account_id)?
+                .exchange_client
+                .get_settings()
+                .order_book_stale_timeout_secs
+                .map(Duration::from_secs)
+        });
+
+    for (market_account_id, violation) in stale_markets {
+        local_snapshots_service.remove_snapshot(market_account_id.market_id());
+        if let Some(exchange) = exchanges_map.get(&market_account_id.exchange_account_id) {
+            exchange.order_book_integrity_violated(market_account_id.currency_pair, violation);
+        }
     }
 }
 
@@ -129,3 +242,282 @@ impl Service for InternalEventsLoop {
         work_finished_receiver
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exchanges::block_reasons::ORDER_BOOK_INTEGRITY_VIOLATED;
+    use crate::exchanges::exchange_blocker::ExchangeBlocker;
+    use crate::exchanges::general::test_helper::{get_test_exchange_with_blocker, TestClient};
+    use crate::infrastructure::init_lifetime_manager;
+    use crate::settings::ExchangeSettings;
+    use mmb_domain::market::CurrencyPair;
+    use mmb_domain::order_book::event::{EventType, OrderBookIntegrityViolation};
+    use mmb_domain::order_book::order_book_data::OrderBookData;
+    use mmb_domain::order_book_data;
+    use rust_decimal::Decimal;
+    use rust_decimal_macros::dec;
+
+    struct TestContext {
+        exchange: Arc<Exchange>,
+        exchanges_map: DashMap<ExchangeAccountId, Arc<Exchange>>,
+        events_receiver: broadcast::Receiver<ExchangeEvent>,
+        local_snapshots_service: LocalSnapshotsService,
+        integrity_tracker: OrderBookIntegrityTracker,
+        exchange_blocker: Arc<ExchangeBlocker>,
+    }
+
+    impl TestContext {
+        fn new(exchange_client: TestClient) -> Self {
+            let _ = init_lifetime_manager();
+            let (exchange, exchange_blocker, events_receiver) =
+                get_test_exchange_with_blocker(exchange_client);
+            let exchanges_map = DashMap::new();
+            let _ = exchanges_map.insert(exchange.exchange_account_id, exchange.clone());
+
+            TestContext {
+                exchange,
+                exchanges_map,
+                events_receiver,
+                local_snapshots_service: LocalSnapshotsService::default(),
+                integrity_tracker: OrderBookIntegrityTracker::default(),
+                exchange_blocker,
+            }
+        }
+
+        fn currency_pair(&self) -> CurrencyPair {
+            *self.exchange.symbols.iter().next().expect("in test").key()
+        }
+
+        fn send_order_book_event(&mut self, event_type: EventType, data: OrderBookData) {
+            let event = OrderBookEvent::new(
+                time_manager::now(),
+                self.exchange.exchange_account_id,
+                self.currency_pair(),
+                "".to_string(),
+                event_type,
+                Arc::new(data),
+            );
+            update_order_book_top_for_exchange(
+                event,
+                &mut self.local_snapshots_service,
+                &mut self.integrity_tracker,
+                &self.exchanges_map,
+            );
+        }
+
+        fn send_snapshot(&mut self) {
+            self.send_order_book_event(
+                EventType::Snapshot,
+                order_book_data![
+                    dec!(3) => dec!(1),
+                    ;
+                    dec!(2) => dec!(1),
+                ],
+            );
+        }
+
+        fn send_crossed_update(&mut self) {
+            self.send_order_book_event(
+                EventType::Update,
+                order_book_data![
+                    ;
+                    dec!(3.5) => dec!(1),
+                ],
+            );
+        }
+
+        fn top_ask_price(&self) -> Option<Decimal> {
+            self.exchange
+                .order_book_top
+                .get(&self.currency_pair())
+                .and_then(|x| x.ask.as_ref().map(|x| x.price))
+        }
+
+        fn test_client(&self) -> &TestClient {
+            self.exchange
+                .exchange_client
+                .as_any()
+                .downcast_ref::<TestClient>()
+                .expect("in test")
+        }
+
+        fn resynced_order_books(&self) -> Vec<CurrencyPair> {
+            self.test_client().resynced_order_books.lock().clone()
+        }
+
+        fn is_market_blocked(&self, currency_pair: CurrencyPair) -> bool {
+            self.exchange_blocker
+                .is_market_blocked(self.exchange.exchange_account_id, currency_pair)
+        }
+
+        async fn wait_market_unblocked(&self) {
+            let reason = ORDER_BOOK_INTEGRITY_VIOLATED.for_market(self.currency_pair());
+            tokio::time::timeout(
+                Duration::from_secs(1),
+                self.exchange_blocker.wait_unblock_with_reason(
+                    self.exchange.exchange_account_id,
+                    reason,
+                    CancellationToken::new(),
+                ),
+            )
+            .await
+            .expect("market should be unblocked in time");
+            assert!(!self.is_market_blocked(self.currency_pair()));
+        }
+
+        fn received_violations(&mut self) -> Vec<OrderBookIntegrityViolation> {
+            let mut violations = Vec::new();
+            while let Ok(event) = self.events_receiver.try_recv() {
+                if let ExchangeEvent::OrderBookIntegrityViolated(event) = event {
+                    violations.push(event.violation);
+                }
+            }
+            violations
+        }
+    }
+
+    fn resyncable_client() -> TestClient {
+        TestClient {
+            can_resync_order_book: true,
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn crossed_order_book_stops_trading_on_market_until_new_snapshot() {
+        let mut ctx = TestContext::new(resyncable_client());
+        let currency_pair = ctx.currency_pair();
+
+        ctx.send_snapshot();
+        assert_eq!(ctx.top_ask_price(), Some(dec!(3)));
+
+        ctx.send_crossed_update();
+        assert!(ctx.is_market_blocked(currency_pair));
+        assert!(ctx.exchange_blocker.is_blocked_by_reason(
+            ctx.exchange.exchange_account_id,
+            ORDER_BOOK_INTEGRITY_VIOLATED.for_market(currency_pair)
+        ));
+        assert_eq!(ctx.top_ask_price(), None);
+        assert_eq!(ctx.resynced_order_books(), vec![currency_pair]);
+        assert_eq!(
+            ctx.received_violations(),
+            vec![OrderBookIntegrityViolation::Crossed {
+                top_ask: dec!(3),
+                top_bid: dec!(3.5)
+            }]
+        );
+        // only the market is stopped, other markets of exchange account keep trading
+        let other_currency_pair = CurrencyPair::from_codes("eth".into(), "btc".into());
+        assert!(!ctx.is_market_blocked(other_currency_pair));
+        // orders are cancelled by strategies while the exchange resyncs order book
+        assert!(ctx.test_client().cancelled_markets.lock().is_empty());
+
+        // updates are rejected until a new snapshot arrives, resync is requested once
+        ctx.send_order_book_event(
+            EventType::Update,
+            order_book_data![
+                dec!(4) => dec!(1),
+                ;
+            ],
+        );
+        assert!(ctx.is_market_blocked(currency_pair));
+        assert_eq!(ctx.resynced_order_books(), vec![currency_pair]);
+        assert!(ctx.received_violations().is_empty());
+
+        ctx.send_snapshot();
+        assert_eq!(ctx.top_ask_price(), Some(dec!(3)));
+        ctx.wait_market_unblocked().await;
+    }
+
+    #[tokio::test]
+    async fn crossed_order_book_cancels_orders_if_client_cannot_resync() {
+        let mut ctx = TestContext::new(TestClient::default());
+        let currency_pair = ctx.currency_pair();
+
+        ctx.send_snapshot();
+        ctx.send_crossed_update();
+
+        assert!(ctx.is_market_blocked(currency_pair));
+        assert_eq!(ctx.top_ask_price(), None);
+        assert!(ctx.resynced_order_books().is_empty());
+        assert_eq!(ctx.received_violations().len(), 1);
+        tokio::time::timeout(Duration::from_secs(1), async {
+            while ctx.test_client().cancelled_markets.lock().is_empty() {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("orders of the market should be cancelled in time");
+        assert_eq!(
+            *ctx.test_client().cancelled_markets.lock(),
+            vec![currency_pair]
+        );
+
+        // broken snapshot isn't updated anymore, trading is resumed by a fresh snapshot from the exchange
+        ctx.send_order_book_event(
+            EventType::Update,
+            order_book_data![
+                dec!(3) => dec!(0),
+                dec!(4) => dec!(1),
+                ;
+            ],
+        );
+        assert!(ctx.is_market_blocked(currency_pair));
+        assert_eq!(ctx.top_ask_price(), None);
+        assert!(ctx.received_violations().is_empty());
+
+        ctx.send_snapshot();
+        assert_eq!(ctx.top_ask_price(), Some(dec!(3)));
+        ctx.wait_market_unblocked().await;
+    }
+
+    #[tokio::test]
+    async fn stale_order_book_stops_trading_on_market_until_new_snapshot() {
+        let mut ctx = TestContext::new(TestClient {
+            settings: ExchangeSettings {
+                order_book_stale_timeout_secs: Some(0),
+                ..Default::default()
+            },
+            ..resyncable_client()
+        });
+        let currency_pair = ctx.currency_pair();
+
+        ctx.send_snapshot();
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        check_order_books_staleness(
+            &mut ctx.local_snapshots_service,
+            &mut ctx.integrity_tracker,
+            &ctx.exchanges_map,
+        );
+
+        assert!(ctx.is_market_blocked(currency_pair));
+        assert_eq!(ctx.top_ask_price(), None);
+        assert_eq!(ctx.resynced_order_books(), vec![currency_pair]);
+        assert!(matches!(
+            ctx.received_violations()[..],
+            [OrderBookIntegrityViolation::Stale { .. }]
+        ));
+
+        ctx.send_snapshot();
+        ctx.wait_market_unblocked().await;
+    }
+
+    #[tokio::test]
+    async fn stale_order_book_is_not_checked_without_timeout() {
+        let mut ctx = TestContext::new(resyncable_client());
+        let currency_pair = ctx.currency_pair();
+
+        ctx.send_snapshot();
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        check_order_books_staleness(
+            &mut ctx.local_snapshots_service,
+            &mut ctx.integrity_tracker,
+            &ctx.exchanges_map,
+        );
+
+        assert!(!ctx.is_market_blocked(currency_pair));
+        assert!(ctx.resynced_order_books().is_empty());
+        assert!(ctx.received_violations().is_empty());
+    }
+}
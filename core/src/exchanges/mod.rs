@@ -1,9 +1,12 @@
 pub mod block_reasons;
 pub mod common;
 pub mod exchange_blocker;
+pub mod exchange_clock;
 pub mod general;
 pub mod hosts;
 pub(crate) mod internal_events_loop;
 pub mod rest_client;
+pub mod simulation;
 pub mod timeouts;
+pub mod traffic;
 pub mod traits;
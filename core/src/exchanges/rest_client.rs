@@ -1,9 +1,15 @@
+use crate::connectivity::NetworkConnector;
+use crate::exchanges::traffic::recorder::TrafficRecorder;
+use crate::exchanges::traffic::TrafficData;
 use crate::exchanges::traits::ExchangeError;
+use crate::metrics::engine_metrics::METRICS;
+use crate::metrics::registry::Histogram;
+use crate::settings::NetworkSettings;
 use anyhow::Result;
 use bytes::{Buf, BufMut, Bytes, BytesMut};
-use hyper::client::HttpConnector;
+use dashmap::DashMap;
 use hyper::http::uri::{Parts, PathAndQuery};
-use hyper::{Body, Client, Error, Request, Response, StatusCode, Uri};
+use hyper::{Body, Client, Error, HeaderMap, Request, Response, StatusCode, Uri};
 use hyper_rustls::{HttpsConnector, HttpsConnectorBuilder};
 use log::log;
 use mmb_domain::market::*;
@@ -12,10 +18,15 @@ use std::borrow::Cow;
 use std::convert::TryInto;
 use std::fmt;
 use std::fmt::{Debug, Display, Formatter, Write};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 use uuid::Uuid;
 
 pub type QueryKey = &'static str;
 
+/// Called with headers of every received response, e.g. to synchronize rate limits
+pub type ResponseHeadersHandler = Box<dyn Fn(&HeaderMap) + Send + Sync>;
+
 /// Trait for specific exchange errors handling
 pub trait ErrorHandler: Sized {
     // To find out if there is any special exchange error in a rest outcome
@@ -147,8 +158,14 @@ fn check_content(content: &str) -> CheckContent {
 }
 
 pub struct RestClient<ErrHandler: ErrorHandler + Send + Sync + 'static> {
-    client: Client<HttpsConnector<HttpConnector>>,
+    client: Client<HttpsConnector<NetworkConnector>>,
+    network_settings: NetworkSettings,
+    is_http_allowed: bool,
     error_handler: ErrorHandlerData<ErrHandler>,
+    response_headers_handler: Option<ResponseHeadersHandler>,
+    traffic_recorder: Option<Arc<TrafficRecorder>>,
+    /// Metric series by request action name
+    request_durations: DashMap<String, Histogram>,
 }
 
 const KEEP_ALIVE: &str = "keep-alive";
@@ -158,8 +175,83 @@ type ResponseType = Result<Response<Body>, Error>;
 impl<ErrHandler: ErrorHandler + Send + Sync + 'static> RestClient<ErrHandler> {
     pub fn new(error_handler: ErrorHandlerData<ErrHandler>) -> Self {
         Self {
-            client: create_client(),
+            client: create_client(NetworkSettings::default(), false),
+            network_settings: NetworkSettings::default(),
+            is_http_allowed: false,
             error_handler,
+            response_headers_handler: None,
+            traffic_recorder: None,
+            request_durations: DashMap::new(),
+        }
+    }
+
+    /// Connect through proxy and from local address if they are specified
+    pub fn with_network_settings(mut self, network_settings: Option<NetworkSettings>) -> Self {
+        if let Some(network_settings) = network_settings {
+            self.client = create_client(network_settings.clone(), self.is_http_allowed);
+            self.network_settings = network_settings;
+        }
+        self
+    }
+
+    /// Plain `http` connections are allowed if REST host is configured with `http` scheme
+    /// (e.g. local mock server), otherwise only `https` connections are established
+    pub fn with_http_allowed(mut self, is_http_allowed: bool) -> Self {
+        if self.is_http_allowed != is_http_allowed {
+            self.client = create_client(self.network_settings.clone(), is_http_allowed);
+            self.is_http_allowed = is_http_allowed;
+        }
+        self
+    }
+
+    pub fn with_response_headers_handler(mut self, handler: ResponseHeadersHandler) -> Self {
+        self.response_headers_handler = Some(handler);
+        self
+    }
+
+    /// Requests and responses are captured by recorder if it is set
+    pub fn with_traffic_recorder(mut self, traffic_recorder: Option<Arc<TrafficRecorder>>) -> Self {
+        self.traffic_recorder = traffic_recorder;
+        self
+    }
+
+    fn observe_request_duration(&self, action_name: &str, duration: Duration) {
+        if !METRICS.is_enabled() {
+            return;
+        }
+
+        let histogram = match self.request_durations.get(action_name) {
+            Some(histogram) => histogram.clone(),
+            None => {
+                let histogram = METRICS.rest_request_duration.with_label_values(&[
+                    &self.error_handler.exchange_account_id.to_string(),
+                    action_name,
+                ]);
+                let _ = self
+                    .request_durations
+                    .insert(action_name.to_owned(), histogram.clone());
+                histogram
+            }
+        };
+        histogram.observe(duration.as_secs_f64());
+    }
+
+    fn record_request(
+        &self,
+        request_id: Uuid,
+        action_name: &str,
+        method: &str,
+        uri: &Uri,
+        body: &[u8],
+    ) {
+        if let Some(traffic_recorder) = &self.traffic_recorder {
+            traffic_recorder.record(TrafficData::RestRequest {
+                request_id,
+                action_name: action_name.to_owned(),
+                method: method.to_owned(),
+                uri: uri.to_string(),
+                body: String::from_utf8_lossy(body).into_owned(),
+            });
         }
     }
 
@@ -173,16 +265,26 @@ impl<ErrHandler: ErrorHandler + Send + Sync + 'static> RestClient<ErrHandler> {
         let request_id = Uuid::new_v4();
         self.error_handler.request_log(action_name, &request_id);
 
+        self.record_request(request_id, action_name, "GET", &uri, &[]);
+
         let req = Request::get(uri)
             .header(hyper::header::CONNECTION, KEEP_ALIVE)
             .header("X-MBX-APIKEY", api_key)
             .body(Body::empty())
             .with_expect(|| format!("Error during creation of http GET request {request_id}"));
 
+        let started_at = Instant::now();
         let response = self.client.request(req).await;
 
-        self.handle_response(response, "GET", action_name, log_args, request_id)
-            .await
+        self.handle_response(
+            response,
+            "GET",
+            action_name,
+            log_args,
+            request_id,
+            started_at,
+        )
+        .await
     }
 
     pub async fn put(
@@ -195,16 +297,26 @@ impl<ErrHandler: ErrorHandler + Send + Sync + 'static> RestClient<ErrHandler> {
         let request_id = Uuid::new_v4();
         self.error_handler.request_log(action_name, &request_id);
 
+        self.record_request(request_id, action_name, "PUT", &uri, &[]);
+
         let req = Request::put(uri)
             .header(hyper::header::CONNECTION, KEEP_ALIVE)
             .header("X-MBX-APIKEY", api_key)
             .body(Body::empty())
             .with_expect(|| format!("Error during creation of http PUT request {request_id}"));
 
+        let started_at = Instant::now();
         let response = self.client.request(req).await;
 
-        self.handle_response(response, "PUT", action_name, log_args, request_id)
-            .await
+        self.handle_response(
+            response,
+            "PUT",
+            action_name,
+            log_args,
+            request_id,
+            started_at,
+        )
+        .await
     }
 
     pub async fn post(
@@ -218,16 +330,26 @@ impl<ErrHandler: ErrorHandler + Send + Sync + 'static> RestClient<ErrHandler> {
         let request_id = Uuid::new_v4();
         self.error_handler.request_log(action_name, &request_id);
 
+        self.record_request(request_id, action_name, "POST", &uri, &query);
+
         let req = Request::post(uri)
             .header(hyper::header::CONNECTION, KEEP_ALIVE)
             .header("X-MBX-APIKEY", api_key)
             .body(Body::from(query))
             .with_expect(|| format!("Error during creation of http POST request {request_id}"));
 
+        let started_at = Instant::now();
         let response = self.client.request(req).await;
 
-        self.handle_response(response, "POST", action_name, log_args, request_id)
-            .await
+        self.handle_response(
+            response,
+            "POST",
+            action_name,
+            log_args,
+            request_id,
+            started_at,
+        )
+        .await
     }
 
     pub async fn delete(
@@ -240,16 +362,26 @@ impl<ErrHandler: ErrorHandler + Send + Sync + 'static> RestClient<ErrHandler> {
         let request_id = Uuid::new_v4();
         self.error_handler.request_log(action_name, &request_id);
 
+        self.record_request(request_id, action_name, "DELETE", &uri, &[]);
+
         let req = Request::delete(uri)
             .header(hyper::header::CONNECTION, KEEP_ALIVE)
             .header("X-MBX-APIKEY", api_key)
             .body(Body::empty())
             .with_expect(|| format!("Error during creation of http DELETE request {request_id}",));
 
+        let started_at = Instant::now();
         let response = self.client.request(req).await;
 
-        self.handle_response(response, "DELETE", action_name, log_args, request_id)
-            .await
+        self.handle_response(
+            response,
+            "DELETE",
+            action_name,
+            log_args,
+            request_id,
+            started_at,
+        )
+        .await
     }
 
     async fn handle_response(
@@ -259,11 +391,16 @@ impl<ErrHandler: ErrorHandler + Send + Sync + 'static> RestClient<ErrHandler> {
         action_name: &'static str,
         log_args: String,
         request_id: Uuid,
+        started_at: Instant,
     ) -> Result<RestResponse, ExchangeError> {
         let response = response.with_expect(|| {
             format!("Unable to send {rest_action} request, request_id: {request_id}")
         });
         let status = response.status();
+        if let Some(response_headers_handler) = &self.response_headers_handler {
+            response_headers_handler(response.headers());
+        }
+
         let request_bytes = hyper::body::to_bytes(response.into_body())
             .await
             .with_expect(|| {
@@ -274,8 +411,19 @@ impl<ErrHandler: ErrorHandler + Send + Sync + 'static> RestClient<ErrHandler> {
             .with_expect(|| format!("Unable to convert response content from utf8: {request_bytes:?}, request_id: {request_id}"))
             .to_owned();
 
+        self.observe_request_duration(action_name, started_at.elapsed());
+
         let request_outcome = RestResponse { status, content };
 
+        if let Some(traffic_recorder) = &self.traffic_recorder {
+            traffic_recorder.record(TrafficData::RestResponse {
+                request_id,
+                action_name: action_name.to_owned(),
+                status: status.as_u16(),
+                content: request_outcome.content.clone(),
+            });
+        }
+
         let err_handler_data = &self.error_handler;
         err_handler_data.response_log(action_name, &log_args, &request_outcome, &request_id);
         err_handler_data.get_rest_error(&request_outcome, &log_args, &request_id)?;
@@ -284,13 +432,19 @@ impl<ErrHandler: ErrorHandler + Send + Sync + 'static> RestClient<ErrHandler> {
     }
 }
 
-fn create_client() -> Client<HttpsConnector<HttpConnector>> {
-    let https = HttpsConnectorBuilder::new()
-        .with_native_roots()
-        .https_only()
+fn create_client(
+    network_settings: NetworkSettings,
+    is_http_allowed: bool,
+) -> Client<HttpsConnector<NetworkConnector>> {
+    let builder = HttpsConnectorBuilder::new().with_native_roots();
+    let builder = match is_http_allowed {
+        true => builder.https_or_http(),
+        false => builder.https_only(),
+    };
+    let https = builder
         .enable_http1()
         .enable_http2()
-        .build();
+        .wrap_connector(NetworkConnector::new(network_settings));
     Client::builder().build::<_, Body>(https)
 }
 
@@ -357,9 +511,16 @@ impl UriBuilder {
         let path_and_query = PathAndQuery::from_maybe_shared(path_and_query)
             .expect("Unable create PathAndQuery from UriQueryBuilder");
 
+        // host can contain scheme, `https` is used by default
+        let (scheme, authority) = host.split_once("://").unwrap_or(("https", host));
+
         let mut parts = Parts::default();
-        parts.scheme = Some("https".try_into().expect("Unable build scheme for url"));
-        parts.authority = Some(host.try_into().expect("Unable build authority for url"));
+        parts.scheme = Some(scheme.try_into().expect("Unable build scheme for url"));
+        parts.authority = Some(
+            authority
+                .try_into()
+                .expect("Unable build authority for url"),
+        );
         parts.path_and_query = Some(path_and_query);
 
         let uri = Uri::from_parts(parts).expect("Unable build url from parts");
@@ -430,6 +591,18 @@ mod tests {
         assert_eq!(path_and_query, Uri::from_static("https://host.com/path"))
     }
 
+    #[test]
+    pub fn build_uri_with_scheme_in_host() {
+        let mut builder = UriBuilder::from_path("/path");
+        builder.add_kv("key", "value");
+
+        let path_and_query = builder.build_uri("http://127.0.0.1:8080", true);
+        assert_eq!(
+            path_and_query,
+            Uri::from_static("http://127.0.0.1:8080/path?key=value")
+        )
+    }
+
     #[test]
     pub fn build_uri_from_empty_builder() {
         let host = "host.com";
@@ -0,0 +1,705 @@
+use std::collections::HashMap;
+
+use itertools::Itertools;
+use mmb_domain::market::CurrencyPair;
+use mmb_domain::order::snapshot::{
+    Amount, ClientOrderId, ExchangeOrderId, OrderExecutionType, OrderRole, OrderSide,
+    OrderTimeInForce, OrderType, Price,
+};
+use mmb_domain::order_book::local_order_book_snapshot::{
+    DataToExcludeOrder, LocalOrderBookSnapshot,
+};
+use mmb_domain::order_book::order_book_data::OrderBookData;
+use mmb_utils::DateTime;
+
+/// Order placed on the simulated exchange
+#[derive(Debug, Clone)]
+pub struct SimulatedOrder {
+    pub client_order_id: ClientOrderId,
+    pub exchange_order_id: ExchangeOrderId,
+    pub currency_pair: CurrencyPair,
+    pub order_type: OrderType,
+    pub side: OrderSide,
+    pub price: Price,
+    pub amount: Amount,
+    pub time_in_force: OrderTimeInForce,
+    pub execution_type: OrderExecutionType,
+    pub filled_amount: Amount,
+    /// Simulated time from which the order takes part in matching
+    pub active_since: DateTime,
+    /// Limit order is resting in the order book and can be filled only as maker
+    pub is_resting: bool,
+}
+
+impl SimulatedOrder {
+    pub fn remaining_amount(&self) -> Amount {
+        self.amount - self.filled_amount
+    }
+
+    fn is_market(&self) -> bool {
+        self.order_type == OrderType::Market
+    }
+
+    /// Limit order which is never resting in the order book
+    fn is_immediate(&self) -> bool {
+        !self.is_market() && self.time_in_force.is_immediate()
+    }
+
+    /// Post-only order which is never filled as taker
+    fn is_maker_only(&self) -> bool {
+        self.execution_type == OrderExecutionType::MakerOnly
+    }
+
+    /// Check if order with specified price of the opposite side can be matched with current order
+    fn is_crossed_by(&self, price: Price) -> bool {
+        match self.side {
+            OrderSide::Buy => price <= self.price,
+            OrderSide::Sell => price >= self.price,
+        }
+    }
+}
+
+/// Part of simulated order execution
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MatchedFill {
+    pub client_order_id: ClientOrderId,
+    pub exchange_order_id: ExchangeOrderId,
+    pub currency_pair: CurrencyPair,
+    pub side: OrderSide,
+    pub price: Price,
+    pub amount: Amount,
+    pub total_filled_amount: Amount,
+    pub is_completed: bool,
+    pub role: OrderRole,
+}
+
+/// Matches orders of the simulated exchange against order books and trades of market data.
+/// Market data can't react to our orders, so liquidity taken by our orders is just
+/// removed from the local copy of an order book until the next snapshot.
+#[derive(Default)]
+pub struct MatchingEngine {
+    order_books: HashMap<CurrencyPair, LocalOrderBookSnapshot>,
+    // orders are kept in placing order for time priority
+    orders: Vec<SimulatedOrder>,
+    /// Not filled immediate orders which are removed from matching after activation
+    expired_orders: Vec<SimulatedOrder>,
+}
+
+impl MatchingEngine {
+    pub fn add_order(&mut self, order: SimulatedOrder) {
+        self.orders.push(order);
+    }
+
+    pub fn remove_order(&mut self, exchange_order_id: &ExchangeOrderId) -> Option<SimulatedOrder> {
+        let position = self
+            .orders
+            .iter()
+            .position(|x| &x.exchange_order_id == exchange_order_id)?;
+
+        Some(self.orders.remove(position))
+    }
+
+    pub fn orders(&self) -> &[SimulatedOrder] {
+        &self.orders
+    }
+
+    pub fn order_book(&self, currency_pair: CurrencyPair) -> Option<&LocalOrderBookSnapshot> {
+        self.order_books.get(&currency_pair)
+    }
+
+    /// Check if limit order with specified price would be matched with the current order book
+    pub fn is_crossing_order_book(
+        &self,
+        currency_pair: CurrencyPair,
+        side: OrderSide,
+        price: Price,
+    ) -> bool {
+        self.order_books
+            .get(&currency_pair)
+            .is_some_and(|order_book| is_crossing(order_book, side, price))
+    }
+
+    /// Start matching of orders which are active to the moment `now`.
+    /// Orders crossing the order book are filled as taker, the rest of limit orders become resting
+    /// except immediate orders which are expired. Maker only orders crossing the order book are expired
+    /// without fills like the exchange rejects them. Expired orders can be taken by `take_expired_orders`.
+    pub fn activate_orders(&mut self, now: DateTime) -> Vec<MatchedFill> {
+        let mut fills = vec![];
+        let mut activated_expired_orders = vec![];
+        for order in &mut self.orders {
+            if order.is_resting || order.active_since > now {
+                continue;
+            }
+
+            let mut is_expired = order.is_immediate();
+            if let Some(order_book) = self.order_books.get_mut(&order.currency_pair) {
+                if order.is_maker_only() {
+                    is_expired |= is_crossing(order_book, order.side, order.price);
+                }
+                // fill-or-kill order is expired without fills if it can't be filled completely
+                else if order.time_in_force != OrderTimeInForce::FillOrKill
+                    || can_be_filled_completely(order, order_book)
+                {
+                    fills.extend(match_as_taker(order, order_book, now));
+                }
+            }
+
+            match is_expired {
+                true => activated_expired_orders.push(order.exchange_order_id.clone()),
+                false => order.is_resting = !order.is_market(),
+            }
+        }
+
+        self.remove_completed_orders();
+        for exchange_order_id in activated_expired_orders {
+            if let Some(order) = self.remove_order(&exchange_order_id) {
+                self.expired_orders.push(order);
+            }
+        }
+
+        fills
+    }
+
+    /// Immediate and crossing maker only orders which were expired by the exchange since the previous call
+    pub fn take_expired_orders(&mut self) -> Vec<SimulatedOrder> {
+        std::mem::take(&mut self.expired_orders)
+    }
+
+    pub fn apply_order_book_snapshot(
+        &mut self,
+        currency_pair: CurrencyPair,
+        data: &OrderBookData,
+        time: DateTime,
+    ) -> Vec<MatchedFill> {
+        let snapshot = LocalOrderBookSnapshot::new(data.asks.clone(), data.bids.clone(), time);
+        let _ = self.order_books.insert(currency_pair, snapshot);
+
+        self.match_with_order_book(currency_pair, time)
+    }
+
+    pub fn apply_order_book_update(
+        &mut self,
+        currency_pair: CurrencyPair,
+        data: &OrderBookData,
+        time: DateTime,
+    ) -> Vec<MatchedFill> {
+        match self.order_books.get_mut(&currency_pair) {
+            Some(order_book) => order_book.apply_update(data, time),
+            None => {
+                log::warn!("Order book update for {currency_pair} is skipped because there is no snapshot before it");
+                return vec![];
+            }
+        }
+
+        self.match_with_order_book(currency_pair, time)
+    }
+
+    /// Fill resting orders crossed by a public trade as maker with the price of the order.
+    /// `taker_side` is a side of the order that initiated the trade.
+    pub fn apply_trade(
+        &mut self,
+        currency_pair: CurrencyPair,
+        price: Price,
+        amount: Amount,
+        taker_side: OrderSide,
+    ) -> Vec<MatchedFill> {
+        let mut available_amount = amount;
+        let mut fills = vec![];
+        for order in
+            orders_by_price_priority(&mut self.orders, currency_pair, taker_side.change_side())
+        {
+            if available_amount.is_zero() {
+                break;
+            }
+
+            if !order.is_crossed_by(price) {
+                continue;
+            }
+
+            let fill_amount = order.remaining_amount().min(available_amount);
+            available_amount -= fill_amount;
+            fills.push(fill_order(
+                order,
+                order.price,
+                fill_amount,
+                OrderRole::Maker,
+            ));
+        }
+
+        self.remove_completed_orders();
+        fills
+    }
+
+    fn match_with_order_book(
+        &mut self,
+        currency_pair: CurrencyPair,
+        time: DateTime,
+    ) -> Vec<MatchedFill> {
+        let order_book = match self.order_books.get_mut(&currency_pair) {
+            Some(order_book) => order_book,
+            None => return vec![],
+        };
+
+        let mut fills = vec![];
+        for order in self
+            .orders
+            .iter_mut()
+            .filter(|x| x.currency_pair == currency_pair && x.active_since <= time)
+        {
+            if order.is_market() {
+                fills.extend(match_as_taker(order, order_book, time));
+            }
+        }
+
+        for side in [OrderSide::Buy, OrderSide::Sell] {
+            // Resting order crossed by the opposite side of the order book
+            // should have been filled as maker with its own price
+            for order in orders_by_price_priority(&mut self.orders, currency_pair, side) {
+                let crossing_levels = order_book_levels(order_book, side.change_side())
+                    .take_while(|(price, _)| order.is_crossed_by(*price))
+                    .collect_vec();
+
+                for (level_price, level_amount) in crossing_levels {
+                    if order.remaining_amount().is_zero() {
+                        break;
+                    }
+
+                    let fill_amount = order.remaining_amount().min(level_amount);
+                    take_liquidity(order_book, side.change_side(), level_price, fill_amount);
+                    fills.push(fill_order(
+                        order,
+                        order.price,
+                        fill_amount,
+                        OrderRole::Maker,
+                    ));
+                }
+            }
+        }
+
+        self.remove_completed_orders();
+        fills
+    }
+
+    fn remove_completed_orders(&mut self) {
+        self.orders.retain(|x| !x.remaining_amount().is_zero());
+    }
+}
+
+/// Resting orders of the side ordered from the best price
+fn orders_by_price_priority(
+    orders: &mut [SimulatedOrder],
+    currency_pair: CurrencyPair,
+    side: OrderSide,
+) -> Vec<&mut SimulatedOrder> {
+    orders
+        .iter_mut()
+        .filter(|x| x.currency_pair == currency_pair && x.side == side && x.is_resting)
+        .sorted_by(|a, b| match side {
+            OrderSide::Buy => b.price.cmp(&a.price),
+            OrderSide::Sell => a.price.cmp(&b.price),
+        })
+        .collect()
+}
+
+fn order_book_levels(
+    order_book: &LocalOrderBookSnapshot,
+    book_side: OrderSide,
+) -> impl Iterator<Item = (Price, Amount)> + '_ {
+    let levels: Box<dyn Iterator<Item = (&Price, &Amount)>> = match book_side {
+        OrderSide::Buy => Box::new(order_book.get_bids_price_levels()),
+        OrderSide::Sell => Box::new(order_book.get_asks_price_levels()),
+    };
+
+    levels.map(|(&price, &amount)| (price, amount))
+}
+
+fn take_liquidity(
+    order_book: &mut LocalOrderBookSnapshot,
+    book_side: OrderSide,
+    price: Price,
+    amount: Amount,
+) {
+    order_book.exclude_orders([DataToExcludeOrder::new(price, amount, book_side)]);
+}
+
+fn is_crossing(order_book: &LocalOrderBookSnapshot, side: OrderSide, price: Price) -> bool {
+    order_book_levels(order_book, side.change_side())
+        .next()
+        .is_some_and(|(level_price, _)| match side {
+            OrderSide::Buy => level_price <= price,
+            OrderSide::Sell => level_price >= price,
+        })
+}
+
+fn can_be_filled_completely(order: &SimulatedOrder, order_book: &LocalOrderBookSnapshot) -> bool {
+    let available_amount: Amount = order_book_levels(order_book, order.side.change_side())
+        .take_while(|(price, _)| order.is_market() || order.is_crossed_by(*price))
+        .map(|(_, amount)| amount)
+        .sum();
+
+    available_amount >= order.remaining_amount()
+}
+
+/// Fill order against price levels of the order book with prices of the levels
+fn match_as_taker(
+    order: &mut SimulatedOrder,
+    order_book: &mut LocalOrderBookSnapshot,
+    time: DateTime,
+) -> Vec<MatchedFill> {
+    let book_side = order.side.change_side();
+    let levels = order_book_levels(order_book, book_side)
+        .take_while(|(price, _)| order.is_market() || order.is_crossed_by(*price))
+        .collect_vec();
+
+    let mut fills = vec![];
+    for (level_price, level_amount) in levels {
+        if order.remaining_amount().is_zero() {
+            break;
+        }
+
+        let fill_amount = order.remaining_amount().min(level_amount);
+        take_liquidity(order_book, book_side, level_price, fill_amount);
+        fills.push(fill_order(
+            order,
+            level_price,
+            fill_amount,
+            OrderRole::Taker,
+        ));
+    }
+
+    order_book.last_update_time = time;
+    fills
+}
+
+fn fill_order(
+    order: &mut SimulatedOrder,
+    price: Price,
+    amount: Amount,
+    role: OrderRole,
+) -> MatchedFill {
+    order.filled_amount += amount;
+
+    MatchedFill {
+        client_order_id: order.client_order_id.clone(),
+        exchange_order_id: order.exchange_order_id.clone(),
+        currency_pair: order.currency_pair,
+        side: order.side,
+        price,
+        amount,
+        total_filled_amount: order.filled_amount,
+        is_completed: order.remaining_amount().is_zero(),
+        role,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone, Utc};
+    use mmb_domain::order_book::order_book_data::OrderBookData;
+    use rust_decimal_macros::dec;
+
+    fn currency_pair() -> CurrencyPair {
+        CurrencyPair::from_codes("btc".into(), "usdt".into())
+    }
+
+    fn start_time() -> DateTime {
+        Utc.ymd(2022, 8, 1).and_hms(0, 0, 0)
+    }
+
+    fn order(id: &str, side: OrderSide, price: Price, amount: Amount) -> SimulatedOrder {
+        SimulatedOrder {
+            client_order_id: id.into(),
+            exchange_order_id: id.into(),
+            currency_pair: currency_pair(),
+            order_type: OrderType::Limit,
+            side,
+            price,
+            amount,
+            time_in_force: OrderTimeInForce::GoodTillCancelled,
+            execution_type: OrderExecutionType::None,
+            filled_amount: dec!(0),
+            active_since: start_time() + Duration::seconds(1),
+            is_resting: false,
+        }
+    }
+
+    fn engine_with_order_book() -> MatchingEngine {
+        let mut engine = MatchingEngine::default();
+        let data = OrderBookData::new(
+            [(dec!(101), dec!(1)), (dec!(102), dec!(2))].into(),
+            [(dec!(99), dec!(1)), (dec!(98), dec!(2))].into(),
+        );
+        let fills = engine.apply_order_book_snapshot(currency_pair(), &data, start_time());
+        assert!(fills.is_empty());
+        engine
+    }
+
+    #[test]
+    fn order_is_not_matched_before_latency_passed() {
+        let mut engine = engine_with_order_book();
+        engine.add_order(order("1", OrderSide::Buy, dec!(102), dec!(1)));
+
+        let fills = engine.activate_orders(start_time());
+
+        assert!(fills.is_empty());
+        assert_eq!(engine.orders().len(), 1);
+        assert!(!engine.orders()[0].is_resting);
+    }
+
+    #[test]
+    fn crossing_order_is_filled_as_taker_by_order_book_levels() {
+        let mut engine = engine_with_order_book();
+        engine.add_order(order("1", OrderSide::Buy, dec!(102), dec!(2)));
+
+        let fills = engine.activate_orders(start_time() + Duration::seconds(1));
+
+        let prices_and_amounts = fills
+            .iter()
+            .map(|x| (x.price, x.amount, x.role))
+            .collect_vec();
+        assert_eq!(
+            prices_and_amounts,
+            [
+                (dec!(101), dec!(1), OrderRole::Taker),
+                (dec!(102), dec!(1), OrderRole::Taker)
+            ]
+        );
+        assert!(fills[1].is_completed);
+        assert!(engine.orders().is_empty());
+
+        let order_book = engine.order_book(currency_pair()).expect("in test");
+        assert_eq!(order_book.get_top_ask(), Some((dec!(102), dec!(1))));
+    }
+
+    #[test]
+    fn not_crossing_order_becomes_resting() {
+        let mut engine = engine_with_order_book();
+        engine.add_order(order("1", OrderSide::Sell, dec!(100), dec!(1)));
+
+        let fills = engine.activate_orders(start_time() + Duration::seconds(1));
+
+        assert!(fills.is_empty());
+        assert!(engine.orders()[0].is_resting);
+    }
+
+    #[test]
+    fn resting_order_is_filled_as_maker_by_trade() {
+        let mut engine = engine_with_order_book();
+        engine.add_order(order("1", OrderSide::Sell, dec!(100), dec!(1)));
+        engine.add_order(order("2", OrderSide::Sell, dec!(100.5), dec!(1)));
+        let _ = engine.activate_orders(start_time() + Duration::seconds(1));
+
+        let fills = engine.apply_trade(currency_pair(), dec!(100.5), dec!(1.5), OrderSide::Buy);
+
+        let ids_and_amounts = fills
+            .iter()
+            .map(|x| (x.client_order_id.as_str(), x.price, x.amount, x.role))
+            .collect_vec();
+        assert_eq!(
+            ids_and_amounts,
+            [
+                ("1", dec!(100), dec!(1), OrderRole::Maker),
+                ("2", dec!(100.5), dec!(0.5), OrderRole::Maker)
+            ]
+        );
+        assert_eq!(engine.orders().len(), 1);
+        assert_eq!(engine.orders()[0].remaining_amount(), dec!(0.5));
+    }
+
+    #[test]
+    fn trade_with_not_crossing_price_does_not_fill() {
+        let mut engine = engine_with_order_book();
+        engine.add_order(order("1", OrderSide::Buy, dec!(100), dec!(1)));
+        let _ = engine.activate_orders(start_time() + Duration::seconds(1));
+
+        let fills = engine.apply_trade(currency_pair(), dec!(100.5), dec!(1), OrderSide::Sell);
+
+        assert!(fills.is_empty());
+    }
+
+    #[test]
+    fn resting_order_is_filled_as_maker_by_moved_order_book() {
+        let mut engine = engine_with_order_book();
+        engine.add_order(order("1", OrderSide::Buy, dec!(100), dec!(3)));
+        let _ = engine.activate_orders(start_time() + Duration::seconds(1));
+
+        let update = OrderBookData::new(
+            [(dec!(99.5), dec!(1)), (dec!(100), dec!(1))].into(),
+            Default::default(),
+        );
+        let fills = engine.apply_order_book_update(
+            currency_pair(),
+            &update,
+            start_time() + Duration::seconds(2),
+        );
+
+        let prices_and_amounts = fills
+            .iter()
+            .map(|x| (x.price, x.amount, x.role))
+            .collect_vec();
+        assert_eq!(
+            prices_and_amounts,
+            [
+                (dec!(100), dec!(1), OrderRole::Maker),
+                (dec!(100), dec!(1), OrderRole::Maker)
+            ]
+        );
+        assert_eq!(engine.orders()[0].remaining_amount(), dec!(1));
+
+        let order_book = engine.order_book(currency_pair()).expect("in test");
+        assert_eq!(order_book.get_top_ask(), Some((dec!(101), dec!(1))));
+    }
+
+    #[test]
+    fn removed_order_is_not_matched() {
+        let mut engine = engine_with_order_book();
+        engine.add_order(order("1", OrderSide::Buy, dec!(100), dec!(1)));
+        let _ = engine.activate_orders(start_time() + Duration::seconds(1));
+
+        let removed = engine.remove_order(&"1".into());
+        let fills = engine.apply_trade(currency_pair(), dec!(99), dec!(1), OrderSide::Sell);
+
+        assert!(removed.is_some());
+        assert!(fills.is_empty());
+    }
+
+    #[test]
+    fn immediate_or_cancel_order_is_expired_after_taker_fill() {
+        let mut engine = engine_with_order_book();
+        engine.add_order(SimulatedOrder {
+            time_in_force: OrderTimeInForce::ImmediateOrCancel,
+            ..order("1", OrderSide::Buy, dec!(101), dec!(3))
+        });
+
+        let fills = engine.activate_orders(start_time() + Duration::seconds(1));
+
+        let prices_and_amounts = fills
+            .iter()
+            .map(|x| (x.price, x.amount, x.role, x.is_completed))
+            .collect_vec();
+        assert_eq!(
+            prices_and_amounts,
+            [(dec!(101), dec!(1), OrderRole::Taker, false)]
+        );
+        assert!(engine.orders().is_empty());
+
+        let expired_orders = engine.take_expired_orders();
+        assert_eq!(expired_orders.len(), 1);
+        assert_eq!(expired_orders[0].remaining_amount(), dec!(2));
+        assert!(engine.take_expired_orders().is_empty());
+    }
+
+    #[test]
+    fn completed_immediate_or_cancel_order_is_not_expired() {
+        let mut engine = engine_with_order_book();
+        engine.add_order(SimulatedOrder {
+            time_in_force: OrderTimeInForce::ImmediateOrCancel,
+            ..order("1", OrderSide::Sell, dec!(98), dec!(2))
+        });
+
+        let fills = engine.activate_orders(start_time() + Duration::seconds(1));
+
+        assert_eq!(fills.len(), 2);
+        assert!(fills[1].is_completed);
+        assert!(engine.orders().is_empty());
+        assert!(engine.take_expired_orders().is_empty());
+    }
+
+    #[test]
+    fn immediate_or_cancel_order_is_not_expired_before_latency_passed() {
+        let mut engine = engine_with_order_book();
+        engine.add_order(SimulatedOrder {
+            time_in_force: OrderTimeInForce::ImmediateOrCancel,
+            ..order("1", OrderSide::Buy, dec!(100), dec!(1))
+        });
+
+        let fills = engine.activate_orders(start_time());
+
+        assert!(fills.is_empty());
+        assert_eq!(engine.orders().len(), 1);
+        assert!(engine.take_expired_orders().is_empty());
+    }
+
+    #[test]
+    fn fill_or_kill_order_is_filled_completely() {
+        let mut engine = engine_with_order_book();
+        engine.add_order(SimulatedOrder {
+            time_in_force: OrderTimeInForce::FillOrKill,
+            ..order("1", OrderSide::Buy, dec!(102), dec!(2))
+        });
+
+        let fills = engine.activate_orders(start_time() + Duration::seconds(1));
+
+        assert_eq!(fills.len(), 2);
+        assert!(fills[1].is_completed);
+        assert!(engine.orders().is_empty());
+        assert!(engine.take_expired_orders().is_empty());
+    }
+
+    #[test]
+    fn fill_or_kill_order_is_expired_without_fills_by_insufficient_liquidity() {
+        let mut engine = engine_with_order_book();
+        engine.add_order(SimulatedOrder {
+            time_in_force: OrderTimeInForce::FillOrKill,
+            ..order("1", OrderSide::Buy, dec!(101), dec!(2))
+        });
+
+        let fills = engine.activate_orders(start_time() + Duration::seconds(1));
+
+        assert!(fills.is_empty());
+        assert!(engine.orders().is_empty());
+        let expired_orders = engine.take_expired_orders();
+        assert_eq!(expired_orders.len(), 1);
+        assert_eq!(expired_orders[0].filled_amount, dec!(0));
+
+        let order_book = engine.order_book(currency_pair()).expect("in test");
+        assert_eq!(order_book.get_top_ask(), Some((dec!(101), dec!(1))));
+    }
+
+    #[test]
+    fn crossing_maker_only_order_is_expired_without_fills() {
+        let mut engine = engine_with_order_book();
+        engine.add_order(SimulatedOrder {
+            execution_type: OrderExecutionType::MakerOnly,
+            ..order("1", OrderSide::Buy, dec!(101), dec!(1))
+        });
+
+        let fills = engine.activate_orders(start_time() + Duration::seconds(1));
+
+        assert!(fills.is_empty());
+        assert!(engine.orders().is_empty());
+        let expired_orders = engine.take_expired_orders();
+        assert_eq!(expired_orders.len(), 1);
+        assert_eq!(expired_orders[0].filled_amount, dec!(0));
+
+        let order_book = engine.order_book(currency_pair()).expect("in test");
+        assert_eq!(order_book.get_top_ask(), Some((dec!(101), dec!(1))));
+    }
+
+    #[test]
+    fn not_crossing_maker_only_order_is_filled_as_maker() {
+        let mut engine = engine_with_order_book();
+        engine.add_order(SimulatedOrder {
+            execution_type: OrderExecutionType::MakerOnly,
+            ..order("1", OrderSide::Buy, dec!(100), dec!(1))
+        });
+        let _ = engine.activate_orders(start_time() + Duration::seconds(1));
+
+        let fills = engine.apply_trade(currency_pair(), dec!(100), dec!(1), OrderSide::Sell);
+
+        let roles = fills.iter().map(|x| x.role).collect_vec();
+        assert_eq!(roles, [OrderRole::Maker]);
+        assert!(engine.take_expired_orders().is_empty());
+    }
+
+    #[test]
+    fn crossing_of_order_book_is_checked_by_opposite_side() {
+        let engine = engine_with_order_book();
+
+        assert!(engine.is_crossing_order_book(currency_pair(), OrderSide::Buy, dec!(101)));
+        assert!(!engine.is_crossing_order_book(currency_pair(), OrderSide::Buy, dec!(100)));
+        assert!(engine.is_crossing_order_book(currency_pair(), OrderSide::Sell, dec!(99)));
+        assert!(!engine.is_crossing_order_book(currency_pair(), OrderSide::Sell, dec!(100)));
+    }
+}
@@ -0,0 +1,3 @@
+pub mod matching_engine;
+pub mod paper_trading;
+pub mod simulated_exchange;
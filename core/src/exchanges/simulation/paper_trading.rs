@@ -0,0 +1,610 @@
+use std::any::Any;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use dashmap::DashMap;
+use mmb_domain::events::{ExchangeBalancesAndPositions, ExchangeEvent};
+use mmb_domain::exchanges::commission::{Commission, CommissionForType};
+use mmb_domain::exchanges::symbol::Symbol;
+use mmb_domain::market::{
+    CurrencyCode, CurrencyId, CurrencyPair, ExchangeAccountId, SpecificCurrencyPair,
+};
+use mmb_domain::order::fill::EventSourceType;
+use mmb_domain::order::pool::OrderRef;
+use mmb_domain::order::snapshot::{
+    OrderCancelling, OrderInfo, OrderInfoExtensionData, OrderSide, Price,
+};
+use mmb_domain::position::{ActivePosition, ClosedPosition};
+use mmb_utils::infrastructure::SpawnFutureFlags;
+use mmb_utils::DateTime;
+use rust_decimal::Decimal;
+use tokio::sync::broadcast;
+use tokio::sync::broadcast::error::RecvError;
+use url::Url;
+
+use crate::connectivity::WebSocketRole;
+use crate::exchanges::exchange_clock::ExchangeClock;
+use crate::exchanges::general::exchange::{BoxExchangeClient, Exchange, RequestResult};
+use crate::exchanges::general::order::cancel::CancelOrderResult;
+use crate::exchanges::general::order::create::CreateOrderResult;
+use crate::exchanges::general::order::get_order_trades::OrderTrade;
+use crate::exchanges::simulation::simulated_exchange::{SimulatedEvents, SimulatedExchange};
+use crate::exchanges::traits::{
+    ExchangeClient, ExchangeError, HandleOrderFilledCb, HandleTradeCb, OrderCancelledCb,
+    OrderCreatedCb, SendWebsocketMessageCb, Support,
+};
+use crate::infrastructure::spawn_future;
+use crate::settings::{ExchangeSettings, PaperTradingSettings};
+
+/// Decorator of an exchange client for paper trading. Market data is received by the wrapped
+/// client of the real exchange, but orders and balances are simulated over this market data
+/// and requests with them are never sent to the exchange.
+pub struct PaperTrading {
+    exchange_client: BoxExchangeClient,
+    simulated_exchange: Arc<SimulatedExchange>,
+    events_channel: broadcast::Sender<ExchangeEvent>,
+    order_created_callback: OrderCreatedCb,
+    order_cancelled_callback: Arc<OrderCancelledCb>,
+    handle_order_filled_callback: Arc<HandleOrderFilledCb>,
+}
+
+impl PaperTrading {
+    pub fn new(
+        exchange_client: BoxExchangeClient,
+        settings: &PaperTradingSettings,
+        events_channel: broadcast::Sender<ExchangeEvent>,
+    ) -> Self {
+        let commission = Commission::new(
+            CommissionForType::new(settings.maker_fee, Decimal::ZERO),
+            CommissionForType::new(settings.taker_fee, Decimal::ZERO),
+        );
+
+        Self {
+            exchange_client,
+            simulated_exchange: Arc::new(SimulatedExchange::new(
+                vec![],
+                settings.initial_balances.clone(),
+                commission,
+                Duration::ZERO,
+            )),
+            events_channel,
+            order_created_callback: Box::new(|_, _, _| {}),
+            order_cancelled_callback: Arc::new(Box::new(|_, _, _| {})),
+            handle_order_filled_callback: Arc::new(Box::new(|_| {})),
+        }
+    }
+
+    pub fn simulated_exchange(&self) -> &Arc<SimulatedExchange> {
+        &self.simulated_exchange
+    }
+}
+
+#[async_trait]
+impl Support for PaperTrading {
+    /// Wrapped client is returned to keep working exchange specific code with downcasting
+    fn as_any(&self) -> &(dyn Any + Send + Sync + 'static) {
+        self.exchange_client.as_any()
+    }
+
+    async fn initialized(&self, exchange: Arc<Exchange>) {
+        self.exchange_client.initialized(exchange.clone()).await;
+
+        let symbols = exchange.symbols.iter().map(|x| x.value().clone()).collect();
+        self.simulated_exchange.set_symbols(symbols);
+
+        spawn_future(
+            "Simulate paper trading by market data",
+            SpawnFutureFlags::STOP_BY_TOKEN,
+            simulate_by_market_data(
+                exchange.exchange_account_id,
+                self.simulated_exchange.clone(),
+                self.order_cancelled_callback.clone(),
+                self.handle_order_filled_callback.clone(),
+                self.events_channel.subscribe(),
+            ),
+        );
+    }
+
+    fn on_websocket_message(&self, msg: &str) -> Result<()> {
+        self.exchange_client.on_websocket_message(msg)
+    }
+
+    fn on_connecting(&self) -> Result<()> {
+        self.exchange_client.on_connecting()
+    }
+
+    fn on_disconnected(&self) -> Result<()> {
+        self.exchange_client.on_disconnected()
+    }
+
+    fn can_resync_order_book(&self) -> bool {
+        self.exchange_client.can_resync_order_book()
+    }
+
+    fn on_order_book_integrity_violated(&self, currency_pair: CurrencyPair) -> Result<()> {
+        self.exchange_client
+            .on_order_book_integrity_violated(currency_pair)
+    }
+
+    fn set_send_websocket_message_callback(&self, callback: SendWebsocketMessageCb) {
+        self.exchange_client
+            .set_send_websocket_message_callback(callback)
+    }
+
+    fn set_order_created_callback(&mut self, callback: OrderCreatedCb) {
+        self.order_created_callback = callback;
+    }
+
+    fn set_order_cancelled_callback(&mut self, callback: OrderCancelledCb) {
+        self.order_cancelled_callback = Arc::new(callback);
+    }
+
+    fn set_handle_order_filled_callback(&mut self, callback: HandleOrderFilledCb) {
+        self.handle_order_filled_callback = Arc::new(callback);
+    }
+
+    fn set_handle_trade_callback(&mut self, callback: HandleTradeCb) {
+        self.exchange_client.set_handle_trade_callback(callback)
+    }
+
+    fn set_traded_specific_currencies(&self, currencies: Vec<SpecificCurrencyPair>) {
+        self.exchange_client
+            .set_traded_specific_currencies(currencies)
+    }
+
+    fn is_websocket_enabled(&self, role: WebSocketRole) -> bool {
+        match role {
+            WebSocketRole::Main => self.exchange_client.is_websocket_enabled(role),
+            // events of the real account are not needed
+            WebSocketRole::Secondary => false,
+        }
+    }
+
+    async fn create_ws_url(&self, role: WebSocketRole) -> Result<Url> {
+        self.exchange_client.create_ws_url(role).await
+    }
+
+    fn get_specific_currency_pair(&self, currency_pair: CurrencyPair) -> SpecificCurrencyPair {
+        self.exchange_client
+            .get_specific_currency_pair(currency_pair)
+    }
+
+    fn get_supported_currencies(&self) -> &DashMap<CurrencyId, CurrencyCode> {
+        self.exchange_client.get_supported_currencies()
+    }
+
+    fn should_log_message(&self, message: &str) -> bool {
+        self.exchange_client.should_log_message(message)
+    }
+
+    fn log_unknown_message(&self, exchange_account_id: ExchangeAccountId, message: &str) {
+        self.exchange_client
+            .log_unknown_message(exchange_account_id, message)
+    }
+
+    fn get_balance_reservation_currency_code(
+        &self,
+        symbol: Arc<Symbol>,
+        side: OrderSide,
+    ) -> CurrencyCode {
+        self.exchange_client
+            .get_balance_reservation_currency_code(symbol, side)
+    }
+
+    fn get_settings(&self) -> &ExchangeSettings {
+        self.exchange_client.get_settings()
+    }
+
+    fn get_initial_extension_data(&self) -> Option<Box<dyn OrderInfoExtensionData>> {
+        self.exchange_client.get_initial_extension_data()
+    }
+
+    fn get_clock(&self) -> Option<Arc<ExchangeClock>> {
+        self.exchange_client.get_clock()
+    }
+
+    fn market_data_now(&self) -> DateTime {
+        self.exchange_client.market_data_now()
+    }
+}
+
+#[async_trait]
+impl ExchangeClient for PaperTrading {
+    async fn create_order(&self, order: &OrderRef) -> CreateOrderResult {
+        let result = self.simulated_exchange.create_order(order);
+        // like real exchanges, creation of order is confirmed by event after response to request
+        if let RequestResult::Success(exchange_order_id) = &result.outcome {
+            (self.order_created_callback)(
+                order.client_order_id(),
+                exchange_order_id.clone(),
+                EventSourceType::Rest,
+            );
+        }
+
+        result
+    }
+
+    async fn cancel_order(&self, order: OrderCancelling) -> CancelOrderResult {
+        let result = self.simulated_exchange.cancel_order(&order);
+        // like real exchanges, cancellation of order is confirmed by event after response to request
+        if let RequestResult::Success(client_order_id) = &result.outcome {
+            (self.order_cancelled_callback)(
+                client_order_id.clone(),
+                order.exchange_order_id,
+                EventSourceType::Rest,
+            );
+        }
+
+        result
+    }
+
+    async fn cancel_all_orders(&self, currency_pair: CurrencyPair) -> Result<()> {
+        for (client_order_id, exchange_order_id) in
+            self.simulated_exchange.cancel_all_orders(currency_pair)?
+        {
+            (self.order_cancelled_callback)(
+                client_order_id,
+                exchange_order_id,
+                EventSourceType::Rest,
+            );
+        }
+
+        Ok(())
+    }
+
+    async fn get_open_orders(&self) -> Result<Vec<OrderInfo>> {
+        Ok(self.simulated_exchange.get_open_orders(|_| true))
+    }
+
+    async fn get_open_orders_by_currency_pair(
+        &self,
+        currency_pair: CurrencyPair,
+    ) -> Result<Vec<OrderInfo>> {
+        Ok(self
+            .simulated_exchange
+            .get_open_orders(|x| x.currency_pair == currency_pair))
+    }
+
+    async fn get_order_info(&self, order: &OrderRef) -> Result<OrderInfo, ExchangeError> {
+        self.simulated_exchange.get_order_info(order)
+    }
+
+    async fn close_position(
+        &self,
+        _position: &ActivePosition,
+        _price: Option<Price>,
+    ) -> Result<ClosedPosition> {
+        Err(anyhow!(
+            "Derivative positions are not supported in paper trading"
+        ))
+    }
+
+    async fn get_active_positions(&self) -> Result<Vec<ActivePosition>> {
+        Ok(vec![])
+    }
+
+    async fn get_balance(&self) -> Result<ExchangeBalancesAndPositions> {
+        Ok(self.simulated_exchange.get_balance())
+    }
+
+    async fn get_balance_and_positions(&self) -> Result<ExchangeBalancesAndPositions> {
+        self.get_balance().await
+    }
+
+    async fn get_my_trades(
+        &self,
+        symbol: &Symbol,
+        last_date_time: Option<DateTime>,
+    ) -> RequestResult<Vec<OrderTrade>> {
+        RequestResult::Success(
+            self.simulated_exchange
+                .get_my_trades(symbol.currency_pair(), last_date_time),
+        )
+    }
+
+    async fn build_all_symbols(&self) -> Result<Vec<Arc<Symbol>>> {
+        self.exchange_client.build_all_symbols().await
+    }
+
+    async fn get_server_time(&self) -> Result<DateTime> {
+        self.exchange_client.get_server_time().await
+    }
+}
+
+async fn simulate_by_market_data(
+    exchange_account_id: ExchangeAccountId,
+    simulated_exchange: Arc<SimulatedExchange>,
+    order_cancelled_callback: Arc<OrderCancelledCb>,
+    handle_order_filled_callback: Arc<HandleOrderFilledCb>,
+    mut events_receiver: broadcast::Receiver<ExchangeEvent>,
+) -> Result<()> {
+    loop {
+        let simulated_events = match events_receiver.recv().await {
+            Ok(ExchangeEvent::OrderBookEvent(event))
+                if event.exchange_account_id == exchange_account_id =>
+            {
+                simulated_exchange.apply_order_book_event(
+                    event.creation_time,
+                    event.currency_pair,
+                    event.event_type,
+                    &event.data,
+                )?
+            }
+            Ok(ExchangeEvent::Trades(event))
+                if event.exchange_account_id == exchange_account_id =>
+            {
+                let mut simulated_events = SimulatedEvents::default();
+                for trade in event.trades {
+                    let trade_events = simulated_exchange.apply_trade(
+                        trade.transaction_time,
+                        event.currency_pair,
+                        trade.price,
+                        trade.quantity,
+                        trade.side,
+                    )?;
+                    simulated_events.fills.extend(trade_events.fills);
+                    simulated_events
+                        .expired_orders
+                        .extend(trade_events.expired_orders);
+                }
+                simulated_events
+            }
+            Ok(_) => continue,
+            Err(RecvError::Lagged(skipped_count)) => {
+                log::warn!("Paper trading on {exchange_account_id} skipped {skipped_count} events of market data");
+                continue;
+            }
+            Err(RecvError::Closed) => return Ok(()),
+        };
+
+        for fill in simulated_events.fills {
+            handle_order_filled_callback(fill);
+        }
+        for (client_order_id, exchange_order_id) in simulated_events.expired_orders {
+            order_cancelled_callback(
+                client_order_id,
+                exchange_order_id,
+                EventSourceType::WebSocket,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exchanges::general::test_helper::TestClient;
+    use mmb_domain::exchanges::symbol::Precision;
+    use mmb_domain::market::ExchangeErrorType;
+    use mmb_domain::order::pool::OrdersPool;
+    use mmb_domain::order::snapshot::{OrderExecutionType, OrderSnapshot, OrderType};
+    use mmb_domain::order_book::event::EventType;
+    use mmb_domain::order_book::order_book_data::OrderBookData;
+    use mmb_utils::hashmap;
+    use parking_lot::{Mutex, RwLock};
+    use rust_decimal_macros::dec;
+
+    fn currency_pair() -> CurrencyPair {
+        CurrencyPair::from_codes("btc".into(), "usdt".into())
+    }
+
+    fn paper_trading() -> PaperTrading {
+        let settings = PaperTradingSettings {
+            maker_fee: dec!(0.1),
+            taker_fee: dec!(0.2),
+            initial_balances: hashmap!["usdt".into() => dec!(100)],
+        };
+        let (events_channel, _) = broadcast::channel(10);
+        let paper_trading =
+            PaperTrading::new(Box::new(TestClient::default()), &settings, events_channel);
+
+        let symbol = Arc::new(Symbol::new(
+            false,
+            "btc".into(),
+            "btc".into(),
+            "usdt".into(),
+            "usdt".into(),
+            None,
+            None,
+            None,
+            None,
+            None,
+            "btc".into(),
+            None,
+            Precision::ByTick { tick: dec!(0.1) },
+            Precision::ByTick { tick: dec!(0.001) },
+        ));
+        paper_trading.simulated_exchange.set_symbols(vec![symbol]);
+
+        paper_trading
+    }
+
+    fn limit_order(side: OrderSide, price: Price, amount: Decimal) -> OrderRef {
+        let order = OrderSnapshot::with_params(
+            "test_order".into(),
+            OrderType::Limit,
+            None,
+            ExchangeAccountId::new("Binance", 0),
+            currency_pair(),
+            price,
+            amount,
+            side,
+            None,
+            "StrategyInUnitTests",
+        );
+
+        OrdersPool::new().add_snapshot_initial(Arc::new(RwLock::new(order)))
+    }
+
+    fn free_balance(paper_trading: &PaperTrading, currency_code: &str) -> Decimal {
+        paper_trading
+            .simulated_exchange
+            .get_balance()
+            .balances
+            .into_iter()
+            .find(|x| x.currency_code == currency_code.into())
+            .map(|x| x.balance)
+            .unwrap_or_default()
+    }
+
+    #[tokio::test]
+    async fn create_order_without_sending_to_exchange() {
+        let paper_trading = paper_trading();
+        let order = limit_order(OrderSide::Buy, dec!(10), dec!(2));
+
+        let result = paper_trading.create_order(&order).await;
+
+        assert!(result.outcome.get_error().is_none());
+        let open_orders = paper_trading.get_open_orders().await.expect("in test");
+        assert_eq!(open_orders.len(), 1);
+        assert_eq!(free_balance(&paper_trading, "usdt"), dec!(80));
+    }
+
+    #[tokio::test]
+    async fn confirm_order_creation_by_callback() {
+        let mut paper_trading = paper_trading();
+        let created_orders = Arc::new(Mutex::new(vec![]));
+        paper_trading.set_order_created_callback(Box::new({
+            let created_orders = created_orders.clone();
+            move |client_order_id, exchange_order_id, _| {
+                created_orders
+                    .lock()
+                    .push((client_order_id, exchange_order_id))
+            }
+        }));
+        let order = limit_order(OrderSide::Buy, dec!(10), dec!(2));
+
+        let result = paper_trading.create_order(&order).await;
+
+        let exchange_order_id = match result.outcome {
+            RequestResult::Success(exchange_order_id) => exchange_order_id,
+            RequestResult::Error(error) => panic!("order should be created: {error:?}"),
+        };
+        assert_eq!(
+            *created_orders.lock(),
+            [(order.client_order_id(), exchange_order_id)]
+        );
+    }
+
+    #[tokio::test]
+    async fn confirm_order_cancellation_by_callback() {
+        let mut paper_trading = paper_trading();
+        let cancelled_orders = Arc::new(Mutex::new(vec![]));
+        paper_trading.set_order_cancelled_callback(Box::new({
+            let cancelled_orders = cancelled_orders.clone();
+            move |client_order_id, exchange_order_id, _| {
+                cancelled_orders
+                    .lock()
+                    .push((client_order_id, exchange_order_id))
+            }
+        }));
+        let order = limit_order(OrderSide::Buy, dec!(10), dec!(2));
+        let exchange_order_id = match paper_trading.create_order(&order).await.outcome {
+            RequestResult::Success(exchange_order_id) => exchange_order_id,
+            RequestResult::Error(error) => panic!("order should be created: {error:?}"),
+        };
+        let order_cancelling = OrderCancelling {
+            header: order.fn_ref(|x| x.header.clone()),
+            exchange_order_id: exchange_order_id.clone(),
+            extension_data: None,
+        };
+
+        let result = paper_trading.cancel_order(order_cancelling).await;
+
+        assert!(result.outcome.get_error().is_none());
+        assert_eq!(
+            *cancelled_orders.lock(),
+            [(order.client_order_id(), exchange_order_id)]
+        );
+        assert_eq!(free_balance(&paper_trading, "usdt"), dec!(100));
+    }
+
+    #[tokio::test]
+    async fn reject_order_with_insufficient_virtual_balance() {
+        let paper_trading = paper_trading();
+        let order = limit_order(OrderSide::Buy, dec!(10), dec!(20));
+
+        let result = paper_trading.create_order(&order).await;
+
+        let error = result.outcome.get_error().expect("in test");
+        assert_eq!(error.error_type, ExchangeErrorType::InsufficientFunds);
+    }
+
+    #[tokio::test]
+    async fn fill_order_by_live_order_book() {
+        let paper_trading = paper_trading();
+        let order = limit_order(OrderSide::Buy, dec!(10), dec!(2));
+        let _ = paper_trading.create_order(&order).await;
+
+        let order_book = OrderBookData::new(
+            [(dec!(9.9), dec!(5))].into_iter().collect(),
+            [(dec!(9.8), dec!(5))].into_iter().collect(),
+        );
+        let fills = paper_trading
+            .simulated_exchange
+            .apply_order_book_event(
+                chrono::Utc::now(),
+                currency_pair(),
+                EventType::Snapshot,
+                &order_book,
+            )
+            .expect("in test")
+            .fills;
+
+        assert_eq!(fills.len(), 1);
+        assert_eq!(fills[0].fill_price, dec!(10));
+        assert_eq!(free_balance(&paper_trading, "usdt"), dec!(80));
+        assert_eq!(free_balance(&paper_trading, "btc"), dec!(1.998));
+        assert!(paper_trading
+            .get_open_orders()
+            .await
+            .expect("in test")
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn reject_maker_only_order_crossing_order_book() {
+        let paper_trading = paper_trading();
+        let order_book = OrderBookData::new(
+            [(dec!(9.9), dec!(5))].into_iter().collect(),
+            [(dec!(9.8), dec!(5))].into_iter().collect(),
+        );
+        let _ = paper_trading
+            .simulated_exchange
+            .apply_order_book_event(
+                chrono::Utc::now(),
+                currency_pair(),
+                EventType::Snapshot,
+                &order_book,
+            )
+            .expect("in test");
+
+        let mut order = OrderSnapshot::with_params(
+            "test_order".into(),
+            OrderType::Limit,
+            None,
+            ExchangeAccountId::new("Binance", 0),
+            currency_pair(),
+            dec!(10),
+            dec!(2),
+            OrderSide::Buy,
+            None,
+            "StrategyInUnitTests",
+        );
+        Arc::make_mut(&mut order.header).execution_type = OrderExecutionType::MakerOnly;
+        let order = OrdersPool::new().add_snapshot_initial(Arc::new(RwLock::new(order)));
+
+        let result = paper_trading.create_order(&order).await;
+
+        let error = result.outcome.get_error().expect("in test");
+        assert_eq!(error.error_type, ExchangeErrorType::InvalidOrder);
+        assert!(paper_trading
+            .get_open_orders()
+            .await
+            .expect("in test")
+            .is_empty());
+        assert_eq!(free_balance(&paper_trading, "usdt"), dec!(100));
+    }
+}
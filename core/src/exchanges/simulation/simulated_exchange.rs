@@ -0,0 +1,604 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use itertools::Itertools;
+use mmb_domain::events::{ExchangeBalance, ExchangeBalancesAndPositions, TradeId};
+use mmb_domain::exchanges::commission::Commission;
+use mmb_domain::exchanges::symbol::{BeforeAfter, Symbol};
+use mmb_domain::market::{CurrencyCode, CurrencyPair, ExchangeErrorType};
+use mmb_domain::order::fill::{EventSourceType, OrderFillType};
+use mmb_domain::order::pool::OrderRef;
+use mmb_domain::order::snapshot::{
+    Amount, ClientOrderId, ExchangeOrderId, OrderCancelling, OrderExecutionType, OrderInfo,
+    OrderRole, OrderSide, OrderStatus, OrderTimeInForce, OrderType, Price,
+};
+use mmb_domain::order_book::event::EventType;
+use mmb_domain::order_book::order_book_data::OrderBookData;
+use mmb_utils::DateTime;
+use parking_lot::Mutex;
+use serde::Serialize;
+
+use crate::exchanges::general::handlers::handle_order_filled::{FillAmount, FillEvent};
+use crate::exchanges::general::order::cancel::CancelOrderResult;
+use crate::exchanges::general::order::create::CreateOrderResult;
+use crate::exchanges::general::order::get_order_trades::OrderTrade;
+use crate::exchanges::simulation::matching_engine::{MatchedFill, MatchingEngine, SimulatedOrder};
+use crate::exchanges::traits::ExchangeError;
+use crate::math::ConvertPercentToRate;
+
+/// Fill of an order on the simulated exchange
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SimulatedFill {
+    pub time: DateTime,
+    pub trade_id: u64,
+    pub client_order_id: ClientOrderId,
+    pub exchange_order_id: ExchangeOrderId,
+    pub currency_pair: CurrencyPair,
+    pub side: OrderSide,
+    pub price: Price,
+    pub amount: Amount,
+    pub role: OrderRole,
+    pub commission_currency_code: CurrencyCode,
+    pub commission_amount: Amount,
+}
+
+/// Events of orders caused by applied market data which should be passed to the engine
+#[derive(Debug, Default)]
+pub struct SimulatedEvents {
+    pub fills: Vec<FillEvent>,
+    /// Immediate orders and crossing maker only orders which are canceled by the exchange after matching
+    pub expired_orders: Vec<(ClientOrderId, ExchangeOrderId)>,
+}
+
+struct OrderRecord {
+    order: SimulatedOrder,
+    status: OrderStatus,
+}
+
+struct SimulatedState {
+    /// Time of the last applied market data event (minimal time before the first one)
+    now: DateTime,
+    symbols: Vec<Arc<Symbol>>,
+    matching_engine: MatchingEngine,
+    balances: HashMap<CurrencyCode, Amount>,
+    orders: HashMap<ClientOrderId, OrderRecord>,
+    fills: Vec<SimulatedFill>,
+    last_prices: HashMap<CurrencyPair, Price>,
+    last_order_id: u64,
+    last_trade_id: u64,
+}
+
+/// Orders, balances and matching of an exchange which is simulated over market data.
+/// Time of the simulation is moved by applied market data events.
+pub struct SimulatedExchange {
+    commission: Commission,
+    /// Delay between order creation and the moment when order takes part in matching
+    latency: Duration,
+    state: Mutex<SimulatedState>,
+}
+
+impl SimulatedExchange {
+    pub fn new(
+        symbols: Vec<Arc<Symbol>>,
+        initial_balances: HashMap<CurrencyCode, Amount>,
+        commission: Commission,
+        latency: Duration,
+    ) -> Self {
+        let state = SimulatedState {
+            now: DateTime::MIN_UTC,
+            symbols,
+            matching_engine: MatchingEngine::default(),
+            balances: initial_balances,
+            orders: HashMap::new(),
+            fills: vec![],
+            last_prices: HashMap::new(),
+            last_order_id: 0,
+            last_trade_id: 0,
+        };
+
+        Self {
+            commission,
+            latency,
+            state: Mutex::new(state),
+        }
+    }
+
+    pub fn set_symbols(&self, symbols: Vec<Arc<Symbol>>) {
+        self.state.lock().symbols = symbols;
+    }
+
+    pub fn now(&self) -> DateTime {
+        self.state.lock().now
+    }
+
+    pub fn balances(&self) -> HashMap<CurrencyCode, Amount> {
+        self.state.lock().balances.clone()
+    }
+
+    pub fn fills(&self) -> Vec<SimulatedFill> {
+        self.state.lock().fills.clone()
+    }
+
+    /// Prices of the last trades or middle prices of order books by markets
+    pub fn last_prices(&self) -> HashMap<CurrencyPair, Price> {
+        self.state.lock().last_prices.clone()
+    }
+
+    pub fn create_order(&self, order: &OrderRef) -> CreateOrderResult {
+        match self.place_order(order) {
+            Ok(exchange_order_id) => {
+                CreateOrderResult::succeed(&exchange_order_id, EventSourceType::Rest)
+            }
+            Err(err) => CreateOrderResult::failed(err, EventSourceType::Rest),
+        }
+    }
+
+    pub fn cancel_order(&self, order: &OrderCancelling) -> CancelOrderResult {
+        let client_order_id = &order.header.client_order_id;
+        match self.remove_order(client_order_id, &order.exchange_order_id) {
+            Ok(filled_amount) => CancelOrderResult::succeed(
+                client_order_id.clone(),
+                EventSourceType::Rest,
+                Some(filled_amount),
+            ),
+            Err(err) => CancelOrderResult::failed(err, EventSourceType::Rest),
+        }
+    }
+
+    /// Cancel active orders of the market. Returns identifiers of cancelled orders.
+    pub fn cancel_all_orders(
+        &self,
+        currency_pair: CurrencyPair,
+    ) -> Result<Vec<(ClientOrderId, ExchangeOrderId)>> {
+        self.get_open_orders(|x| x.currency_pair == currency_pair)
+            .into_iter()
+            .map(|order| {
+                self.remove_order(&order.client_order_id, &order.exchange_order_id)?;
+                Ok((order.client_order_id, order.exchange_order_id))
+            })
+            .collect()
+    }
+
+    pub fn get_open_orders(&self, filter: impl Fn(&SimulatedOrder) -> bool) -> Vec<OrderInfo> {
+        self.state
+            .lock()
+            .orders
+            .values()
+            .filter(|x| x.status == OrderStatus::Created && filter(&x.order))
+            .map(to_order_info)
+            .collect_vec()
+    }
+
+    pub fn get_order_info(&self, order: &OrderRef) -> Result<OrderInfo, ExchangeError> {
+        let client_order_id = order.client_order_id();
+        self.state
+            .lock()
+            .orders
+            .get(&client_order_id)
+            .map(to_order_info)
+            .ok_or_else(|| {
+                ExchangeError::new(
+                    ExchangeErrorType::OrderNotFound,
+                    format!("Order {client_order_id} is not found"),
+                    None,
+                )
+            })
+    }
+
+    /// Balances without amounts reserved by active orders
+    pub fn get_balance(&self) -> ExchangeBalancesAndPositions {
+        let state = self.state.lock();
+        let balances = state
+            .balances
+            .keys()
+            .map(|&currency_code| ExchangeBalance {
+                currency_code,
+                balance: state.free_balance(currency_code),
+            })
+            .collect_vec();
+
+        ExchangeBalancesAndPositions {
+            balances,
+            positions: None,
+        }
+    }
+
+    pub fn get_my_trades(
+        &self,
+        currency_pair: CurrencyPair,
+        last_date_time: Option<DateTime>,
+    ) -> Vec<OrderTrade> {
+        self.state
+            .lock()
+            .fills
+            .iter()
+            .filter(|x| x.currency_pair == currency_pair)
+            .filter(|x| last_date_time.is_none_or(|last| x.time > last))
+            .map(|fill| {
+                OrderTrade::new(
+                    fill.exchange_order_id.clone(),
+                    TradeId::Number(fill.trade_id),
+                    fill.time,
+                    fill.price,
+                    fill.amount,
+                    fill.role,
+                    fill.commission_currency_code,
+                    None,
+                    Some(fill.commission_amount),
+                    OrderFillType::UserTrade,
+                )
+            })
+            .collect_vec()
+    }
+
+    /// Apply order book event and match orders with it
+    pub fn apply_order_book_event(
+        &self,
+        time: DateTime,
+        currency_pair: CurrencyPair,
+        event_type: EventType,
+        data: &OrderBookData,
+    ) -> Result<SimulatedEvents> {
+        self.apply_market_data(time, |state, now| {
+            let fills = match event_type {
+                EventType::Snapshot => {
+                    state
+                        .matching_engine
+                        .apply_order_book_snapshot(currency_pair, data, now)
+                }
+                EventType::Update => {
+                    state
+                        .matching_engine
+                        .apply_order_book_update(currency_pair, data, now)
+                }
+            };
+
+            if let Some(middle_price) = state.middle_price(currency_pair) {
+                let _ = state.last_prices.insert(currency_pair, middle_price);
+            }
+
+            fills
+        })
+    }
+
+    /// Apply public trade and match resting orders with it
+    pub fn apply_trade(
+        &self,
+        time: DateTime,
+        currency_pair: CurrencyPair,
+        price: Price,
+        amount: Amount,
+        taker_side: OrderSide,
+    ) -> Result<SimulatedEvents> {
+        self.apply_market_data(time, |state, _| {
+            let _ = state.last_prices.insert(currency_pair, price);
+            state
+                .matching_engine
+                .apply_trade(currency_pair, price, amount, taker_side)
+        })
+    }
+
+    fn apply_market_data(
+        &self,
+        time: DateTime,
+        apply: impl FnOnce(&mut SimulatedState, DateTime) -> Vec<MatchedFill>,
+    ) -> Result<SimulatedEvents> {
+        let mut state = self.state.lock();
+        state.now = state.now.max(time);
+
+        let now = state.now;
+        let mut fills = state.matching_engine.activate_orders(now);
+        let expired_orders = state.matching_engine.take_expired_orders();
+        fills.extend(apply(&mut state, now));
+
+        let fills = fills
+            .into_iter()
+            .map(|fill| self.apply_fill(&mut state, fill))
+            .try_collect()?;
+
+        let expired_orders = expired_orders
+            .into_iter()
+            .map(|order| {
+                if let Some(record) = state.orders.get_mut(&order.client_order_id) {
+                    record.status = OrderStatus::Canceled;
+                }
+                (order.client_order_id, order.exchange_order_id)
+            })
+            .collect_vec();
+
+        Ok(SimulatedEvents {
+            fills,
+            expired_orders,
+        })
+    }
+
+    fn place_order(&self, order: &OrderRef) -> Result<ExchangeOrderId, ExchangeError> {
+        let (
+            client_order_id,
+            currency_pair,
+            order_type,
+            side,
+            price,
+            amount,
+            time_in_force,
+            execution_type,
+        ) = order.fn_ref(|x| {
+            (
+                x.header.client_order_id.clone(),
+                x.header.currency_pair,
+                x.header.order_type,
+                x.header.side,
+                x.price(),
+                x.header.amount,
+                x.header.time_in_force,
+                x.header.execution_type,
+            )
+        });
+
+        let invalid_order =
+            |message: String| ExchangeError::new(ExchangeErrorType::InvalidOrder, message, None);
+
+        let mut state = self.state.lock();
+
+        let symbol = state
+            .get_symbol(currency_pair)
+            .ok_or_else(|| invalid_order(format!("Unknown currency pair {currency_pair}")))?;
+
+        if !matches!(order_type, OrderType::Limit | OrderType::Market) {
+            return Err(invalid_order(format!(
+                "Order type {order_type:?} is not supported"
+            )));
+        }
+
+        if time_in_force != OrderTimeInForce::GoodTillCancelled && !time_in_force.is_immediate() {
+            return Err(invalid_order(format!(
+                "Time in force {time_in_force:?} is not supported"
+            )));
+        }
+
+        if amount <= Amount::ZERO {
+            return Err(invalid_order(format!("Invalid order amount {amount}")));
+        }
+
+        // like LIMIT_MAKER order on Binance, maker only order is rejected if it would take liquidity
+        if execution_type == OrderExecutionType::MakerOnly {
+            if order_type == OrderType::Market {
+                return Err(invalid_order("Market order can't be maker only".to_owned()));
+            }
+
+            if state
+                .matching_engine
+                .is_crossing_order_book(currency_pair, side, price)
+            {
+                return Err(invalid_order(
+                    "Order would immediately match and take".to_owned(),
+                ));
+            }
+        }
+
+        // market order reserves funds by the top price of the order book
+        let price = match order_type {
+            OrderType::Market => state
+                .matching_engine
+                .order_book(currency_pair)
+                .and_then(|x| x.get_top(side.change_side()))
+                .map(|(price, _)| price)
+                .ok_or_else(|| {
+                    invalid_order(format!("There is no order book for {currency_pair}"))
+                })?,
+            _ => price,
+        };
+
+        let spent_currency_code = symbol.get_trade_code(side, BeforeAfter::Before);
+        let required_amount =
+            symbol.convert_amount_from_amount_currency_code(spent_currency_code, amount, price);
+        let free_balance = state.free_balance(spent_currency_code);
+        if free_balance < required_amount {
+            return Err(ExchangeError::new(
+                ExchangeErrorType::InsufficientFunds,
+                format!("Required {required_amount} {spent_currency_code} but free balance is {free_balance}"),
+                None,
+            ));
+        }
+
+        state.last_order_id += 1;
+        let exchange_order_id: ExchangeOrderId = state.last_order_id.to_string().as_str().into();
+
+        let simulated_order = SimulatedOrder {
+            client_order_id: client_order_id.clone(),
+            exchange_order_id: exchange_order_id.clone(),
+            currency_pair,
+            order_type,
+            side,
+            price,
+            amount,
+            time_in_force,
+            execution_type,
+            filled_amount: Amount::ZERO,
+            active_since: state.now + to_chrono_duration(self.latency),
+            is_resting: false,
+        };
+
+        state.matching_engine.add_order(simulated_order.clone());
+        let _ = state.orders.insert(
+            client_order_id,
+            OrderRecord {
+                order: simulated_order,
+                status: OrderStatus::Created,
+            },
+        );
+
+        Ok(exchange_order_id)
+    }
+
+    /// Remove order from matching. Returns filled amount of the order
+    fn remove_order(
+        &self,
+        client_order_id: &ClientOrderId,
+        exchange_order_id: &ExchangeOrderId,
+    ) -> Result<Amount, ExchangeError> {
+        let mut state_guard = self.state.lock();
+        let state = &mut *state_guard;
+
+        let record = state.orders.get_mut(client_order_id).ok_or_else(|| {
+            ExchangeError::new(
+                ExchangeErrorType::OrderNotFound,
+                format!("Order {client_order_id} is not found"),
+                None,
+            )
+        })?;
+
+        if record.status == OrderStatus::Completed {
+            return Err(ExchangeError::new(
+                ExchangeErrorType::OrderCompleted,
+                format!("Order {client_order_id} is already completed"),
+                None,
+            ));
+        }
+
+        let _ = state.matching_engine.remove_order(exchange_order_id);
+        record.status = OrderStatus::Canceled;
+
+        Ok(record.order.filled_amount)
+    }
+
+    fn apply_fill(&self, state: &mut SimulatedState, fill: MatchedFill) -> Result<FillEvent> {
+        let symbol = state
+            .get_symbol(fill.currency_pair)
+            .with_context(|| format!("Unknown symbol for filled order {fill:?}"))?;
+
+        let spent_currency_code = symbol.get_trade_code(fill.side, BeforeAfter::Before);
+        let received_currency_code = symbol.get_trade_code(fill.side, BeforeAfter::After);
+        let spent_amount = symbol.convert_amount_from_amount_currency_code(
+            spent_currency_code,
+            fill.amount,
+            fill.price,
+        );
+        let received_amount = symbol.convert_amount_from_amount_currency_code(
+            received_currency_code,
+            fill.amount,
+            fill.price,
+        );
+
+        let commission_rate = self
+            .commission
+            .get_commission(fill.role)
+            .fee
+            .percent_to_rate();
+        let commission_amount = received_amount * commission_rate;
+
+        *state.balances.entry(spent_currency_code).or_default() -= spent_amount;
+        *state.balances.entry(received_currency_code).or_default() +=
+            received_amount - commission_amount;
+
+        if let Some(record) = state.orders.get_mut(&fill.client_order_id) {
+            record.order.filled_amount = fill.total_filled_amount;
+            if fill.is_completed {
+                record.status = OrderStatus::Completed;
+            }
+        }
+
+        state.last_trade_id += 1;
+        let trade_id = state.last_trade_id;
+        state.fills.push(SimulatedFill {
+            time: state.now,
+            trade_id,
+            client_order_id: fill.client_order_id.clone(),
+            exchange_order_id: fill.exchange_order_id.clone(),
+            currency_pair: fill.currency_pair,
+            side: fill.side,
+            price: fill.price,
+            amount: fill.amount,
+            role: fill.role,
+            commission_currency_code: received_currency_code,
+            commission_amount,
+        });
+
+        Ok(FillEvent {
+            source_type: EventSourceType::WebSocket,
+            trade_id: Some(TradeId::Number(trade_id)),
+            client_order_id: Some(fill.client_order_id),
+            exchange_order_id: fill.exchange_order_id,
+            fill_price: fill.price,
+            fill_amount: FillAmount::Incremental {
+                fill_amount: fill.amount,
+                total_filled_amount: Some(fill.total_filled_amount),
+            },
+            order_role: Some(fill.role),
+            commission_currency_code: Some(received_currency_code),
+            commission_rate: Some(commission_rate),
+            commission_amount: Some(commission_amount),
+            fill_type: OrderFillType::UserTrade,
+            special_order_data: None,
+            fill_date: Some(state.now),
+        })
+    }
+}
+
+impl SimulatedState {
+    fn get_symbol(&self, currency_pair: CurrencyPair) -> Option<Arc<Symbol>> {
+        self.symbols
+            .iter()
+            .find(|x| x.currency_pair() == currency_pair)
+            .cloned()
+    }
+
+    fn free_balance(&self, currency_code: CurrencyCode) -> Amount {
+        let balance = self
+            .balances
+            .get(&currency_code)
+            .copied()
+            .unwrap_or_default();
+        balance - self.locked_balance(currency_code)
+    }
+
+    /// Amount of currency reserved by not filled part of active orders
+    fn locked_balance(&self, currency_code: CurrencyCode) -> Amount {
+        self.matching_engine
+            .orders()
+            .iter()
+            .filter_map(|order| {
+                let symbol = self.get_symbol(order.currency_pair)?;
+                let spent_currency_code = symbol.get_trade_code(order.side, BeforeAfter::Before);
+                (spent_currency_code == currency_code).then(|| {
+                    symbol.convert_amount_from_amount_currency_code(
+                        spent_currency_code,
+                        order.remaining_amount(),
+                        order.price,
+                    )
+                })
+            })
+            .sum()
+    }
+
+    fn middle_price(&self, currency_pair: CurrencyPair) -> Option<Price> {
+        let order_book = self.matching_engine.order_book(currency_pair)?;
+        let (ask, _) = order_book.get_top_ask()?;
+        let (bid, _) = order_book.get_top_bid()?;
+        Some((ask + bid) / Price::TWO)
+    }
+}
+
+fn to_order_info(record: &OrderRecord) -> OrderInfo {
+    let order = &record.order;
+    OrderInfo::new(
+        order.currency_pair,
+        order.exchange_order_id.clone(),
+        order.client_order_id.clone(),
+        order.side,
+        record.status,
+        order.price,
+        order.amount,
+        order.price,
+        order.filled_amount,
+        None,
+        None,
+        None,
+    )
+}
+
+fn to_chrono_duration(duration: Duration) -> chrono::Duration {
+    chrono::Duration::from_std(duration).unwrap_or_else(|_| chrono::Duration::max_value())
+}
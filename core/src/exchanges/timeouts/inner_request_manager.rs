@@ -1,15 +1,16 @@
 use super::{
     more_or_equals_available_requests_count_trigger_scheduler::MoreOrEqualsAvailableRequestsCountTriggerScheduler,
     pre_reserved_group::PreReservedGroup, request::Request,
-    triggers::handle_trigger_trait::TriggerHandler,
+    request_weight_limit::RequestWeightWindow, triggers::handle_trigger_trait::TriggerHandler,
 };
-use crate::exchanges::general::request_type::RequestType;
+use crate::exchanges::general::request_type::{RequestPriority, RequestType};
 use crate::exchanges::timeouts::requests_timeout_manager::RequestGroupId;
 use anyhow::{bail, Result};
 use chrono::Duration;
 use function_name::named;
 use mmb_domain::market::ExchangeAccountId;
 use mmb_utils::DateTime;
+use std::cmp::Reverse;
 use std::collections::HashMap;
 
 pub(super) struct InnerRequestsTimeoutManager {
@@ -19,6 +20,7 @@ pub(super) struct InnerRequestsTimeoutManager {
     pub(super) requests: Vec<Request>,
     pub(super) pre_reserved_groups: Vec<PreReservedGroup>,
     pub(super) last_time: Option<DateTime>,
+    pub(super) weight_windows: Vec<RequestWeightWindow>,
 
     pub(super) group_was_reserved: Box<dyn Fn(PreReservedGroup) + Send>,
     pub(super) group_was_removed: Box<dyn Fn(PreReservedGroup) + Send>,
@@ -43,7 +45,7 @@ impl InnerRequestsTimeoutManager {
         let _all_available_requests_count = self.get_all_available_requests_count();
         let available_requests_count = self.get_available_requests_count_at_present(current_time);
 
-        if available_requests_count == 0 {
+        if available_requests_count == 0 || !self.can_reserve_weight(request_type, current_time) {
             // TODO save to DataRecorder
 
             return false;
@@ -78,21 +80,31 @@ impl InnerRequestsTimeoutManager {
         count
     }
 
-    #[named]
     pub(super) fn add_request(
         &mut self,
         request_type: RequestType,
         current_time: DateTime,
         group_id: Option<RequestGroupId>,
     ) -> Request {
-        let request = Request::new(request_type, current_time, group_id);
+        let priority = self.get_request_priority(request_type, group_id);
+        let request = Request::new(request_type, priority, current_time, group_id);
+        self.insert_request(request.clone());
+
+        request
+    }
+
+    #[named]
+    fn insert_request(&mut self, request: Request) {
+        for weight_window in &mut self.weight_windows {
+            weight_window.reserve(request.request_type, request.allowed_start_time);
+        }
 
         let request_index = self
             .requests
             .binary_search_by_key(&request.allowed_start_time, |r| r.allowed_start_time)
             .map_or_else(|idx| idx, |idx| idx);
 
-        self.requests.insert(request_index, request.clone());
+        self.requests.insert(request_index, request);
 
         let last_request_start_time = self
             .requests
@@ -102,10 +114,124 @@ impl InnerRequestsTimeoutManager {
 
         self.handle_all_decreasing_triggers();
         self.handle_all_increasing_triggers(last_request_start_time);
+    }
+
+    /// Priority of request type raised to priority of pre-reserved group
+    pub(super) fn get_request_priority(
+        &self,
+        request_type: RequestType,
+        group_id: Option<RequestGroupId>,
+    ) -> RequestPriority {
+        let group_priority = group_id
+            .and_then(|group_id| self.pre_reserved_groups.iter().find(|x| x.id == group_id))
+            .map(|group| group.priority)
+            .unwrap_or_default();
+
+        request_type.priority().max(group_priority)
+    }
+
+    /// Earliest time when request can be started after already scheduled requests
+    fn get_request_start_time(
+        &self,
+        request_type: RequestType,
+        current_time: DateTime,
+    ) -> DateTime {
+        let request_start_time = match self.requests.last() {
+            Some(last_request) => {
+                let last_request_start_time = last_request.allowed_start_time;
+
+                let available_requests_count_for_period =
+                    self.get_available_requests_count_in_last_period(last_request_start_time);
+                let request_start_time = if available_requests_count_for_period == 0 {
+                    last_request_start_time + self.period_duration + self.delay_to_next_time_period
+                } else {
+                    last_request_start_time
+                };
+
+                request_start_time.max(current_time)
+            }
+            None => current_time,
+        };
+
+        self.get_weight_available_time(request_type, request_start_time)
+    }
+
+    /// Schedule request with specified priority. Not started requests with lower priority
+    /// are rescheduled after it, so higher priority request takes the earliest available time
+    pub(super) fn schedule_request(
+        &mut self,
+        request_type: RequestType,
+        priority: RequestPriority,
+        current_time: DateTime,
+    ) -> Request {
+        let displaced_requests = self.take_displaceable_requests(priority, current_time);
+
+        let request_start_time = self.get_request_start_time(request_type, current_time);
+        let request = Request::new(request_type, priority, request_start_time, None);
+        self.insert_request(request.clone());
+
+        for mut displaced_request in displaced_requests {
+            displaced_request.allowed_start_time = self
+                .get_request_start_time(displaced_request.request_type, current_time)
+                .max(displaced_request.allowed_start_time);
+
+            log::trace!(
+                "Request {:?} with priority {:?} is postponed to {}",
+                displaced_request.request_type,
+                displaced_request.priority,
+                displaced_request.allowed_start_time
+            );
+
+            self.insert_request(displaced_request);
+        }
 
         request
     }
 
+    /// Remove requests which aren't started yet and have lower priority
+    fn take_displaceable_requests(
+        &mut self,
+        priority: RequestPriority,
+        current_time: DateTime,
+    ) -> Vec<Request> {
+        let is_displaceable = |request: &Request| {
+            request.allowed_start_time > current_time
+                && request.priority < priority
+                && request.group_id.is_none()
+        };
+
+        if !self.requests.iter().any(is_displaceable) {
+            return Vec::new();
+        }
+
+        let (mut displaced_requests, requests): (Vec<_>, Vec<_>) =
+            self.requests.drain(..).partition(is_displaceable);
+        self.requests = requests;
+
+        // requests with higher priority are rescheduled first, order of the same priority is kept
+        displaced_requests.sort_by_key(|x| Reverse(x.priority));
+
+        for displaced_request in &displaced_requests {
+            for weight_window in &mut self.weight_windows {
+                weight_window.unreserve(
+                    displaced_request.request_type,
+                    displaced_request.allowed_start_time,
+                );
+            }
+        }
+
+        displaced_requests
+    }
+
+    /// Current start time of scheduled request. It can be changed when request is displaced
+    /// by request with higher priority
+    pub(super) fn get_scheduled_start_time(&self, request: &Request) -> Option<DateTime> {
+        self.requests
+            .iter()
+            .find(|x| x.id == request.id)
+            .map(|x| x.allowed_start_time)
+    }
+
     pub(super) fn handle_all_decreasing_triggers(&mut self) {
         let available_requests_count = self.get_all_available_requests_count();
 
@@ -244,6 +370,58 @@ impl InnerRequestsTimeoutManager {
             .expect("Overflowed deadline in remove_outdated_requests");
 
         self.requests.retain(|r| r.allowed_start_time >= deadline);
+
+        for weight_window in &mut self.weight_windows {
+            weight_window.remove_outdated(current_time);
+        }
+    }
+
+    pub(super) fn remove_request(&mut self, request: &Request) {
+        if let Some(position) = self
+            .requests
+            .iter()
+            .position(|stored_request| stored_request.id == request.id)
+        {
+            let request = self.requests.remove(position);
+            for weight_window in &mut self.weight_windows {
+                weight_window.unreserve(request.request_type, request.allowed_start_time);
+            }
+        }
+    }
+
+    pub(super) fn can_reserve_weight(&self, request_type: RequestType, time: DateTime) -> bool {
+        self.weight_windows
+            .iter()
+            .all(|x| x.can_reserve(request_type, time))
+    }
+
+    /// Earliest time not before `time` when request fits to all weight limits
+    pub(super) fn get_weight_available_time(
+        &self,
+        request_type: RequestType,
+        time: DateTime,
+    ) -> DateTime {
+        let mut available_time = time;
+        loop {
+            let next_available_time = self
+                .weight_windows
+                .iter()
+                .map(|x| {
+                    x.get_available_time(
+                        request_type,
+                        available_time,
+                        self.delay_to_next_time_period,
+                    )
+                })
+                .max()
+                .unwrap_or(available_time);
+
+            if next_available_time == available_time {
+                return available_time;
+            }
+
+            available_time = next_available_time;
+        }
     }
 
     pub(super) fn get_non_decreasing_time(&self, time: DateTime) -> DateTime {
@@ -2,6 +2,7 @@ pub mod inner_request_manager;
 pub mod more_or_equals_available_requests_count_trigger_scheduler;
 pub mod pre_reserved_group;
 pub mod request;
+pub mod request_weight_limit;
 pub mod requests_timeout_manager;
 pub mod requests_timeout_manager_factory;
 pub mod timeout_manager;
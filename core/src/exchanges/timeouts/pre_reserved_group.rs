@@ -1,3 +1,4 @@
+use crate::exchanges::general::request_type::RequestPriority;
 use crate::exchanges::timeouts::requests_timeout_manager::RequestGroupId;
 
 #[derive(Clone)]
@@ -6,14 +7,22 @@ pub struct PreReservedGroup {
     pub(crate) id: RequestGroupId,
     pub(crate) group_type: String,
     pub(crate) pre_reserved_requests_count: usize,
+    /// Requests of group have at least this priority
+    pub(crate) priority: RequestPriority,
 }
 
 impl PreReservedGroup {
-    pub fn new(id: RequestGroupId, group_type: String, pre_reserved_requests_count: usize) -> Self {
+    pub fn new(
+        id: RequestGroupId,
+        group_type: String,
+        pre_reserved_requests_count: usize,
+        priority: RequestPriority,
+    ) -> Self {
         Self {
             id,
             group_type,
             pre_reserved_requests_count,
+            priority,
         }
     }
 }
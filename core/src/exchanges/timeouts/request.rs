@@ -1,11 +1,15 @@
 use mmb_utils::DateTime;
 
-use crate::exchanges::general::request_type::RequestType;
+use uuid::Uuid;
+
+use crate::exchanges::general::request_type::{RequestPriority, RequestType};
 use crate::exchanges::timeouts::requests_timeout_manager::RequestGroupId;
 
 #[derive(Clone, PartialEq, Eq, Debug)]
 pub struct Request {
+    pub(crate) id: Uuid,
     pub(crate) request_type: RequestType,
+    pub(crate) priority: RequestPriority,
     pub(crate) allowed_start_time: DateTime,
     pub(crate) group_id: Option<RequestGroupId>,
 }
@@ -13,11 +17,14 @@ pub struct Request {
 impl Request {
     pub fn new(
         request_type: RequestType,
+        priority: RequestPriority,
         allowed_start_time: DateTime,
         group_id: Option<RequestGroupId>,
     ) -> Self {
         Self {
+            id: Uuid::new_v4(),
             request_type,
+            priority,
             allowed_start_time,
             group_id,
         }
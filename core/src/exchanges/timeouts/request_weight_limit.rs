@@ -0,0 +1,251 @@
+use std::collections::HashMap;
+
+use chrono::Duration;
+use mmb_utils::DateTime;
+
+use crate::exchanges::general::request_type::RequestType;
+
+/// Limit of summary weight of requests in sliding time window.
+/// Exchange can declare several limits which are checked together with requests count limit
+/// of `RequestsTimeoutManager`, e.g. request weight per minute and orders count per 10 seconds
+#[derive(Debug, Clone)]
+pub struct RequestWeightLimit {
+    pub name: &'static str,
+    pub max_weight: usize,
+    pub period: Duration,
+    default_weight: usize,
+    weights: HashMap<RequestType, usize>,
+    used_weight_header: Option<&'static str>,
+}
+
+impl RequestWeightLimit {
+    /// Every request has weight 1 in created limit
+    pub fn new(name: &'static str, max_weight: usize, period: Duration) -> Self {
+        Self {
+            name,
+            max_weight,
+            period,
+            default_weight: 1,
+            weights: HashMap::new(),
+            used_weight_header: None,
+        }
+    }
+
+    /// Weight of request types without specified weight. Zero means that they aren't limited
+    pub fn with_default_weight(mut self, default_weight: usize) -> Self {
+        self.default_weight = default_weight;
+        self
+    }
+
+    pub fn with_weight(mut self, request_type: RequestType, weight: usize) -> Self {
+        let _ = self.weights.insert(request_type, weight);
+        self
+    }
+
+    /// Response header with weight used on the exchange side. It is used for synchronization
+    /// of local counters with actual exchange state
+    pub fn with_used_weight_header(mut self, header_name: &'static str) -> Self {
+        self.used_weight_header = Some(header_name);
+        self
+    }
+
+    pub fn get_weight(&self, request_type: RequestType) -> usize {
+        let weight = self
+            .weights
+            .get(&request_type)
+            .copied()
+            .unwrap_or(self.default_weight);
+
+        // request with weight more than limit can't be ever reserved otherwise
+        weight.min(self.max_weight)
+    }
+
+    pub fn used_weight_header(&self) -> Option<&'static str> {
+        self.used_weight_header
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ReservedWeight {
+    time: DateTime,
+    weight: usize,
+}
+
+/// Weights of reserved requests in time window of `RequestWeightLimit`
+pub(super) struct RequestWeightWindow {
+    limit: RequestWeightLimit,
+    // sorted by time
+    reserved: Vec<ReservedWeight>,
+}
+
+impl RequestWeightWindow {
+    pub(super) fn new(limit: RequestWeightLimit) -> Self {
+        Self {
+            limit,
+            reserved: Vec::new(),
+        }
+    }
+
+    pub(super) fn limit(&self) -> &RequestWeightLimit {
+        &self.limit
+    }
+
+    pub(super) fn remove_outdated(&mut self, current_time: DateTime) {
+        let deadline = current_time - self.limit.period;
+        self.reserved.retain(|x| x.time >= deadline);
+    }
+
+    /// Weight of requests which are reserved in period before specified time and after it
+    fn get_used_weight(&self, time: DateTime) -> usize {
+        let deadline = time - self.limit.period;
+        self.reserved
+            .iter()
+            .filter(|x| x.time > deadline)
+            .map(|x| x.weight)
+            .sum()
+    }
+
+    pub(super) fn can_reserve(&self, request_type: RequestType, time: DateTime) -> bool {
+        let weight = self.limit.get_weight(request_type);
+        weight == 0 || self.get_used_weight(time) + weight <= self.limit.max_weight
+    }
+
+    /// Earliest time not before `time` when request can be reserved
+    pub(super) fn get_available_time(
+        &self,
+        request_type: RequestType,
+        time: DateTime,
+        delay_to_next_time_period: Duration,
+    ) -> DateTime {
+        let weight = self.limit.get_weight(request_type);
+        if weight == 0 {
+            return time;
+        }
+
+        let mut available_time = time;
+        loop {
+            let deadline = available_time - self.limit.period;
+            let mut used_weights = self.reserved.iter().filter(|x| x.time > deadline);
+            let used_weight: usize = used_weights.clone().map(|x| x.weight).sum();
+            if used_weight + weight <= self.limit.max_weight {
+                return available_time;
+            }
+
+            // wait until the earliest reserved request leaves the window
+            match used_weights.next() {
+                Some(earliest) => {
+                    available_time = earliest.time + self.limit.period + delay_to_next_time_period
+                }
+                None => return available_time,
+            }
+        }
+    }
+
+    pub(super) fn reserve(&mut self, request_type: RequestType, time: DateTime) {
+        let weight = self.limit.get_weight(request_type);
+        self.add(time, weight);
+    }
+
+    pub(super) fn unreserve(&mut self, request_type: RequestType, time: DateTime) {
+        let reserved = ReservedWeight {
+            time,
+            weight: self.limit.get_weight(request_type),
+        };
+        if let Some(position) = self.reserved.iter().position(|x| *x == reserved) {
+            self.reserved.remove(position);
+        }
+    }
+
+    /// Take into account weight used on the exchange side which is unknown locally
+    /// (e.g. requests of other applications from the same IP)
+    pub(super) fn sync_used_weight(&mut self, used_weight: usize, current_time: DateTime) {
+        let deadline = current_time - self.limit.period;
+        let local_used_weight: usize = self
+            .reserved
+            .iter()
+            .filter(|x| x.time > deadline && x.time <= current_time)
+            .map(|x| x.weight)
+            .sum();
+
+        if used_weight > local_used_weight {
+            log::info!(
+                "Used weight of limit {} synchronized from {local_used_weight} to {used_weight}",
+                self.limit.name
+            );
+            self.add(current_time, used_weight - local_used_weight);
+        }
+    }
+
+    fn add(&mut self, time: DateTime, weight: usize) {
+        if weight == 0 {
+            return;
+        }
+
+        let index = self.reserved.partition_point(|x| x.time <= time);
+        self.reserved.insert(index, ReservedWeight { time, weight });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn orders_window() -> RequestWeightWindow {
+        RequestWeightWindow::new(
+            RequestWeightLimit::new("ORDERS_10S", 3, Duration::seconds(10))
+                .with_default_weight(0)
+                .with_weight(RequestType::CreateOrder, 1),
+        )
+    }
+
+    #[test]
+    fn requests_without_weight_are_not_limited() {
+        let mut window = orders_window();
+        let now = Utc::now();
+
+        for _ in 0..3 {
+            assert!(window.can_reserve(RequestType::CreateOrder, now));
+            window.reserve(RequestType::CreateOrder, now);
+        }
+
+        assert!(!window.can_reserve(RequestType::CreateOrder, now));
+        assert!(window.can_reserve(RequestType::CancelOrder, now));
+    }
+
+    #[test]
+    fn available_time_is_after_earliest_request_leaves_window() {
+        let mut window = orders_window();
+        let now = Utc::now();
+        let delay = Duration::milliseconds(1);
+
+        window.reserve(RequestType::CreateOrder, now);
+        window.reserve(RequestType::CreateOrder, now + Duration::seconds(2));
+        window.reserve(RequestType::CreateOrder, now + Duration::seconds(4));
+
+        assert_eq!(
+            window.get_available_time(RequestType::CreateOrder, now, delay),
+            now + Duration::seconds(10) + delay
+        );
+        assert_eq!(
+            window.get_available_time(RequestType::CancelOrder, now, delay),
+            now
+        );
+
+        window.remove_outdated(now + Duration::seconds(13));
+        assert!(window.can_reserve(RequestType::CreateOrder, now + Duration::seconds(13)));
+    }
+
+    #[test]
+    fn used_weight_is_synchronized_only_upwards() {
+        let mut window = orders_window();
+        let now = Utc::now();
+
+        window.reserve(RequestType::CreateOrder, now);
+        window.sync_used_weight(0, now);
+        assert!(window.can_reserve(RequestType::CreateOrder, now));
+
+        window.sync_used_weight(3, now);
+        assert!(!window.can_reserve(RequestType::CreateOrder, now));
+    }
+}
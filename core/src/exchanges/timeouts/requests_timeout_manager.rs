@@ -3,6 +3,7 @@ use std::sync::{Arc, Weak};
 
 use anyhow::{anyhow, bail, Context, Result};
 use chrono::Duration;
+use hyper::HeaderMap;
 use mmb_utils::cancellation_token::CancellationToken;
 use mmb_utils::infrastructure::{FutureOutcome, SpawnFutureFlags};
 use mmb_utils::{DateTime, OPERATION_CANCELED_MSG};
@@ -14,11 +15,14 @@ use uuid::Uuid;
 use super::{
     inner_request_manager::InnerRequestsTimeoutManager,
     more_or_equals_available_requests_count_trigger_scheduler::MoreOrEqualsAvailableRequestsCountTriggerScheduler,
-    pre_reserved_group::PreReservedGroup, request::Request,
+    pre_reserved_group::PreReservedGroup,
+    request::Request,
+    request_weight_limit::{RequestWeightLimit, RequestWeightWindow},
     triggers::every_requests_count_change_trigger::EveryRequestsCountChangeTrigger,
     triggers::less_or_equals_requests_count_trigger::LessOrEqualsRequestsCountTrigger,
 };
-use crate::{exchanges::general::request_type::RequestType, infrastructure::spawn_future};
+use crate::exchanges::general::request_type::{RequestPriority, RequestType};
+use crate::infrastructure::spawn_future;
 use mmb_domain::market::ExchangeAccountId;
 use mmb_utils::time::ToStdExpected;
 
@@ -47,6 +51,7 @@ impl RequestsTimeoutManager {
         period_duration: Duration,
         exchange_account_id: ExchangeAccountId,
         more_or_equals_available_requests_count_trigger_scheduler: MoreOrEqualsAvailableRequestsCountTriggerScheduler,
+        weight_limits: Vec<RequestWeightLimit>,
     ) -> Arc<Self> {
         let inner = InnerRequestsTimeoutManager {
             requests_per_period,
@@ -55,6 +60,10 @@ impl RequestsTimeoutManager {
             requests: Default::default(),
             pre_reserved_groups: Default::default(),
             last_time: None,
+            weight_windows: weight_limits
+                .into_iter()
+                .map(RequestWeightWindow::new)
+                .collect(),
             delay_to_next_time_period: Duration::milliseconds(1),
             group_was_reserved: Box::new(|_| {}),
             group_was_removed: Box::new(|_| {}),
@@ -74,6 +83,21 @@ impl RequestsTimeoutManager {
         current_time: DateTime,
         requests_count: usize,
         // call_source: SourceInfo, // TODO not needed until DataRecorder is ready
+    ) -> Option<RequestGroupId> {
+        self.try_reserve_group_with_priority(
+            group_type,
+            RequestPriority::default(),
+            current_time,
+            requests_count,
+        )
+    }
+
+    pub fn try_reserve_group_with_priority(
+        &self,
+        group_type: String,
+        priority: RequestPriority,
+        current_time: DateTime,
+        requests_count: usize,
     ) -> Option<RequestGroupId> {
         let mut inner = self.inner.lock();
 
@@ -89,13 +113,11 @@ impl RequestsTimeoutManager {
         }
 
         let group_id = RequestGroupId::generate();
-        let group = PreReservedGroup::new(group_id, group_type, requests_count);
+        let group = PreReservedGroup::new(group_id, group_type, requests_count, priority);
         inner.pre_reserved_groups.push(group.clone());
 
         log::info!(
-            "PreReserved group with group_id {} and request_count {} was added",
-            group_id,
-            requests_count
+            "PreReserved group with group_id {group_id}, request_count {requests_count} and priority {priority:?} was added"
         );
 
         // TODO save to DataRecorder
@@ -193,7 +215,9 @@ impl RequestsTimeoutManager {
                 let available_requests_count =
                     available_requests_count_without_group + rest_requests_count_in_group;
 
-                if available_requests_count == 0 {
+                if available_requests_count == 0
+                    || !inner.can_reserve_weight(request_type, current_time)
+                {
                     // TODO save to DataRecorder
 
                     return false;
@@ -217,6 +241,17 @@ impl RequestsTimeoutManager {
         }
     }
 
+    /// Priority of request type raised to priority of pre-reserved group
+    pub fn get_request_priority(
+        &self,
+        request_type: RequestType,
+        pre_reserved_group_id: Option<RequestGroupId>,
+    ) -> RequestPriority {
+        self.inner
+            .lock()
+            .get_request_priority(request_type, pre_reserved_group_id)
+    }
+
     pub fn try_reserve_request_instant(
         &self,
         request_type: RequestType,
@@ -232,6 +267,22 @@ impl RequestsTimeoutManager {
         request_type: RequestType,
         current_time: DateTime,
         cancellation_token: CancellationToken,
+    ) -> (JoinHandle<FutureOutcome>, DateTime, Duration) {
+        let priority = request_type.priority();
+        self.reserve_when_available_with_priority(
+            request_type,
+            priority,
+            current_time,
+            cancellation_token,
+        )
+    }
+
+    pub fn reserve_when_available_with_priority(
+        self: Arc<Self>,
+        request_type: RequestType,
+        priority: RequestPriority,
+        current_time: DateTime,
+        cancellation_token: CancellationToken,
     ) -> (JoinHandle<FutureOutcome>, DateTime, Duration) {
         // Note: calculation doesn't support request cancellation
         // Note: suppose that exchange restriction work as your have n request on period and n request from beginning of next period and so on
@@ -239,6 +290,7 @@ impl RequestsTimeoutManager {
         // Algorithm:
         // 1. We check: can we do request now
         // 2. if not form schedule for request where put at start period by requestsPerPeriod requests
+        // 3. waiting requests with lower priority are moved to schedule after the request
 
         let mut inner = self.inner.lock();
 
@@ -247,34 +299,13 @@ impl RequestsTimeoutManager {
 
         let _available_requests_count = inner.get_all_available_requests_count();
 
-        let mut request_start_time;
-        let delay;
-        let available_requests_count_for_period;
-        let request = if let Some(last_request) = inner.requests.last() {
-            let last_request_start_time = last_request.allowed_start_time;
-
-            available_requests_count_for_period =
-                inner.get_available_requests_count_in_last_period(last_request_start_time);
-            request_start_time = if available_requests_count_for_period == 0 {
-                last_request_start_time + inner.period_duration + inner.delay_to_next_time_period
-            } else {
-                last_request_start_time
-            };
-
-            request_start_time = request_start_time.max(current_time);
-            delay = request_start_time - current_time;
-            inner.add_request(request_type, request_start_time, None)
-        } else {
-            request_start_time = current_time;
-            delay = Duration::zero();
-            // available_requests_count_for_period = inner.requests_per_period;
-            inner.add_request(request_type, current_time, None)
-        };
+        let request = inner.schedule_request(request_type, priority, current_time);
+        let request_start_time = request.allowed_start_time;
+        let delay = request_start_time - current_time;
 
-        log::info!("Request {request_type:?} reserved, available in request_start_time {request_start_time}");
+        log::info!("Request {request_type:?} with priority {priority:?} reserved, available in request_start_time {request_start_time}");
 
-        // TODO save to DataRecorder. Delete drop
-        // drop(available_requests_count_for_period);
+        // TODO save to DataRecorder
 
         inner.last_time = Some(current_time);
 
@@ -301,32 +332,39 @@ impl RequestsTimeoutManager {
         delay: Duration,
         cancellation_token: CancellationToken,
     ) -> Result<()> {
-        // Should never panic, because function wait_for_request_availability
-        // has one call with guaranteed non-negative delay.
-        let delay = delay.to_std_expected();
-
-        match timeout(delay, cancellation_token.when_cancelled()).await {
-            Err(_) => {
-                let strong_self = Self::try_get_strong(weak_self)?;
-                (strong_self.inner.lock().time_has_come_for_request)(request);
-            }
-            Ok(()) => {
+        let mut scheduled_start_time = request.allowed_start_time;
+        let mut delay = delay;
+        loop {
+            // Should never panic, because delay is calculated as non-negative difference of times
+            let std_delay = delay.to_std_expected();
+
+            if timeout(std_delay, cancellation_token.when_cancelled())
+                .await
+                .is_ok()
+            {
                 let strong_self = Self::try_get_strong(weak_self)?;
                 let mut inner = strong_self.inner.lock();
                 (inner.time_has_come_for_request)(request.clone());
-                if let Some(position) = inner
-                    .requests
-                    .iter()
-                    .position(|stored_request| *stored_request == request)
-                {
-                    inner.requests.remove(position);
-                }
+                inner.remove_request(&request);
 
                 bail!(OPERATION_CANCELED_MSG)
             }
-        };
 
-        Ok(())
+            let strong_self = Self::try_get_strong(weak_self.clone())?;
+            let inner = strong_self.inner.lock();
+
+            // request can be postponed by request with higher priority while waiting
+            match inner.get_scheduled_start_time(&request) {
+                Some(start_time) if start_time > scheduled_start_time => {
+                    delay = start_time - scheduled_start_time;
+                    scheduled_start_time = start_time;
+                }
+                _ => {
+                    (inner.time_has_come_for_request)(request);
+                    return Ok(());
+                }
+            }
+        }
     }
 
     fn try_get_strong(
@@ -379,9 +417,44 @@ impl RequestsTimeoutManager {
             .push(Box::new(trigger));
     }
 
+    /// Part of requests limit of the current period which is used or reserved, from 0 to 1
+    pub fn get_utilisation(&self, current_time: DateTime) -> f64 {
+        let inner = self.inner.lock();
+        match inner.requests_per_period {
+            0 => 0.,
+            requests_per_period => {
+                let available_requests_count =
+                    inner.get_available_requests_count_at_present(current_time);
+                (requests_per_period - available_requests_count) as f64 / requests_per_period as f64
+            }
+        }
+    }
+
     pub fn get_period_duration(&self) -> std::time::Duration {
         self.inner.lock().get_period_duration().to_std_expected()
     }
+
+    /// Synchronize weight limits with used weights reported by the exchange in response headers
+    pub fn sync_by_response_headers(&self, headers: &HeaderMap, current_time: DateTime) {
+        let mut inner = self.inner.lock();
+        let current_time = inner.get_non_decreasing_time(current_time);
+        inner.remove_outdated_requests(current_time);
+
+        for weight_window in &mut inner.weight_windows {
+            let header_name = match weight_window.limit().used_weight_header() {
+                Some(header_name) => header_name,
+                None => continue,
+            };
+
+            let used_weight = headers
+                .get(header_name)
+                .and_then(|x| x.to_str().ok())
+                .and_then(|x| x.parse::<usize>().ok());
+            if let Some(used_weight) = used_weight {
+                weight_window.sync_used_weight(used_weight, current_time);
+            }
+        }
+    }
 }
 
 #[cfg(test)]
@@ -2457,4 +2530,218 @@ mod test {
             Ok(())
         }
     }
+
+    mod weight_limits {
+        use crate::infrastructure::init_lifetime_manager;
+
+        use super::*;
+
+        #[fixture]
+        fn timeout_manager() -> Arc<RequestsTimeoutManager> {
+            let exchange_account_id = ExchangeAccountId::new("test_exchange_account_id", 0);
+            RequestsTimeoutManagerFactory::from_requests_per_period(
+                RequestTimeoutArguments::from_requests_per_minute(100)
+                    .with_weight_limit(
+                        RequestWeightLimit::new("REQUEST_WEIGHT", 10, Duration::minutes(1))
+                            .with_weight(RequestType::GetBalance, 5),
+                    )
+                    .with_weight_limit(
+                        RequestWeightLimit::new("ORDERS", 2, Duration::seconds(10))
+                            .with_default_weight(0)
+                            .with_weight(RequestType::CreateOrder, 1)
+                            .with_used_weight_header("x-order-count"),
+                    ),
+                exchange_account_id,
+            )
+        }
+
+        #[rstest]
+        fn all_limits_are_checked(timeout_manager: Arc<RequestsTimeoutManager>) {
+            let current_time = Utc::now();
+            let reserve = |request_type| {
+                timeout_manager.try_reserve_instant(request_type, current_time, None)
+            };
+
+            assert!(reserve(RequestType::CreateOrder));
+            assert!(reserve(RequestType::CreateOrder));
+            // orders limit is exhausted
+            assert!(!reserve(RequestType::CreateOrder));
+
+            assert!(reserve(RequestType::GetBalance));
+            // request weight limit is exhausted: 2 + 5 + 5 > 10
+            assert!(!reserve(RequestType::GetBalance));
+            assert!(reserve(RequestType::CancelOrder));
+
+            assert_eq!(timeout_manager.inner.lock().requests.len(), 4);
+        }
+
+        #[rstest]
+        #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+        async fn reserve_when_available_waits_for_weight(
+            timeout_manager: Arc<RequestsTimeoutManager>,
+        ) {
+            let _ = init_lifetime_manager();
+
+            let current_time = Utc::now();
+            timeout_manager.try_reserve_instant(RequestType::CreateOrder, current_time, None);
+            timeout_manager.try_reserve_instant(RequestType::CreateOrder, current_time, None);
+
+            let (_, request_start_time, delay) = timeout_manager.clone().reserve_when_available(
+                RequestType::CreateOrder,
+                current_time,
+                CancellationToken::new(),
+            );
+
+            let expected_delay = Duration::seconds(10) + Duration::milliseconds(1);
+            assert_eq!(delay, expected_delay);
+            assert_eq!(request_start_time, current_time + expected_delay);
+        }
+
+        #[rstest]
+        fn sync_by_response_headers(timeout_manager: Arc<RequestsTimeoutManager>) {
+            let current_time = Utc::now();
+            let mut headers = HeaderMap::new();
+            headers.insert("x-order-count", "2".parse().expect("in test"));
+
+            timeout_manager.sync_by_response_headers(&headers, current_time);
+
+            assert!(!timeout_manager.try_reserve_instant(
+                RequestType::CreateOrder,
+                current_time,
+                None
+            ));
+            assert!(timeout_manager.try_reserve_instant(
+                RequestType::CancelOrder,
+                current_time,
+                None
+            ));
+        }
+    }
+
+    mod priorities {
+        use crate::infrastructure::init_lifetime_manager;
+
+        use super::*;
+
+        fn timeout_manager(period: Duration) -> Arc<RequestsTimeoutManager> {
+            let exchange_account_id = ExchangeAccountId::new("test_exchange_account_id", 0);
+            RequestsTimeoutManagerFactory::from_requests_per_period(
+                RequestTimeoutArguments::new(2, period),
+                exchange_account_id,
+            )
+        }
+
+        #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+        async fn cancel_displaces_waiting_requests_with_lower_priority() {
+            let _ = init_lifetime_manager();
+
+            let timeout_manager = timeout_manager(Duration::minutes(1));
+            let current_time = Utc::now();
+            let period = Duration::minutes(1) + Duration::milliseconds(1);
+
+            timeout_manager.try_reserve_instant(RequestType::CreateOrder, current_time, None);
+            timeout_manager.try_reserve_instant(RequestType::CreateOrder, current_time, None);
+
+            let reserve = |request_type| {
+                timeout_manager.clone().reserve_when_available(
+                    request_type,
+                    current_time,
+                    CancellationToken::new(),
+                )
+            };
+            let (_, first_start_time, _) = reserve(RequestType::GetBalance);
+            let (_, second_start_time, _) = reserve(RequestType::CreateOrder);
+            assert_eq!(first_start_time, current_time + period);
+            assert_eq!(second_start_time, current_time + period);
+
+            let (_, cancel_start_time, _) = reserve(RequestType::CancelOrder);
+            assert_eq!(cancel_start_time, current_time + period);
+
+            let inner = timeout_manager.inner.lock();
+            let start_time_of = |request_type| {
+                inner
+                    .requests
+                    .iter()
+                    .find(|x| x.request_type == request_type)
+                    .expect("in test")
+                    .allowed_start_time
+            };
+            assert_eq!(start_time_of(RequestType::CreateOrder), current_time);
+            assert_eq!(
+                start_time_of(RequestType::GetBalance),
+                current_time + period * 2
+            );
+
+            let mut scheduled_in_next_period = inner
+                .requests
+                .iter()
+                .filter(|x| x.allowed_start_time == current_time + period)
+                .map(|x| x.priority)
+                .collect::<Vec<_>>();
+            scheduled_in_next_period.sort();
+            assert_eq!(
+                scheduled_in_next_period,
+                vec![RequestPriority::Normal, RequestPriority::High]
+            );
+        }
+
+        #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+        async fn postponed_request_waits_for_new_start_time() -> Result<()> {
+            let _ = init_lifetime_manager();
+
+            let period = Duration::milliseconds(100);
+            let timeout_manager = timeout_manager(period);
+            let start = std::time::Instant::now();
+            let current_time = Utc::now();
+
+            timeout_manager.try_reserve_instant(RequestType::CreateOrder, current_time, None);
+            timeout_manager.try_reserve_instant(RequestType::CreateOrder, current_time, None);
+
+            let reserve = |request_type| {
+                timeout_manager
+                    .clone()
+                    .reserve_when_available(request_type, current_time, CancellationToken::new())
+                    .0
+            };
+            let low_priority_requests = [
+                reserve(RequestType::GetBalance),
+                reserve(RequestType::GetBalance),
+            ];
+            let cancel_request = reserve(RequestType::CancelOrder);
+
+            cancel_request.await?.into_result()?;
+            let cancel_elapsed = start.elapsed();
+
+            for low_priority_request in low_priority_requests {
+                low_priority_request.await?.into_result()?;
+            }
+
+            assert!(start.elapsed() >= (period * 2).to_std_expected());
+            assert!(cancel_elapsed < (period * 2).to_std_expected());
+
+            Ok(())
+        }
+
+        #[test]
+        fn group_raises_priority_of_its_requests() {
+            let timeout_manager = timeout_manager(Duration::minutes(1));
+
+            let group_id = timeout_manager.try_reserve_group_with_priority(
+                "GroupType".to_owned(),
+                RequestPriority::High,
+                Utc::now(),
+                1,
+            );
+            assert!(group_id.is_some());
+
+            assert_eq!(
+                timeout_manager.get_request_priority(RequestType::GetBalance, group_id),
+                RequestPriority::High
+            );
+            assert_eq!(
+                timeout_manager.get_request_priority(RequestType::GetBalance, None),
+                RequestPriority::Low
+            );
+        }
+    }
 }
@@ -10,7 +10,7 @@ use mmb_domain::market::ExchangeAccountId;
 
 use super::{
     more_or_equals_available_requests_count_trigger_scheduler::MoreOrEqualsAvailableRequestsCountTriggerScheduler,
-    requests_timeout_manager::RequestsTimeoutManager,
+    request_weight_limit::RequestWeightLimit, requests_timeout_manager::RequestsTimeoutManager,
 };
 
 pub struct RequestsTimeoutManagerFactory {}
@@ -30,6 +30,7 @@ impl RequestsTimeoutManagerFactory {
             timeout_arguments.period,
             exchange_account_id,
             trigger_scheduler,
+            timeout_arguments.weight_limits,
         )
     }
 }
@@ -37,6 +38,8 @@ impl RequestsTimeoutManagerFactory {
 pub struct RequestTimeoutArguments {
     pub requests_per_period: usize,
     pub period: Duration,
+    /// Limits which are checked together with requests count limit
+    pub weight_limits: Vec<RequestWeightLimit>,
 }
 
 impl RequestTimeoutArguments {
@@ -44,9 +47,15 @@ impl RequestTimeoutArguments {
         Self {
             requests_per_period,
             period,
+            weight_limits: Vec::new(),
         }
     }
 
+    pub fn with_weight_limit(mut self, weight_limit: RequestWeightLimit) -> Self {
+        self.weight_limits.push(weight_limit);
+        self
+    }
+
     pub fn unlimited() -> RequestTimeoutArguments {
         Self::from_requests_per_second(usize::MAX)
     }
@@ -78,6 +87,16 @@ impl Display for RequestTimeoutArguments {
             f,
             "Requests per period: {}, period: {}",
             self.requests_per_period, self.period
-        )
+        )?;
+
+        for weight_limit in &self.weight_limits {
+            write!(
+                f,
+                ", {}: {} per {}",
+                weight_limit.name, weight_limit.max_weight, weight_limit.period
+            )?;
+        }
+
+        Ok(())
     }
 }
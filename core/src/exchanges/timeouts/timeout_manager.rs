@@ -1,9 +1,11 @@
 use futures::future::ready;
 use futures::future::Either;
 use futures::FutureExt;
+use hyper::HeaderMap;
 use mmb_utils::cancellation_token::CancellationToken;
 use mmb_utils::infrastructure::{CompletionReason, FutureOutcome, WithExpect};
 use mmb_utils::DateTime;
+use parking_lot::RwLock;
 use std::collections::HashMap;
 use std::future::Future;
 use std::sync::Arc;
@@ -14,7 +16,7 @@ use uuid::Uuid;
 use anyhow::Result;
 use chrono::Utc;
 
-use crate::exchanges::general::request_type::RequestType;
+use crate::exchanges::general::request_type::{RequestPriority, RequestType};
 use crate::exchanges::timeouts::requests_timeout_manager::{
     RequestGroupId, RequestsTimeoutManager,
 };
@@ -23,7 +25,7 @@ use mmb_domain::market::ExchangeAccountId;
 pub type BoxFuture = Box<dyn Future<Output = Result<()>> + Sync + Send>;
 
 pub struct TimeoutManager {
-    inner: HashMap<ExchangeAccountId, Arc<RequestsTimeoutManager>>,
+    inner: RwLock<HashMap<ExchangeAccountId, Arc<RequestsTimeoutManager>>>,
 }
 
 impl TimeoutManager {
@@ -31,17 +33,44 @@ impl TimeoutManager {
         timeout_managers: HashMap<ExchangeAccountId, Arc<RequestsTimeoutManager>>,
     ) -> Arc<Self> {
         Arc::new(TimeoutManager {
-            inner: timeout_managers,
+            inner: RwLock::new(timeout_managers),
         })
     }
 
+    /// Register requests timeout manager of exchange account connected at runtime
+    pub fn add_exchange_account(
+        &self,
+        exchange_account_id: ExchangeAccountId,
+        requests_timeout_manager: Arc<RequestsTimeoutManager>,
+    ) {
+        let _ = self
+            .inner
+            .write()
+            .insert(exchange_account_id, requests_timeout_manager);
+    }
+
+    /// Forget requests timeout manager of exchange account removed at runtime
+    pub fn remove_exchange_account(&self, exchange_account_id: ExchangeAccountId) {
+        let _ = self.inner.write().remove(&exchange_account_id);
+    }
+
+    fn inner(&self, exchange_account_id: ExchangeAccountId) -> Arc<RequestsTimeoutManager> {
+        self.inner
+            .read()
+            .get(&exchange_account_id)
+            .with_expect(|| format!("Can't find timeout manger for {exchange_account_id}"))
+            .clone()
+    }
+
     pub fn try_reserve_group(
         &self,
         exchange_account_id: ExchangeAccountId,
         requests_count: usize,
         group_type: String,
+        priority: RequestPriority,
     ) -> Option<RequestGroupId> {
-        self.inner[&exchange_account_id].try_reserve_group(group_type, now(), requests_count)
+        self.inner(exchange_account_id)
+            .try_reserve_group_with_priority(group_type, priority, now(), requests_count)
     }
 
     pub fn remove_group(
@@ -49,7 +78,8 @@ impl TimeoutManager {
         exchange_account_id: ExchangeAccountId,
         group_id: RequestGroupId,
     ) -> bool {
-        self.inner[&exchange_account_id].remove_group(group_id, now())
+        self.inner(exchange_account_id)
+            .remove_group(group_id, now())
     }
 
     pub fn try_reserve_instant(
@@ -57,7 +87,8 @@ impl TimeoutManager {
         exchange_account_id: ExchangeAccountId,
         request_type: RequestType,
     ) -> bool {
-        self.inner[&exchange_account_id].try_reserve_instant(request_type, now(), None)
+        self.inner(exchange_account_id)
+            .try_reserve_instant(request_type, now(), None)
     }
 
     pub fn try_reserve_group_instant(
@@ -66,7 +97,7 @@ impl TimeoutManager {
         request_type: RequestType,
         pre_reserved_group_id: Option<RequestGroupId>,
     ) -> bool {
-        self.inner[&exchange_account_id].try_reserve_instant(
+        self.inner(exchange_account_id).try_reserve_instant(
             request_type,
             now(),
             pre_reserved_group_id,
@@ -80,7 +111,7 @@ impl TimeoutManager {
         pre_reservation_group_id: Option<RequestGroupId>,
         cancellation_token: CancellationToken,
     ) -> impl Future<Output = FutureOutcome> + Send + Sync {
-        let inner = (&self.inner[&exchange_account_id]).clone();
+        let inner = self.inner(exchange_account_id);
 
         let convert = |handle: JoinHandle<FutureOutcome>| {
             handle.map(|res| match res {
@@ -111,15 +142,38 @@ impl TimeoutManager {
             )));
         }
 
-        let result = inner.reserve_when_available(request_type, now, cancellation_token);
+        // requests of group keep its priority when pre-reserved requests are exhausted
+        let priority = inner.get_request_priority(request_type, pre_reservation_group_id);
+        let result = inner.reserve_when_available_with_priority(
+            request_type,
+            priority,
+            now,
+            cancellation_token,
+        );
         Either::Left(convert(result.0))
     }
 
+    pub fn sync_by_response_headers(
+        &self,
+        exchange_account_id: ExchangeAccountId,
+        headers: &HeaderMap,
+    ) {
+        if let Some(requests_timeout_manager) = self.inner.read().get(&exchange_account_id) {
+            requests_timeout_manager.sync_by_response_headers(headers, now());
+        }
+    }
+
     pub fn get_period_duration(&self, exchange_account_id: ExchangeAccountId) -> Duration {
+        self.inner(exchange_account_id).get_period_duration()
+    }
+
+    /// Part of requests limit of the current period which is used or reserved, from 0 to 1
+    pub fn get_utilisation(&self, exchange_account_id: ExchangeAccountId) -> f64 {
         self.inner
+            .read()
             .get(&exchange_account_id)
-            .with_expect(|| format!("Can't find timeout manger for {exchange_account_id}"))
-            .get_period_duration()
+            .map(|x| x.get_utilisation(now()))
+            .unwrap_or_default()
     }
 }
 
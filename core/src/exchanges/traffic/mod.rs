@@ -0,0 +1,38 @@
+use mmb_utils::DateTime;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::connectivity::WebSocketRole;
+
+pub mod recorder;
+pub mod replay;
+
+/// Raw exchange traffic item with local time of sending or receiving
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TrafficRecord {
+    pub time: DateTime,
+    #[serde(flatten)]
+    pub data: TrafficData,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum TrafficData {
+    RestRequest {
+        request_id: Uuid,
+        action_name: String,
+        method: String,
+        uri: String,
+        body: String,
+    },
+    RestResponse {
+        request_id: Uuid,
+        action_name: String,
+        status: u16,
+        content: String,
+    },
+    WebSocketMessage {
+        role: WebSocketRole,
+        message: String,
+    },
+}
@@ -0,0 +1,293 @@
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Arc};
+use std::thread;
+
+use anyhow::{Context, Result};
+use itertools::Itertools;
+use mmb_domain::market::ExchangeAccountId;
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+
+use crate::exchanges::traffic::{TrafficData, TrafficRecord};
+use crate::misc::time::time_manager;
+use crate::settings::{ExchangeSettings, TrafficCaptureSettings};
+
+const CAPTURE_FILE_EXTENSION: &str = "jsonl";
+
+// Recorders are shared by REST client and websocket connections of the same exchange account
+static RECORDERS: Lazy<Mutex<HashMap<ExchangeAccountId, Arc<TrafficRecorder>>>> =
+    Lazy::new(Default::default);
+
+struct CaptureFile {
+    writer: BufWriter<File>,
+    size: u64,
+}
+
+enum WriterCommand {
+    Write(TrafficRecord),
+    /// Sender is notified when all previously sent records are written to file
+    Flush(mpsc::Sender<()>),
+}
+
+/// Writes raw REST requests/responses and websocket messages of exchange account
+/// to rotating files in JSON lines format.
+/// Records are written by dedicated thread, so recording doesn't block connections of exchange
+pub struct TrafficRecorder {
+    exchange_account_id: ExchangeAccountId,
+    commands_sender: mpsc::Sender<WriterCommand>,
+}
+
+impl TrafficRecorder {
+    pub fn new(
+        exchange_account_id: ExchangeAccountId,
+        settings: TrafficCaptureSettings,
+    ) -> Result<Arc<Self>> {
+        fs::create_dir_all(&settings.dir).with_context(|| {
+            format!(
+                "Unable to create traffic capture dir {}",
+                settings.dir.display()
+            )
+        })?;
+
+        let (commands_sender, commands_receiver) = mpsc::channel();
+        let writer = CaptureWriter {
+            exchange_account_id,
+            settings,
+            file: None,
+            file_seq: 0,
+        };
+        let _ = thread::Builder::new()
+            .name(format!("traffic_recorder_{exchange_account_id}"))
+            .spawn(move || writer.run(commands_receiver))
+            .context("Unable to start traffic recorder thread")?;
+
+        Ok(Arc::new(Self {
+            exchange_account_id,
+            commands_sender,
+        }))
+    }
+
+    /// Shared recorder of exchange account. `None` if traffic capture isn't enabled in settings
+    pub fn for_exchange_account(settings: &ExchangeSettings) -> Option<Arc<Self>> {
+        let capture_settings = settings.traffic_capture.as_ref()?;
+        let exchange_account_id = settings.exchange_account_id;
+
+        let mut recorders = RECORDERS.lock();
+        if let Some(recorder) = recorders.get(&exchange_account_id) {
+            return Some(recorder.clone());
+        }
+
+        match Self::new(exchange_account_id, capture_settings.clone()) {
+            Ok(recorder) => {
+                let _ = recorders.insert(exchange_account_id, recorder.clone());
+                Some(recorder)
+            }
+            Err(err) => {
+                log::error!("Traffic capture is disabled for {exchange_account_id}: {err:?}");
+                None
+            }
+        }
+    }
+
+    pub fn record(&self, data: TrafficData) {
+        let record = TrafficRecord {
+            time: time_manager::now(),
+            data,
+        };
+
+        if self
+            .commands_sender
+            .send(WriterCommand::Write(record))
+            .is_err()
+        {
+            log::error!(
+                "Unable to record traffic of {} because writer is stopped",
+                self.exchange_account_id
+            );
+        }
+    }
+
+    /// Wait until all recorded traffic is written to file
+    pub fn flush(&self) {
+        let (done_sender, done_receiver) = mpsc::channel();
+        if self
+            .commands_sender
+            .send(WriterCommand::Flush(done_sender))
+            .is_ok()
+        {
+            let _ = done_receiver.recv();
+        }
+    }
+}
+
+struct CaptureWriter {
+    exchange_account_id: ExchangeAccountId,
+    settings: TrafficCaptureSettings,
+    file: Option<CaptureFile>,
+    file_seq: u64,
+}
+
+impl CaptureWriter {
+    fn run(mut self, commands_receiver: mpsc::Receiver<WriterCommand>) {
+        while let Ok(command) = commands_receiver.recv() {
+            self.handle(command);
+            // all pending records are written by buffered writer before flushing
+            for command in commands_receiver.try_iter() {
+                self.handle(command);
+            }
+            // capture should survive crash of the application
+            self.flush();
+        }
+    }
+
+    fn handle(&mut self, command: WriterCommand) {
+        match command {
+            WriterCommand::Write(record) => {
+                if let Err(err) = self.write(&record) {
+                    log::error!(
+                        "Unable to write traffic record of {}: {err:?}",
+                        self.exchange_account_id
+                    );
+                }
+            }
+            WriterCommand::Flush(done_sender) => {
+                self.flush();
+                let _ = done_sender.send(());
+            }
+        }
+    }
+
+    fn write(&mut self, record: &TrafficRecord) -> Result<()> {
+        let mut line =
+            serde_json::to_string(record).context("Unable to serialize traffic record")?;
+        line.push('\n');
+
+        let file = match self.file.take() {
+            Some(file) if file.size < self.settings.max_file_size_bytes => file,
+            Some(mut file) => {
+                file.writer.flush()?;
+                self.rotate()?
+            }
+            None => self.rotate()?,
+        };
+        let file = self.file.insert(file);
+
+        file.writer.write_all(line.as_bytes())?;
+        file.size += line.len() as u64;
+
+        Ok(())
+    }
+
+    fn flush(&mut self) {
+        if let Some(file) = &mut self.file {
+            if let Err(err) = file.writer.flush() {
+                log::error!(
+                    "Unable to flush traffic capture file of {}: {err:?}",
+                    self.exchange_account_id
+                );
+            }
+        }
+    }
+
+    fn rotate(&mut self) -> Result<CaptureFile> {
+        let file_name = format!(
+            "{}_{}_{:04}.{CAPTURE_FILE_EXTENSION}",
+            self.exchange_account_id,
+            time_manager::now().format("%Y%m%d_%H%M%S_%3f"),
+            self.file_seq,
+        );
+        self.file_seq += 1;
+        let path = self.settings.dir.join(file_name);
+        let file = File::create(&path)
+            .with_context(|| format!("Unable to create capture file {}", path.display()))?;
+
+        self.remove_old_files()?;
+
+        Ok(CaptureFile {
+            writer: BufWriter::new(file),
+            size: 0,
+        })
+    }
+
+    fn remove_old_files(&self) -> Result<()> {
+        let files = capture_files(&self.settings.dir, self.exchange_account_id)?;
+        let count_to_remove = files.len().saturating_sub(self.settings.max_files);
+        for path in files.iter().take(count_to_remove) {
+            fs::remove_file(path)
+                .with_context(|| format!("Unable to remove capture file {}", path.display()))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Capture files of exchange account from the oldest to the newest
+pub fn capture_files(dir: &Path, exchange_account_id: ExchangeAccountId) -> Result<Vec<PathBuf>> {
+    let prefix = format!("{exchange_account_id}_");
+    let entries = fs::read_dir(dir)
+        .with_context(|| format!("Unable to read traffic capture dir {}", dir.display()))?;
+
+    let mut files = Vec::new();
+    for entry in entries {
+        let path = entry?.path();
+        let is_capture_file = path
+            .file_name()
+            .and_then(|x| x.to_str())
+            .map(|x| x.starts_with(&prefix) && x.ends_with(CAPTURE_FILE_EXTENSION))
+            .unwrap_or(false);
+        if is_capture_file {
+            files.push(path);
+        }
+    }
+
+    Ok(files.into_iter().sorted().collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::connectivity::WebSocketRole;
+    use crate::exchanges::traffic::replay::TrafficSession;
+    use std::env;
+    use uuid::Uuid;
+
+    fn message(index: usize) -> TrafficData {
+        TrafficData::WebSocketMessage {
+            role: WebSocketRole::Main,
+            message: format!(r#"{{"index":{index}}}"#),
+        }
+    }
+
+    #[test]
+    fn rotate_capture_files() {
+        let exchange_account_id = ExchangeAccountId::new("Binance", 0);
+        let dir = env::temp_dir().join(format!("traffic_capture_{}", Uuid::new_v4()));
+        let settings = TrafficCaptureSettings {
+            dir: dir.clone(),
+            max_file_size_bytes: 1,
+            max_files: 2,
+        };
+
+        let recorder = TrafficRecorder::new(exchange_account_id, settings).expect("in test");
+        for index in 0..3 {
+            recorder.record(message(index));
+        }
+        recorder.flush();
+
+        let files = capture_files(&dir, exchange_account_id).expect("in test");
+        assert_eq!(files.len(), 2);
+
+        let session = TrafficSession::load(&dir, exchange_account_id).expect("in test");
+        let messages = session
+            .records()
+            .iter()
+            .map(|x| x.data.clone())
+            .collect_vec();
+        assert_eq!(messages, vec![message(1), message(2)]);
+
+        fs::remove_dir_all(dir).expect("in test");
+    }
+}
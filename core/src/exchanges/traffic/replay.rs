@@ -0,0 +1,137 @@
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use hyper::StatusCode;
+use mmb_domain::market::ExchangeAccountId;
+
+use crate::exchanges::rest_client::RestResponse;
+use crate::exchanges::traffic::recorder::capture_files;
+use crate::exchanges::traffic::{TrafficData, TrafficRecord};
+use crate::exchanges::traits::Support;
+
+/// Captured exchange traffic for deterministic replaying in connector regression tests
+#[derive(Debug, Clone, Default)]
+pub struct TrafficSession {
+    records: Vec<TrafficRecord>,
+}
+
+impl TrafficSession {
+    pub fn new(records: Vec<TrafficRecord>) -> Self {
+        Self { records }
+    }
+
+    /// Parse captured records in JSON lines format
+    pub fn parse(captured: &str) -> Result<Self> {
+        let records = captured
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .enumerate()
+            .map(|(index, line)| {
+                serde_json::from_str(line)
+                    .with_context(|| format!("Unable to parse traffic record #{index}: {line}"))
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(Self { records })
+    }
+
+    /// Load all capture files of exchange account in chronological order
+    pub fn load(dir: &Path, exchange_account_id: ExchangeAccountId) -> Result<Self> {
+        let mut records = Vec::new();
+        for path in capture_files(dir, exchange_account_id)? {
+            let captured = fs::read_to_string(&path)
+                .with_context(|| format!("Unable to read capture file {}", path.display()))?;
+            records.extend(Self::parse(&captured)?.records);
+        }
+
+        Ok(Self { records })
+    }
+
+    pub fn records(&self) -> &[TrafficRecord] {
+        &self.records
+    }
+
+    /// Responses of REST requests with specified action name in order of receiving
+    pub fn rest_responses<'a>(
+        &'a self,
+        action_name: &'a str,
+    ) -> impl Iterator<Item = Result<RestResponse>> + 'a {
+        self.records
+            .iter()
+            .filter_map(move |record| match &record.data {
+                TrafficData::RestResponse {
+                    action_name: name,
+                    status,
+                    content,
+                    ..
+                } if name == action_name => Some(to_rest_response(*status, content)),
+                _ => None,
+            })
+    }
+
+    /// Feed captured websocket messages to exchange client and REST responses to `on_rest_response`
+    /// in order of capturing. Replaying is stopped on the first error
+    pub fn replay(
+        &self,
+        exchange_client: &dyn Support,
+        mut on_rest_response: impl FnMut(&str, &RestResponse) -> Result<()>,
+    ) -> Result<()> {
+        for (index, record) in self.records.iter().enumerate() {
+            match &record.data {
+                TrafficData::RestRequest { .. } => {}
+                TrafficData::RestResponse {
+                    action_name,
+                    status,
+                    content,
+                    ..
+                } => {
+                    let response = to_rest_response(*status, content)?;
+                    on_rest_response(action_name, &response).with_context(|| {
+                        format!("Unable to replay {action_name} response #{index}: {content}")
+                    })?;
+                }
+                TrafficData::WebSocketMessage { message, .. } => exchange_client
+                    .on_websocket_message(message)
+                    .with_context(|| {
+                        format!("Unable to replay websocket message #{index}: {message}")
+                    })?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn to_rest_response(status: u16, content: &str) -> Result<RestResponse> {
+    Ok(RestResponse {
+        status: StatusCode::from_u16(status)
+            .with_context(|| format!("Invalid captured status code {status}"))?,
+        content: content.to_owned(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_captured_session() {
+        let captured = r#"
+{"time":"2022-05-01T10:00:00Z","type":"RestRequest","request_id":"67e55044-10b1-426f-9247-bb680e5fe0c8","action_name":"request_all_symbols","method":"GET","uri":"https://api.binance.com/api/v3/exchangeInfo","body":""}
+{"time":"2022-05-01T10:00:01Z","type":"RestResponse","request_id":"67e55044-10b1-426f-9247-bb680e5fe0c8","action_name":"request_all_symbols","status":200,"content":"{\"symbols\":[]}"}
+{"time":"2022-05-01T10:00:02Z","type":"WebSocketMessage","role":"Main","message":"{\"e\":\"trade\"}"}
+"#;
+
+        let session = TrafficSession::parse(captured).expect("in test");
+        assert_eq!(session.records().len(), 3);
+
+        let responses = session
+            .rest_responses("request_all_symbols")
+            .collect::<Result<Vec<_>>>()
+            .expect("in test");
+        assert_eq!(responses.len(), 1);
+        assert_eq!(responses[0].status, StatusCode::OK);
+        assert_eq!(responses[0].content, r#"{"symbols":[]}"#);
+    }
+}
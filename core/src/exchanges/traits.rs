@@ -4,6 +4,7 @@ use super::{
     timeouts::requests_timeout_manager_factory::RequestTimeoutArguments,
 };
 use crate::connectivity::WebSocketRole;
+use crate::exchanges::exchange_clock::ExchangeClock;
 use crate::exchanges::general::exchange::BoxExchangeClient;
 use crate::exchanges::general::exchange::{Exchange, RequestResult};
 use crate::exchanges::general::features::ExchangeFeatures;
@@ -11,8 +12,9 @@ use crate::exchanges::general::order::cancel::CancelOrderResult;
 use crate::exchanges::general::order::create::CreateOrderResult;
 use crate::exchanges::timeouts::timeout_manager::TimeoutManager;
 use crate::lifecycle::app_lifetime_manager::AppLifetimeManager;
+use crate::misc::time::time_manager;
 use crate::settings::ExchangeSettings;
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use async_trait::async_trait;
 use dashmap::DashMap;
 use mmb_domain::events::ExchangeEvent;
@@ -123,6 +125,11 @@ pub trait ExchangeClient: Support {
     ) -> RequestResult<Vec<OrderTrade>>;
 
     async fn build_all_symbols(&self) -> Result<Vec<Arc<Symbol>>>;
+
+    /// Should be implemented if `Support::get_clock` returns clock for synchronization
+    async fn get_server_time(&self) -> Result<DateTime> {
+        Err(anyhow!("Getting server time isn't supported"))
+    }
 }
 
 pub type OrderCreatedCb =
@@ -148,6 +155,19 @@ pub trait Support: Send + Sync {
     fn on_websocket_message(&self, msg: &str) -> Result<()>;
     fn on_connecting(&self) -> Result<()>;
     fn on_disconnected(&self) -> Result<()>;
+
+    /// Whether exchange client is able to request a new order book snapshot after integrity violation.
+    /// Otherwise orders on the market are cancelled and trading waits for a fresh snapshot from the exchange
+    fn can_resync_order_book(&self) -> bool {
+        false
+    }
+
+    /// Called when local order book of currency pair can't be trusted anymore.
+    /// Exchange client should request a new order book snapshot if it is able to
+    fn on_order_book_integrity_violated(&self, _currency_pair: CurrencyPair) -> Result<()> {
+        Ok(())
+    }
+
     fn set_send_websocket_message_callback(&self, callback: SendWebsocketMessageCb);
 
     fn set_order_created_callback(&mut self, callback: OrderCreatedCb);
@@ -187,6 +207,18 @@ pub trait Support: Send + Sync {
     fn get_initial_extension_data(&self) -> Option<Box<dyn OrderInfoExtensionData>> {
         None
     }
+
+    /// Clock of exchange server that is used for signing requests.
+    /// It is synchronized periodically by `ExchangeClient::get_server_time` if it is returned
+    fn get_clock(&self) -> Option<Arc<ExchangeClock>> {
+        None
+    }
+
+    /// Current time in terms of creation time of market data events.
+    /// It's local time for real exchanges and time of replayed market data for simulated ones
+    fn market_data_now(&self) -> DateTime {
+        time_manager::now()
+    }
 }
 
 pub struct ExchangeClientBuilderResult {
@@ -194,7 +226,8 @@ pub struct ExchangeClientBuilderResult {
     pub features: ExchangeFeatures,
 }
 
-pub trait ExchangeClientBuilder {
+pub trait ExchangeClientBuilder: Send + Sync {
+    /// Fails if exchange client can't be configured by settings, e.g. hosts of custom environment aren't set
     fn create_exchange_client(
         &self,
         exchange_settings: ExchangeSettings,
@@ -202,7 +235,7 @@ pub trait ExchangeClientBuilder {
         lifetime_manager: Arc<AppLifetimeManager>,
         timeout_manager: Arc<TimeoutManager>,
         orders: Arc<OrdersPool>,
-    ) -> ExchangeClientBuilderResult;
+    ) -> Result<ExchangeClientBuilderResult>;
 
     fn get_timeout_arguments(&self) -> RequestTimeoutArguments;
 
@@ -148,12 +148,36 @@ where
     F: Fn() -> Fut + Send + Sync + 'static,
     Fut: Future<Output = ()> + Send + 'static,
 {
-    mmb_utils::infrastructure::spawn_by_timer(
+    spawn_by_timer_with_token(
         name,
         delay,
         period,
         flags,
         get_futures_cancellation_token(),
+        action,
+    )
+}
+
+/// The same as spawn_by_timer(), but with `SpawnFutureFlags::STOP_BY_TOKEN` the timer is stopped
+/// by specified `cancellation_token`. It should be linked to futures cancellation token of AppLifetimeManager
+pub fn spawn_by_timer_with_token<F, Fut>(
+    name: &str,
+    delay: Duration,
+    period: Duration,
+    flags: SpawnFutureFlags,
+    cancellation_token: CancellationToken,
+    action: F,
+) -> JoinHandle<FutureOutcome>
+where
+    F: Fn() -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    mmb_utils::infrastructure::spawn_by_timer(
+        name,
+        delay,
+        period,
+        flags,
+        cancellation_token,
         spawn_graceful_shutdown,
         action,
     )
@@ -33,6 +33,7 @@ pub mod disposition_execution;
 pub mod explanation;
 pub mod lifecycle;
 pub mod math;
+pub mod metrics;
 pub mod order_book;
 pub(crate) mod services;
 pub mod settings;
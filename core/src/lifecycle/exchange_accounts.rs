@@ -0,0 +1,394 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{bail, Context, Result};
+use mmb_domain::market::ExchangeAccountId;
+use mmb_utils::cancellation_token::CancellationToken;
+use scopeguard::ScopeGuard;
+use tokio::time::{sleep, timeout};
+
+use crate::balance::manager::balance_manager::BalanceManager;
+use crate::exchanges::block_reasons;
+use crate::exchanges::exchange_blocker::BlockType;
+use crate::exchanges::general::exchange_creation::try_create_exchange;
+use crate::exchanges::timeouts::requests_timeout_manager_factory::RequestsTimeoutManagerFactory;
+use crate::lifecycle::trading_engine::EngineContext;
+use crate::settings::ExchangeSettings;
+
+const DRAIN_CHECK_PERIOD: Duration = Duration::from_millis(100);
+const DRAIN_TIMEOUT: Duration = Duration::from_secs(30);
+
+impl EngineContext {
+    /// Connect exchange account while other accounts keep trading.
+    /// The account isn't saved to config, so it is connected until restart only
+    pub async fn add_exchange_account(&self, exchange_settings: ExchangeSettings) -> Result<()> {
+        let exchange_account_id = exchange_settings.exchange_account_id;
+        // account is reserved before checking of connected accounts, so it can't be added twice concurrently
+        if !self.adding_exchange_accounts.insert(exchange_account_id) {
+            bail!("Exchange account {exchange_account_id} is already being added");
+        }
+        let _adding_guard = scopeguard::guard(exchange_account_id, |exchange_account_id| {
+            let _ = self.adding_exchange_accounts.remove(&exchange_account_id);
+        });
+
+        if self.exchanges.contains_key(&exchange_account_id) {
+            bail!("Exchange account {exchange_account_id} is already connected");
+        }
+
+        let exchange_client_builder = self
+            .build_config
+            .supported_exchange_clients
+            .get(&exchange_account_id.exchange_id)
+            .with_context(|| {
+                format!(
+                    "Exchange {} isn't supported",
+                    exchange_account_id.exchange_id
+                )
+            })?;
+
+        log::info!("Adding exchange account {exchange_account_id}");
+
+        self.timeout_manager.add_exchange_account(
+            exchange_account_id,
+            RequestsTimeoutManagerFactory::from_requests_per_period(
+                exchange_client_builder.get_timeout_arguments(),
+                exchange_account_id,
+            ),
+        );
+        self.exchange_blocker
+            .add_exchange_account(exchange_account_id);
+        // registration is rolled back if exchange isn't created
+        let registration_guard = scopeguard::guard(exchange_account_id, |exchange_account_id| {
+            self.timeout_manager
+                .remove_exchange_account(exchange_account_id);
+            self.exchange_blocker
+                .remove_exchange_account(exchange_account_id);
+        });
+
+        let exchange = try_create_exchange(
+            &exchange_settings,
+            &self.build_config,
+            self.exchange_events.get_events_sender(),
+            self.lifetime_manager.clone(),
+            self.timeout_manager.clone(),
+            Arc::downgrade(&self.exchange_blocker),
+        )
+        .await?;
+        let _ = ScopeGuard::into_inner(registration_guard);
+
+        exchange.setup_balance_manager(self.balance_manager.clone());
+        exchange.setup_event_recorder(self.event_recorder.clone());
+        self.balance_manager.lock().add_exchange(exchange.clone());
+
+        let _ = self.exchanges.insert(exchange_account_id, exchange);
+
+        BalanceManager::update_balances_for_exchanges(
+            self.balance_manager.clone(),
+            self.lifetime_manager.stop_token(),
+        )
+        .await;
+
+        log::info!("Exchange account {exchange_account_id} is added");
+        Ok(())
+    }
+
+    /// Retire exchange account while other accounts keep trading: new orders are blocked,
+    /// open orders are canceled and the account is forgotten when all its orders are finished.
+    /// Strategy instances trading on the account should be stopped before. Accounts used by
+    /// profit loss services can't be removed because the services keep their exchanges until restart
+    pub async fn remove_exchange_account(
+        &self,
+        exchange_account_id: ExchangeAccountId,
+        cancellation_token: CancellationToken,
+    ) -> Result<()> {
+        let exchange = self
+            .exchanges
+            .get(&exchange_account_id)
+            .map(|x| x.value().clone())
+            .with_context(|| format!("Exchange account {exchange_account_id} isn't configured"))?;
+
+        if self.is_used_by_profit_loss(exchange_account_id) {
+            bail!("Exchange account {exchange_account_id} is used by profit loss services");
+        }
+
+        log::info!("Removing exchange account {exchange_account_id}");
+
+        // blocker and timeout manager of the account are kept until all its orders are finished
+        self.exchange_blocker.block(
+            exchange_account_id,
+            block_reasons::EXCHANGE_ACCOUNT_REMOVED,
+            BlockType::Manual,
+        );
+
+        exchange
+            .clone()
+            .cancel_opened_orders(cancellation_token.clone(), true)
+            .await;
+
+        let drain_orders = async {
+            while !exchange.orders.not_finished.is_empty() {
+                sleep(DRAIN_CHECK_PERIOD).await;
+            }
+        };
+        tokio::select! {
+            result = timeout(DRAIN_TIMEOUT, drain_orders) => {
+                if result.is_err() {
+                    bail!(
+                        "{} orders of exchange account {exchange_account_id} aren't finished during {} secs",
+                        exchange.orders.not_finished.len(),
+                        DRAIN_TIMEOUT.as_secs()
+                    );
+                }
+            }
+            _ = cancellation_token.when_cancelled() => {
+                bail!("Removing of exchange account {exchange_account_id} was cancelled");
+            }
+        }
+
+        exchange.disconnect_ws().await;
+        exchange.stop_token.cancel();
+
+        let _ = self.exchanges.remove(&exchange_account_id);
+        self.balance_manager
+            .lock()
+            .remove_exchange(exchange_account_id);
+        self.exchange_blocker
+            .remove_exchange_account(exchange_account_id);
+        self.timeout_manager
+            .remove_exchange_account(exchange_account_id);
+
+        log::info!("Exchange account {exchange_account_id} is removed");
+        Ok(())
+    }
+
+    fn is_used_by_profit_loss(&self, exchange_account_id: ExchangeAccountId) -> bool {
+        self.core_settings
+            .profit_loss
+            .as_ref()
+            .is_some_and(|profit_loss| {
+                let is_used_by_price_sources = profit_loss
+                    .price_sources
+                    .iter()
+                    .flat_map(|x| &x.exchange_id_currency_pair_settings)
+                    .any(|x| x.exchange_account_id == exchange_account_id);
+                let is_used_by_stoppers = profit_loss
+                    .stoppers
+                    .iter()
+                    .any(|x| x.exchange_account_id == exchange_account_id);
+
+                is_used_by_price_sources || is_used_by_stoppers
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::events::recorder::EventRecorder;
+    use crate::exchanges::exchange_blocker::ExchangeBlocker;
+    use crate::exchanges::general::currency_pair_to_symbol_converter::CurrencyPairToSymbolConverter;
+    use crate::exchanges::general::test_helper::{
+        create_test_symbol, TestClientBuilder, TEST_EXCHANGE_ID,
+    };
+    use crate::exchanges::timeouts::timeout_manager::TimeoutManager;
+    use crate::infrastructure::init_lifetime_manager;
+    use crate::lifecycle::launcher::EngineBuildConfig;
+    use crate::settings::{
+        CoreSettings, CurrencyPairSetting, ProfitLossSettings, ProfitLossStopperSettings,
+    };
+    use dashmap::DashMap;
+    use mmb_domain::events::ExchangeEvents;
+    use mmb_domain::market::CurrencyPair;
+    use mmb_utils::hashmap;
+    use std::collections::HashMap;
+    use tokio::sync::{broadcast, oneshot, Notify};
+
+    fn exchange_account_id() -> ExchangeAccountId {
+        ExchangeAccountId::new(TEST_EXCHANGE_ID, 0)
+    }
+
+    fn exchange_settings() -> ExchangeSettings {
+        ExchangeSettings {
+            exchange_account_id: exchange_account_id(),
+            currency_pairs: Some(vec![CurrencyPairSetting::Ordinary {
+                base: "PHB".into(),
+                quote: "BTC".into(),
+            }]),
+            ..Default::default()
+        }
+    }
+
+    async fn engine_context(client_builder: TestClientBuilder) -> Arc<EngineContext> {
+        engine_context_with_settings(CoreSettings::default(), client_builder).await
+    }
+
+    async fn engine_context_with_settings(
+        core_settings: CoreSettings,
+        client_builder: TestClientBuilder,
+    ) -> Arc<EngineContext> {
+        let lifetime_manager = init_lifetime_manager();
+        let (events_sender, _) = broadcast::channel(10);
+
+        EngineContext::new(
+            core_settings,
+            DashMap::new(),
+            ExchangeEvents::new(events_sender),
+            oneshot::channel().0,
+            ExchangeBlocker::new(vec![]),
+            TimeoutManager::new(HashMap::new()),
+            lifetime_manager,
+            BalanceManager::new(CurrencyPairToSymbolConverter::new(hashmap![]), None),
+            EventRecorder::start(None).await.expect("in test"),
+            EngineBuildConfig::new(vec![Box::new(client_builder)]),
+        )
+    }
+
+    fn client_builder() -> TestClientBuilder {
+        TestClientBuilder {
+            symbols: vec![create_test_symbol(false, "PHB", "BTC", "PHB")],
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn same_exchange_account_is_not_added_concurrently() {
+        let symbols_ready = Arc::new(Notify::new());
+        let engine_context = engine_context(TestClientBuilder {
+            symbols_ready: Some(symbols_ready.clone()),
+            ..client_builder()
+        })
+        .await;
+
+        let first_adding = tokio::spawn({
+            let engine_context = engine_context.clone();
+            async move {
+                engine_context
+                    .add_exchange_account(exchange_settings())
+                    .await
+            }
+        });
+        timeout(Duration::from_secs(1), async {
+            while !engine_context
+                .adding_exchange_accounts
+                .contains(&exchange_account_id())
+            {
+                sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("in test");
+
+        let second_adding = engine_context
+            .add_exchange_account(exchange_settings())
+            .await;
+        assert!(second_adding.is_err());
+
+        symbols_ready.notify_one();
+        timeout(Duration::from_secs(5), first_adding)
+            .await
+            .expect("in test")
+            .expect("in test")
+            .expect("in test");
+
+        assert!(engine_context
+            .exchanges
+            .contains_key(&exchange_account_id()));
+        assert!(engine_context.adding_exchange_accounts.is_empty());
+        assert!(engine_context
+            .add_exchange_account(exchange_settings())
+            .await
+            .is_err());
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn registration_is_rolled_back_if_exchange_is_not_created() {
+        let engine_context = engine_context(TestClientBuilder {
+            is_websocket_enabled: true,
+            ..client_builder()
+        })
+        .await;
+
+        let result = engine_context
+            .add_exchange_account(exchange_settings())
+            .await;
+
+        assert!(result.is_err());
+        assert!(engine_context.exchanges.is_empty());
+        assert!(engine_context.adding_exchange_accounts.is_empty());
+        // exchange was blocked by failed websocket connection before it is forgotten
+        assert!(engine_context.exchange_blocker.active_blockers().is_empty());
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn removed_exchange_account_is_forgotten_and_can_be_added_again() {
+        let engine_context = engine_context(client_builder()).await;
+        engine_context
+            .add_exchange_account(exchange_settings())
+            .await
+            .expect("in test");
+        let exchange = engine_context
+            .exchanges
+            .get(&exchange_account_id())
+            .map(|x| x.value().clone())
+            .expect("in test");
+
+        engine_context
+            .remove_exchange_account(exchange_account_id(), CancellationToken::new())
+            .await
+            .expect("in test");
+
+        assert!(engine_context.exchanges.is_empty());
+        assert!(exchange.stop_token.is_cancellation_requested());
+        // blocker of the account is removed together with its block reason
+        assert!(engine_context.exchange_blocker.active_blockers().is_empty());
+
+        engine_context
+            .add_exchange_account(exchange_settings())
+            .await
+            .expect("in test");
+
+        assert!(!engine_context.exchange_blocker.is_blocked_by_reason(
+            exchange_account_id(),
+            block_reasons::EXCHANGE_ACCOUNT_REMOVED
+        ));
+        let exchange = engine_context
+            .exchanges
+            .get(&exchange_account_id())
+            .map(|x| x.value().clone())
+            .expect("in test");
+        assert!(!exchange.stop_token.is_cancellation_requested());
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn exchange_account_used_by_profit_loss_is_not_removed() {
+        let core_settings = CoreSettings {
+            profit_loss: Some(ProfitLossSettings {
+                price_sources: vec![],
+                stoppers: vec![ProfitLossStopperSettings {
+                    exchange_account_id: exchange_account_id(),
+                    currency_pair: CurrencyPair::from_codes("PHB".into(), "BTC".into()),
+                    conditions: vec![],
+                }],
+            }),
+            ..Default::default()
+        };
+        let engine_context = engine_context_with_settings(core_settings, client_builder()).await;
+        engine_context
+            .add_exchange_account(exchange_settings())
+            .await
+            .expect("in test");
+
+        let result = engine_context
+            .remove_exchange_account(exchange_account_id(), CancellationToken::new())
+            .await;
+
+        assert!(result.is_err());
+        assert!(engine_context
+            .exchanges
+            .contains_key(&exchange_account_id()));
+        assert!(!engine_context.exchange_blocker.is_blocked_by_reason(
+            exchange_account_id(),
+            block_reasons::EXCHANGE_ACCOUNT_REMOVED
+        ));
+    }
+}
@@ -9,10 +9,19 @@ use crate::exchanges::general::exchange_creation::create_timeout_manager;
 use crate::exchanges::internal_events_loop::InternalEventsLoop;
 use crate::exchanges::timeouts::timeout_manager::TimeoutManager;
 use crate::exchanges::traits::ExchangeClientBuilder;
+use crate::infrastructure::spawn_future;
 use crate::infrastructure::{init_lifetime_manager, spawn_by_timer, spawn_future_ok};
 use crate::lifecycle::app_lifetime_manager::AppLifetimeManager;
+use crate::lifecycle::profit_loss::{
+    start_profit_loss_services, validate_profit_loss_settings, EngineProfitLossDependencies,
+    ProfitLossDependenciesFactory,
+};
+use crate::lifecycle::settings_reload::StrategiesReloader;
+use crate::lifecycle::state_recovery::restore_state;
 use crate::lifecycle::trading_engine::{EngineContext, TradingEngine};
-use crate::order_book::local_snapshot_service::LocalSnapshotsService;
+use crate::metrics::engine_metrics::METRICS;
+use crate::metrics::order_events::start_order_metrics_collector;
+use crate::metrics::server::start_metrics_server;
 use crate::rpc::config_waiter::ConfigWaiter;
 use crate::rpc::core_api::CoreApi;
 use crate::services::cleanup_orders::CleanupOrdersService;
@@ -20,9 +29,6 @@ use crate::settings::{AppSettings, BaseStrategySettings, CoreSettings};
 use crate::statistic_service::StatisticEventHandler;
 use crate::statistic_service::StatisticService;
 use crate::strategies::disposition_strategy::DispositionStrategy;
-use crate::{
-    disposition_execution::executor::DispositionExecutorService, infrastructure::spawn_future,
-};
 use anyhow::{anyhow, bail, Context, Result};
 use core::fmt::Debug;
 use dashmap::DashMap;
@@ -30,16 +36,16 @@ use futures::{future::join_all, FutureExt};
 use itertools::Itertools;
 use mmb_database::postgres_db::migrator::apply_migrations;
 use mmb_domain::events::{ExchangeEvent, ExchangeEvents, CHANNEL_MAX_EVENTS_COUNT};
-use mmb_domain::market::ExchangeAccountId;
 use mmb_domain::market::ExchangeId;
+use mmb_domain::market::{ExchangeAccountId, MarketAccountId};
 use mmb_utils::infrastructure::{init_infrastructure, SpawnFutureFlags};
 use mmb_utils::logger::print_info;
 use mmb_utils::nothing_to_do;
 use parking_lot::Mutex;
 use serde::de::DeserializeOwned;
-use serde::{Deserialize, Serialize};
+use serde::Serialize;
 use std::any::Any;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fmt::{Display, Formatter};
 use std::panic::{self, AssertUnwindSafe};
 use std::sync::{Arc, Weak};
@@ -50,19 +56,27 @@ use tokio::time::timeout;
 
 use crate::lifecycle::app_lifetime_manager::ActionAfterGracefulShutdown;
 
+#[derive(Clone)]
 pub struct EngineBuildConfig {
-    pub supported_exchange_clients: HashMap<ExchangeId, Box<dyn ExchangeClientBuilder + 'static>>,
+    pub supported_exchange_clients: HashMap<ExchangeId, Arc<dyn ExchangeClientBuilder + 'static>>,
+    pub(crate) profit_loss_dependencies: ProfitLossDependenciesFactory,
 }
 
 impl EngineBuildConfig {
     pub fn new(client_builders: Vec<Box<dyn ExchangeClientBuilder>>) -> Self {
         let mut supported_exchange_clients = HashMap::new();
         for builder in client_builders {
-            supported_exchange_clients.insert(builder.get_exchange_id(), builder);
+            supported_exchange_clients.insert(builder.get_exchange_id(), Arc::from(builder));
         }
 
         EngineBuildConfig {
             supported_exchange_clients,
+            profit_loss_dependencies: Arc::new(|engine_context, events_sender| {
+                Arc::new(EngineProfitLossDependencies::new(
+                    engine_context,
+                    events_sender,
+                ))
+            }),
         }
     }
 }
@@ -128,7 +142,6 @@ async fn before_engine_context_init<StrategySettings>(
     broadcast::Sender<ExchangeEvent>,
     broadcast::Receiver<ExchangeEvent>,
     AppSettings<StrategySettings>,
-    DashMap<ExchangeAccountId, Arc<Exchange>>,
     Arc<EngineContext>,
     oneshot::Receiver<ActionAfterGracefulShutdown>,
 )>
@@ -155,6 +168,14 @@ where
         }
     };
 
+    validate_strategies_settings(&settings)?;
+    validate_profit_loss_settings(&settings.core)?;
+
+    // metrics are enabled before exchanges are created, so their connections are measured too
+    if settings.core.metrics.is_some() {
+        METRICS.enable();
+    }
+
     let (events_sender, events_receiver) = broadcast::channel(CHANNEL_MAX_EVENTS_COUNT);
 
     let timeout_manager = create_timeout_manager(&settings.core, build_settings);
@@ -219,9 +240,15 @@ where
     .await;
 
     for exchange in &exchanges_map {
-        exchange
-            .value()
-            .setup_balance_manager(balance_manager.clone())
+        let exchange = exchange.value();
+        exchange.setup_balance_manager(balance_manager.clone());
+        exchange.setup_event_recorder(event_recorder.clone());
+    }
+
+    if let Some(db) = &settings.core.database {
+        restore_state(&db.url, &exchanges_map, &balance_manager)
+            .await
+            .context("unable restore engine state from database")?;
     }
 
     start_updating_balances(&lifetime_manager, &balance_manager);
@@ -230,7 +257,7 @@ where
 
     let engine_context = EngineContext::new(
         settings.core.clone(),
-        exchanges_map.clone(),
+        exchanges_map,
         exchange_events,
         finish_graceful_shutdown_tx,
         exchange_blocker,
@@ -238,18 +265,70 @@ where
         lifetime_manager.clone(),
         balance_manager,
         event_recorder,
+        build_settings.clone(),
     );
 
+    if let Some(profit_loss_settings) = &settings.core.profit_loss {
+        start_profit_loss_services(profit_loss_settings, &engine_context, events_sender.clone())
+            .await
+            .context("unable start profit loss services")?;
+    }
+
+    if let Some(metrics_settings) = &settings.core.metrics {
+        start_order_metrics_collector(events_sender.subscribe());
+        start_metrics_server(metrics_settings, &engine_context)
+            .context("unable start metrics server")?;
+    }
+
     Ok((
         events_sender,
         events_receiver,
         settings,
-        exchanges_map,
         engine_context,
         finish_graceful_shutdown_rx,
     ))
 }
 
+/// Every strategy instance should trade on its own market of a configured exchange account,
+/// otherwise DispositionExecutors would handle orders of each other
+pub(super) fn validate_strategies_settings<StrategySettings>(
+    settings: &AppSettings<StrategySettings>,
+) -> Result<()>
+where
+    StrategySettings: BaseStrategySettings + Clone,
+{
+    let mut markets = HashSet::with_capacity(settings.strategies.len());
+    for strategy_settings in &settings.strategies {
+        let market_account_id = MarketAccountId::new(
+            strategy_settings.exchange_account_id(),
+            strategy_settings.currency_pair(),
+        );
+
+        if !settings
+            .core
+            .exchanges
+            .iter()
+            .any(|x| x.exchange_account_id == market_account_id.exchange_account_id)
+        {
+            bail!(
+                "Strategy instance for {} {} refers to not configured exchange account",
+                market_account_id.exchange_account_id,
+                market_account_id.currency_pair
+            );
+        }
+
+        if !markets.insert(market_account_id) {
+            bail!(
+                "There are several strategy instances for the same market {} {}",
+                market_account_id.exchange_account_id,
+                market_account_id.currency_pair
+            );
+        }
+    }
+
+    Ok(())
+}
+
 fn start_updating_balances(
     lifetime_manager: &Arc<AppLifetimeManager>,
     balance_manager: &Arc<Mutex<BalanceManager>>,
@@ -273,22 +352,28 @@ fn start_updating_balances(
 }
 
 #[allow(clippy::too_many_arguments)]
-fn run_services<'a, StrategySettings>(
+fn run_services<StrategySettings>(
     engine_context: Arc<EngineContext>,
     events_sender: broadcast::Sender<ExchangeEvent>,
     events_receiver: broadcast::Receiver<ExchangeEvent>,
     settings: AppSettings<StrategySettings>,
-    exchanges_map: DashMap<ExchangeAccountId, Arc<Exchange>>,
     init_user_settings: InitSettings<StrategySettings>,
-    build_strategy: impl Fn(
-        &AppSettings<StrategySettings>,
-        Arc<EngineContext>,
-    ) -> Box<dyn DispositionStrategy + 'static>,
+    build_strategy: impl Fn(&StrategySettings, Arc<EngineContext>) -> Box<dyn DispositionStrategy + 'static>
+        + Send
+        + Sync
+        + 'static,
     finish_graceful_shutdown_rx: oneshot::Receiver<ActionAfterGracefulShutdown>,
     cleanup_orders_service: Arc<CleanupOrdersService>,
 ) -> TradingEngine
 where
-    StrategySettings: BaseStrategySettings + Clone + Debug + Deserialize<'a> + Serialize,
+    StrategySettings: BaseStrategySettings
+        + Clone
+        + PartialEq
+        + Debug
+        + DeserializeOwned
+        + Serialize
+        + Send
+        + 'static,
 {
     let internal_events_loop = InternalEventsLoop::new();
     engine_context
@@ -299,10 +384,18 @@ where
     let statistic_service = StatisticService::new();
     let statistic_event_handler =
         create_statistic_event_handler(exchange_events, statistic_service.clone());
+    let settings_reloader = StrategiesReloader::start(
+        engine_context.clone(),
+        settings,
+        build_strategy,
+        statistic_event_handler.stats.clone(),
+    );
     let control_panel = CoreApi::create_and_start(
         engine_context.lifetime_manager.clone(),
         load_pretty_settings(init_user_settings),
         statistic_service,
+        engine_context.clone(),
+        settings_reloader,
     )
     .expect("Unable to start control panel");
     engine_context
@@ -318,7 +411,8 @@ where
         SpawnFutureFlags::STOP_BY_TOKEN | SpawnFutureFlags::DENY_CANCELLATION,
         internal_events_loop.start(
             events_receiver,
-            exchanges_map.into_iter().collect(),
+            engine_context.clone(),
+            engine_context.event_recorder.clone(),
             engine_context.lifetime_manager.stop_token(),
         ),
     );
@@ -331,18 +425,6 @@ where
         move || cleanup_orders_service.clone().cleanup_outdated_orders(),
     );
 
-    let disposition_strategy = build_strategy(&settings, engine_context.clone());
-    let disposition_executor_service = create_disposition_executor_service(
-        &settings.strategy,
-        &engine_context,
-        disposition_strategy,
-        &statistic_event_handler.stats,
-    );
-
-    engine_context
-        .shutdown_service
-        .register_user_service(disposition_executor_service);
-
     log::info!("TradingEngine started");
     TradingEngine::new(engine_context, finish_graceful_shutdown_rx)
 }
@@ -405,13 +487,20 @@ pub(crate) fn unwrap_or_handle_panic<T>(
 pub async fn launch_trading_engine<StrategySettings>(
     build_settings: &EngineBuildConfig,
     init_user_settings: InitSettings<StrategySettings>,
-    build_strategy: impl Fn(
-        &AppSettings<StrategySettings>,
-        Arc<EngineContext>,
-    ) -> Box<dyn DispositionStrategy + 'static>,
+    build_strategy: impl Fn(&StrategySettings, Arc<EngineContext>) -> Box<dyn DispositionStrategy + 'static>
+        + Send
+        + Sync
+        + 'static,
 ) -> Result<TradingEngine>
 where
-    StrategySettings: BaseStrategySettings + Clone + Debug + DeserializeOwned + Serialize,
+    StrategySettings: BaseStrategySettings
+        + Clone
+        + PartialEq
+        + Debug
+        + DeserializeOwned
+        + Serialize
+        + Send
+        + 'static,
 {
     print_info("The TradingEngine is going to start...");
     let action_outcome = AssertUnwindSafe(before_engine_context_init(
@@ -422,14 +511,8 @@ where
     .await;
 
     let message_template = "Panic happened during EngineContext initialization";
-    let (
-        events_sender,
-        events_receiver,
-        settings,
-        exchanges_map,
-        engine_context,
-        finish_graceful_shutdown_rx,
-    ) = unwrap_or_handle_panic(action_outcome, message_template, None)??;
+    let (events_sender, events_receiver, settings, engine_context, finish_graceful_shutdown_rx) =
+        unwrap_or_handle_panic(action_outcome, message_template, None)??;
 
     let cloned_lifetime_manager = engine_context.lifetime_manager.clone();
     let action = async move {
@@ -446,7 +529,7 @@ where
     );
 
     let cleanup_orders_service =
-        Arc::new(CleanupOrdersService::new(engine_context.exchanges.clone()));
+        Arc::new(CleanupOrdersService::new(Arc::downgrade(&engine_context)));
 
     let action_outcome = panic::catch_unwind(AssertUnwindSafe(|| {
         run_services(
@@ -454,7 +537,6 @@ where
             events_sender,
             events_receiver,
             settings,
-            exchanges_map,
             init_user_settings,
             build_strategy,
             finish_graceful_shutdown_rx,
@@ -474,24 +556,6 @@ where
     result
 }
 
-fn create_disposition_executor_service(
-    base_settings: &dyn BaseStrategySettings,
-    engine_context: &Arc<EngineContext>,
-    disposition_strategy: Box<dyn DispositionStrategy>,
-    statistics: &Arc<StatisticService>,
-) -> Arc<DispositionExecutorService> {
-    DispositionExecutorService::new(
-        engine_context.clone(),
-        engine_context.get_events_channel(),
-        LocalSnapshotsService::default(),
-        base_settings.exchange_account_id(),
-        base_settings.currency_pair(),
-        disposition_strategy,
-        engine_context.lifetime_manager.stop_token(),
-        statistics.clone(),
-    )
-}
-
 fn create_statistic_event_handler(
     events: ExchangeEvents,
     statistic_service: Arc<StatisticService>,
@@ -519,3 +583,93 @@ pub async fn create_exchanges(
     }))
     .await
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::settings::ExchangeSettings;
+    use mmb_domain::market::CurrencyPair;
+    use mmb_domain::order::snapshot::Amount;
+    use rust_decimal_macros::dec;
+
+    #[derive(Debug, Clone)]
+    struct TestStrategySettings {
+        exchange_account_id: ExchangeAccountId,
+        currency_pair: CurrencyPair,
+    }
+
+    impl BaseStrategySettings for TestStrategySettings {
+        fn exchange_account_id(&self) -> ExchangeAccountId {
+            self.exchange_account_id
+        }
+
+        fn currency_pair(&self) -> CurrencyPair {
+            self.currency_pair
+        }
+
+        fn max_amount(&self) -> Amount {
+            dec!(1)
+        }
+    }
+
+    fn strategy(exchange_account_number: u8, base: &str) -> TestStrategySettings {
+        TestStrategySettings {
+            exchange_account_id: ExchangeAccountId::new("Binance", exchange_account_number),
+            currency_pair: CurrencyPair::from_codes(base.into(), "usdt".into()),
+        }
+    }
+
+    fn settings(strategies: Vec<TestStrategySettings>) -> AppSettings<TestStrategySettings> {
+        let exchanges = [0, 1]
+            .map(|number| {
+                ExchangeSettings::new_short(
+                    ExchangeAccountId::new("Binance", number),
+                    "api_key".to_owned(),
+                    "secret_key".to_owned(),
+                    false,
+                )
+            })
+            .to_vec();
+
+        AppSettings {
+            strategies,
+            core: CoreSettings {
+                exchanges,
+                ..Default::default()
+            },
+        }
+    }
+
+    #[test]
+    fn strategies_on_different_markets_are_valid() {
+        let settings = settings(vec![
+            strategy(0, "btc"),
+            strategy(0, "eth"),
+            strategy(1, "btc"),
+        ]);
+
+        validate_strategies_settings(&settings).expect("in test");
+    }
+
+    #[test]
+    fn strategies_on_same_market_are_rejected() {
+        let settings = settings(vec![strategy(0, "btc"), strategy(0, "btc")]);
+
+        let error = validate_strategies_settings(&settings).expect_err("in test");
+
+        assert!(error
+            .to_string()
+            .contains("several strategy instances for the same market"));
+    }
+
+    #[test]
+    fn strategy_on_not_configured_exchange_account_is_rejected() {
+        let settings = settings(vec![strategy(2, "btc")]);
+
+        let error = validate_strategies_settings(&settings).expect_err("in test");
+
+        assert!(error
+            .to_string()
+            .contains("not configured exchange account"));
+    }
+}
@@ -1,4 +1,8 @@
 pub mod app_lifetime_manager;
+mod exchange_accounts;
 pub mod launcher;
+mod profit_loss;
+pub mod settings_reload;
 pub mod shutdown;
+mod state_recovery;
 pub mod trading_engine;
@@ -0,0 +1,444 @@
+use std::sync::Arc;
+
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use mmb_domain::events::ExchangeEvent;
+use mmb_utils::infrastructure::SpawnFutureFlags;
+use mockall_double::double;
+use tokio::sync::broadcast;
+
+use crate::balance::changes::balance_changes_service::BalanceChangesService;
+use crate::balance::changes::profit_loss_stopper_service::ProfitLossStopperService;
+#[double]
+use crate::exchanges::general::currency_pair_to_symbol_converter::CurrencyPairToSymbolConverter;
+use crate::exchanges::general::exchange::Exchange;
+use crate::infrastructure::spawn_future;
+use crate::lifecycle::trading_engine::EngineContext;
+#[double]
+use crate::services::usd_convertion::usd_converter::UsdConverter;
+use crate::settings::{CoreSettings, ProfitLossSettings, ProfitLossStopperSettings};
+
+pub(crate) use engine_dependencies::EngineProfitLossDependencies;
+
+/// Dependencies of profit loss services which are replaced by mocks in unit tests of the crate
+#[async_trait]
+pub(crate) trait ProfitLossDependencies: Send + Sync {
+    fn currency_pair_to_symbol_converter(&self) -> Arc<CurrencyPairToSymbolConverter>;
+
+    async fn create_usd_converter(
+        &self,
+        profit_loss_settings: &ProfitLossSettings,
+    ) -> Result<UsdConverter>;
+
+    fn create_stopper_service(
+        &self,
+        stopper_settings: &ProfitLossStopperSettings,
+        exchange: Arc<Exchange>,
+    ) -> Arc<ProfitLossStopperService>;
+}
+
+/// Creates dependencies of profit loss services over running engine, unit tests set it in `EngineBuildConfig` to inject mocks
+pub(crate) type ProfitLossDependenciesFactory = Arc<
+    dyn Fn(Arc<EngineContext>, broadcast::Sender<ExchangeEvent>) -> Arc<dyn ProfitLossDependencies>
+        + Send
+        + Sync,
+>;
+
+/// Every profit loss stopper should limit losses of a configured exchange account
+pub(crate) fn validate_profit_loss_settings(core_settings: &CoreSettings) -> Result<()> {
+    let Some(profit_loss_settings) = &core_settings.profit_loss else {
+        return Ok(());
+    };
+
+    for stopper_settings in &profit_loss_settings.stoppers {
+        if !core_settings
+            .exchanges
+            .iter()
+            .any(|x| x.exchange_account_id == stopper_settings.exchange_account_id)
+        {
+            bail!(
+                "Profit loss stopper for {} {} refers to not configured exchange account",
+                stopper_settings.exchange_account_id,
+                stopper_settings.currency_pair
+            );
+        }
+
+        if stopper_settings.conditions.is_empty() {
+            bail!(
+                "Profit loss stopper for {} {} should have conditions",
+                stopper_settings.exchange_account_id,
+                stopper_settings.currency_pair
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Balance changes from order fills are converted to USD and accumulated for every configured market,
+/// so exchange is blocked and positions are closed when loss limit of the market is exceeded
+pub(crate) async fn start_profit_loss_services(
+    profit_loss_settings: &ProfitLossSettings,
+    engine_context: &Arc<EngineContext>,
+    events_sender: broadcast::Sender<ExchangeEvent>,
+) -> Result<()> {
+    let dependencies = (engine_context.build_config.profit_loss_dependencies)(
+        engine_context.clone(),
+        events_sender,
+    );
+
+    let profit_loss_stopper_services = profit_loss_settings
+        .stoppers
+        .iter()
+        .map(|stopper_settings| {
+            let exchange = engine_context
+                .exchanges
+                .get(&stopper_settings.exchange_account_id)
+                .map(|x| x.value().clone())
+                .with_context(|| {
+                    format!(
+                        "Profit loss stopper refers to not connected exchange account {}",
+                        stopper_settings.exchange_account_id
+                    )
+                })?;
+
+            Ok(dependencies.create_stopper_service(stopper_settings, exchange))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let usd_converter = dependencies
+        .create_usd_converter(profit_loss_settings)
+        .await?;
+
+    let lifetime_manager = engine_context.lifetime_manager.clone();
+    let balance_changes_service = BalanceChangesService::new(
+        dependencies.currency_pair_to_symbol_converter(),
+        profit_loss_stopper_services,
+        usd_converter,
+        lifetime_manager.clone(),
+    );
+    engine_context
+        .balance_manager
+        .lock()
+        .set_balance_changes_service(balance_changes_service.clone());
+
+    engine_context
+        .shutdown_service
+        .register_core_service(balance_changes_service.clone());
+
+    spawn_future(
+        "BalanceChangesService run",
+        SpawnFutureFlags::STOP_BY_TOKEN | SpawnFutureFlags::DENY_CANCELLATION,
+        balance_changes_service.run(lifetime_manager.stop_token()),
+    );
+
+    Ok(())
+}
+
+mod engine_dependencies {
+    use std::sync::Arc;
+
+    use anyhow::{Context, Result};
+    use async_trait::async_trait;
+    use itertools::Itertools;
+    use mmb_database::postgres_db::PgPool;
+    use mmb_domain::events::ExchangeEvent;
+    use mmb_utils::infrastructure::SpawnFutureFlags;
+    use mockall_double::double;
+    use tokio::sync::broadcast;
+
+    use super::ProfitLossDependencies;
+    use crate::balance::changes::profit_loss_stopper_service::ProfitLossStopperService;
+    #[double]
+    use crate::balance::manager::balance_manager::BalanceManager;
+    #[double]
+    use crate::exchanges::exchange_blocker::ExchangeBlocker;
+    #[double]
+    use crate::exchanges::general::currency_pair_to_symbol_converter::CurrencyPairToSymbolConverter;
+    #[double]
+    use crate::exchanges::general::engine_api::EngineApi;
+    use crate::exchanges::general::exchange::Exchange;
+    use crate::infrastructure::spawn_future_ok;
+    use crate::lifecycle::trading_engine::EngineContext;
+    use crate::services::usd_convertion::price_source_service::PriceSourceService;
+    use crate::services::usd_convertion::price_sources_loader::PriceSourcesLoader;
+    use crate::services::usd_convertion::prices_sources_saver::PriceSourcesSaver;
+    #[double]
+    use crate::services::usd_convertion::usd_converter::UsdConverter;
+    use crate::services::usd_convertion::usd_denominator::UsdDenominator;
+    use crate::settings::{ProfitLossSettings, ProfitLossStopperSettings};
+
+    /// Profit loss services are built over exchanges and balances of running engine
+    pub(crate) struct EngineProfitLossDependencies {
+        engine_context: Arc<EngineContext>,
+        events_sender: broadcast::Sender<ExchangeEvent>,
+        currency_pair_to_symbol_converter: Arc<CurrencyPairToSymbolConverter>,
+    }
+
+    impl EngineProfitLossDependencies {
+        pub(crate) fn new(
+            engine_context: Arc<EngineContext>,
+            events_sender: broadcast::Sender<ExchangeEvent>,
+        ) -> Self {
+            let currency_pair_to_symbol_converter = CurrencyPairToSymbolConverter::new(
+                engine_context.exchanges.clone().into_iter().collect(),
+            );
+            Self {
+                engine_context,
+                events_sender,
+                currency_pair_to_symbol_converter,
+            }
+        }
+    }
+
+    #[async_trait]
+    impl ProfitLossDependencies for EngineProfitLossDependencies {
+        fn currency_pair_to_symbol_converter(&self) -> Arc<CurrencyPairToSymbolConverter> {
+            self.currency_pair_to_symbol_converter.clone()
+        }
+
+        async fn create_usd_converter(
+            &self,
+            profit_loss_settings: &ProfitLossSettings,
+        ) -> Result<UsdConverter> {
+            let lifetime_manager = self.engine_context.lifetime_manager.clone();
+
+            let price_sources_pool = match &self.engine_context.core_settings.database {
+                Some(db) => Some(
+                    PgPool::create(&db.url, 1)
+                        .await
+                        .context("unable connect to database for loading price sources")?,
+                ),
+                None => None,
+            };
+
+            let price_source_service = PriceSourceService::new(
+                self.currency_pair_to_symbol_converter.clone(),
+                &profit_loss_settings.price_sources,
+                PriceSourcesLoader::new(price_sources_pool),
+            );
+            spawn_future_ok(
+                "PriceSourceService start",
+                SpawnFutureFlags::STOP_BY_TOKEN | SpawnFutureFlags::DENY_CANCELLATION,
+                price_source_service.clone().start(
+                    PriceSourcesSaver::new(self.engine_context.event_recorder.clone()),
+                    self.events_sender.subscribe(),
+                    lifetime_manager.stop_token(),
+                ),
+            );
+
+            let usd_currencies = profit_loss_settings
+                .price_sources
+                .iter()
+                .map(|x| x.end_currency_code)
+                .collect_vec();
+
+            Ok(UsdConverter::new(
+                &usd_currencies,
+                price_source_service,
+                UsdDenominator::without_market_service(lifetime_manager),
+            ))
+        }
+
+        fn create_stopper_service(
+            &self,
+            stopper_settings: &ProfitLossStopperSettings,
+            exchange: Arc<Exchange>,
+        ) -> Arc<ProfitLossStopperService> {
+            Arc::new(ProfitLossStopperService::new(
+                stopper_settings,
+                ExchangeBlocker::shared(&self.engine_context.exchange_blocker),
+                Some(BalanceManager::shared(&self.engine_context.balance_manager)),
+                EngineApi::new(exchange),
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::balance::manager::balance_manager::BalanceManager;
+    use crate::database::events::recorder::EventRecorder;
+    #[double]
+    use crate::exchanges::exchange_blocker::ExchangeBlocker;
+    use crate::exchanges::general::currency_pair_to_symbol_converter::CurrencyPairToSymbolConverter as RealCurrencyPairToSymbolConverter;
+    #[double]
+    use crate::exchanges::general::engine_api::EngineApi;
+    use crate::exchanges::general::test_helper::{
+        create_test_symbol, get_test_exchange_with_symbol, TEST_EXCHANGE_ID,
+    };
+    use crate::exchanges::timeouts::timeout_manager::TimeoutManager;
+    use crate::infrastructure::init_lifetime_manager;
+    use crate::lifecycle::launcher::EngineBuildConfig;
+    use crate::misc::time::tests::init_mock;
+    use crate::settings::{ExchangeSettings, StopperCondition, TimePeriodKind};
+    use dashmap::DashMap;
+    use mmb_domain::events::ExchangeEvents;
+    use mmb_domain::market::{CurrencyPair, ExchangeAccountId};
+    use mmb_utils::hashmap;
+    use parking_lot::Mutex;
+    use rust_decimal_macros::dec;
+    use std::collections::HashMap;
+    use tokio::sync::{broadcast, oneshot};
+
+    #[derive(Default)]
+    struct TestDependencies {
+        stopper_exchange_account_ids: Mutex<Vec<ExchangeAccountId>>,
+    }
+
+    #[async_trait]
+    impl ProfitLossDependencies for TestDependencies {
+        fn currency_pair_to_symbol_converter(&self) -> Arc<CurrencyPairToSymbolConverter> {
+            Arc::new(CurrencyPairToSymbolConverter::default())
+        }
+
+        async fn create_usd_converter(
+            &self,
+            _profit_loss_settings: &ProfitLossSettings,
+        ) -> Result<UsdConverter> {
+            let mut usd_converter = UsdConverter::default();
+            usd_converter
+                .expect_convert_amount()
+                .returning(|_, amount, _| Some(amount));
+            Ok(usd_converter)
+        }
+
+        fn create_stopper_service(
+            &self,
+            stopper_settings: &ProfitLossStopperSettings,
+            exchange: Arc<Exchange>,
+        ) -> Arc<ProfitLossStopperService> {
+            self.stopper_exchange_account_ids
+                .lock()
+                .push(exchange.exchange_account_id);
+            Arc::new(ProfitLossStopperService::new(
+                stopper_settings,
+                Arc::new(ExchangeBlocker::default()),
+                None,
+                Arc::new(EngineApi::default()),
+            ))
+        }
+    }
+
+    fn exchange_account_id() -> ExchangeAccountId {
+        ExchangeAccountId::new(TEST_EXCHANGE_ID, 0)
+    }
+
+    fn profit_loss_settings(exchange_account_id: ExchangeAccountId) -> ProfitLossSettings {
+        ProfitLossSettings {
+            price_sources: vec![],
+            stoppers: vec![ProfitLossStopperSettings {
+                exchange_account_id,
+                currency_pair: CurrencyPair::from_codes("PHB".into(), "BTC".into()),
+                conditions: vec![StopperCondition {
+                    period_kind: TimePeriodKind::Day,
+                    period_value: 1,
+                    limit: dec!(50),
+                }],
+            }],
+        }
+    }
+
+    async fn engine_context(dependencies: Arc<TestDependencies>) -> Arc<EngineContext> {
+        let lifetime_manager = init_lifetime_manager();
+        let (exchange, _) =
+            get_test_exchange_with_symbol(create_test_symbol(false, "PHB", "BTC", "PHB"));
+        let (events_sender, _) = broadcast::channel(10);
+        let mut build_config = EngineBuildConfig::new(vec![]);
+        build_config.profit_loss_dependencies = Arc::new(move |_, _| dependencies.clone());
+
+        EngineContext::new(
+            CoreSettings::default(),
+            DashMap::from_iter([(exchange_account_id(), exchange.clone())]),
+            ExchangeEvents::new(events_sender),
+            oneshot::channel().0,
+            crate::exchanges::exchange_blocker::ExchangeBlocker::new(vec![exchange_account_id()]),
+            TimeoutManager::new(HashMap::new()),
+            lifetime_manager,
+            BalanceManager::new(
+                RealCurrencyPairToSymbolConverter::new(hashmap![exchange_account_id() => exchange]),
+                None,
+            ),
+            EventRecorder::start(None).await.expect("in test"),
+            build_config,
+        )
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn balance_changes_are_accumulated_by_stoppers_of_connected_exchange_accounts() {
+        let (_time_manager_mock, _mock_locker) = init_mock(Arc::new(Mutex::new(0)));
+        let dependencies = Arc::new(TestDependencies::default());
+        let engine_context = engine_context(dependencies.clone()).await;
+
+        start_profit_loss_services(
+            &profit_loss_settings(exchange_account_id()),
+            &engine_context,
+            broadcast::channel(10).0,
+        )
+        .await
+        .expect("in test");
+
+        assert_eq!(
+            *dependencies.stopper_exchange_account_ids.lock(),
+            vec![exchange_account_id()]
+        );
+        assert!(engine_context
+            .balance_manager
+            .lock()
+            .has_balance_changes_service());
+
+        engine_context.lifetime_manager.stop_token().cancel();
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn stopper_of_not_connected_exchange_account_is_rejected() {
+        let (_time_manager_mock, _mock_locker) = init_mock(Arc::new(Mutex::new(0)));
+        let dependencies = Arc::new(TestDependencies::default());
+        let engine_context = engine_context(dependencies.clone()).await;
+
+        let result = start_profit_loss_services(
+            &profit_loss_settings(ExchangeAccountId::new(TEST_EXCHANGE_ID, 1)),
+            &engine_context,
+            broadcast::channel(10).0,
+        )
+        .await;
+
+        assert!(result.is_err());
+        assert!(dependencies.stopper_exchange_account_ids.lock().is_empty());
+        assert!(!engine_context
+            .balance_manager
+            .lock()
+            .has_balance_changes_service());
+    }
+
+    #[test]
+    fn validate_exchange_accounts_and_conditions_of_stoppers() {
+        let settings = |exchange_account_id, conditions: Option<Vec<StopperCondition>>| {
+            let mut profit_loss_settings = profit_loss_settings(exchange_account_id);
+            if let Some(conditions) = conditions {
+                profit_loss_settings.stoppers[0].conditions = conditions;
+            }
+            CoreSettings {
+                exchanges: vec![ExchangeSettings::new_short(
+                    self::exchange_account_id(),
+                    "".into(),
+                    "".into(),
+                    false,
+                )],
+                profit_loss: Some(profit_loss_settings),
+                ..Default::default()
+            }
+        };
+
+        validate_profit_loss_settings(&settings(exchange_account_id(), None)).expect("in test");
+        assert!(validate_profit_loss_settings(&settings(
+            ExchangeAccountId::new(TEST_EXCHANGE_ID, 1),
+            None
+        ))
+        .is_err());
+        assert!(
+            validate_profit_loss_settings(&settings(exchange_account_id(), Some(vec![]))).is_err()
+        );
+    }
+}
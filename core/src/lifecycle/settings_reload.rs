@@ -0,0 +1,709 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt::Debug;
+use std::sync::Arc;
+
+use anyhow::{bail, Context, Result};
+use futures::future::join_all;
+use itertools::Itertools;
+use mmb_domain::market::{ExchangeAccountId, MarketAccountId};
+use mmb_utils::infrastructure::SpawnFutureFlags;
+use mmb_utils::nothing_to_do;
+use parking_lot::Mutex;
+use serde::de::DeserializeOwned;
+
+use crate::disposition_execution::executor::DispositionExecutorService;
+use crate::infrastructure::spawn_future_ok;
+use crate::lifecycle::launcher::validate_strategies_settings;
+use crate::lifecycle::profit_loss::validate_profit_loss_settings;
+use crate::lifecycle::trading_engine::{EngineContext, Service};
+use crate::order_book::local_snapshot_service::LocalSnapshotsService;
+use crate::service_configuration::configuration_descriptor::ConfigurationDescriptor;
+use crate::settings::{AppSettings, BaseStrategySettings, CoreSettings, ExchangeSettings};
+use crate::statistic_service::StatisticService;
+use crate::strategies::disposition_strategy::DispositionStrategy;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SettingsReloadOutcome {
+    /// Changes were applied to running trading engine
+    Applied,
+    /// Changes can't be applied in place, so trading engine should be restarted
+    RestartRequired,
+}
+
+/// Applying of new settings without restarting trading engine. RPC API doesn't know
+/// the type of strategy settings, so it works with settings serialized in toml
+pub trait SettingsReloader: Send + Sync {
+    /// Nothing is applied if `RestartRequired` is returned
+    fn reload(&self, settings: &str) -> Result<SettingsReloadOutcome>;
+
+    /// Whether any running strategy instance trades on the exchange account
+    fn is_exchange_account_traded(&self, exchange_account_id: ExchangeAccountId) -> bool;
+}
+
+/// Differences of strategy instances between running and new settings
+#[derive(Debug, PartialEq, Eq)]
+struct StrategiesChanges<StrategySettings> {
+    added: Vec<StrategySettings>,
+    changed: Vec<StrategySettings>,
+    removed: Vec<MarketAccountId>,
+}
+
+fn market_account_id(strategy_settings: &impl BaseStrategySettings) -> MarketAccountId {
+    MarketAccountId::new(
+        strategy_settings.exchange_account_id(),
+        strategy_settings.currency_pair(),
+    )
+}
+
+/// Exchange accounts, database, profit loss services and metrics server are created on start of trading engine only.
+/// Exchange accounts are compared by id, so their order in settings doesn't matter
+fn has_structural_changes(running: &CoreSettings, new: &CoreSettings) -> bool {
+    fn exchanges_by_id(core: &CoreSettings) -> HashMap<ExchangeAccountId, &ExchangeSettings> {
+        core.exchanges
+            .iter()
+            .map(|x| (x.exchange_account_id, x))
+            .collect()
+    }
+
+    running.database != new.database
+        || running.profit_loss != new.profit_loss
+        || running.metrics != new.metrics
+        || exchanges_by_id(running) != exchanges_by_id(new)
+}
+
+/// `None` if settings have structural changes which can be applied only by restart
+fn diff_settings<StrategySettings>(
+    running: &AppSettings<StrategySettings>,
+    new: &AppSettings<StrategySettings>,
+) -> Option<StrategiesChanges<StrategySettings>>
+where
+    StrategySettings: BaseStrategySettings + Clone + PartialEq,
+{
+    if has_structural_changes(&running.core, &new.core) {
+        return None;
+    }
+
+    let running_strategies: HashMap<_, _> = running
+        .strategies
+        .iter()
+        .map(|x| (market_account_id(x), x))
+        .collect();
+
+    let mut added = Vec::new();
+    let mut changed = Vec::new();
+    for strategy_settings in &new.strategies {
+        match running_strategies.get(&market_account_id(strategy_settings)) {
+            None => added.push(strategy_settings.clone()),
+            Some(&running_settings) if running_settings != strategy_settings => {
+                changed.push(strategy_settings.clone())
+            }
+            Some(_) => nothing_to_do(),
+        }
+    }
+
+    let new_markets: HashSet<_> = new.strategies.iter().map(market_account_id).collect();
+    let removed = running
+        .strategies
+        .iter()
+        .map(market_account_id)
+        .filter(|x| !new_markets.contains(x))
+        .collect();
+
+    Some(StrategiesChanges {
+        added,
+        changed,
+        removed,
+    })
+}
+
+struct RunningStrategy {
+    executor: Arc<DispositionExecutorService>,
+    /// Amount limit of strategy instance in BalanceManager is set by this descriptor
+    configuration_descriptor: ConfigurationDescriptor,
+}
+
+struct RunningStrategies<StrategySettings>
+where
+    StrategySettings: BaseStrategySettings + Clone,
+{
+    settings: AppSettings<StrategySettings>,
+    executors: HashMap<MarketAccountId, RunningStrategy>,
+}
+
+/// Owner of DispositionExecutors of strategy instances. Changed strategy parameters are applied
+/// by replacing strategy inside running DispositionExecutor, so its orders aren't canceled
+pub(crate) struct StrategiesReloader<StrategySettings, BuildStrategy>
+where
+    StrategySettings: BaseStrategySettings + Clone,
+{
+    engine_context: Arc<EngineContext>,
+    statistics: Arc<StatisticService>,
+    build_strategy: BuildStrategy,
+    running: Mutex<RunningStrategies<StrategySettings>>,
+}
+
+impl<StrategySettings, BuildStrategy> StrategiesReloader<StrategySettings, BuildStrategy>
+where
+    StrategySettings:
+        BaseStrategySettings + Clone + PartialEq + Debug + DeserializeOwned + Send + 'static,
+    BuildStrategy: Fn(&StrategySettings, Arc<EngineContext>) -> Box<dyn DispositionStrategy>
+        + Send
+        + Sync
+        + 'static,
+{
+    /// Start DispositionExecutors for all strategy instances of settings
+    pub(crate) fn start(
+        engine_context: Arc<EngineContext>,
+        settings: AppSettings<StrategySettings>,
+        build_strategy: BuildStrategy,
+        statistics: Arc<StatisticService>,
+    ) -> Arc<Self> {
+        let reloader = Self {
+            engine_context,
+            statistics,
+            build_strategy,
+            running: Mutex::new(RunningStrategies {
+                settings: AppSettings {
+                    strategies: Vec::new(),
+                    core: settings.core.clone(),
+                },
+                executors: HashMap::new(),
+            }),
+        };
+
+        {
+            let mut running = reloader.running.lock();
+            for strategy_settings in settings.strategies {
+                let strategy =
+                    (reloader.build_strategy)(&strategy_settings, reloader.engine_context.clone());
+                let running_strategy = reloader.start_executor(&strategy_settings, strategy);
+                let _ = running
+                    .executors
+                    .insert(market_account_id(&strategy_settings), running_strategy);
+                running.settings.strategies.push(strategy_settings);
+            }
+        }
+
+        Arc::new(reloader)
+    }
+
+    fn start_executor(
+        &self,
+        strategy_settings: &StrategySettings,
+        strategy: Box<dyn DispositionStrategy>,
+    ) -> RunningStrategy {
+        let configuration_descriptor = strategy.configuration_descriptor();
+        let executor = DispositionExecutorService::new(
+            self.engine_context.clone(),
+            self.engine_context.get_events_channel(),
+            LocalSnapshotsService::default(),
+            strategy_settings.exchange_account_id(),
+            strategy_settings.currency_pair(),
+            strategy,
+            // executor of removed strategy instance is stopped separately from trading engine
+            self.engine_context
+                .lifetime_manager
+                .stop_token()
+                .create_linked_token(),
+            self.statistics.clone(),
+        );
+
+        self.engine_context
+            .shutdown_service
+            .register_user_service(executor.clone());
+
+        RunningStrategy {
+            executor,
+            configuration_descriptor,
+        }
+    }
+
+    fn stop_executor(&self, market_account_id: MarketAccountId, running_strategy: RunningStrategy) {
+        let RunningStrategy {
+            executor,
+            configuration_descriptor,
+        } = running_strategy;
+
+        executor.stop();
+        self.engine_context
+            .shutdown_service
+            .unregister_user_service(&(executor as Arc<dyn Service>));
+        self.engine_context
+            .balance_manager
+            .lock()
+            .remove_target_amount_limit(
+                configuration_descriptor,
+                market_account_id.exchange_account_id,
+                market_account_id.currency_pair,
+            );
+    }
+
+    fn ensure_market_exists(&self, market_account_id: MarketAccountId) -> Result<()> {
+        let exchange = match self
+            .engine_context
+            .exchanges
+            .get(&market_account_id.exchange_account_id)
+        {
+            None => bail!(
+                "Exchange account {} isn't configured",
+                market_account_id.exchange_account_id
+            ),
+            Some(v) => v.clone(),
+        };
+
+        if exchange
+            .get_symbol(market_account_id.currency_pair)
+            .is_err()
+        {
+            bail!(
+                "Currency pair {} isn't supported by {}",
+                market_account_id.currency_pair,
+                market_account_id.exchange_account_id
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Orders of removed strategy instance would be never handled by DispositionExecutor anymore
+    fn cancel_market_orders(&self, market_account_id: MarketAccountId) {
+        let exchange = match self
+            .engine_context
+            .exchanges
+            .get(&market_account_id.exchange_account_id)
+        {
+            None => return,
+            Some(v) => v.clone(),
+        };
+
+        let orders = exchange
+            .orders
+            .not_finished
+            .iter()
+            .map(|x| x.value().clone())
+            .filter(|order| {
+                order.currency_pair() == market_account_id.currency_pair
+                    && !order.fn_ref(|s| s.header.order_type.is_external_order())
+            })
+            .collect_vec();
+
+        if orders.is_empty() {
+            return;
+        }
+
+        let cancellation_token = self.engine_context.lifetime_manager.stop_token();
+        let action = async move {
+            let results = join_all(orders.iter().map(|order| {
+                exchange.wait_cancel_order(order.clone(), None, true, cancellation_token.clone())
+            }))
+            .await;
+
+            for (order, result) in orders.iter().zip(results) {
+                if let Err(error) = result {
+                    log::error!(
+                        "Unable to cancel order {} of removed strategy instance: {error:?}",
+                        order.client_order_id()
+                    );
+                }
+            }
+        };
+
+        spawn_future_ok(
+            "Cancel orders of removed strategy instance",
+            SpawnFutureFlags::STOP_BY_TOKEN | SpawnFutureFlags::DENY_CANCELLATION,
+            action,
+        );
+    }
+}
+
+impl<StrategySettings, BuildStrategy> SettingsReloader
+    for StrategiesReloader<StrategySettings, BuildStrategy>
+where
+    StrategySettings:
+        BaseStrategySettings + Clone + PartialEq + Debug + DeserializeOwned + Send + 'static,
+    BuildStrategy: Fn(&StrategySettings, Arc<EngineContext>) -> Box<dyn DispositionStrategy>
+        + Send
+        + Sync
+        + 'static,
+{
+    fn reload(&self, settings: &str) -> Result<SettingsReloadOutcome> {
+        let new_settings = toml_edit::de::from_str::<AppSettings<StrategySettings>>(settings)
+            .context("Unable parse new settings")?;
+        validate_strategies_settings(&new_settings)?;
+        validate_profit_loss_settings(&new_settings.core)?;
+
+        let mut running = self.running.lock();
+        let changes = match diff_settings(&running.settings, &new_settings) {
+            None => {
+                log::info!(
+                    "New settings have structural changes, so they can be applied by restart only"
+                );
+                return Ok(SettingsReloadOutcome::RestartRequired);
+            }
+            Some(v) => v,
+        };
+
+        // nothing is applied until every strategy instance is validated and built
+        for strategy_settings in &changes.added {
+            self.ensure_market_exists(market_account_id(strategy_settings))?;
+        }
+
+        let mut replacing = Vec::with_capacity(changes.changed.len());
+        for strategy_settings in &changes.changed {
+            let market_account_id = market_account_id(strategy_settings);
+            let running_strategy =
+                running.executors.get(&market_account_id).with_context(|| {
+                    format!("There is no DispositionExecutor for {market_account_id:?}")
+                })?;
+            if running_strategy.executor.is_stopped() {
+                bail!("DispositionExecutor for {market_account_id:?} is already stopped");
+            }
+
+            replacing.push(market_account_id);
+        }
+
+        // strategy applies its amount limit to BalanceManager on creation
+        let changed_strategies = changes
+            .changed
+            .iter()
+            .map(|x| (self.build_strategy)(x, self.engine_context.clone()))
+            .collect_vec();
+        let added_strategies = changes
+            .added
+            .iter()
+            .map(|x| (self.build_strategy)(x, self.engine_context.clone()))
+            .collect_vec();
+
+        for (market_account_id, strategy) in replacing.into_iter().zip(changed_strategies) {
+            let running_strategy = match running.executors.get_mut(&market_account_id) {
+                None => continue,
+                Some(v) => v,
+            };
+
+            let configuration_descriptor = strategy.configuration_descriptor();
+            if let Err(err) = running_strategy.executor.replace_strategy(strategy) {
+                // executor was stopped right after checking, so it keeps stopped as before reloading
+                log::error!("Unable to update settings of strategy instance for {market_account_id:?}: {err:?}");
+                continue;
+            }
+
+            // amount limit set by previous strategy isn't overwritten by the new one
+            if running_strategy.configuration_descriptor != configuration_descriptor {
+                self.engine_context
+                    .balance_manager
+                    .lock()
+                    .remove_target_amount_limit(
+                        running_strategy.configuration_descriptor,
+                        market_account_id.exchange_account_id,
+                        market_account_id.currency_pair,
+                    );
+                running_strategy.configuration_descriptor = configuration_descriptor;
+            }
+            log::info!("Settings of strategy instance for {market_account_id:?} were updated");
+        }
+
+        for (strategy_settings, strategy) in changes.added.iter().zip(added_strategies) {
+            let market_account_id = market_account_id(strategy_settings);
+            let running_strategy = self.start_executor(strategy_settings, strategy);
+            let _ = running
+                .executors
+                .insert(market_account_id, running_strategy);
+            log::info!("Strategy instance for {market_account_id:?} was started");
+        }
+
+        for &market_account_id in &changes.removed {
+            if let Some(running_strategy) = running.executors.remove(&market_account_id) {
+                self.stop_executor(market_account_id, running_strategy);
+            }
+            self.cancel_market_orders(market_account_id);
+            log::info!("Strategy instance for {market_account_id:?} was stopped");
+        }
+
+        running.settings = new_settings;
+
+        Ok(SettingsReloadOutcome::Applied)
+    }
+
+    fn is_exchange_account_traded(&self, exchange_account_id: ExchangeAccountId) -> bool {
+        self.running
+            .lock()
+            .executors
+            .keys()
+            .any(|x| x.exchange_account_id == exchange_account_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::balance::manager::balance_manager::BalanceManager;
+    use crate::database::events::recorder::EventRecorder;
+    use crate::disposition_execution::{PriceSlot, TradingContext};
+    use crate::exchanges::exchange_blocker::ExchangeBlocker;
+    use crate::exchanges::general::currency_pair_to_symbol_converter::CurrencyPairToSymbolConverter;
+    use crate::exchanges::general::test_helper::{
+        create_test_symbol, get_test_exchange_with_symbol, get_test_timeout_manager,
+        TEST_EXCHANGE_ID,
+    };
+    use crate::explanation::Explanation;
+    use crate::infrastructure::init_lifetime_manager;
+    use crate::lifecycle::launcher::EngineBuildConfig;
+    use crate::settings::MetricsSettings;
+    use dashmap::DashMap;
+    use mmb_domain::events::ExchangeEvents;
+    use mmb_domain::market::{CurrencyPair, ExchangeAccountId};
+    use mmb_domain::order::snapshot::{Amount, OrderSnapshot};
+    use mmb_utils::cancellation_token::CancellationToken;
+    use mmb_utils::{hashmap, DateTime};
+    use rust_decimal_macros::dec;
+    use serde::{Deserialize, Serialize};
+    use tokio::sync::{broadcast, oneshot};
+
+    #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+    struct TestStrategySettings {
+        currency_pair: CurrencyPair,
+        max_amount: Amount,
+    }
+
+    impl BaseStrategySettings for TestStrategySettings {
+        fn exchange_account_id(&self) -> ExchangeAccountId {
+            ExchangeAccountId::new(TEST_EXCHANGE_ID, 0)
+        }
+
+        fn currency_pair(&self) -> CurrencyPair {
+            self.currency_pair
+        }
+
+        fn max_amount(&self) -> Amount {
+            self.max_amount
+        }
+    }
+
+    fn strategy(base: &str, max_amount: Amount) -> TestStrategySettings {
+        TestStrategySettings {
+            currency_pair: CurrencyPair::from_codes(base.into(), "usdt".into()),
+            max_amount,
+        }
+    }
+
+    fn settings(strategies: Vec<TestStrategySettings>) -> AppSettings<TestStrategySettings> {
+        AppSettings {
+            strategies,
+            core: CoreSettings {
+                exchanges: vec![ExchangeSettings::new_short(
+                    ExchangeAccountId::new(TEST_EXCHANGE_ID, 0),
+                    "api_key".to_owned(),
+                    "secret_key".to_owned(),
+                    false,
+                )],
+                ..Default::default()
+            },
+        }
+    }
+
+    /// Strategy which never trades
+    struct IdleStrategy {
+        max_amount: Amount,
+    }
+
+    impl DispositionStrategy for IdleStrategy {
+        fn calculate_trading_context(
+            &mut self,
+            _now: DateTime,
+            _local_snapshots_service: &LocalSnapshotsService,
+            _explanation: &mut Explanation,
+        ) -> Option<TradingContext> {
+            None
+        }
+
+        fn handle_order_fill(
+            &self,
+            _cloned_order: &Arc<OrderSnapshot>,
+            _price_slot: &PriceSlot,
+            _target_eai: ExchangeAccountId,
+            _cancellation_token: CancellationToken,
+        ) -> Result<()> {
+            Ok(())
+        }
+
+        fn configuration_descriptor(&self) -> ConfigurationDescriptor {
+            ConfigurationDescriptor::new(
+                "IdleStrategy".into(),
+                self.max_amount.to_string().as_str().into(),
+            )
+        }
+    }
+
+    type BuiltStrategies = Arc<Mutex<Vec<TestStrategySettings>>>;
+    type BuildTestStrategy = Box<
+        dyn Fn(&TestStrategySettings, Arc<EngineContext>) -> Box<dyn DispositionStrategy>
+            + Send
+            + Sync,
+    >;
+
+    /// Reloader for markets btc/usdt and eth/usdt of test exchange. Settings of built strategies are collected
+    async fn start_reloader(
+        running: AppSettings<TestStrategySettings>,
+    ) -> (
+        Arc<StrategiesReloader<TestStrategySettings, BuildTestStrategy>>,
+        BuiltStrategies,
+    ) {
+        let lifetime_manager = init_lifetime_manager();
+
+        let (exchange, _) =
+            get_test_exchange_with_symbol(create_test_symbol(false, "btc", "usdt", "btc"));
+        let eth_symbol = create_test_symbol(false, "eth", "usdt", "eth");
+        let _ = exchange
+            .symbols
+            .insert(eth_symbol.currency_pair(), eth_symbol);
+        let exchange_account_id = exchange.exchange_account_id;
+        let (events_sender, _) = broadcast::channel(10);
+        let engine_context = EngineContext::new(
+            CoreSettings::default(),
+            DashMap::from_iter([(exchange_account_id, exchange.clone())]),
+            ExchangeEvents::new(events_sender),
+            oneshot::channel().0,
+            ExchangeBlocker::new(vec![exchange_account_id]),
+            get_test_timeout_manager(exchange_account_id),
+            lifetime_manager,
+            BalanceManager::new(
+                CurrencyPairToSymbolConverter::new(hashmap![exchange_account_id => exchange]),
+                None,
+            ),
+            EventRecorder::start(None).await.expect("in test"),
+            EngineBuildConfig::new(vec![]),
+        );
+
+        let built_strategies = BuiltStrategies::default();
+        let build_strategy: BuildTestStrategy = {
+            let built_strategies = built_strategies.clone();
+            Box::new(move |settings, _| {
+                built_strategies.lock().push(settings.clone());
+                Box::new(IdleStrategy {
+                    max_amount: settings.max_amount,
+                })
+            })
+        };
+        let reloader = StrategiesReloader::start(
+            engine_context,
+            running,
+            build_strategy,
+            StatisticService::new(),
+        );
+
+        (reloader, built_strategies)
+    }
+
+    fn to_toml(settings: &AppSettings<TestStrategySettings>) -> String {
+        toml_edit::ser::to_string(settings).expect("in test")
+    }
+
+    #[test]
+    fn diff_strategies() {
+        let running = settings(vec![
+            strategy("btc", dec!(1)),
+            strategy("eth", dec!(1)),
+            strategy("ltc", dec!(1)),
+        ]);
+        let new = settings(vec![
+            strategy("btc", dec!(1)),
+            strategy("eth", dec!(2)),
+            strategy("xrp", dec!(1)),
+        ]);
+
+        let changes = diff_settings(&running, &new).expect("in test");
+
+        assert_eq!(
+            changes,
+            StrategiesChanges {
+                added: vec![strategy("xrp", dec!(1))],
+                changed: vec![strategy("eth", dec!(2))],
+                removed: vec![market_account_id(&strategy("ltc", dec!(1)))],
+            }
+        );
+    }
+
+    #[test]
+    fn restart_required_for_new_exchange_account() {
+        let running = settings(vec![strategy("btc", dec!(1))]);
+        let mut new = running.clone();
+        new.core.exchanges.push(ExchangeSettings::new_short(
+            ExchangeAccountId::new("Binance", 1),
+            "api_key".to_owned(),
+            "secret_key".to_owned(),
+            false,
+        ));
+
+        assert_eq!(diff_settings(&running, &new), None);
+    }
+
+    #[test]
+    fn restart_required_for_changed_exchange_account() {
+        let running = settings(vec![strategy("btc", dec!(1))]);
+        let mut new = running.clone();
+        new.core.exchanges[0].api_key = "new_api_key".to_owned();
+
+        assert_eq!(diff_settings(&running, &new), None);
+    }
+
+    #[test]
+    fn restart_required_for_changed_metrics() {
+        let running = settings(vec![strategy("btc", dec!(1))]);
+        let mut new = running.clone();
+        new.core.metrics = Some(MetricsSettings {
+            address: "127.0.0.1:9100".parse().expect("in test"),
+        });
+
+        assert_eq!(diff_settings(&running, &new), None);
+    }
+
+    #[test]
+    fn reordered_exchange_accounts_are_applied_in_place() {
+        let mut running = settings(vec![strategy("btc", dec!(1))]);
+        running.core.exchanges.push(ExchangeSettings::new_short(
+            ExchangeAccountId::new("Binance", 1),
+            "api_key".to_owned(),
+            "secret_key".to_owned(),
+            false,
+        ));
+        let mut new = settings(vec![strategy("btc", dec!(2))]);
+        new.core.exchanges = running.core.exchanges.iter().rev().cloned().collect();
+
+        let changes = diff_settings(&running, &new).expect("in test");
+
+        assert_eq!(changes.changed, vec![strategy("btc", dec!(2))]);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn nothing_is_applied_if_any_strategy_instance_can_not_be_updated() {
+        let running = settings(vec![strategy("btc", dec!(1)), strategy("eth", dec!(1))]);
+        let (reloader, built_strategies) = start_reloader(running).await;
+        built_strategies.lock().clear();
+
+        let eth_executor = reloader.running.lock().executors
+            [&market_account_id(&strategy("eth", dec!(1)))]
+            .executor
+            .clone();
+        eth_executor.stop();
+        tokio::time::timeout(std::time::Duration::from_secs(1), async {
+            while !eth_executor.is_stopped() {
+                tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("in test");
+
+        let new = settings(vec![strategy("btc", dec!(2)), strategy("eth", dec!(2))]);
+        let result = reloader.reload(&to_toml(&new));
+
+        assert!(result.is_err());
+        assert!(built_strategies.lock().is_empty());
+
+        // running settings are kept, so changes of other instances are applied by the next reloading
+        let new = settings(vec![strategy("btc", dec!(2)), strategy("eth", dec!(1))]);
+        let outcome = reloader.reload(&to_toml(&new)).expect("in test");
+
+        assert_eq!(outcome, SettingsReloadOutcome::Applied);
+        assert_eq!(*built_strategies.lock(), vec![strategy("btc", dec!(2))]);
+    }
+}
@@ -53,6 +53,14 @@ impl ShutdownService {
         self.state.lock().user_services.push(service);
     }
 
+    /// Forget user service which is stopped before graceful shutdown of trading engine
+    pub fn unregister_user_service(self: &Arc<Self>, service: &Arc<dyn Service>) {
+        self.state
+            .lock()
+            .user_services
+            .retain(|x| !Arc::ptr_eq(x, service));
+    }
+
     pub(crate) fn register_core_service(self: &Arc<Self>, service: Arc<dyn Service>) {
         print_info(service_has_been_registered_msg(service.name(), "core"));
         self.state.lock().core_services.push(service);
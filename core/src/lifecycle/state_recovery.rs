@@ -0,0 +1,333 @@
+use crate::balance::manager::balance_manager::BalanceManager;
+use crate::balance::manager::balances::Balances;
+use crate::exchanges::general::exchange::Exchange;
+use crate::orders::events::ORDERS_TABLE_NAME;
+use anyhow::{Context, Result};
+use chrono::{Duration, Utc};
+use dashmap::DashMap;
+use itertools::Itertools;
+use mmb_database::postgres_db::events::{load_last_event, load_last_events_by_key, DbEvent, Event};
+use mmb_database::postgres_db::PgPool;
+use mmb_domain::market::ExchangeAccountId;
+use mmb_domain::order::snapshot::{ClientOrderId, OrderSnapshot};
+use parking_lot::{Mutex, RwLock};
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::sync::Arc;
+
+const ORDER_CLIENT_ORDER_ID_PATH: &str = "{order, header, client_order_id}";
+/// Orders saved earlier are not restored, so whole orders history isn't scanned on every start
+const SAVED_ORDERS_RECOVERY_DAYS: i64 = 7;
+
+/// Owned counterpart of `OrderEventRecord` for reading saved orders back
+#[derive(Deserialize)]
+struct SavedOrderEvent {
+    order: OrderSnapshot,
+}
+
+/// Restore state that was persisted before restart of the engine:
+/// not finished orders which are still opened on exchanges are added back to orders pools,
+/// balance reservations, virtual balance diffs and positions are restored in `BalanceManager`.
+/// Reservations of orders that were finished while the engine was stopped are released.
+/// Recovery fails if opened orders can't be requested from an exchange, because otherwise
+/// reservations of orders that are still opened would be released.
+pub(crate) async fn restore_state(
+    database_url: &str,
+    exchanges: &DashMap<ExchangeAccountId, Arc<Exchange>>,
+    balance_manager: &Arc<Mutex<BalanceManager>>,
+) -> Result<()> {
+    let pool = PgPool::create(database_url, 1)
+        .await
+        .context("unable connect to database for state recovery")?;
+
+    let balances = load_last_event(&pool, <Balances as Event>::TABLE_NAME)
+        .await
+        .context("unable load last balances")?
+        .map(|event| serde_json::from_value::<Balances>(event.json))
+        .transpose()
+        .context("unable deserialize last balances")?;
+
+    let saved_orders = load_last_events_by_key(
+        &pool,
+        ORDERS_TABLE_NAME,
+        ORDER_CLIENT_ORDER_ID_PATH,
+        Utc::now() - Duration::days(SAVED_ORDERS_RECOVERY_DAYS),
+    )
+    .await
+    .context("unable load saved orders")?;
+
+    let mut surviving_orders = HashSet::new();
+    for (exchange_account_id, orders) in &select_not_finished_orders(saved_orders)
+        .into_iter()
+        .group_by(|order| order.header.exchange_account_id)
+    {
+        let exchange = exchanges.get(&exchange_account_id).map(|x| x.clone());
+        match exchange {
+            None => log::warn!(
+                "Saved not finished orders of {exchange_account_id} are skipped because exchange account is not configured"
+            ),
+            Some(exchange) => {
+                surviving_orders.extend(reattach_opened_orders(&exchange, orders.collect()).await?)
+            }
+        }
+    }
+
+    if let Some(balances) = balances {
+        let mut balance_manager = balance_manager.lock();
+        balance_manager.restore_balance_state(&balances, false);
+
+        for reservation_id in balance_manager.get_reservation_ids() {
+            let has_surviving_orders = balance_manager
+                .get_reservation_expected(reservation_id)
+                .approved_parts
+                .keys()
+                .any(|client_order_id| surviving_orders.contains(client_order_id));
+
+            if !has_surviving_orders {
+                balance_manager
+                    .unreserve_rest(reservation_id)
+                    .with_context(|| format!("unable release reservation {reservation_id}"))?;
+            }
+        }
+    }
+
+    log::info!(
+        "Engine state restored from database with {} opened orders",
+        surviving_orders.len()
+    );
+
+    Ok(())
+}
+
+/// Select the orders that were not finished at the moment of saving
+fn select_not_finished_orders(saved_orders: Vec<DbEvent>) -> Vec<OrderSnapshot> {
+    saved_orders
+        .into_iter()
+        .filter_map(
+            |event| match serde_json::from_value::<SavedOrderEvent>(event.json) {
+                Ok(saved) => Some(saved.order),
+                Err(err) => {
+                    log::warn!(
+                        "Unable deserialize saved order with id {}: {err:?}",
+                        event.id
+                    );
+                    None
+                }
+            },
+        )
+        .filter(|order| !order.is_finished())
+        .sorted_by_key(|order| order.header.exchange_account_id.to_string())
+        .collect()
+}
+
+/// Add orders that are still opened on the exchange to its orders pool
+/// and return client order ids of them. Opened orders which aren't found among saved ones
+/// are added to the pool as missed, so they are cancelled on shutdown like other orders
+async fn reattach_opened_orders(
+    exchange: &Exchange,
+    orders: Vec<OrderSnapshot>,
+) -> Result<Vec<ClientOrderId>> {
+    let exchange_account_id = exchange.exchange_account_id;
+    let open_orders = exchange
+        .get_open_orders(false)
+        .await
+        .with_context(|| format!("unable get opened orders of {exchange_account_id}"))?;
+
+    let mut reattached = vec![];
+    for mut order in orders {
+        let client_order_id = order.header.client_order_id.clone();
+        let open_order = open_orders.iter().find(|x| {
+            x.client_order_id == client_order_id
+                || Some(&x.exchange_order_id) == order.props.exchange_order_id.as_ref()
+        });
+
+        match open_order {
+            None => log::info!(
+                "Saved order {client_order_id} on {exchange_account_id} is not opened on exchange anymore"
+            ),
+            Some(open_order) => {
+                order.props.exchange_order_id = Some(open_order.exchange_order_id.clone());
+                order.props.status = open_order.order_status;
+
+                let order_ref = exchange
+                    .orders
+                    .add_snapshot_initial(Arc::new(RwLock::new(order)));
+                let _ = exchange
+                    .orders
+                    .cache_by_exchange_id
+                    .insert(open_order.exchange_order_id.clone(), order_ref);
+
+                log::info!("Saved order {client_order_id} on {exchange_account_id} is restored");
+                reattached.push(client_order_id);
+            }
+        }
+    }
+
+    let unknown_orders = open_orders
+        .into_iter()
+        .filter(|x| {
+            !exchange
+                .orders
+                .cache_by_exchange_id
+                .contains_key(&x.exchange_order_id)
+        })
+        .collect_vec();
+    if !unknown_orders.is_empty() {
+        log::warn!(
+            "Opened orders {:?} on {exchange_account_id} aren't found among saved orders",
+            unknown_orders
+                .iter()
+                .map(|x| x.exchange_order_id.as_str())
+                .collect_vec()
+        );
+        exchange.add_missing_open_orders(&unknown_orders);
+    }
+
+    Ok(reattached)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exchanges::general::test_helper::{get_test_exchange_with_blocker, TestClient};
+    use crate::infrastructure::init_lifetime_manager;
+    use crate::orders::events::OrderEventRecord;
+    use mmb_domain::market::CurrencyPair;
+    use mmb_domain::order::event::OrderEventType;
+    use mmb_domain::order::snapshot::{
+        ExchangeOrderId, OrderInfo, OrderSide, OrderStatus, OrderType,
+    };
+    use rust_decimal_macros::dec;
+
+    fn order_snapshot(
+        exchange_account_id: ExchangeAccountId,
+        client_order_id: &str,
+        status: OrderStatus,
+    ) -> OrderSnapshot {
+        let mut order = OrderSnapshot::with_params(
+            client_order_id.into(),
+            OrderType::Limit,
+            None,
+            exchange_account_id,
+            CurrencyPair::from_codes("btc".into(), "usdt".into()),
+            dec!(10),
+            dec!(1),
+            OrderSide::Buy,
+            None,
+            "test",
+        );
+        order.props.status = status;
+        order
+    }
+
+    fn open_order(client_order_id: &str, exchange_order_id: &str) -> OrderInfo {
+        OrderInfo::new(
+            CurrencyPair::from_codes("btc".into(), "usdt".into()),
+            ExchangeOrderId::from(exchange_order_id),
+            client_order_id.into(),
+            OrderSide::Buy,
+            OrderStatus::Created,
+            dec!(10),
+            dec!(1),
+            dec!(0),
+            dec!(0),
+            None,
+            None,
+            None,
+        )
+    }
+
+    fn saved_order_event(id: u64, client_order_id: &str, status: OrderStatus) -> DbEvent {
+        let order = order_snapshot(
+            ExchangeAccountId::new("Binance", 0),
+            client_order_id,
+            status,
+        );
+
+        DbEvent {
+            id,
+            insert_time: Utc::now(),
+            version: 1,
+            json: OrderEventRecord::new(&OrderEventType::CreateOrderSucceeded, &order)
+                .get_json()
+                .expect("in test"),
+        }
+    }
+
+    #[test]
+    fn select_only_not_finished_orders() {
+        let saved_orders = vec![
+            saved_order_event(1, "created", OrderStatus::Created),
+            saved_order_event(2, "canceled", OrderStatus::Canceled),
+            saved_order_event(3, "completed", OrderStatus::Completed),
+            saved_order_event(4, "canceling", OrderStatus::Canceling),
+        ];
+
+        let orders = select_not_finished_orders(saved_orders);
+
+        let client_order_ids = orders
+            .iter()
+            .map(|x| x.header.client_order_id.as_str())
+            .collect_vec();
+        assert_eq!(client_order_ids, ["created", "canceling"]);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn reattach_only_orders_opened_on_exchange() {
+        let _ = init_lifetime_manager();
+        let (exchange, _exchange_blocker, _rx) = get_test_exchange_with_blocker(TestClient {
+            open_orders: Mutex::new(vec![
+                open_order("opened", "exchange_order_id"),
+                open_order("unknown", "unknown_exchange_order_id"),
+            ]),
+            ..Default::default()
+        });
+        let orders = ["opened", "closed"]
+            .map(|x| order_snapshot(exchange.exchange_account_id, x, OrderStatus::Created));
+
+        let reattached = reattach_opened_orders(&exchange, orders.into())
+            .await
+            .expect("in test");
+
+        assert_eq!(reattached, [ClientOrderId::from("opened")]);
+        assert!(exchange
+            .orders
+            .cache_by_exchange_id
+            .contains_key(&ExchangeOrderId::from("exchange_order_id")));
+        assert!(!exchange
+            .orders
+            .cache_by_client_id
+            .contains_key(&ClientOrderId::from("closed")));
+
+        let unknown_order = exchange
+            .orders
+            .cache_by_exchange_id
+            .get(&ExchangeOrderId::from("unknown_exchange_order_id"))
+            .map(|x| x.clone())
+            .expect("in test");
+        assert_eq!(unknown_order.client_order_id(), "unknown".into());
+        assert!(exchange
+            .orders
+            .not_finished
+            .contains_key(&unknown_order.client_order_id()));
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn fail_reattach_if_open_orders_are_not_received() {
+        let _ = init_lifetime_manager();
+        let (exchange, _exchange_blocker, _rx) = get_test_exchange_with_blocker(TestClient {
+            is_get_open_orders_failed: true,
+            ..Default::default()
+        });
+        let orders = vec![order_snapshot(
+            exchange.exchange_account_id,
+            "opened",
+            OrderStatus::Created,
+        )];
+
+        let result = reattach_opened_orders(&exchange, orders).await;
+
+        assert!(result.is_err());
+        assert!(exchange.orders.cache_by_client_id.is_empty());
+    }
+}
@@ -6,8 +6,8 @@ use std::sync::atomic::AtomicBool;
 use std::sync::atomic::Ordering;
 use std::sync::Arc;
 
-use anyhow::Result;
-use dashmap::DashMap;
+use anyhow::{bail, Result};
+use dashmap::{DashMap, DashSet};
 use futures::future::join_all;
 use mmb_utils::cancellation_token::CancellationToken;
 use tokio::sync::{broadcast, oneshot};
@@ -18,6 +18,7 @@ use crate::database::events::recorder::EventRecorder;
 use crate::exchanges::block_reasons;
 use crate::exchanges::exchange_blocker::BlockType;
 use crate::exchanges::exchange_blocker::ExchangeBlocker;
+use crate::exchanges::general::engine_api::EngineApi;
 use crate::exchanges::general::exchange::Exchange;
 use crate::exchanges::timeouts::timeout_manager::TimeoutManager;
 use crate::infrastructure::unset_lifetime_manager;
@@ -29,7 +30,7 @@ use mmb_domain::market::ExchangeAccountId;
 use mmb_utils::nothing_to_do;
 use parking_lot::Mutex;
 
-use super::launcher::unwrap_or_handle_panic;
+use super::launcher::{unwrap_or_handle_panic, EngineBuildConfig};
 use crate::lifecycle::app_lifetime_manager::ActionAfterGracefulShutdown;
 
 pub trait Service: Send + Sync + 'static {
@@ -51,7 +52,11 @@ pub struct EngineContext {
     pub balance_manager: Arc<Mutex<BalanceManager>>,
     pub event_recorder: Arc<EventRecorder>,
     is_graceful_shutdown_started: AtomicBool,
-    exchange_events: ExchangeEvents,
+    is_kill_switch_active: AtomicBool,
+    pub(crate) build_config: EngineBuildConfig,
+    pub(crate) exchange_events: ExchangeEvents,
+    /// Exchange accounts which are being connected at the moment
+    pub(crate) adding_exchange_accounts: DashSet<ExchangeAccountId>,
     finish_graceful_shutdown_sender: Mutex<Option<oneshot::Sender<ActionAfterGracefulShutdown>>>,
 }
 
@@ -67,6 +72,7 @@ impl EngineContext {
         lifetime_manager: Arc<AppLifetimeManager>,
         balance_manager: Arc<Mutex<BalanceManager>>,
         event_recorder: Arc<EventRecorder>,
+        build_config: EngineBuildConfig,
     ) -> Arc<Self> {
         let engine_context = Arc::new(EngineContext {
             core_settings,
@@ -78,7 +84,10 @@ impl EngineContext {
             balance_manager,
             event_recorder,
             is_graceful_shutdown_started: Default::default(),
+            is_kill_switch_active: Default::default(),
+            build_config,
             exchange_events,
+            adding_exchange_accounts: Default::default(),
             finish_graceful_shutdown_sender: Mutex::new(Some(finish_graceful_shutdown_sender)),
         });
 
@@ -163,6 +172,79 @@ impl EngineContext {
         print_info("Graceful shutdown finished");
     }
 
+    /// Stop trading on exchange account until `resume_trading` is called
+    pub fn pause_trading(&self, exchange_account_id: ExchangeAccountId) -> Result<()> {
+        self.ensure_exchange_exists(exchange_account_id)?;
+        log::info!("Trading on {exchange_account_id} is paused manually");
+        self.exchange_blocker.block(
+            exchange_account_id,
+            block_reasons::MANUAL_PAUSE,
+            BlockType::Manual,
+        );
+        Ok(())
+    }
+
+    pub fn resume_trading(&self, exchange_account_id: ExchangeAccountId) -> Result<()> {
+        self.ensure_exchange_exists(exchange_account_id)?;
+        log::info!("Trading on {exchange_account_id} is resumed manually");
+        self.exchange_blocker
+            .unblock(exchange_account_id, block_reasons::MANUAL_PAUSE);
+        Ok(())
+    }
+
+    pub(crate) fn ensure_exchange_exists(
+        &self,
+        exchange_account_id: ExchangeAccountId,
+    ) -> Result<()> {
+        if !self.exchanges.contains_key(&exchange_account_id) {
+            bail!("Exchange account {exchange_account_id} isn't configured");
+        }
+        Ok(())
+    }
+
+    /// Pause trading on all exchange accounts, cancel all open orders and close all active positions.
+    /// Trading engine keeps working, so trading can be resumed by `resume_trading`.
+    /// Does nothing if kill switch is already active
+    pub async fn activate_kill_switch(&self, cancellation_token: CancellationToken) {
+        if self
+            .is_kill_switch_active
+            .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+            .is_err()
+        {
+            log::warn!("Kill switch is already active");
+            return;
+        }
+
+        let _guard = scopeguard::guard((), |_| {
+            self.is_kill_switch_active.store(false, Ordering::SeqCst)
+        });
+
+        print_info("Kill switch activated");
+
+        self.exchanges.iter().for_each(|x| {
+            self.exchange_blocker.block(
+                x.exchange_account_id,
+                block_reasons::MANUAL_PAUSE,
+                BlockType::Manual,
+            )
+        });
+
+        cancel_opened_orders(&self.exchanges, cancellation_token.clone(), true).await;
+
+        join_all(self.exchanges.iter().map(|x| {
+            let engine_api = EngineApi::new(x.clone());
+            let cancellation_token = cancellation_token.clone();
+            async move { engine_api.close_active_positions(cancellation_token).await }
+        }))
+        .await;
+
+        print_info("Kill switch finished: all orders are canceled and positions are closed");
+    }
+
+    pub fn is_kill_switch_active(&self) -> bool {
+        self.is_kill_switch_active.load(Ordering::SeqCst)
+    }
+
     pub fn get_events_channel(&self) -> broadcast::Receiver<ExchangeEvent> {
         self.exchange_events.get_events_channel()
     }
@@ -218,3 +300,165 @@ impl TradingEngine {
         .expect("Failed to receive message from finished_graceful_shutdown")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use chrono::Utc;
+    use mmb_domain::events::ExchangeEvent;
+    use mmb_domain::market::CurrencyPair;
+    use mmb_domain::order::snapshot::{
+        OrderCreating, OrderExecutionType, OrderHeader, OrderSide, OrderStatus, OrderType,
+    };
+    use mmb_utils::hashmap;
+    use rust_decimal_macros::dec;
+
+    use super::*;
+    use crate::exchanges::general::currency_pair_to_symbol_converter::CurrencyPairToSymbolConverter;
+    use crate::exchanges::general::test_helper::{get_test_exchange_with_blocker, TestClient};
+    use crate::infrastructure::init_lifetime_manager;
+
+    async fn engine_context() -> (Arc<EngineContext>, broadcast::Receiver<ExchangeEvent>) {
+        let lifetime_manager = init_lifetime_manager();
+        let (exchange, exchange_blocker, events_receiver) =
+            get_test_exchange_with_blocker(TestClient::default());
+        let (events_sender, _) = broadcast::channel(10);
+
+        let engine_context = EngineContext::new(
+            CoreSettings::default(),
+            DashMap::from_iter([(exchange.exchange_account_id, exchange)]),
+            ExchangeEvents::new(events_sender),
+            oneshot::channel().0,
+            exchange_blocker,
+            TimeoutManager::new(HashMap::new()),
+            lifetime_manager,
+            BalanceManager::new(CurrencyPairToSymbolConverter::new(hashmap![]), None),
+            EventRecorder::start(None).await.expect("in test"),
+            EngineBuildConfig::new(vec![]),
+        );
+
+        (engine_context, events_receiver)
+    }
+
+    fn exchange(engine_context: &EngineContext) -> Arc<Exchange> {
+        engine_context
+            .exchanges
+            .iter()
+            .next()
+            .expect("in test")
+            .clone()
+    }
+
+    fn stop_loss(exchange_account_id: ExchangeAccountId) -> OrderCreating {
+        OrderCreating {
+            header: OrderHeader::new(
+                "stop_loss".into(),
+                Utc::now(),
+                exchange_account_id,
+                CurrencyPair::from_codes("phb".into(), "btc".into()),
+                OrderType::StopLoss,
+                OrderSide::Buy,
+                dec!(1),
+                OrderExecutionType::None,
+                None,
+                None,
+                "test".to_owned(),
+            ),
+            price: dec!(0),
+            stop_loss_price: dec!(110),
+            trailing_stop_delta: dec!(0),
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn pause_and_resume_trading() {
+        let (engine_context, _events_receiver) = engine_context().await;
+        let exchange_account_id = exchange(&engine_context).exchange_account_id;
+        let exchange_blocker = engine_context.exchange_blocker.clone();
+
+        engine_context
+            .pause_trading(exchange_account_id)
+            .expect("in test");
+        assert!(
+            exchange_blocker.is_blocked_by_reason(exchange_account_id, block_reasons::MANUAL_PAUSE)
+        );
+
+        engine_context
+            .resume_trading(exchange_account_id)
+            .expect("in test");
+        timeout(
+            Duration::from_secs(1),
+            exchange_blocker.wait_unblock(exchange_account_id, CancellationToken::new()),
+        )
+        .await
+        .expect("in test");
+        assert!(!exchange_blocker.is_blocked(exchange_account_id));
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn pause_trading_of_unknown_exchange_account_is_error() {
+        let (engine_context, _events_receiver) = engine_context().await;
+
+        let result = engine_context.pause_trading(ExchangeAccountId::new("unknown", 0));
+
+        assert!(result.is_err());
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn kill_switch_pauses_trading_and_cancels_orders() {
+        let (engine_context, mut events_receiver) = engine_context().await;
+        let exchange = exchange(&engine_context);
+        let exchange_account_id = exchange.exchange_account_id;
+
+        let create_order_handle = tokio::spawn({
+            let exchange = exchange.clone();
+            async move {
+                exchange
+                    .create_emulated_conditional_order(
+                        stop_loss(exchange_account_id),
+                        None,
+                        CancellationToken::new(),
+                    )
+                    .await
+            }
+        });
+        // order is waiting for trigger after the first order event
+        let _ = timeout(Duration::from_secs(1), events_receiver.recv())
+            .await
+            .expect("in test");
+
+        engine_context
+            .activate_kill_switch(CancellationToken::new())
+            .await;
+
+        let created_order = timeout(Duration::from_secs(1), create_order_handle)
+            .await
+            .expect("in test")
+            .expect("in test")
+            .expect("in test");
+        assert_eq!(created_order.status(), OrderStatus::Canceled);
+        assert!(engine_context
+            .exchange_blocker
+            .is_blocked_by_reason(exchange_account_id, block_reasons::MANUAL_PAUSE));
+        assert!(!engine_context.is_kill_switch_active());
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn kill_switch_is_not_activated_while_it_is_active() {
+        let (engine_context, _events_receiver) = engine_context().await;
+        let exchange_account_id = exchange(&engine_context).exchange_account_id;
+        engine_context
+            .is_kill_switch_active
+            .store(true, Ordering::SeqCst);
+
+        engine_context
+            .activate_kill_switch(CancellationToken::new())
+            .await;
+
+        assert!(!engine_context
+            .exchange_blocker
+            .is_blocked(exchange_account_id));
+        assert!(engine_context.is_kill_switch_active());
+    }
+}
@@ -0,0 +1,241 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use itertools::Itertools;
+use once_cell::sync::Lazy;
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+
+use crate::exchanges::exchange_blocker::BlockType;
+use crate::lifecycle::trading_engine::EngineContext;
+use crate::metrics::registry::{
+    Counter, CounterVec, Gauge, GaugeVec, HistogramVec, LATENCY_BUCKETS,
+};
+
+/// Metrics which are updated by engine components while they are working.
+/// Metrics of engine state are collected from `EngineContext` on every scrape
+pub static METRICS: Lazy<EngineMetrics> = Lazy::new(EngineMetrics::new);
+
+pub struct EngineMetrics {
+    is_enabled: AtomicBool,
+    pub(crate) rest_request_duration: HistogramVec,
+    pub(crate) websocket_messages: CounterVec,
+    pub(crate) websocket_reconnects: CounterVec,
+    pub(crate) order_events: CounterVec,
+    pub(crate) order_create_duration: HistogramVec,
+    pub(crate) order_cancel_duration: HistogramVec,
+    pub(crate) order_time_to_fill: HistogramVec,
+    pub(crate) events_channel_lag: GaugeVec,
+    pub(crate) events_channel_skipped: CounterVec,
+}
+
+impl EngineMetrics {
+    fn new() -> Self {
+        Self {
+            is_enabled: AtomicBool::new(false),
+            rest_request_duration: HistogramVec::new(
+                "mmb_rest_request_duration_seconds",
+                "Duration of REST requests to exchange including reading of response body",
+                &["exchange_account_id", "request"],
+                LATENCY_BUCKETS,
+            ),
+            websocket_messages: CounterVec::new(
+                "mmb_websocket_messages_total",
+                "Received websocket text messages. Use rate() to get messages per second",
+                &["exchange_account_id", "role"],
+            ),
+            websocket_reconnects: CounterVec::new(
+                "mmb_websocket_reconnects_total",
+                "Websocket reconnections after connection was lost",
+                &["exchange_account_id"],
+            ),
+            order_events: CounterVec::new(
+                "mmb_order_events_total",
+                "Order events: created, create_failed, filled, completed, canceled, cancel_failed",
+                &["exchange_account_id", "currency_pair", "event"],
+            ),
+            order_create_duration: HistogramVec::new(
+                "mmb_order_create_duration_seconds",
+                "Time from order creation request to confirmation of creation by exchange",
+                &["exchange_account_id", "currency_pair"],
+                LATENCY_BUCKETS,
+            ),
+            order_cancel_duration: HistogramVec::new(
+                "mmb_order_cancel_duration_seconds",
+                "Time from order cancellation request to confirmation of cancellation by exchange",
+                &["exchange_account_id", "currency_pair"],
+                LATENCY_BUCKETS,
+            ),
+            order_time_to_fill: HistogramVec::new(
+                "mmb_order_time_to_fill_seconds",
+                "Time from order creation request to receiving of order fill",
+                &["exchange_account_id", "currency_pair"],
+                LATENCY_BUCKETS,
+            ),
+            events_channel_lag: GaugeVec::new(
+                "mmb_events_channel_lag",
+                "Events of exchange events channel which are not handled by receiver yet",
+                &["receiver"],
+            ),
+            events_channel_skipped: CounterVec::new(
+                "mmb_events_channel_skipped_total",
+                "Events of exchange events channel which are skipped by lagged receiver",
+                &["receiver"],
+            ),
+        }
+    }
+
+    /// Metrics are recorded only if they are configured, so disabled metrics cost nothing on hot paths
+    pub(crate) fn enable(&self) {
+        self.is_enabled.store(true, Ordering::Relaxed);
+    }
+
+    pub(crate) fn is_enabled(&self) -> bool {
+        self.is_enabled.load(Ordering::Relaxed)
+    }
+
+    fn render(&self, out: &mut String) {
+        self.rest_request_duration.render(out);
+        self.websocket_messages.render(out);
+        self.websocket_reconnects.render(out);
+        self.order_events.render(out);
+        self.order_create_duration.render(out);
+        self.order_cancel_duration.render(out);
+        self.order_time_to_fill.render(out);
+        self.events_channel_lag.render(out);
+        self.events_channel_skipped.render(out);
+    }
+}
+
+/// Metrics of exchange events channel for its receiver. Nothing is recorded if metrics are disabled
+pub(crate) struct EventsReceiverMetrics(Option<(Gauge, Counter)>);
+
+impl EventsReceiverMetrics {
+    pub(crate) fn new(receiver_name: &str) -> Self {
+        Self(METRICS.is_enabled().then(|| {
+            (
+                METRICS
+                    .events_channel_lag
+                    .with_label_values(&[receiver_name]),
+                METRICS
+                    .events_channel_skipped
+                    .with_label_values(&[receiver_name]),
+            )
+        }))
+    }
+
+    pub(crate) fn set_lag(&self, not_received_count: usize) {
+        if let Some((lag, _)) = &self.0 {
+            lag.set(not_received_count as f64);
+        }
+    }
+
+    pub(crate) fn add_skipped(&self, skipped_count: u64) {
+        if let Some((_, skipped)) = &self.0 {
+            skipped.inc_by(skipped_count as f64);
+        }
+    }
+}
+
+/// All metrics in Prometheus text format
+pub(crate) fn render_metrics(engine_context: &EngineContext) -> String {
+    let mut out = String::new();
+    METRICS.render(&mut out);
+    render_engine_state(engine_context, &mut out);
+    out
+}
+
+fn render_engine_state(engine_context: &EngineContext, out: &mut String) {
+    let exchange_account_ids = engine_context
+        .exchanges
+        .iter()
+        .map(|x| *x.key())
+        .collect_vec();
+
+    let timeout_manager_utilisation = GaugeVec::new(
+        "mmb_requests_timeout_manager_utilisation",
+        "Part of requests limit of the current period which is used or reserved, from 0 to 1",
+        &["exchange_account_id"],
+    );
+    for exchange_account_id in &exchange_account_ids {
+        timeout_manager_utilisation.set(
+            &[&exchange_account_id.to_string()],
+            engine_context
+                .timeout_manager
+                .get_utilisation(*exchange_account_id),
+        );
+    }
+    timeout_manager_utilisation.render(out);
+
+    let blockers_count = GaugeVec::new(
+        "mmb_exchange_blockers_count",
+        "Active exchange blockers. Trading on exchange account is blocked if it isn't 0",
+        &["exchange_account_id"],
+    );
+    let blocker_remaining_time = GaugeVec::new(
+        "mmb_exchange_blocker_remaining_seconds",
+        "Remaining time of active exchange blocker, -1 for manual blockers",
+        &["exchange_account_id", "reason"],
+    );
+    let active_blockers = engine_context.exchange_blocker.active_blockers();
+    for exchange_account_id in &exchange_account_ids {
+        let count = active_blockers
+            .iter()
+            .filter(|x| x.exchange_account_id == *exchange_account_id)
+            .count();
+        blockers_count.set(&[&exchange_account_id.to_string()], count as f64);
+    }
+    for blocker in &active_blockers {
+        let remaining_time = match blocker.block_type {
+            BlockType::Manual => -1.,
+            BlockType::Timed(remaining) => remaining.as_secs_f64(),
+        };
+        blocker_remaining_time.set(
+            &[
+                &blocker.exchange_account_id.to_string(),
+                &blocker.reason.to_string(),
+            ],
+            remaining_time,
+        );
+    }
+    blockers_count.render(out);
+    blocker_remaining_time.render(out);
+
+    let balance = GaugeVec::new(
+        "mmb_balance",
+        "Balance of currency on exchange account",
+        &["exchange_account_id", "currency_code"],
+    );
+    let position = GaugeVec::new(
+        "mmb_position",
+        "Position of market calculated by order fills, in amount currency",
+        &["exchange_account_id", "currency_pair"],
+    );
+    let balances = engine_context.balance_manager.lock().get_balances();
+    for (exchange_account_id, balances) in balances.balances_by_exchange_id.iter().flatten() {
+        for (currency_code, amount) in balances {
+            balance.set(
+                &[&exchange_account_id.to_string(), &currency_code.to_string()],
+                to_f64(*amount),
+            );
+        }
+    }
+    for (market_account_id, amount) in balances
+        .position_by_fill_amount
+        .iter()
+        .flat_map(|x| x.positions())
+    {
+        position.set(
+            &[
+                &market_account_id.exchange_account_id.to_string(),
+                &market_account_id.currency_pair.to_string(),
+            ],
+            to_f64(*amount),
+        );
+    }
+    balance.render(out);
+    position.render(out);
+}
+
+fn to_f64(value: Decimal) -> f64 {
+    value.to_f64().unwrap_or(f64::NAN)
+}
@@ -0,0 +1,6 @@
+//! Metrics of engine internals exposed on HTTP `/metrics` endpoint in Prometheus text format
+
+pub mod engine_metrics;
+pub(crate) mod order_events;
+pub mod registry;
+pub(crate) mod server;
@@ -0,0 +1,106 @@
+use anyhow::Result;
+use mmb_domain::events::ExchangeEvent;
+use mmb_domain::order::event::{OrderEvent, OrderEventType};
+use mmb_domain::order::snapshot::{OrderSnapshot, OrderStatus};
+use mmb_utils::infrastructure::SpawnFutureFlags;
+use mmb_utils::DateTime;
+use tokio::sync::broadcast;
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::infrastructure::spawn_future;
+use crate::metrics::engine_metrics::{EventsReceiverMetrics, METRICS};
+
+const RECEIVER_NAME: &str = "order_metrics";
+
+/// Update order counters and latencies by order events
+pub(crate) fn start_order_metrics_collector(events_receiver: broadcast::Receiver<ExchangeEvent>) {
+    spawn_future(
+        "Order metrics collector",
+        SpawnFutureFlags::STOP_BY_TOKEN | SpawnFutureFlags::DENY_CANCELLATION,
+        collect_order_metrics(events_receiver),
+    );
+}
+
+async fn collect_order_metrics(
+    mut events_receiver: broadcast::Receiver<ExchangeEvent>,
+) -> Result<()> {
+    let receiver_metrics = EventsReceiverMetrics::new(RECEIVER_NAME);
+    loop {
+        let event = events_receiver.recv().await;
+        receiver_metrics.set_lag(events_receiver.len());
+
+        match event {
+            Ok(ExchangeEvent::OrderEvent(order_event)) => handle_order_event(&order_event),
+            Ok(_) => continue,
+            Err(RecvError::Lagged(skipped_count)) => {
+                log::warn!("Order metrics collector skipped {skipped_count} events");
+                receiver_metrics.add_skipped(skipped_count);
+            }
+            Err(RecvError::Closed) => return Ok(()),
+        }
+    }
+}
+
+fn handle_order_event(order_event: &OrderEvent) {
+    let exchange_account_id = order_event.order.exchange_account_id().to_string();
+    let currency_pair = order_event.order.currency_pair().to_string();
+    let labels = [exchange_account_id.as_str(), currency_pair.as_str()];
+    let inc_event = |event: &str| {
+        METRICS
+            .order_events
+            .inc(&[&exchange_account_id, &currency_pair, event])
+    };
+
+    match &order_event.event_type {
+        OrderEventType::CreateOrderSucceeded => {
+            inc_event("created");
+            let duration = order_event.order.fn_ref(|order| {
+                status_time(order, OrderStatus::Created).map(|x| seconds(order.init_time(), x))
+            });
+            if let Some(duration) = duration {
+                METRICS.order_create_duration.observe(&labels, duration);
+            }
+        }
+        OrderEventType::CreateOrderFailed => inc_event("create_failed"),
+        OrderEventType::OrderFilled { cloned_order } => {
+            inc_event("filled");
+            if let Some(fill_time) = cloned_order.fills.last_fill_received_time() {
+                METRICS
+                    .order_time_to_fill
+                    .observe(&labels, seconds(cloned_order.init_time(), fill_time));
+            }
+        }
+        OrderEventType::OrderCompleted { .. } => inc_event("completed"),
+        OrderEventType::CancelOrderSucceeded => {
+            inc_event("canceled");
+            // there is no cancellation request if order was canceled by exchange
+            let duration = order_event.order.fn_ref(|order| {
+                let canceling_time = status_time(order, OrderStatus::Canceling)?;
+                let canceled_time = status_time(order, OrderStatus::Canceled)?;
+                Some(seconds(canceling_time, canceled_time))
+            });
+            if let Some(duration) = duration {
+                METRICS.order_cancel_duration.observe(&labels, duration);
+            }
+        }
+        OrderEventType::CancelOrderFailed => inc_event("cancel_failed"),
+        OrderEventType::ConditionalOrderWaiting => inc_event("waiting_for_trigger"),
+    }
+}
+
+fn status_time(order: &OrderSnapshot, status: OrderStatus) -> Option<DateTime> {
+    order
+        .status_history
+        .status_changes()
+        .iter()
+        .rev()
+        .find(|x| x.status() == status)
+        .map(|x| x.time())
+}
+
+fn seconds(from: DateTime, to: DateTime) -> f64 {
+    (to - from)
+        .to_std()
+        .map(|x| x.as_secs_f64())
+        .unwrap_or_default()
+}
@@ -0,0 +1,413 @@
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use parking_lot::RwLock;
+
+/// Buckets of latency histograms in seconds
+pub const LATENCY_BUCKETS: &[f64] = &[
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1., 2.5, 5., 10., 30.,
+];
+
+#[derive(Clone, Copy)]
+enum MetricType {
+    Counter,
+    Gauge,
+    Histogram,
+}
+
+impl MetricType {
+    fn as_str(&self) -> &'static str {
+        match self {
+            MetricType::Counter => "counter",
+            MetricType::Gauge => "gauge",
+            MetricType::Histogram => "histogram",
+        }
+    }
+}
+
+/// `f64` which is updated without locks, stored as bits in `AtomicU64`
+#[derive(Default)]
+struct AtomicF64(AtomicU64);
+
+impl AtomicF64 {
+    fn get(&self) -> f64 {
+        f64::from_bits(self.0.load(Ordering::Relaxed))
+    }
+
+    fn set(&self, value: f64) {
+        self.0.store(value.to_bits(), Ordering::Relaxed);
+    }
+
+    fn add(&self, value: f64) {
+        let _ = self
+            .0
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |bits| {
+                Some((f64::from_bits(bits) + value).to_bits())
+            });
+    }
+}
+
+/// Series of metric by label values. Label values are ordered the same as label names.
+/// Series are resolved once into handles, so recording by handle neither locks nor allocates
+struct MetricFamily<T> {
+    name: &'static str,
+    help: &'static str,
+    metric_type: MetricType,
+    label_names: &'static [&'static str],
+    series: RwLock<BTreeMap<Vec<String>, Arc<T>>>,
+}
+
+impl<T> MetricFamily<T> {
+    fn new(
+        name: &'static str,
+        help: &'static str,
+        metric_type: MetricType,
+        label_names: &'static [&'static str],
+    ) -> Self {
+        Self {
+            name,
+            help,
+            metric_type,
+            label_names,
+            series: RwLock::new(BTreeMap::new()),
+        }
+    }
+
+    /// Series with wrong count of label values isn't registered, so values recorded to it are ignored
+    fn get_or_create(&self, label_values: &[&str], create: impl FnOnce() -> T) -> Arc<T> {
+        if label_values.len() != self.label_names.len() {
+            log::error!(
+                "Metric {} expects labels {:?} but got values {label_values:?}",
+                self.name,
+                self.label_names
+            );
+            return Arc::new(create());
+        }
+
+        let key: Vec<_> = label_values.iter().map(|x| x.to_string()).collect();
+        if let Some(series) = self.series.read().get(&key) {
+            return series.clone();
+        }
+
+        self.series
+            .write()
+            .entry(key)
+            .or_insert_with(|| Arc::new(create()))
+            .clone()
+    }
+
+    fn render(&self, out: &mut String, render_value: impl Fn(&mut String, &str, &[String], &T)) {
+        out.push_str(&format!("# HELP {} {}\n", self.name, self.help));
+        out.push_str(&format!(
+            "# TYPE {} {}\n",
+            self.name,
+            self.metric_type.as_str()
+        ));
+        for (label_values, value) in self.series.read().iter() {
+            render_value(out, self.name, label_values, value);
+        }
+    }
+
+    fn labels(&self, label_values: &[String], extra_label: Option<(&str, &str)>) -> String {
+        let labels = self
+            .label_names
+            .iter()
+            .zip(label_values)
+            .map(|(name, value)| (*name, value.as_str()))
+            .chain(extra_label)
+            .map(|(name, value)| format!("{name}=\"{}\"", escape_label_value(value)))
+            .collect::<Vec<_>>();
+
+        match labels.is_empty() {
+            true => String::new(),
+            false => format!("{{{}}}", labels.join(",")),
+        }
+    }
+}
+
+fn escape_label_value(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// Handle of counter series with specific label values
+#[derive(Clone)]
+pub struct Counter(Arc<AtomicF64>);
+
+impl Counter {
+    pub fn inc(&self) {
+        self.inc_by(1.);
+    }
+
+    pub fn inc_by(&self, value: f64) {
+        self.0.add(value);
+    }
+}
+
+/// Monotonically increasing value, e.g. count of events
+pub struct CounterVec(MetricFamily<AtomicF64>);
+
+impl CounterVec {
+    pub fn new(
+        name: &'static str,
+        help: &'static str,
+        label_names: &'static [&'static str],
+    ) -> Self {
+        Self(MetricFamily::new(
+            name,
+            help,
+            MetricType::Counter,
+            label_names,
+        ))
+    }
+
+    /// Handle for recording of frequently updated series
+    pub fn with_label_values(&self, label_values: &[&str]) -> Counter {
+        Counter(self.0.get_or_create(label_values, AtomicF64::default))
+    }
+
+    pub fn inc(&self, label_values: &[&str]) {
+        self.with_label_values(label_values).inc();
+    }
+
+    pub fn inc_by(&self, label_values: &[&str], value: f64) {
+        self.with_label_values(label_values).inc_by(value);
+    }
+
+    pub fn render(&self, out: &mut String) {
+        self.0.render(out, |out, name, label_values, value| {
+            let labels = self.0.labels(label_values, None);
+            out.push_str(&format!("{name}{labels} {}\n", value.get()));
+        });
+    }
+}
+
+/// Handle of gauge series with specific label values
+#[derive(Clone)]
+pub struct Gauge(Arc<AtomicF64>);
+
+impl Gauge {
+    pub fn set(&self, value: f64) {
+        self.0.set(value);
+    }
+}
+
+/// Value which can go up and down, e.g. balance
+pub struct GaugeVec(MetricFamily<AtomicF64>);
+
+impl GaugeVec {
+    pub fn new(
+        name: &'static str,
+        help: &'static str,
+        label_names: &'static [&'static str],
+    ) -> Self {
+        Self(MetricFamily::new(
+            name,
+            help,
+            MetricType::Gauge,
+            label_names,
+        ))
+    }
+
+    /// Handle for recording of frequently updated series
+    pub fn with_label_values(&self, label_values: &[&str]) -> Gauge {
+        Gauge(self.0.get_or_create(label_values, AtomicF64::default))
+    }
+
+    pub fn set(&self, label_values: &[&str], value: f64) {
+        self.with_label_values(label_values).set(value);
+    }
+
+    pub fn render(&self, out: &mut String) {
+        self.0.render(out, |out, name, label_values, value| {
+            let labels = self.0.labels(label_values, None);
+            out.push_str(&format!("{name}{labels} {}\n", value.get()));
+        });
+    }
+}
+
+struct HistogramValue {
+    /// Not cumulative counts of observations by buckets, the last one is `+Inf` bucket
+    bucket_counts: Vec<AtomicU64>,
+    sum: AtomicF64,
+    count: AtomicU64,
+}
+
+impl HistogramValue {
+    fn new(buckets_count: usize) -> Self {
+        Self {
+            bucket_counts: (0..=buckets_count).map(|_| AtomicU64::new(0)).collect(),
+            sum: AtomicF64::default(),
+            count: AtomicU64::new(0),
+        }
+    }
+}
+
+/// Handle of histogram series with specific label values
+#[derive(Clone)]
+pub struct Histogram {
+    buckets: &'static [f64],
+    value: Arc<HistogramValue>,
+}
+
+impl Histogram {
+    pub fn observe(&self, value: f64) {
+        let bucket_index = self
+            .buckets
+            .iter()
+            .position(|&upper_bound| value <= upper_bound)
+            .unwrap_or(self.buckets.len());
+
+        let _ = self.value.bucket_counts[bucket_index].fetch_add(1, Ordering::Relaxed);
+        self.value.sum.add(value);
+        let _ = self.value.count.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Distribution of observed values, e.g. latencies
+pub struct HistogramVec {
+    family: MetricFamily<HistogramValue>,
+    buckets: &'static [f64],
+}
+
+impl HistogramVec {
+    pub fn new(
+        name: &'static str,
+        help: &'static str,
+        label_names: &'static [&'static str],
+        buckets: &'static [f64],
+    ) -> Self {
+        Self {
+            family: MetricFamily::new(name, help, MetricType::Histogram, label_names),
+            buckets,
+        }
+    }
+
+    /// Handle for recording of frequently updated series
+    pub fn with_label_values(&self, label_values: &[&str]) -> Histogram {
+        Histogram {
+            buckets: self.buckets,
+            value: self
+                .family
+                .get_or_create(label_values, || HistogramValue::new(self.buckets.len())),
+        }
+    }
+
+    pub fn observe(&self, label_values: &[&str], value: f64) {
+        self.with_label_values(label_values).observe(value);
+    }
+
+    pub fn render(&self, out: &mut String) {
+        self.family
+            .render(out, |out, name, label_values, histogram| {
+                let mut cumulative_count = 0;
+                let upper_bounds = self
+                    .buckets
+                    .iter()
+                    .map(|x| x.to_string())
+                    .chain(["+Inf".to_owned()]);
+                for (upper_bound, count) in upper_bounds.zip(&histogram.bucket_counts) {
+                    cumulative_count += count.load(Ordering::Relaxed);
+                    let labels = self.family.labels(label_values, Some(("le", &upper_bound)));
+                    out.push_str(&format!("{name}_bucket{labels} {cumulative_count}\n"));
+                }
+
+                let labels = self.family.labels(label_values, None);
+                out.push_str(&format!("{name}_sum{labels} {}\n", histogram.sum.get()));
+                out.push_str(&format!(
+                    "{name}_count{labels} {}\n",
+                    histogram.count.load(Ordering::Relaxed)
+                ));
+            });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_counter() {
+        let counter = CounterVec::new("test_total", "Test counter", &["exchange_account_id"]);
+        counter.inc(&["Binance_0"]);
+        counter.inc_by(&["Binance_0"], 2.);
+        counter.inc(&["Binance_1"]);
+        // wrong count of label values is ignored
+        counter.inc(&[]);
+
+        let mut out = String::new();
+        counter.render(&mut out);
+
+        assert_eq!(
+            out,
+            "# HELP test_total Test counter\n\
+             # TYPE test_total counter\n\
+             test_total{exchange_account_id=\"Binance_0\"} 3\n\
+             test_total{exchange_account_id=\"Binance_1\"} 1\n"
+        );
+    }
+
+    #[test]
+    fn record_by_resolved_handle() {
+        let counter = CounterVec::new("test_total", "Test counter", &["receiver"]);
+        let handle = counter.with_label_values(&["loop"]);
+        handle.inc();
+        counter.inc(&["loop"]);
+        handle.inc_by(2.);
+        // handle of wrong count of label values isn't rendered
+        counter.with_label_values(&["loop", "extra"]).inc();
+
+        let mut out = String::new();
+        counter.render(&mut out);
+
+        assert_eq!(
+            out,
+            "# HELP test_total Test counter\n\
+             # TYPE test_total counter\n\
+             test_total{receiver=\"loop\"} 4\n"
+        );
+    }
+
+    #[test]
+    fn render_gauge_with_escaped_label_value() {
+        let gauge = GaugeVec::new("test", "Test gauge", &["reason"]);
+        gauge.set(&["say \"hi\"\\\n"], 1.5);
+        gauge.set(&["say \"hi\"\\\n"], -0.5);
+
+        let mut out = String::new();
+        gauge.render(&mut out);
+
+        assert_eq!(
+            out,
+            "# HELP test Test gauge\n\
+             # TYPE test gauge\n\
+             test{reason=\"say \\\"hi\\\"\\\\\\n\"} -0.5\n"
+        );
+    }
+
+    #[test]
+    fn render_histogram() {
+        let histogram = HistogramVec::new("test_seconds", "Test histogram", &[], &[0.1, 1.]);
+        histogram.observe(&[], 0.0625);
+        histogram.observe(&[], 0.5);
+        histogram.observe(&[], 1.);
+        histogram.observe(&[], 5.);
+
+        let mut out = String::new();
+        histogram.render(&mut out);
+
+        assert_eq!(
+            out,
+            "# HELP test_seconds Test histogram\n\
+             # TYPE test_seconds histogram\n\
+             test_seconds_bucket{le=\"0.1\"} 1\n\
+             test_seconds_bucket{le=\"1\"} 3\n\
+             test_seconds_bucket{le=\"+Inf\"} 4\n\
+             test_seconds_sum 6.5625\n\
+             test_seconds_count 4\n"
+        );
+    }
+}
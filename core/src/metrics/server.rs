@@ -0,0 +1,77 @@
+use std::convert::Infallible;
+use std::sync::{Arc, Weak};
+
+use anyhow::{Context, Result};
+use hyper::header::{HeaderValue, CONTENT_TYPE};
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Method, Request, Response, Server, StatusCode};
+use mmb_utils::infrastructure::SpawnFutureFlags;
+
+use crate::infrastructure::spawn_future;
+use crate::lifecycle::trading_engine::EngineContext;
+use crate::metrics::engine_metrics::render_metrics;
+use crate::settings::MetricsSettings;
+
+const PROMETHEUS_CONTENT_TYPE: &str = "text/plain; version=0.0.4";
+
+/// Serve metrics on `/metrics` endpoint until engine is stopped
+pub(crate) fn start_metrics_server(
+    settings: &MetricsSettings,
+    engine_context: &Arc<EngineContext>,
+) -> Result<()> {
+    let engine_context_weak = Arc::downgrade(engine_context);
+    let make_service = make_service_fn(move |_| {
+        let engine_context = engine_context_weak.clone();
+        async move {
+            Ok::<_, Infallible>(service_fn(move |request| {
+                handle_request(request, engine_context.clone())
+            }))
+        }
+    });
+
+    let stop_token = engine_context.lifetime_manager.stop_token();
+    let server = Server::try_bind(&settings.address)
+        .with_context(|| format!("Unable to bind metrics server to {}", settings.address))?
+        .serve(make_service)
+        .with_graceful_shutdown(async move { stop_token.when_cancelled().await });
+
+    log::info!("Metrics are served on http://{}/metrics", settings.address);
+
+    spawn_future(
+        "Metrics server",
+        SpawnFutureFlags::STOP_BY_TOKEN | SpawnFutureFlags::DENY_CANCELLATION,
+        async move { server.await.context("Metrics server failed") },
+    );
+
+    Ok(())
+}
+
+async fn handle_request(
+    request: Request<Body>,
+    engine_context: Weak<EngineContext>,
+) -> Result<Response<Body>, Infallible> {
+    let engine_context = match engine_context.upgrade() {
+        Some(engine_context) => engine_context,
+        None => return Ok(response(StatusCode::SERVICE_UNAVAILABLE, String::new())),
+    };
+
+    let response = match (request.method(), request.uri().path()) {
+        (&Method::GET, "/metrics") => {
+            let mut response = response(StatusCode::OK, render_metrics(&engine_context));
+            let _ = response.headers_mut().insert(
+                CONTENT_TYPE,
+                HeaderValue::from_static(PROMETHEUS_CONTENT_TYPE),
+            );
+            response
+        }
+        _ => response(StatusCode::NOT_FOUND, String::new()),
+    };
+
+    Ok(response)
+}
+
+fn response(status: StatusCode, body: String) -> Response<Body> {
+    let mut response = Response::new(Body::from(body));
+    *response.status_mut() = status;
+    response
+}
@@ -1,31 +1,65 @@
-use mmb_domain::market::ExchangeId;
+use mmb_database::impl_event;
+use mmb_domain::market::MarketId;
 use mmb_domain::order::snapshot::Price;
 use mmb_utils::DateTime;
+use serde::{Deserialize, Serialize};
 
-use mmb_domain::market::CurrencyPair;
+pub(crate) const PRICE_SOURCES_TABLE_NAME: &str = "price_sources";
 
+/// Top prices of a market order book used for converting currencies
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub(crate) struct PriceSourceModel {
-    pub _init_time: DateTime,
-    pub _exchange_id: ExchangeId,
-    pub _currency_pair: CurrencyPair,
-    pub _bid: Option<Price>,
-    pub _ask: Option<Price>,
+    pub init_time: DateTime,
+    pub market_id: MarketId,
+    pub bid: Option<Price>,
+    pub ask: Option<Price>,
 }
 
 impl PriceSourceModel {
     pub fn new(
-        _init_time: DateTime,
-        _exchange_id: ExchangeId,
-        _currency_pair: CurrencyPair,
-        _bid: Option<Price>,
-        _ask: Option<Price>,
+        init_time: DateTime,
+        market_id: MarketId,
+        bid: Option<Price>,
+        ask: Option<Price>,
     ) -> Self {
         Self {
-            _init_time,
-            _exchange_id,
-            _currency_pair,
-            _bid,
-            _ask,
+            init_time,
+            market_id,
+            bid,
+            ask,
         }
     }
 }
+
+impl_event!(PriceSourceModel, PRICE_SOURCES_TABLE_NAME);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use mmb_database::postgres_db::events::Event;
+    use mmb_domain::market::CurrencyPair;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn price_source_json_roundtrip() {
+        let price_source = PriceSourceModel::new(
+            Utc::now(),
+            MarketId::new(
+                "Binance".into(),
+                CurrencyPair::from_codes("btc".into(), "usdt".into()),
+            ),
+            Some(dec!(20000)),
+            None,
+        );
+
+        let json = price_source.get_json().expect("in test");
+
+        assert_eq!(json["market_id"]["exchange_id"], "Binance");
+        assert_eq!(json["market_id"]["currency_pair"], "btc/usdt");
+        assert_eq!(
+            serde_json::from_value::<PriceSourceModel>(json).expect("in test"),
+            price_source
+        );
+    }
+}
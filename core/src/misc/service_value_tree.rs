@@ -5,7 +5,7 @@ use crate::service_configuration::configuration_descriptor::{
     ConfigurationDescriptor, ServiceConfigurationKey, ServiceName,
 };
 use mmb_domain::market::{CurrencyCode, CurrencyPair, ExchangeAccountId};
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 use mmb_domain::order::snapshot::Amount;
 use mmb_utils::hashmap;
@@ -25,7 +25,7 @@ pub(crate) type ValueByCurrencyCode = HashMap<CurrencyCode, Amount>;
 ///     NOTE: there is storing all balances by ServiceNames(strategy name),
 ///     that will contain several configuration keys for strategies, next layer is one or more accounts for
 ///     selected ServiceName and here stored CurrencyCodes by CurrencyPairs and amount for every currency code.
-#[derive(Debug, Default, Clone, Serialize)]
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct ServiceValueTree {
     tree: ConfigurationKeyByServiceName,
 }
@@ -193,6 +193,22 @@ impl ServiceValueTree {
         );
     }
 
+    pub fn remove_by_currency_pair(
+        &mut self,
+        service_name: ServiceName,
+        configuration_key: ServiceConfigurationKey,
+        exchange_account_id: ExchangeAccountId,
+        currency_pair: CurrencyPair,
+    ) {
+        if let Some(sub_tree) = self.get_mut_by_exchange_account_id(
+            service_name,
+            configuration_key,
+            exchange_account_id,
+        ) {
+            let _ = sub_tree.remove(&currency_pair);
+        }
+    }
+
     pub fn get_as_balances(&self) -> HashMap<BalanceRequest, Amount> {
         self.tree
             .iter()
@@ -335,6 +351,31 @@ mod test {
         assert_eq!(test_data.0.get_as_balances(), test_data.1);
     }
 
+    #[test]
+    pub fn remove_by_currency_pair() {
+        init_logger_file_named("log.txt");
+        let (mut service_value_tree, mut balances) = get_test_data();
+        let service_name = get_service_names()[0];
+        let configuration_key = get_configuration_keys()[0];
+        let exchange_account_id = get_exchange_account_ids()[0];
+        let currency_pair = get_currency_pairs()[0];
+
+        service_value_tree.remove_by_currency_pair(
+            service_name,
+            configuration_key,
+            exchange_account_id,
+            currency_pair,
+        );
+
+        balances.retain(|request, _| {
+            request.configuration_descriptor
+                != ConfigurationDescriptor::new(service_name, configuration_key)
+                || request.exchange_account_id != exchange_account_id
+                || request.currency_pair != currency_pair
+        });
+        assert_eq!(service_value_tree.get_as_balances(), balances);
+    }
+
     #[test]
     pub fn set() {
         init_logger_file_named("log.txt");
@@ -0,0 +1,122 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use mmb_domain::market::{ExchangeAccountId, MarketAccountId};
+use mmb_domain::order_book::event::OrderBookIntegrityViolation;
+use mmb_utils::DateTime;
+
+/// Tracks integrity of local order books by markets.
+/// Market stays broken after violation until a valid order book event is received for it
+#[derive(Debug, Default)]
+pub struct OrderBookIntegrityTracker {
+    last_update_times: HashMap<MarketAccountId, DateTime>,
+    violations: HashMap<MarketAccountId, OrderBookIntegrityViolation>,
+}
+
+impl OrderBookIntegrityTracker {
+    /// Returns true if order book of market was broken and now it is valid again
+    pub fn on_valid_event(&mut self, market_account_id: MarketAccountId, now: DateTime) -> bool {
+        let _ = self.last_update_times.insert(market_account_id, now);
+        self.violations.remove(&market_account_id).is_some()
+    }
+
+    /// Returns true if order book of market wasn't broken before
+    pub fn on_violation(
+        &mut self,
+        market_account_id: MarketAccountId,
+        violation: OrderBookIntegrityViolation,
+    ) -> bool {
+        self.violations
+            .insert(market_account_id, violation)
+            .is_none()
+    }
+
+    /// Find markets without order book events longer than stale timeout of their exchange
+    /// and mark them as broken
+    pub fn check_staleness(
+        &mut self,
+        now: DateTime,
+        get_stale_timeout: impl Fn(ExchangeAccountId) -> Option<Duration>,
+    ) -> Vec<(MarketAccountId, OrderBookIntegrityViolation)> {
+        let stale_markets: Vec<_> = self
+            .last_update_times
+            .iter()
+            .filter(|(market_account_id, _)| !self.violations.contains_key(market_account_id))
+            .filter_map(|(market_account_id, last_update_time)| {
+                let stale_timeout = get_stale_timeout(market_account_id.exchange_account_id)?;
+                let stale_timeout = chrono::Duration::from_std(stale_timeout).ok()?;
+                (*last_update_time + stale_timeout < now).then(|| {
+                    let violation = OrderBookIntegrityViolation::Stale {
+                        last_update_time: *last_update_time,
+                    };
+                    (*market_account_id, violation)
+                })
+            })
+            .collect();
+
+        for (market_account_id, violation) in &stale_markets {
+            let _ = self.violations.insert(*market_account_id, *violation);
+        }
+
+        stale_markets
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use mmb_domain::market::CurrencyPair;
+    use rust_decimal_macros::dec;
+
+    fn market_account_id() -> MarketAccountId {
+        MarketAccountId::new(
+            ExchangeAccountId::new("Binance", 0),
+            CurrencyPair::from_codes("btc".into(), "usdt".into()),
+        )
+    }
+
+    #[test]
+    fn market_is_restored_by_valid_event() {
+        let mut tracker = OrderBookIntegrityTracker::default();
+        let market_account_id = market_account_id();
+        let violation = OrderBookIntegrityViolation::Crossed {
+            top_ask: dec!(1),
+            top_bid: dec!(2),
+        };
+
+        assert!(!tracker.on_valid_event(market_account_id, Utc::now()));
+        assert!(tracker.on_violation(market_account_id, violation));
+        assert!(!tracker.on_violation(
+            market_account_id,
+            OrderBookIntegrityViolation::UpdateWithoutSnapshot
+        ));
+
+        assert!(tracker.on_valid_event(market_account_id, Utc::now()));
+        assert!(!tracker.on_valid_event(market_account_id, Utc::now()));
+    }
+
+    #[test]
+    fn stale_market_is_reported_once() {
+        let mut tracker = OrderBookIntegrityTracker::default();
+        let market_account_id = market_account_id();
+        let last_update_time = Utc::now();
+        let timeout = |_| Some(Duration::from_secs(10));
+
+        tracker.on_valid_event(market_account_id, last_update_time);
+
+        let now = last_update_time + chrono::Duration::seconds(5);
+        assert!(tracker.check_staleness(now, timeout).is_empty());
+
+        let now = last_update_time + chrono::Duration::seconds(11);
+        assert_eq!(
+            tracker.check_staleness(now, timeout),
+            vec![(
+                market_account_id,
+                OrderBookIntegrityViolation::Stale { last_update_time }
+            )]
+        );
+        assert!(tracker.check_staleness(now, timeout).is_empty());
+        assert!(tracker.check_staleness(now, |_| None).is_empty());
+    }
+}
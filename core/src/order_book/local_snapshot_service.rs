@@ -1,5 +1,6 @@
 use mmb_domain::market::{MarketAccountId, MarketId};
 use mmb_domain::order_book::event;
+use mmb_domain::order_book::event::OrderBookIntegrityViolation;
 use mmb_domain::order_book::local_order_book_snapshot::LocalOrderBookSnapshot;
 use mmb_utils::infrastructure::WithExpect;
 use std::collections::HashMap;
@@ -47,6 +48,33 @@ impl LocalSnapshotsService {
             }
         }
     }
+
+    /// Same as `update` but also checks integrity of order book after it.
+    /// Crossed snapshot is kept, so caller decides whether to remove it and wait for a new snapshot
+    pub fn update_with_integrity_check(
+        &mut self,
+        event: event::OrderBookEvent,
+    ) -> Result<MarketAccountId, OrderBookIntegrityViolation> {
+        let market_account_id = self
+            .update(event)
+            .ok_or(OrderBookIntegrityViolation::UpdateWithoutSnapshot)?;
+
+        let market_id = market_account_id.market_id();
+        let snapshot = self.get_snapshot_expected(market_id);
+        if let (Some((top_ask, _)), Some((top_bid, _))) =
+            (snapshot.get_top_ask(), snapshot.get_top_bid())
+        {
+            if top_ask <= top_bid {
+                return Err(OrderBookIntegrityViolation::Crossed { top_ask, top_bid });
+            }
+        }
+
+        Ok(market_account_id)
+    }
+
+    pub fn remove_snapshot(&mut self, market_id: MarketId) {
+        let _ = self.local_snapshots.remove(&market_id);
+    }
 }
 
 impl Default for LocalSnapshotsService {
@@ -231,4 +259,50 @@ mod tests {
             None
         );
     }
+
+    #[test]
+    fn crossed_update_is_rejected() {
+        let mut snapshot_service = LocalSnapshotsService::default();
+        let currency_pair = CurrencyPair::from_codes("base".into(), "quote".into());
+
+        let snapshot_event = create_order_book_event_for_tests(
+            "does_not_matter".into(),
+            currency_pair,
+            event::EventType::Snapshot,
+            order_book_data![
+                dec!(3.0) => dec!(1.0),
+                ;
+                dec!(2.0) => dec!(1.0),
+            ],
+        );
+        let market_account_id = snapshot_service
+            .update_with_integrity_check(snapshot_event)
+            .expect("in test");
+
+        let crossed_event = create_order_book_event_for_tests(
+            "does_not_matter".into(),
+            currency_pair,
+            event::EventType::Update,
+            order_book_data![
+                ;
+                dec!(3.5) => dec!(1.0),
+            ],
+        );
+        assert_eq!(
+            snapshot_service.update_with_integrity_check(crossed_event.clone()),
+            Err(OrderBookIntegrityViolation::Crossed {
+                top_ask: dec!(3.0),
+                top_bid: dec!(3.5)
+            })
+        );
+        assert!(snapshot_service
+            .get_snapshot(market_account_id.market_id())
+            .is_some());
+
+        snapshot_service.remove_snapshot(market_account_id.market_id());
+        assert_eq!(
+            snapshot_service.update_with_integrity_check(crossed_event),
+            Err(OrderBookIntegrityViolation::UpdateWithoutSnapshot)
+        );
+    }
 }
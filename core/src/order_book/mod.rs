@@ -1 +1,2 @@
+pub mod integrity;
 pub mod local_snapshot_service;
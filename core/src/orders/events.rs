@@ -0,0 +1,145 @@
+use mmb_database::impl_event;
+use mmb_domain::market::{CurrencyPair, ExchangeAccountId};
+use mmb_domain::order::event::OrderEventType;
+use mmb_domain::order::fill::OrderFill;
+use mmb_domain::order::snapshot::{ClientOrderId, ExchangeOrderId, OrderSide, OrderSnapshot};
+use serde::Serialize;
+
+pub const ORDERS_TABLE_NAME: &str = "orders";
+pub const ORDER_FILLS_TABLE_NAME: &str = "order_fills";
+
+/// State of an order at the moment of an order event.
+/// Status history and fills are saved as parts of the order snapshot.
+#[derive(Debug, Clone, Serialize)]
+pub struct OrderEventRecord<'a> {
+    pub event_type: &'static str,
+    pub order: &'a OrderSnapshot,
+}
+
+impl<'a> OrderEventRecord<'a> {
+    pub fn new(event_type: &OrderEventType, order: &'a OrderSnapshot) -> Self {
+        Self {
+            event_type: event_type_name(event_type),
+            order,
+        }
+    }
+}
+
+impl_event!(OrderEventRecord<'_>, ORDERS_TABLE_NAME);
+
+/// Single fill of an order with identifiers of the order it belongs to
+#[derive(Debug, Clone, Serialize)]
+pub struct OrderFillRecord<'a> {
+    pub exchange_account_id: ExchangeAccountId,
+    pub currency_pair: CurrencyPair,
+    pub client_order_id: &'a ClientOrderId,
+    pub exchange_order_id: Option<&'a ExchangeOrderId>,
+    pub side: OrderSide,
+    pub fill: &'a OrderFill,
+}
+
+impl<'a> OrderFillRecord<'a> {
+    pub fn new(order: &'a OrderSnapshot, fill: &'a OrderFill) -> Self {
+        Self {
+            exchange_account_id: order.header.exchange_account_id,
+            currency_pair: order.header.currency_pair,
+            client_order_id: &order.header.client_order_id,
+            exchange_order_id: order.props.exchange_order_id.as_ref(),
+            side: order.header.side,
+            fill,
+        }
+    }
+}
+
+impl_event!(OrderFillRecord<'_>, ORDER_FILLS_TABLE_NAME);
+
+fn event_type_name(event_type: &OrderEventType) -> &'static str {
+    match event_type {
+        OrderEventType::CreateOrderSucceeded => "CreateOrderSucceeded",
+        OrderEventType::CreateOrderFailed => "CreateOrderFailed",
+        OrderEventType::OrderFilled { .. } => "OrderFilled",
+        OrderEventType::OrderCompleted { .. } => "OrderCompleted",
+        OrderEventType::CancelOrderSucceeded => "CancelOrderSucceeded",
+        OrderEventType::CancelOrderFailed => "CancelOrderFailed",
+        OrderEventType::ConditionalOrderWaiting => "ConditionalOrderWaiting",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use mmb_database::postgres_db::events::Event;
+    use mmb_domain::order::fill::OrderFillType;
+    use mmb_domain::order::snapshot::{OrderFillRole, OrderType};
+    use rust_decimal_macros::dec;
+    use uuid::Uuid;
+
+    fn test_order() -> OrderSnapshot {
+        let mut order = OrderSnapshot::with_params(
+            "test_client_order_id".into(),
+            OrderType::Limit,
+            None,
+            ExchangeAccountId::new("Binance", 0),
+            CurrencyPair::from_codes("btc".into(), "usdt".into()),
+            dec!(10),
+            dec!(1),
+            OrderSide::Buy,
+            None,
+            "test",
+        );
+        order.props.exchange_order_id = Some("test_exchange_order_id".into());
+        order
+    }
+
+    #[test]
+    fn order_event_record_json() {
+        let order = test_order();
+
+        let json = OrderEventRecord::new(&OrderEventType::CancelOrderSucceeded, &order)
+            .get_json()
+            .expect("in test");
+
+        assert_eq!(json["event_type"], "CancelOrderSucceeded");
+        assert_eq!(
+            json["order"]["header"]["client_order_id"],
+            "test_client_order_id"
+        );
+        assert_eq!(json["order"]["header"]["exchange_account_id"], "Binance_0");
+    }
+
+    #[test]
+    fn order_fill_record_json() {
+        let order = test_order();
+        let fill = OrderFill::new(
+            Uuid::new_v4(),
+            None,
+            Utc::now(),
+            OrderFillType::UserTrade,
+            None,
+            dec!(10),
+            dec!(0.5),
+            dec!(5),
+            OrderFillRole::Maker,
+            "usdt".into(),
+            dec!(0.01),
+            dec!(0),
+            "usdt".into(),
+            dec!(0.01),
+            dec!(0.01),
+            false,
+            None,
+            Some(OrderSide::Buy),
+        );
+
+        let json = OrderFillRecord::new(&order, &fill)
+            .get_json()
+            .expect("in test");
+
+        assert_eq!(json["exchange_account_id"], "Binance_0");
+        assert_eq!(json["currency_pair"], "btc/usdt");
+        assert_eq!(json["client_order_id"], "test_client_order_id");
+        assert_eq!(json["exchange_order_id"], "test_exchange_order_id");
+        assert_eq!(json["fill"]["amount"], "0.5");
+    }
+}
@@ -1 +1,2 @@
 pub mod buffered_fills;
+pub mod events;
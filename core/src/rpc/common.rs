@@ -7,6 +7,7 @@ use jsonrpc_ipc_server::{Server, ServerBuilder};
 use mmb_rpc::rest_api::{server_side_error, ErrorCode, MmbRpc, IPC_ADDRESS};
 use mmb_utils::infrastructure::SpawnFutureFlags;
 use parking_lot::Mutex;
+use tokio::runtime::Handle;
 use tokio::sync::{mpsc, oneshot};
 
 use crate::{
@@ -93,7 +94,11 @@ pub(super) struct RpcServerAndChannels<T> {
 pub(super) fn crate_server_and_channels<T>(rpc: impl MmbRpc) -> RpcServerAndChannels<T> {
     let (work_finished_sender, work_finished_receiver) = oneshot::channel();
     let io = build_io(rpc);
-    let builder = ServerBuilder::new(io);
+    let mut builder = ServerBuilder::new(io);
+    // RPC methods should be executed on the runtime of trading engine to be able to use its services
+    if let Ok(handle) = Handle::try_current() {
+        builder = builder.event_loop_executor(handle);
+    }
     let server = builder.start(IPC_ADDRESS).expect("Couldn't open socket");
 
     RpcServerAndChannels {
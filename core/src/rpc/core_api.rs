@@ -3,9 +3,13 @@ use parking_lot::Mutex;
 use tokio::sync::{mpsc, oneshot};
 
 use crate::lifecycle::app_lifetime_manager::{ActionAfterGracefulShutdown, AppLifetimeManager};
+use crate::lifecycle::settings_reload::SettingsReloader;
 use std::sync::Arc;
 
-use crate::{lifecycle::trading_engine::Service, statistic_service::StatisticService};
+use crate::{
+    lifecycle::trading_engine::{EngineContext, Service},
+    statistic_service::StatisticService,
+};
 
 use super::{
     common::{
@@ -27,6 +31,8 @@ impl CoreApi {
         lifetime_manager: Arc<AppLifetimeManager>,
         engine_settings: String,
         statistics: Arc<StatisticService>,
+        engine_context: Arc<EngineContext>,
+        settings_reloader: Arc<dyn SettingsReloader>,
     ) -> Result<Arc<Self>> {
         let (server_stopper_tx, server_stopper_rx) =
             mpsc::channel::<ActionAfterGracefulShutdown>(10);
@@ -39,6 +45,8 @@ impl CoreApi {
             server_stopper_tx.clone(),
             statistics,
             engine_settings,
+            engine_context,
+            settings_reloader,
         ));
 
         spawn_server_stopping_action(
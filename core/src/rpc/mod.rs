@@ -3,3 +3,4 @@ pub mod config_waiter;
 pub mod core_api;
 pub mod rpc_impl;
 pub mod rpc_impl_no_config;
+mod views;
@@ -1,23 +1,46 @@
-use jsonrpc_core::Result;
+use itertools::Itertools;
+use jsonrpc_core::{BoxFuture, Result};
+use mmb_domain::market::ExchangeAccountId;
+use mmb_domain::order::snapshot::ClientOrderId;
 use mmb_rpc::rest_api::server_side_error;
 use mmb_rpc::rest_api::MmbRpc;
+use mmb_utils::cancellation_token::CancellationToken;
+use mmb_utils::infrastructure::SpawnFutureFlags;
 use parking_lot::Mutex;
+use serde::Serialize;
 use tokio::sync::mpsc;
 
 use std::sync::Arc;
 
+use crate::exchanges::exchange_blocker::BlockType;
+use crate::infrastructure::spawn_future;
 use crate::lifecycle::app_lifetime_manager::ActionAfterGracefulShutdown;
+use crate::lifecycle::settings_reload::{SettingsReloadOutcome, SettingsReloader};
+use crate::lifecycle::trading_engine::EngineContext;
+use crate::settings::ExchangeSettings;
 use crate::statistic_service::StatisticService;
 use mmb_rpc::rest_api::ErrorCode;
 
+use super::views::{BalancesView, OrderBookTopView, OrderView, PositionView};
+
 use super::common::send_restart;
 use super::common::send_stop;
 use super::common::set_config;
 
+#[derive(Serialize)]
+struct BlockerInfo {
+    exchange_account_id: String,
+    reason: String,
+    block_type: &'static str,
+    remaining_time_ms: Option<u128>,
+}
+
 pub struct RpcImpl {
     server_stopper_tx: Arc<Mutex<Option<mpsc::Sender<ActionAfterGracefulShutdown>>>>,
     statistics: Arc<StatisticService>,
-    engine_settings: String,
+    engine_settings: Mutex<String>,
+    engine_context: Arc<EngineContext>,
+    settings_reloader: Arc<dyn SettingsReloader>,
 }
 
 impl RpcImpl {
@@ -25,13 +48,31 @@ impl RpcImpl {
         server_stopper_tx: Arc<Mutex<Option<mpsc::Sender<ActionAfterGracefulShutdown>>>>,
         statistics: Arc<StatisticService>,
         engine_settings: String,
+        engine_context: Arc<EngineContext>,
+        settings_reloader: Arc<dyn SettingsReloader>,
     ) -> Self {
         Self {
             server_stopper_tx,
             statistics,
-            engine_settings,
+            engine_settings: Mutex::new(engine_settings),
+            engine_context,
+            settings_reloader,
         }
     }
+
+    fn to_json(value: &impl Serialize) -> Result<String> {
+        serde_json::to_string(value).map_err(|err| {
+            log::warn!("Failed to serialize RPC response: {err}");
+            server_side_error(ErrorCode::FailedToSerializeResponse)
+        })
+    }
+
+    fn parse_exchange_account_id(exchange_account_id: &str) -> Result<ExchangeAccountId> {
+        exchange_account_id.parse().map_err(|err| {
+            log::warn!("Failed to parse exchange account id {exchange_account_id}: {err:?}");
+            server_side_error(ErrorCode::UnknownExchangeAccount)
+        })
+    }
 }
 
 impl MmbRpc for RpcImpl {
@@ -44,13 +85,29 @@ impl MmbRpc for RpcImpl {
     }
 
     fn get_config(&self) -> Result<String> {
-        Ok(self.engine_settings.clone())
+        Ok(self.engine_settings.lock().clone())
     }
 
     fn set_config(&self, settings: String) -> Result<String> {
-        set_config(settings)?;
-        send_restart(self.server_stopper_tx.clone())?;
-        Ok("Config was successfully updated. Trading engine will be restarted".into())
+        let outcome = self.settings_reloader.reload(&settings).map_err(|err| {
+            log::warn!("Error while trying to apply new config in set_config endpoint: {err:?}");
+            server_side_error(ErrorCode::FailedToApplyNewConfig)
+        })?;
+
+        match outcome {
+            SettingsReloadOutcome::Applied => {
+                // running settings are already changed, so they are returned by get_config even if they aren't saved
+                *self.engine_settings.lock() = settings.clone();
+                set_config(settings)?;
+                Ok("Config was successfully updated and applied without restart".into())
+            }
+            SettingsReloadOutcome::RestartRequired => {
+                // nothing is applied, so trading engine isn't restarted if settings aren't saved
+                set_config(settings)?;
+                send_restart(self.server_stopper_tx.clone())?;
+                Ok("Config was successfully updated. Trading engine will be restarted".into())
+            }
+        }
     }
 
     fn stats(&self) -> Result<String> {
@@ -66,4 +123,230 @@ impl MmbRpc for RpcImpl {
 
         Ok(json_statistic)
     }
+
+    fn pause(&self, exchange_account_id: String) -> Result<String> {
+        let exchange_account_id = Self::parse_exchange_account_id(&exchange_account_id)?;
+        self.engine_context
+            .pause_trading(exchange_account_id)
+            .map_err(|err| {
+                log::warn!("Failed to pause trading: {err:?}");
+                server_side_error(ErrorCode::UnknownExchangeAccount)
+            })?;
+
+        Ok(format!("Trading on {exchange_account_id} is paused"))
+    }
+
+    fn resume(&self, exchange_account_id: String) -> Result<String> {
+        let exchange_account_id = Self::parse_exchange_account_id(&exchange_account_id)?;
+        self.engine_context
+            .resume_trading(exchange_account_id)
+            .map_err(|err| {
+                log::warn!("Failed to resume trading: {err:?}");
+                server_side_error(ErrorCode::UnknownExchangeAccount)
+            })?;
+
+        Ok(format!("Trading on {exchange_account_id} is resumed"))
+    }
+
+    fn blockers(&self) -> Result<String> {
+        let blockers: Vec<_> = self
+            .engine_context
+            .exchange_blocker
+            .active_blockers()
+            .into_iter()
+            .map(|blocker| {
+                let (block_type, remaining_time_ms) = match blocker.block_type {
+                    BlockType::Manual => ("Manual", None),
+                    BlockType::Timed(remaining) => ("Timed", Some(remaining.as_millis())),
+                };
+                BlockerInfo {
+                    exchange_account_id: blocker.exchange_account_id.to_string(),
+                    reason: blocker.reason.to_string(),
+                    block_type,
+                    remaining_time_ms,
+                }
+            })
+            .collect();
+
+        Self::to_json(&blockers)
+    }
+
+    fn kill_switch(&self) -> Result<String> {
+        if self.engine_context.is_kill_switch_active() {
+            return Ok("Kill switch is already active".into());
+        }
+
+        let engine_context = self.engine_context.clone();
+        spawn_future("Kill switch", SpawnFutureFlags::STOP_BY_TOKEN, async move {
+            engine_context
+                .activate_kill_switch(CancellationToken::new())
+                .await;
+            Ok(())
+        });
+
+        Ok("Kill switch is activated: trading is paused, orders are being canceled and positions closed".into())
+    }
+
+    fn orders(&self) -> Result<String> {
+        let orders: Vec<_> = self
+            .engine_context
+            .exchanges
+            .iter()
+            .flat_map(|exchange| {
+                exchange
+                    .orders
+                    .not_finished
+                    .iter()
+                    .map(|order| order.fn_ref(|x| OrderView::from(x)))
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+
+        Self::to_json(&orders)
+    }
+
+    fn balances(&self) -> Result<String> {
+        let balances = self.engine_context.balance_manager.lock().get_balances();
+        Self::to_json(&BalancesView::from(&balances))
+    }
+
+    fn positions(&self) -> Result<String> {
+        // positions are received only for exchange accounts with derivative markets
+        let derivative_exchange_account_ids: Vec<_> = self
+            .engine_context
+            .exchanges
+            .iter()
+            .filter(|exchange| exchange.symbols.iter().any(|x| x.is_derivative))
+            .map(|exchange| exchange.exchange_account_id)
+            .sorted_by_key(|x| x.to_string())
+            .collect();
+
+        let balance_manager = self.engine_context.balance_manager.lock();
+        let mut positions = Vec::new();
+        for exchange_account_id in derivative_exchange_account_ids {
+            let last_positions = balance_manager
+                .get_last_positions(exchange_account_id)
+                .ok_or_else(|| {
+                    log::warn!("Positions of {exchange_account_id} aren't received yet");
+                    server_side_error(ErrorCode::PositionsAreNotReceived)
+                })?;
+
+            positions.extend(
+                last_positions
+                    .iter()
+                    .filter(|x| !x.position.is_zero())
+                    .map(|x| PositionView::new(exchange_account_id, x)),
+            );
+        }
+
+        Self::to_json(&positions)
+    }
+
+    fn order_book_top(&self) -> Result<String> {
+        let order_book_tops: Vec<_> = self
+            .engine_context
+            .exchanges
+            .iter()
+            .flat_map(|exchange| {
+                exchange
+                    .order_book_top
+                    .iter()
+                    .map(|x| {
+                        OrderBookTopView::new(exchange.exchange_account_id, *x.key(), x.value())
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+
+        Self::to_json(&order_book_tops)
+    }
+
+    fn cancel_order(
+        &self,
+        exchange_account_id: String,
+        client_order_id: String,
+    ) -> BoxFuture<Result<String>> {
+        let engine_context = self.engine_context.clone();
+        Box::pin(async move {
+            let exchange_account_id = Self::parse_exchange_account_id(&exchange_account_id)?;
+            let exchange = engine_context
+                .exchanges
+                .get(&exchange_account_id)
+                .map(|x| x.value().clone())
+                .ok_or_else(|| server_side_error(ErrorCode::UnknownExchangeAccount))?;
+
+            let client_order_id = ClientOrderId::from(client_order_id.as_str());
+            let order = exchange
+                .orders
+                .cache_by_client_id
+                .get(&client_order_id)
+                .map(|x| x.value().clone())
+                .ok_or_else(|| server_side_error(ErrorCode::UnknownOrder))?;
+
+            log::info!(
+                "Canceling order {client_order_id} on {exchange_account_id} by control panel"
+            );
+            exchange
+                .wait_cancel_order(order, None, true, CancellationToken::new())
+                .await
+                .map_err(|err| {
+                    log::warn!("Failed to cancel order {client_order_id}: {err:?}");
+                    server_side_error(ErrorCode::FailedToCancelOrder)
+                })?;
+
+            Ok(format!(
+                "Order {client_order_id} on {exchange_account_id} is canceled"
+            ))
+        })
+    }
+
+    fn add_exchange_account(&self, settings: String) -> BoxFuture<Result<String>> {
+        let engine_context = self.engine_context.clone();
+        Box::pin(async move {
+            let exchange_settings = toml_edit::de::from_str::<ExchangeSettings>(&settings)
+                .map_err(|err| {
+                    log::warn!("Failed to parse settings of exchange account: {err:?}");
+                    server_side_error(ErrorCode::FailedToAddExchangeAccount)
+                })?;
+            let exchange_account_id = exchange_settings.exchange_account_id;
+
+            engine_context
+                .add_exchange_account(exchange_settings)
+                .await
+                .map_err(|err| {
+                    log::warn!("Failed to add exchange account {exchange_account_id}: {err:?}");
+                    server_side_error(ErrorCode::FailedToAddExchangeAccount)
+                })?;
+
+            Ok(format!("Exchange account {exchange_account_id} is added"))
+        })
+    }
+
+    fn remove_exchange_account(&self, exchange_account_id: String) -> BoxFuture<Result<String>> {
+        let engine_context = self.engine_context.clone();
+        let settings_reloader = self.settings_reloader.clone();
+        Box::pin(async move {
+            let exchange_account_id = Self::parse_exchange_account_id(&exchange_account_id)?;
+            if !engine_context.exchanges.contains_key(&exchange_account_id) {
+                return Err(server_side_error(ErrorCode::UnknownExchangeAccount));
+            }
+            if settings_reloader.is_exchange_account_traded(exchange_account_id) {
+                log::warn!("Unable to remove exchange account {exchange_account_id} because strategy instances trade on it");
+                return Err(server_side_error(ErrorCode::FailedToRemoveExchangeAccount));
+            }
+
+            engine_context
+                .remove_exchange_account(
+                    exchange_account_id,
+                    engine_context.lifetime_manager.stop_token(),
+                )
+                .await
+                .map_err(|err| {
+                    log::warn!("Failed to remove exchange account {exchange_account_id}: {err:?}");
+                    server_side_error(ErrorCode::FailedToRemoveExchangeAccount)
+                })?;
+
+            Ok(format!("Exchange account {exchange_account_id} is removed"))
+        })
+    }
 }
@@ -1,4 +1,5 @@
-use jsonrpc_core::Result;
+use futures::future;
+use jsonrpc_core::{BoxFuture, Result};
 use mmb_rpc::rest_api::MmbRpc;
 use mmb_utils::send_expected::SendExpectedByRef;
 use parking_lot::Mutex;
@@ -52,4 +53,52 @@ impl MmbRpc for RpcImplNoConfig {
     fn stats(&self) -> Result<String> {
         Ok(CONFIG_IS_NOT_SET.into())
     }
+
+    fn pause(&self, _exchange_account_id: String) -> Result<String> {
+        Ok(CONFIG_IS_NOT_SET.into())
+    }
+
+    fn resume(&self, _exchange_account_id: String) -> Result<String> {
+        Ok(CONFIG_IS_NOT_SET.into())
+    }
+
+    fn blockers(&self) -> Result<String> {
+        Ok(CONFIG_IS_NOT_SET.into())
+    }
+
+    fn kill_switch(&self) -> Result<String> {
+        Ok(CONFIG_IS_NOT_SET.into())
+    }
+
+    fn orders(&self) -> Result<String> {
+        Ok(CONFIG_IS_NOT_SET.into())
+    }
+
+    fn balances(&self) -> Result<String> {
+        Ok(CONFIG_IS_NOT_SET.into())
+    }
+
+    fn positions(&self) -> Result<String> {
+        Ok(CONFIG_IS_NOT_SET.into())
+    }
+
+    fn order_book_top(&self) -> Result<String> {
+        Ok(CONFIG_IS_NOT_SET.into())
+    }
+
+    fn cancel_order(
+        &self,
+        _exchange_account_id: String,
+        _client_order_id: String,
+    ) -> BoxFuture<Result<String>> {
+        Box::pin(future::ok(CONFIG_IS_NOT_SET.into()))
+    }
+
+    fn add_exchange_account(&self, _settings: String) -> BoxFuture<Result<String>> {
+        Box::pin(future::ok(CONFIG_IS_NOT_SET.into()))
+    }
+
+    fn remove_exchange_account(&self, _exchange_account_id: String) -> BoxFuture<Result<String>> {
+        Box::pin(future::ok(CONFIG_IS_NOT_SET.into()))
+    }
 }
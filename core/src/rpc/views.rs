@@ -0,0 +1,460 @@
+//! JSON schemas of inspection responses of RPC API.
+//! Fields shouldn't be renamed or removed because external dashboards depend on them
+
+use itertools::Itertools;
+use mmb_domain::order::fill::{OrderFill, OrderFillType};
+use mmb_domain::order::snapshot::{
+    Amount, OrderFillRole, OrderRole, OrderSide, OrderSnapshot, OrderStatus, OrderStatusChange,
+    OrderType, Price,
+};
+use mmb_domain::position::DerivativePosition;
+use mmb_utils::DateTime;
+use rust_decimal::Decimal;
+use serde::Serialize;
+
+use crate::balance::manager::balance_reservation::BalanceReservation;
+use crate::balance::manager::balances::Balances;
+use crate::exchanges::general::exchange::{OrderBookTop, PriceLevel};
+use crate::misc::service_value_tree::ServiceValueTree;
+use mmb_domain::market::{CurrencyPair, ExchangeAccountId};
+use mmb_domain::order::snapshot::ReservationId;
+
+#[derive(Serialize)]
+pub(super) struct OrderView {
+    exchange_account_id: String,
+    client_order_id: String,
+    exchange_order_id: Option<String>,
+    currency_pair: String,
+    order_type: OrderType,
+    side: OrderSide,
+    role: Option<OrderRole>,
+    price: Option<Price>,
+    amount: Amount,
+    filled_amount: Amount,
+    status: OrderStatus,
+    init_time: DateTime,
+    fills: Vec<OrderFillView>,
+    status_history: Vec<OrderStatusChangeView>,
+}
+
+impl From<&OrderSnapshot> for OrderView {
+    fn from(order: &OrderSnapshot) -> Self {
+        Self {
+            exchange_account_id: order.header.exchange_account_id.to_string(),
+            client_order_id: order.header.client_order_id.to_string(),
+            exchange_order_id: order
+                .props
+                .exchange_order_id
+                .as_ref()
+                .map(|x| x.to_string()),
+            currency_pair: order.header.currency_pair.to_string(),
+            order_type: order.header.order_type,
+            side: order.header.side,
+            role: order.props.role,
+            price: order.props.raw_price,
+            amount: order.header.amount,
+            filled_amount: order.fills.filled_amount,
+            status: order.props.status,
+            init_time: order.header.init_time,
+            fills: order.fills.fills.iter().map(OrderFillView::from).collect(),
+            status_history: order
+                .status_history
+                .status_changes()
+                .iter()
+                .map(OrderStatusChangeView::from)
+                .collect(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct OrderFillView {
+    trade_id: Option<String>,
+    receive_time: DateTime,
+    fill_type: OrderFillType,
+    role: OrderFillRole,
+    price: Price,
+    amount: Amount,
+    commission_currency_code: String,
+    commission_amount: Amount,
+}
+
+impl From<&OrderFill> for OrderFillView {
+    fn from(fill: &OrderFill) -> Self {
+        Self {
+            trade_id: fill.trade_id().map(|x| x.to_string()),
+            receive_time: fill.receive_time(),
+            fill_type: fill.fill_type(),
+            role: fill.role(),
+            price: fill.price(),
+            amount: fill.amount(),
+            commission_currency_code: fill.commission_currency_code().to_string(),
+            commission_amount: fill.commission_amount(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct OrderStatusChangeView {
+    status: OrderStatus,
+    time: DateTime,
+}
+
+impl From<&OrderStatusChange> for OrderStatusChangeView {
+    fn from(status_change: &OrderStatusChange) -> Self {
+        Self {
+            status: status_change.status(),
+            time: status_change.time(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub(super) struct BalancesView {
+    init_time: DateTime,
+    balances: Vec<BalanceView>,
+    virtual_diff_balances: Vec<ServiceValueView>,
+    reserved_amounts: Vec<ServiceValueView>,
+    reservations: Vec<ReservationView>,
+}
+
+impl From<&Balances> for BalancesView {
+    fn from(balances: &Balances) -> Self {
+        let balances_by_exchange_id = balances
+            .balances_by_exchange_id
+            .iter()
+            .flatten()
+            .flat_map(|(exchange_account_id, balances)| {
+                balances.iter().map(|(currency_code, amount)| BalanceView {
+                    exchange_account_id: exchange_account_id.to_string(),
+                    currency_code: currency_code.to_string(),
+                    amount: *amount,
+                })
+            })
+            .sorted_by(|a, b| {
+                (&a.exchange_account_id, &a.currency_code)
+                    .cmp(&(&b.exchange_account_id, &b.currency_code))
+            })
+            .collect();
+
+        Self {
+            init_time: balances.init_time,
+            balances: balances_by_exchange_id,
+            virtual_diff_balances: ServiceValueView::from_tree(&balances.virtual_diff_balances),
+            reserved_amounts: ServiceValueView::from_tree(&balances.reserved_amount),
+            reservations: balances
+                .balance_reservations_by_reservation_id
+                .iter()
+                .flatten()
+                .map(|(reservation_id, reservation)| {
+                    ReservationView::new(*reservation_id, reservation)
+                })
+                .sorted_by_key(|x| x.reservation_id)
+                .collect(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct BalanceView {
+    exchange_account_id: String,
+    currency_code: String,
+    amount: Amount,
+}
+
+#[derive(Serialize)]
+struct ServiceValueView {
+    service_name: String,
+    service_configuration_key: String,
+    exchange_account_id: String,
+    currency_pair: String,
+    currency_code: String,
+    value: Amount,
+}
+
+impl ServiceValueView {
+    fn from_tree(tree: &Option<ServiceValueTree>) -> Vec<Self> {
+        tree.iter()
+            .flat_map(|x| x.get_as_balances())
+            .map(|(request, value)| Self {
+                service_name: request.configuration_descriptor.service_name.to_string(),
+                service_configuration_key: request
+                    .configuration_descriptor
+                    .service_configuration_key
+                    .to_string(),
+                exchange_account_id: request.exchange_account_id.to_string(),
+                currency_pair: request.currency_pair.to_string(),
+                currency_code: request.currency_code.to_string(),
+                value,
+            })
+            .sorted_by(|a, b| {
+                let key = |x: &Self| {
+                    (
+                        x.service_name.clone(),
+                        x.service_configuration_key.clone(),
+                        x.exchange_account_id.clone(),
+                        x.currency_pair.clone(),
+                        x.currency_code.clone(),
+                    )
+                };
+                key(a).cmp(&key(b))
+            })
+            .collect()
+    }
+}
+
+#[derive(Serialize)]
+struct ReservationView {
+    reservation_id: ReservationId,
+    service_name: String,
+    service_configuration_key: String,
+    exchange_account_id: String,
+    currency_pair: String,
+    side: OrderSide,
+    price: Price,
+    amount: Amount,
+    not_approved_amount: Amount,
+    unreserved_amount: Amount,
+    reservation_currency_code: String,
+    approved_client_order_ids: Vec<String>,
+}
+
+impl ReservationView {
+    fn new(reservation_id: ReservationId, reservation: &BalanceReservation) -> Self {
+        Self {
+            reservation_id,
+            service_name: reservation
+                .configuration_descriptor
+                .service_name
+                .to_string(),
+            service_configuration_key: reservation
+                .configuration_descriptor
+                .service_configuration_key
+                .to_string(),
+            exchange_account_id: reservation.exchange_account_id.to_string(),
+            currency_pair: reservation.symbol.currency_pair().to_string(),
+            side: reservation.order_side,
+            price: reservation.price,
+            amount: reservation.amount,
+            not_approved_amount: reservation.not_approved_amount,
+            unreserved_amount: reservation.unreserved_amount,
+            reservation_currency_code: reservation.reservation_currency_code.to_string(),
+            approved_client_order_ids: reservation
+                .approved_parts
+                .keys()
+                .map(|x| x.to_string())
+                .sorted()
+                .collect(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub(super) struct PositionView {
+    exchange_account_id: String,
+    currency_pair: String,
+    side: Option<OrderSide>,
+    position: Decimal,
+    average_entry_price: Price,
+    liquidation_price: Price,
+    leverage: Decimal,
+}
+
+impl PositionView {
+    pub(super) fn new(
+        exchange_account_id: ExchangeAccountId,
+        position: &DerivativePosition,
+    ) -> Self {
+        Self {
+            exchange_account_id: exchange_account_id.to_string(),
+            currency_pair: position.currency_pair.to_string(),
+            side: position.side,
+            position: position.position,
+            average_entry_price: position.average_entry_price,
+            liquidation_price: position.liquidation_price,
+            leverage: position.leverage,
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub(super) struct OrderBookTopView {
+    exchange_account_id: String,
+    currency_pair: String,
+    ask: Option<PriceLevelView>,
+    bid: Option<PriceLevelView>,
+}
+
+impl OrderBookTopView {
+    pub(super) fn new(
+        exchange_account_id: ExchangeAccountId,
+        currency_pair: CurrencyPair,
+        order_book_top: &OrderBookTop,
+    ) -> Self {
+        Self {
+            exchange_account_id: exchange_account_id.to_string(),
+            currency_pair: currency_pair.to_string(),
+            ask: order_book_top.ask.as_ref().map(PriceLevelView::from),
+            bid: order_book_top.bid.as_ref().map(PriceLevelView::from),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct PriceLevelView {
+    price: Price,
+    amount: Amount,
+}
+
+impl From<&PriceLevel> for PriceLevelView {
+    fn from(price_level: &PriceLevel) -> Self {
+        Self {
+            price: price_level.price,
+            amount: price_level.amount,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::balance::manager::balance_position_by_fill_amount::BalancePositionByFillAmount;
+    use crate::exchanges::general::test_helper::create_order_ref;
+    use chrono::{DateTime, Utc};
+    use mmb_utils::hashmap;
+    use rust_decimal_macros::dec;
+    use serde_json::{json, Value};
+    use std::collections::HashMap;
+
+    fn exchange_account_id() -> ExchangeAccountId {
+        ExchangeAccountId::new("Binance", 0)
+    }
+
+    fn currency_pair() -> CurrencyPair {
+        CurrencyPair::from_codes("btc".into(), "usdt".into())
+    }
+
+    fn to_json(value: &impl Serialize) -> Value {
+        serde_json::to_value(value).expect("in test")
+    }
+
+    #[test]
+    fn order_view() {
+        let order = create_order_ref(
+            &"client_order_id".into(),
+            Some(OrderRole::Maker),
+            exchange_account_id(),
+            currency_pair(),
+            dec!(100),
+            dec!(2),
+            OrderSide::Buy,
+        );
+
+        let json = to_json(&order.fn_ref(|x| OrderView::from(x)));
+
+        let init_time = to_json(&order.fn_ref(|x| x.header.init_time));
+        assert_eq!(
+            json,
+            json!({
+                "exchange_account_id": "Binance_0",
+                "client_order_id": "client_order_id",
+                "exchange_order_id": null,
+                "currency_pair": "btc/usdt",
+                "order_type": "Liquidation",
+                "side": "Buy",
+                "role": "Maker",
+                "price": "100",
+                "amount": "2",
+                "filled_amount": "0",
+                "status": "Creating",
+                "init_time": init_time,
+                "fills": [],
+                "status_history": [],
+            })
+        );
+    }
+
+    #[test]
+    fn balances_view() {
+        let init_time: DateTime<Utc> = "2022-01-01T00:00:00Z".parse().expect("in test");
+        let balances = Balances::new(
+            hashmap![exchange_account_id() => hashmap!["usdt".into() => dec!(1000), "btc".into() => dec!(1)]],
+            init_time,
+            ServiceValueTree::default(),
+            ServiceValueTree::default(),
+            BalancePositionByFillAmount::default(),
+            ServiceValueTree::default(),
+            HashMap::new(),
+        );
+
+        let json = to_json(&BalancesView::from(&balances));
+
+        assert_eq!(
+            json,
+            json!({
+                "init_time": "2022-01-01T00:00:00Z",
+                "balances": [
+                    {"exchange_account_id": "Binance_0", "currency_code": "btc", "amount": "1"},
+                    {"exchange_account_id": "Binance_0", "currency_code": "usdt", "amount": "1000"},
+                ],
+                "virtual_diff_balances": [],
+                "reserved_amounts": [],
+                "reservations": [],
+            })
+        );
+    }
+
+    #[test]
+    fn position_view() {
+        let position = DerivativePosition::new(
+            currency_pair(),
+            dec!(0.5),
+            Some(OrderSide::Sell),
+            dec!(20000),
+            dec!(30000),
+            dec!(10),
+        );
+
+        let json = to_json(&PositionView::new(exchange_account_id(), &position));
+
+        assert_eq!(
+            json,
+            json!({
+                "exchange_account_id": "Binance_0",
+                "currency_pair": "btc/usdt",
+                "side": "Sell",
+                "position": "0.5",
+                "average_entry_price": "20000",
+                "liquidation_price": "30000",
+                "leverage": "10",
+            })
+        );
+    }
+
+    #[test]
+    fn order_book_top_view() {
+        let order_book_top = OrderBookTop {
+            ask: Some(PriceLevel {
+                price: dec!(101),
+                amount: dec!(1),
+            }),
+            bid: None,
+        };
+
+        let json = to_json(&OrderBookTopView::new(
+            exchange_account_id(),
+            currency_pair(),
+            &order_book_top,
+        ));
+
+        assert_eq!(
+            json,
+            json!({
+                "exchange_account_id": "Binance_0",
+                "currency_pair": "btc/usdt",
+                "ask": {"price": "101", "amount": "1"},
+                "bid": null,
+            })
+        );
+    }
+}
@@ -1,15 +1,13 @@
-use crate::exchanges::general::exchange::Exchange;
-use crate::lifecycle::trading_engine::Service;
+use crate::lifecycle::trading_engine::{EngineContext, Service};
 use chrono::{DateTime, Utc};
 use dashmap::DashMap;
-use mmb_domain::market::ExchangeAccountId;
 use mmb_domain::order::pool::OrderRef;
 use std::hash::Hash;
-use std::sync::Arc;
+use std::sync::{Arc, Weak};
 use tokio::sync::oneshot::Receiver;
 
 pub struct CleanupOrdersService {
-    exchanges: DashMap<ExchangeAccountId, Arc<Exchange>>,
+    engine_context: Weak<EngineContext>,
 }
 
 impl Service for CleanupOrdersService {
@@ -23,13 +21,18 @@ impl Service for CleanupOrdersService {
 }
 
 impl CleanupOrdersService {
-    pub fn new(exchanges: DashMap<ExchangeAccountId, Arc<Exchange>>) -> Self {
-        Self { exchanges }
+    pub fn new(engine_context: Weak<EngineContext>) -> Self {
+        Self { engine_context }
     }
 
     pub async fn cleanup_outdated_orders(self: Arc<Self>) {
+        let engine_context = match self.engine_context.upgrade() {
+            Some(engine_context) => engine_context,
+            None => return,
+        };
+
         let deadline = Utc::now() - chrono::Duration::minutes(30);
-        self.exchanges.iter().for_each(|pair| {
+        engine_context.exchanges.iter().for_each(|pair| {
             cleanup(&pair.orders.cache_by_exchange_id, deadline);
             cleanup(&pair.orders.cache_by_client_id, deadline);
         });
@@ -48,7 +51,7 @@ where
 mod tests {
     use super::*;
     use chrono::{Duration, Utc};
-    use mmb_domain::market::CurrencyPair;
+    use mmb_domain::market::{CurrencyPair, ExchangeAccountId};
     use mmb_domain::order::pool::OrdersPool;
     use mmb_domain::order::snapshot::{
         ClientOrderId, OrderExecutionType, OrderHeader, OrderSide, OrderStatus, OrderType,
@@ -369,16 +369,21 @@ impl PriceSourceService {
         time_in_past: DateTime,
         cancellation_token: CancellationToken,
     ) -> Option<Amount> {
-        let price_sources = self
+        let price_sources = match self
             .price_sources_loader
-            .load(time_in_past, cancellation_token.clone())
+            .load(time_in_past, cancellation_token)
             .await
-            .with_expect(|| {
-                format!(
-                    "Failed to get price_sources for {} from database",
-                    time_in_past
-                )
-            });
+        {
+            Ok(price_sources) => price_sources,
+            Err(error) => {
+                log::error!(
+                    "Failed to get price_sources for {} from database: {:?}",
+                    time_in_past,
+                    error
+                );
+                return None;
+            }
+        };
 
         let convert_currency_direction = ConvertCurrencyDirection::new(from, to);
 
@@ -1,46 +1,66 @@
-use mmb_domain::market::MarketId;
 use std::collections::HashMap;
 
+use anyhow::{bail, Context, Result};
+use chrono::Duration;
+use mmb_database::postgres_db::events::load_last_events_by_key_before;
+use mmb_database::postgres_db::PgPool;
+use mmb_domain::market::MarketId;
+use mmb_domain::order::snapshot::PriceByOrderSide;
 use mmb_utils::{cancellation_token::CancellationToken, DateTime};
 
-use mmb_domain::order::snapshot::PriceByOrderSide;
+use crate::misc::price_source_model::{PriceSourceModel, PRICE_SOURCES_TABLE_NAME};
+
+const PRICE_SOURCE_MARKET_ID_PATH: &str = "{market_id}";
+const PRICE_SOURCE_INIT_TIME_PATH: &str = "{init_time}";
+
+/// Prices which are older than this period are considered too outdated to convert amounts
+const PRICE_SOURCE_LOOKBACK_HOURS: i64 = 1;
 
 #[derive(Default)]
 pub struct PriceSourcesLoader {
-    // TODO: fix when DatabaseManager will be added
-    //database_manager: DatabaseManager
+    /// Prices are not loaded if there is no database
+    pool: Option<PgPool>,
 }
 
 impl PriceSourcesLoader {
-    pub fn new(//database_manager: DatabaseManager
-    ) -> Self {
-        Self{
-            //database_manager: DatabaseManager
-        }
+    pub fn new(pool: Option<PgPool>) -> Self {
+        Self { pool }
     }
 
+    /// Load the latest prices of every market at or before `save_time` within `PRICE_SOURCE_LOOKBACK_HOURS`
     pub async fn load(
         &self,
-        _save_time: DateTime,
-        _cancellation_token: CancellationToken,
-    ) -> Option<HashMap<MarketId, PriceByOrderSide>> {
-        //     const string sqlQuery =
-        //         "SELECT a.* FROM public.\"PriceSources\" a " +
-        //         "JOIN ( " +
-        //         "SELECT \"ExchangeName\", \"CurrencyCodePair\", max(\"DateTime\") \"DateTime\" " +
-        //         "FROM public.\"PriceSources\" " +
-        //         "WHERE \"DateTime\" <= {0} " +
-        //         "GROUP BY \"ExchangeName\", \"CurrencyCodePair\" " +
-        //         ") b ON a.\"ExchangeName\" = b.\"ExchangeName\" AND a.\"CurrencyCodePair\" = b.\"CurrencyCodePair\" AND a.\"DateTime\" = b.\"DateTime\"";
-
-        //     await using var session = _databaseManager.Sql;
-        //     return await session.Set<PriceSourceModel>()
-        //         .FromSqlRaw(sqlQuery, dateTime)
-        //         .ToDictionaryAsync(
-        //             x => new ExchangeNameSymbol(x.ExchangeName, x.CurrencyCodePair),
-        //             x => new PricesBySide(x.Ask, x.Bid),
-        //             cancellationToken);
-
-        Some(HashMap::new())
+        save_time: DateTime,
+        cancellation_token: CancellationToken,
+    ) -> Result<HashMap<MarketId, PriceByOrderSide>> {
+        let pool = match &self.pool {
+            Some(pool) => pool,
+            None => return Ok(HashMap::new()),
+        };
+
+        let events = tokio::select! {
+            events = load_last_events_by_key_before(
+                pool,
+                PRICE_SOURCES_TABLE_NAME,
+                PRICE_SOURCE_MARKET_ID_PATH,
+                PRICE_SOURCE_INIT_TIME_PATH,
+                save_time - Duration::hours(PRICE_SOURCE_LOOKBACK_HOURS),
+                save_time,
+            ) => events.context("unable load price sources")?,
+            _ = cancellation_token.when_cancelled() => bail!("loading of price sources has been stopped by CancellationToken"),
+        };
+
+        events
+            .into_iter()
+            .map(|event| {
+                let price_source = serde_json::from_value::<PriceSourceModel>(event.json)
+                    .context("unable deserialize price source")?;
+
+                Ok((
+                    price_source.market_id,
+                    PriceByOrderSide::new(price_source.bid, price_source.ask),
+                ))
+            })
+            .collect()
     }
 }
@@ -1,3 +1,5 @@
+use std::sync::Arc;
+
 use mmb_domain::market::MarketId;
 use mmb_domain::order::snapshot::PriceByOrderSide;
 use mockall_double::double;
@@ -5,30 +7,30 @@ use mockall_double::double;
 #[double]
 use crate::misc::time::time_manager;
 
+use crate::database::events::recorder::EventRecorder;
 use crate::misc::price_source_model::PriceSourceModel;
 
-#[derive(Default)]
 pub struct PriceSourcesSaver {
-    // TODO: implement when DataRecorder will be added
-    // data_recorder: DataRecorder;
+    event_recorder: Arc<EventRecorder>,
 }
 
 impl PriceSourcesSaver {
-    pub fn new(// data_recorder: DataRecorder
-    ) -> Self {
-        Self{
-            // data_recorder
-        }
+    pub fn new(event_recorder: Arc<EventRecorder>) -> Self {
+        Self { event_recorder }
     }
 
     pub fn save(&mut self, market_id: MarketId, prices: PriceByOrderSide) {
-        let _prices_source = PriceSourceModel::new(
+        let price_source = PriceSourceModel::new(
             time_manager::now(),
-            market_id.exchange_id,
-            market_id.currency_pair,
+            market_id,
             prices.top_bid,
             prices.top_ask,
         );
-        //     _dataRecorder.Save(priceSource);
+
+        self.event_recorder
+            .save(price_source)
+            .unwrap_or_else(|err| {
+                log::error!("unable save price source for {market_id:?}: {err:?}")
+            });
     }
 }
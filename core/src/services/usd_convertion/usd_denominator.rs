@@ -1,6 +1,7 @@
 use std::{collections::HashMap, sync::Arc, time::Duration};
 
 use crate::lifecycle::app_lifetime_manager::AppLifetimeManager;
+use async_trait::async_trait;
 use itertools::Itertools;
 use mmb_domain::market::CurrencyCode;
 use mmb_domain::market::CurrencyId;
@@ -14,6 +15,15 @@ use crate::{
     services::market_prices::market_currency_code_price::MarketCurrencyCodePrice,
 };
 
+struct NoMarketService;
+
+#[async_trait]
+impl GetMarketCurrencyCodePrice for NoMarketService {
+    async fn get_market_currency_code_price(&self) -> Vec<MarketCurrencyCodePrice> {
+        Vec::new()
+    }
+}
+
 pub struct UsdDenominator {
     market_service: Arc<dyn GetMarketCurrencyCodePrice>,
     lifetime_manager: Arc<AppLifetimeManager>,
@@ -95,6 +105,16 @@ impl UsdDenominator {
         )
     }
 
+    /// Denominator without prices for working when there is no external market service
+    pub fn without_market_service(lifetime_manager: Arc<AppLifetimeManager>) -> Arc<Self> {
+        UsdDenominator::new(
+            Arc::new(NoMarketService),
+            Vec::new(),
+            false,
+            lifetime_manager,
+        )
+    }
+
     pub fn get_non_refreshing_usd_denominator(&self) -> Arc<Self> {
         UsdDenominator::new(
             self.market_service.clone(),
@@ -1,6 +1,9 @@
+use mmb_domain::exchanges::commission::Percent;
 use mmb_domain::market::{CurrencyCode, CurrencyPair, ExchangeAccountId};
 use mmb_domain::order::snapshot::Amount;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
 use std::path::PathBuf;
 
 pub trait BaseStrategySettings {
@@ -11,13 +14,15 @@ pub trait BaseStrategySettings {
 
 /// Application settings
 /// Attention! After changing in runtime, you need to save the settings. See issue #146
-/// For the settings to be applied, the trading engine must be restarted after changing the config
+/// Changes of strategy instances are applied without restart when the config is set by control panel,
+/// but changes of core settings are applied by restarting of the trading engine
 #[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize, Serialize)]
 pub struct AppSettings<StrategySettings>
 where
     StrategySettings: BaseStrategySettings + Clone,
 {
-    pub strategy: StrategySettings,
+    /// Strategy instances. Every instance trades on its own market with its own DispositionExecutor
+    pub strategies: Vec<StrategySettings>,
     pub core: CoreSettings,
 }
 
@@ -25,6 +30,16 @@ where
 pub struct CoreSettings {
     pub database: Option<DbSettings>,
     pub exchanges: Vec<ExchangeSettings>,
+    /// Loss limits for markets. Trading is stopped on exchange if any limit is exceeded
+    pub profit_loss: Option<ProfitLossSettings>,
+    /// Metrics of engine internals are served in Prometheus text format if it is set
+    pub metrics: Option<MetricsSettings>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct MetricsSettings {
+    /// Address of HTTP server with `/metrics` endpoint, e.g. `127.0.0.1:9100`
+    pub address: SocketAddr,
 }
 
 #[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize, Serialize)]
@@ -60,7 +75,126 @@ pub struct ExchangeSettings {
     pub is_reducing_market_data: Option<bool>,
     pub subscribe_to_market_data: bool,
     pub websocket_channels: Vec<String>,
+    /// Local order book is considered stale if there are no order book events longer than this timeout.
+    /// Staleness isn't checked if it is not set
+    pub order_book_stale_timeout_secs: Option<u64>,
+    /// Exchange environment which hosts are used for connections. Mainnet is used if it is not set
+    pub environment: Option<ExchangeEnvironment>,
     pub currency_pairs: Option<Vec<CurrencyPairSetting>>,
+    /// Orders and balances are simulated over live market data of the exchange if it is set
+    pub paper_trading: Option<PaperTradingSettings>,
+    /// Proxy and local address for REST and websocket connections of the exchange account
+    pub network: Option<NetworkSettings>,
+    /// Explicit hosts which override hosts of the selected environment
+    pub hosts: Option<HostsSettings>,
+    /// Synchronization with exchange server clock. Default settings are used if it is not set
+    pub clock_sync: Option<ClockSyncSettings>,
+    /// Raw REST and websocket traffic of the exchange account is written to files if it is set
+    pub traffic_capture: Option<TrafficCaptureSettings>,
+    /// Heartbeat and reconnection of websockets. Default settings are used if it is not set
+    pub websocket: Option<WebSocketSettings>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct WebSocketSettings {
+    /// Ping is sent if there are no messages from exchange during this interval
+    pub heartbeat_interval_ms: u64,
+    /// Connection is closed if there are no messages from exchange during this timeout
+    pub heartbeat_fail_timeout_ms: u64,
+    /// Delay before the first reconnection attempt. It is doubled for every next failed attempt
+    pub reconnect_initial_delay_ms: u64,
+    pub reconnect_max_delay_ms: u64,
+    /// Random delay up to this value is added to every reconnection delay
+    pub reconnect_jitter_ms: u64,
+}
+
+impl Default for WebSocketSettings {
+    fn default() -> Self {
+        WebSocketSettings {
+            heartbeat_interval_ms: 5_000,
+            heartbeat_fail_timeout_ms: 10_000,
+            reconnect_initial_delay_ms: 500,
+            reconnect_max_delay_ms: 60_000,
+            reconnect_jitter_ms: 500,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct ClockSyncSettings {
+    pub period_secs: u64,
+    /// Exchange is blocked while offset between local and exchange clocks exceeds this value
+    pub max_offset_ms: u64,
+    /// Measurements with longer round-trip delay are too inaccurate and are skipped
+    pub max_round_trip_delay_ms: u64,
+}
+
+impl Default for ClockSyncSettings {
+    fn default() -> Self {
+        ClockSyncSettings {
+            period_secs: 60,
+            max_offset_ms: 1000,
+            max_round_trip_delay_ms: 2000,
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub enum ExchangeEnvironment {
+    #[default]
+    Mainnet,
+    Testnet,
+    /// All hosts are taken from `ExchangeSettings::hosts` (e.g. local mock server)
+    Custom,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct HostsSettings {
+    /// REST host with scheme, e.g. `https://api.binance.com`
+    pub rest_host: Option<String>,
+    pub web_socket_host: Option<String>,
+    /// Websocket host for private data. `web_socket_host` is used if it is not set for custom environment
+    pub web_socket2_host: Option<String>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct NetworkSettings {
+    /// Local IP address which outgoing connections are bound to
+    pub local_address: Option<IpAddr>,
+    pub proxy: Option<ProxySettings>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct ProxySettings {
+    pub kind: ProxyKind,
+    /// Proxy address in format `host:port`
+    pub address: String,
+    pub username: Option<String>,
+    pub password: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub enum ProxyKind {
+    /// HTTP proxy with CONNECT method
+    Http,
+    Socks5,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct TrafficCaptureSettings {
+    pub dir: PathBuf,
+    /// Capture file is rotated when its size exceeds this limit
+    pub max_file_size_bytes: u64,
+    /// The oldest capture files of the exchange account are removed when their count exceeds this limit
+    pub max_files: usize,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct PaperTradingSettings {
+    pub maker_fee: Percent,
+    pub taker_fee: Percent,
+    /// Virtual balances at the start of trading
+    pub initial_balances: HashMap<CurrencyCode, Amount>,
 }
 
 impl ExchangeSettings {
@@ -78,9 +212,17 @@ impl ExchangeSettings {
             is_margin_trading,
             request_trades: false,
             websocket_channels: vec![],
+            order_book_stale_timeout_secs: None,
+            environment: None,
             currency_pairs: None,
             subscribe_to_market_data: true,
             is_reducing_market_data: None,
+            paper_trading: None,
+            network: None,
+            hosts: None,
+            clock_sync: None,
+            traffic_capture: None,
+            websocket: None,
         }
     }
 }
@@ -94,13 +236,22 @@ impl Default for ExchangeSettings {
             is_margin_trading: false,
             request_trades: false,
             websocket_channels: vec![],
+            order_book_stale_timeout_secs: None,
+            environment: None,
             currency_pairs: None,
             subscribe_to_market_data: true,
             is_reducing_market_data: None,
+            paper_trading: None,
+            network: None,
+            hosts: None,
+            clock_sync: None,
+            traffic_capture: None,
+            websocket: None,
         }
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
 pub struct CurrencyPriceSourceSettings {
     pub start_currency_code: CurrencyCode,
     pub end_currency_code: CurrencyCode,
@@ -122,22 +273,36 @@ impl CurrencyPriceSourceSettings {
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
 pub struct ExchangeIdCurrencyPairSettings {
     pub exchange_account_id: ExchangeAccountId,
     pub currency_pair: CurrencyPair,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
 pub enum TimePeriodKind {
     Hour,
     Day,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
 pub struct StopperCondition {
     pub period_kind: TimePeriodKind,
     pub period_value: i64,
     pub limit: Amount,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
 pub struct ProfitLossStopperSettings {
+    /// Market which balance changes are accumulated for checking limits
+    pub exchange_account_id: ExchangeAccountId,
+    pub currency_pair: CurrencyPair,
     pub conditions: Vec<StopperCondition>,
 }
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct ProfitLossSettings {
+    /// Chains of markets for converting balance changes to USD by prices of order books
+    pub price_sources: Vec<CurrencyPriceSourceSettings>,
+    pub stoppers: Vec<ProfitLossStopperSettings>,
+}
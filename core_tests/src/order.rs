@@ -109,10 +109,7 @@ impl OrderProxy {
 
     pub async fn create_order(&self, exchange: Arc<Exchange>) -> Result<OrderRef> {
         let header = self.make_header();
-        let to_create = OrderCreating {
-            price: self.price,
-            header: header.clone(),
-        };
+        let to_create = OrderCreating::new(header.clone(), self.price);
 
         with_timeout(
             self.timeout,
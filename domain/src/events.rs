@@ -12,7 +12,7 @@ use tokio::sync::broadcast;
 use crate::market::{CurrencyCode, CurrencyPair, ExchangeAccountId};
 use crate::order::event::OrderEvent;
 use crate::order::snapshot::{Amount, OrderSide, Price};
-use crate::order_book::event::OrderBookEvent;
+use crate::order_book::event::{OrderBookEvent, OrderBookIntegrityEvent};
 use crate::position::DerivativePosition;
 
 pub const CHANNEL_MAX_EVENTS_COUNT: usize = 200_000;
@@ -173,6 +173,7 @@ pub enum ExchangeEvent {
     BalanceUpdate(BalanceUpdateEvent),
     LiquidationPrice(LiquidationPriceEvent),
     Trades(TradesEvent),
+    OrderBookIntegrityViolated(OrderBookIntegrityEvent),
 }
 
 pub struct ExchangeEvents {
@@ -187,6 +188,10 @@ impl ExchangeEvents {
     pub fn get_events_channel(&self) -> broadcast::Receiver<ExchangeEvent> {
         self.events_sender.subscribe()
     }
+
+    pub fn get_events_sender(&self) -> broadcast::Sender<ExchangeEvent> {
+        self.events_sender.clone()
+    }
 }
 
 #[derive(Debug, Default, Clone, PartialEq, Eq, Copy)]
@@ -7,7 +7,7 @@ use anyhow::{Context, Result};
 use rust_decimal::Decimal;
 use rust_decimal::MathematicalOps;
 use rust_decimal_macros::dec;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 pub enum Round {
     Floor,
@@ -27,7 +27,7 @@ pub enum BeforeAfter {
 /// ```ignore
 /// Precision::ByTick { tick: dec!(0.001) } // for AmountPrecision = 3 equal pow(0.1, 3)
 /// ```
-#[derive(Debug, Clone, Eq, PartialEq, Hash, Serialize)]
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
 pub enum Precision {
     /// Rounding is performed to a number divisible to the specified tick
     /// Look at round_by_tick test below
@@ -47,7 +47,7 @@ impl Precision {
 }
 
 /// Metadata for a currency pair
-#[derive(Debug, Clone, Eq, Serialize)]
+#[derive(Debug, Clone, Eq, Serialize, Deserialize)]
 pub struct Symbol {
     pub is_derivative: bool,
     pub base_currency_id: CurrencyId,
@@ -44,7 +44,7 @@ impl FromStr for ExchangeAccountId {
     type Err = ExchangeIdParseError;
 
     fn from_str(text: &str) -> std::result::Result<Self, Self::Err> {
-        let regex = Regex::new(r"(^[A-Za-z0-9\-\.]+)_(\d+$)")
+        let regex = Regex::new(r"(^[A-Za-z0-9_\-\.]+)_(\d+$)")
             .map_err(|err| ExchangeIdParseError(err.to_string()))?;
 
         let captures = regex
@@ -145,7 +145,7 @@ impl MarketId {
 }
 
 /// Exchange account id and currency pair
-#[derive(Debug, Clone, Copy, Eq, PartialEq, Deserialize, Hash)]
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
 pub struct MarketAccountId {
     pub exchange_account_id: ExchangeAccountId,
     pub currency_pair: CurrencyPair,
@@ -174,6 +174,45 @@ impl Serialize for MarketAccountId {
     }
 }
 
+struct MarketAccountIdVisitor;
+
+impl<'de> Visitor<'de> for MarketAccountIdVisitor {
+    type Value = MarketAccountId;
+
+    fn expecting(&self, formatter: &mut Formatter) -> fmt::Result {
+        write!(formatter, "string for MarketAccountId")
+    }
+
+    fn visit_str<E>(self, v: &str) -> std::result::Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        let invalid_value = || {
+            de::Error::invalid_value(
+                de::Unexpected::Str(v),
+                &"MarketAccountId as a string with ExchangeAccountId and CurrencyPair separated by a '|' character",
+            )
+        };
+
+        let (exchange_account_id, currency_pair) = v.split_once('|').ok_or_else(invalid_value)?;
+        let exchange_account_id = exchange_account_id.parse().map_err(|_| invalid_value())?;
+
+        Ok(MarketAccountId::new(
+            exchange_account_id,
+            CurrencyPair::from_raw(currency_pair),
+        ))
+    }
+}
+
+impl<'de> Deserialize<'de> for MarketAccountId {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_str(MarketAccountIdVisitor)
+    }
+}
+
 #[derive(Debug, Eq, PartialEq, Copy, Clone, Serialize, Deserialize)]
 pub enum ExchangeErrorType {
     Unknown,
@@ -266,6 +305,15 @@ mod tests {
             );
         }
 
+        #[test]
+        pub fn exchange_id_with_underscore() {
+            let exchange_account_id = "local_exchange_id_0".parse::<ExchangeAccountId>();
+            assert_eq!(
+                exchange_account_id,
+                Ok(ExchangeAccountId::new("local_exchange_id", 0))
+            );
+        }
+
         #[test]
         pub fn failed_because_no_exchange_name() {
             let exchange_account_id = "123".parse::<ExchangeAccountId>();
@@ -297,6 +345,34 @@ mod tests {
         }
     }
 
+    mod serde_market_account_id {
+        use super::*;
+        use pretty_assertions::assert_eq;
+        use std::collections::HashMap;
+
+        #[test]
+        pub fn as_map_key() {
+            let market_account_id = MarketAccountId::new(
+                ExchangeAccountId::new("Binance", 0),
+                CurrencyPair::from_codes("btc".into(), "usdt".into()),
+            );
+            let map = HashMap::from([(market_account_id, 1)]);
+
+            let serialized = serde_json::to_string(&map).expect("in test");
+            assert_eq!(serialized, r#"{"Binance_0|btc/usdt":1}"#);
+
+            let deserialized: HashMap<MarketAccountId, i32> =
+                serde_json::from_str(&serialized).expect("in test");
+            assert_eq!(deserialized, map);
+        }
+
+        #[test]
+        pub fn failed_because_no_separator() {
+            let result = serde_json::from_str::<MarketAccountId>(r#""Binance_0""#);
+            assert!(result.is_err());
+        }
+    }
+
     mod to_string_exchange_account_id {
         use super::*;
         use pretty_assertions::assert_eq;
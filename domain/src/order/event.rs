@@ -9,10 +9,16 @@ use crate::order::snapshot::OrderSnapshot;
 pub enum OrderEventType {
     CreateOrderSucceeded,
     CreateOrderFailed,
-    OrderFilled { cloned_order: Arc<OrderSnapshot> },
-    OrderCompleted { cloned_order: Arc<OrderSnapshot> },
+    OrderFilled {
+        cloned_order: Arc<OrderSnapshot>,
+    },
+    OrderCompleted {
+        cloned_order: Arc<OrderSnapshot>,
+    },
     CancelOrderSucceeded,
     CancelOrderFailed,
+    /// Emulated conditional order is registered and waiting for its trigger
+    ConditionalOrderWaiting,
 }
 
 #[derive(Debug, Clone)]
@@ -96,6 +96,7 @@ pub enum OrderType {
     Liquidation = 5,
     ClosePosition = 6,
     MissedFill = 7,
+    TakeProfit = 8,
 }
 
 impl OrderType {
@@ -103,6 +104,12 @@ impl OrderType {
         use OrderType::*;
         matches!(*self, Liquidation | ClosePosition | MissedFill)
     }
+
+    /// Orders which are placed to the market only when trigger price is reached
+    pub fn is_conditional(&self) -> bool {
+        use OrderType::*;
+        matches!(*self, StopLoss | TakeProfit | TrailingStop)
+    }
 }
 
 #[derive(Debug, Eq, PartialEq, Copy, Clone, Serialize, Deserialize, Hash)]
@@ -111,6 +118,29 @@ pub enum OrderExecutionType {
     MakerOnly = 1,
 }
 
+/// How long order stays active on the exchange
+#[derive(Debug, Default, Eq, PartialEq, Copy, Clone, Serialize, Deserialize, Hash)]
+pub enum OrderTimeInForce {
+    #[default]
+    GoodTillCancelled,
+    /// Unfilled part of order is cancelled immediately after matching
+    ImmediateOrCancel,
+    /// Order is cancelled immediately if it can't be filled completely
+    FillOrKill,
+    /// Order is cancelled by the exchange at specified time
+    GoodTillDate(DateTime),
+}
+
+impl OrderTimeInForce {
+    /// Order can be cancelled by the exchange right after creation
+    pub fn is_immediate(&self) -> bool {
+        matches!(
+            self,
+            OrderTimeInForce::ImmediateOrCancel | OrderTimeInForce::FillOrKill
+        )
+    }
+}
+
 impl_str_id!(ClientOrderId);
 impl_str_id!(ClientOrderFillId);
 impl_str_id!(ExchangeOrderId);
@@ -124,6 +154,8 @@ pub enum OrderStatus {
     Canceled = 5,
     FailedToCancel = 6,
     Completed = 7,
+    /// Conditional order emulated by the engine is waiting for its trigger and isn't placed to the exchange yet
+    WaitingForTrigger = 8,
 }
 
 impl Default for OrderStatus {
@@ -165,6 +197,9 @@ pub struct OrderHeader {
 
     pub execution_type: OrderExecutionType,
 
+    #[serde(default)]
+    pub time_in_force: OrderTimeInForce,
+
     pub reservation_id: Option<ReservationId>,
 
     pub signal_id: Option<String>,
@@ -196,12 +231,29 @@ impl OrderHeader {
             side,
             amount,
             execution_type,
+            time_in_force: OrderTimeInForce::default(),
             reservation_id,
             signal_id,
             strategy_name,
         })
     }
 
+    /// Copy of header with specified time in force
+    pub fn with_time_in_force(&self, time_in_force: OrderTimeInForce) -> Arc<Self> {
+        Arc::new(Self {
+            time_in_force,
+            ..self.clone()
+        })
+    }
+
+    /// Copy of header with specified balance reservation
+    pub fn with_reservation_id(&self, reservation_id: ReservationId) -> Arc<Self> {
+        Arc::new(Self {
+            reservation_id: Some(reservation_id),
+            ..self.clone()
+        })
+    }
+
     pub fn version(&self) -> u32 {
         self.version
     }
@@ -226,7 +278,9 @@ pub struct OrderSimpleProps {
     pub raw_price: Option<Price>,
     pub role: Option<OrderRole>,
     pub exchange_order_id: Option<ExchangeOrderId>,
+    /// Trigger price for `StopLoss` and `TakeProfit` orders and activation price for `TrailingStop` orders
     pub stop_loss_price: Decimal,
+    /// Callback rate of `TrailingStop` orders in percents
     pub trailing_stop_delta: Decimal,
 
     pub status: OrderStatus,
@@ -306,11 +360,27 @@ pub struct OrderStatusChange {
     time: DateTime,
 }
 
+impl OrderStatusChange {
+    pub fn status(&self) -> OrderStatus {
+        self.status
+    }
+
+    pub fn time(&self) -> DateTime {
+        self.time
+    }
+}
+
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct OrderStatusHistory {
     status_changes: Vec<OrderStatusChange>,
 }
 
+impl OrderStatusHistory {
+    pub fn status_changes(&self) -> &[OrderStatusChange] {
+        &self.status_changes
+    }
+}
+
 /// Helping properties for trading engine internal use
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct SystemInternalOrderProps {
@@ -323,13 +393,13 @@ pub struct SystemInternalOrderProps {
     pub last_order_cancellation_status_request_time: Option<DateTime>,
     pub last_cancellation_error: Option<ExchangeErrorType>,
 
-    #[serde(skip_serializing)]
+    #[serde(skip)]
     pub is_canceling_from_wait_cancel_order: bool,
 
-    #[serde(skip_serializing)]
+    #[serde(skip)]
     pub canceled_not_from_wait_cancel_order: bool,
 
-    #[serde(skip_serializing)]
+    #[serde(skip)]
     pub was_cancellation_event_raised: bool,
 
     pub last_order_trades_request_time: Option<DateTime>,
@@ -454,6 +524,23 @@ impl OrderInfo {
 pub struct OrderCreating {
     pub header: Arc<OrderHeader>,
     pub price: Price,
+    /// Same as `OrderSimpleProps::stop_loss_price`, used only for conditional orders
+    #[serde(default)]
+    pub stop_loss_price: Decimal,
+    /// Same as `OrderSimpleProps::trailing_stop_delta`, used only for trailing stop orders
+    #[serde(default)]
+    pub trailing_stop_delta: Decimal,
+}
+
+impl OrderCreating {
+    pub fn new(header: Arc<OrderHeader>, price: Price) -> Self {
+        Self {
+            header,
+            price,
+            stop_loss_price: Decimal::ZERO,
+            trailing_stop_delta: Decimal::ZERO,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -2,6 +2,7 @@ use mmb_utils::DateTime;
 
 use crate::market::CurrencyPair;
 use crate::market::*;
+use crate::order::snapshot::Price;
 use crate::order_book::order_book_data::OrderBookData;
 use std::sync::Arc;
 
@@ -52,3 +53,24 @@ impl OrderBookEvent {
         MarketAccountId::new(self.exchange_account_id, self.currency_pair)
     }
 }
+
+/// Reason why local order book can't be trusted anymore
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum OrderBookIntegrityViolation {
+    /// Update was received for market without order book snapshot
+    UpdateWithoutSnapshot,
+    /// Best ask is lower or equal to best bid (crossed or locked order book)
+    Crossed { top_ask: Price, top_bid: Price },
+    /// There were no order book events longer than timeout
+    Stale { last_update_time: DateTime },
+}
+
+/// Event about broken local order book. Trading on the market is stopped until a new valid snapshot is received
+/// if exchange client is able to resync order book
+#[derive(Debug, Clone)]
+pub struct OrderBookIntegrityEvent {
+    pub exchange_account_id: ExchangeAccountId,
+    pub currency_pair: CurrencyPair,
+    pub violation: OrderBookIntegrityViolation,
+    pub time: DateTime,
+}
@@ -0,0 +1,167 @@
+#![deny(
+    non_ascii_idents,
+    non_shorthand_field_patterns,
+    no_mangle_generic_items,
+    overflowing_literals,
+    path_statements,
+    unused_allocation,
+    unused_comparisons,
+    unused_parens,
+    while_true,
+    trivial_numeric_casts,
+    unused_extern_crates,
+    unused_import_braces,
+    unused_qualifications,
+    unused_must_use,
+    clippy::unwrap_used
+)]
+
+use std::env;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use backtest::backtest::{BacktestBuilder, BacktestSettings};
+use backtest::market_data::MarketDataSource;
+use mmb_core::lifecycle::launcher::{launch_trading_engine, EngineBuildConfig, InitSettings};
+use mmb_core::lifecycle::trading_engine::TradingEngine;
+use mmb_core::settings::{
+    AppSettings, BaseStrategySettings, CoreSettings, CurrencyPairSetting, ExchangeSettings,
+};
+use mmb_domain::exchanges::commission::{Commission, CommissionForType};
+use mmb_domain::exchanges::symbol::{Precision, Symbol};
+use mmb_domain::market::ExchangeAccountId;
+use mmb_utils::hashmap;
+use rust_decimal_macros::dec;
+
+use strategies::example_strategy::{ExampleStrategy, ExampleStrategySettings};
+
+const DEFAULT_MARKET_DATA_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/market_data.jsonl");
+const REPORT_PATH: &str = "backtest_report.json";
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let market_data_path = env::args()
+        .nth(1)
+        .unwrap_or_else(|| DEFAULT_MARKET_DATA_PATH.to_owned());
+
+    let engine = launch_backtest(backtest_settings(PathBuf::from(market_data_path))).await?;
+
+    // backtest exchange shuts the engine down when all market data is replayed
+    engine.run().await;
+
+    Ok(())
+}
+
+async fn launch_backtest(settings: BacktestSettings) -> Result<TradingEngine> {
+    let engine_config = EngineBuildConfig::new(vec![Box::new(BacktestBuilder::new(settings))]);
+
+    let init_settings = InitSettings::Directly(app_settings());
+    launch_trading_engine(&engine_config, init_settings, |settings, ctx| {
+        Box::new(ExampleStrategy::new(
+            settings.exchange_account_id(),
+            settings.currency_pair(),
+            settings.spread,
+            settings.max_amount,
+            ctx,
+        ))
+    })
+    .await
+}
+
+fn backtest_settings(market_data_path: PathBuf) -> BacktestSettings {
+    let symbol = Symbol::new(
+        false,
+        "BTC".into(),
+        "btc".into(),
+        "USDT".into(),
+        "usdt".into(),
+        None,
+        None,
+        Some(dec!(0.001)),
+        None,
+        None,
+        "btc".into(),
+        None,
+        Precision::ByTick { tick: dec!(0.1) },
+        Precision::ByTick { tick: dec!(0.001) },
+    );
+
+    let fee = CommissionForType::new(dec!(0.1), dec!(0));
+    BacktestSettings {
+        initial_balances: hashmap!["btc".into() => dec!(1), "usdt".into() => dec!(100)],
+        commission: Commission::new(fee.clone(), fee),
+        latency: Duration::from_millis(100),
+        speed: Some(60),
+        report_path: Some(REPORT_PATH.into()),
+        ..BacktestSettings::new(
+            MarketDataSource::File(market_data_path),
+            vec![Arc::new(symbol)],
+        )
+    }
+}
+
+fn app_settings() -> AppSettings<ExampleStrategySettings> {
+    let exchange_account_id = ExchangeAccountId::new("Backtest", 0);
+    let currency_pair = CurrencyPairSetting::Ordinary {
+        base: "btc".into(),
+        quote: "usdt".into(),
+    };
+
+    AppSettings {
+        strategies: vec![ExampleStrategySettings {
+            spread: dec!(0.2),
+            currency_pair: currency_pair.clone(),
+            max_amount: dec!(0.5),
+            exchange_account_id,
+        }],
+        core: CoreSettings {
+            database: None,
+            exchanges: vec![ExchangeSettings {
+                exchange_account_id,
+                currency_pairs: Some(vec![currency_pair]),
+                ..ExchangeSettings::default()
+            }],
+            profit_loss: None,
+            metrics: None,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use backtest::backtest::Backtest;
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn replay_of_market_data_produces_fills() {
+        let settings = BacktestSettings {
+            start_delay: Duration::from_millis(200),
+            // replaying without pauses would let strategy lag behind market data
+            speed: Some(300),
+            report_path: None,
+            ..backtest_settings(PathBuf::from(DEFAULT_MARKET_DATA_PATH))
+        };
+
+        let engine = launch_backtest(settings).await.expect("in test");
+        let context = engine.context();
+        engine.run().await;
+
+        let exchange = context
+            .exchanges
+            .get(&ExchangeAccountId::new("Backtest", 0))
+            .expect("in test")
+            .clone();
+        let report = exchange
+            .exchange_client
+            .as_any()
+            .downcast_ref::<Backtest>()
+            .expect("in test")
+            .report();
+
+        assert!(report.finished_at.is_some());
+        assert!(!report.fills.is_empty());
+        assert!(!report.profit_loss.is_empty());
+    }
+}
@@ -0,0 +1,23 @@
+CREATE TABLE orders (
+    id bigint PRIMARY KEY GENERATED BY DEFAULT AS IDENTITY,
+    insert_time timestamp WITH TIME ZONE NOT NULL DEFAULT now(),
+    version int,
+    json jsonb NOT NULL
+);
+
+CREATE INDEX orders__insert_time_idx ON orders USING btree (insert_time);
+CREATE INDEX orders__exchange_account_id_idx ON orders USING btree (((json #>> '{order, header, exchange_account_id}')::text));
+CREATE INDEX orders__currency_pair_idx ON orders USING btree (((json #>> '{order, header, currency_pair}')::text));
+CREATE INDEX orders__client_order_id_idx ON orders USING btree (((json #>> '{order, header, client_order_id}')::text));
+
+CREATE TABLE order_fills (
+    id bigint PRIMARY KEY GENERATED BY DEFAULT AS IDENTITY,
+    insert_time timestamp WITH TIME ZONE NOT NULL DEFAULT now(),
+    version int,
+    json jsonb NOT NULL
+);
+
+CREATE INDEX order_fills__insert_time_idx ON order_fills USING btree (insert_time);
+CREATE INDEX order_fills__exchange_account_id_idx ON order_fills USING btree (((json ->> 'exchange_account_id')::text));
+CREATE INDEX order_fills__currency_pair_idx ON order_fills USING btree (((json ->> 'currency_pair')::text));
+CREATE INDEX order_fills__client_order_id_idx ON order_fills USING btree (((json ->> 'client_order_id')::text));
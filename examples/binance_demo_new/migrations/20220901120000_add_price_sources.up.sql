@@ -0,0 +1,9 @@
+CREATE TABLE price_sources (
+    id bigint PRIMARY KEY GENERATED BY DEFAULT AS IDENTITY,
+    insert_time timestamp WITH TIME ZONE NOT NULL DEFAULT now(),
+    version int,
+    json jsonb NOT NULL
+);
+
+CREATE INDEX price_sources__insert_time_idx ON price_sources USING btree (insert_time);
+CREATE INDEX price_sources__market_id_idx ON price_sources USING btree (((json #>> '{market_id}')::text));
@@ -0,0 +1,2 @@
+DROP INDEX price_sources__market_id_init_time_idx;
+DROP FUNCTION json_timestamptz(text);
@@ -0,0 +1,4 @@
+-- text to timestamptz cast isn't immutable in general, but json timestamps always contain time zone offset
+CREATE OR REPLACE FUNCTION json_timestamptz(value text) RETURNS timestamptz AS $$ SELECT value::timestamptz $$ LANGUAGE sql IMMUTABLE;
+
+CREATE INDEX price_sources__market_id_init_time_idx ON price_sources USING btree (((json #>> '{market_id}')::text), json_timestamptz(json #>> '{init_time}'));
@@ -43,28 +43,29 @@ async fn main() -> Result<()> {
     loop {
         let engine =
             launch_trading_engine(&engine_config, init_settings.clone(), |settings, ctx| {
-                spawn_future(
-                    "Save visualization data",
-                    SpawnFutureFlags::STOP_BY_TOKEN | SpawnFutureFlags::DENY_CANCELLATION,
-                    start_visualization_data_saving(ctx.clone(), STRATEGY_NAME),
-                );
-
-                spawn_future_ok(
-                    "Checking orders activity",
-                    SpawnFutureFlags::STOP_BY_TOKEN | SpawnFutureFlags::DENY_CANCELLATION,
-                    orders_activity::checking_orders_activity(ctx.clone()),
-                );
-
                 Box::new(ExampleStrategy::new(
-                    settings.strategy.exchange_account_id(),
-                    settings.strategy.currency_pair(),
-                    settings.strategy.spread,
-                    settings.strategy.max_amount,
+                    settings.exchange_account_id(),
+                    settings.currency_pair(),
+                    settings.spread,
+                    settings.max_amount,
                     ctx,
                 ))
             })
             .await?;
 
+        let ctx = engine.context();
+        spawn_future(
+            "Save visualization data",
+            SpawnFutureFlags::STOP_BY_TOKEN | SpawnFutureFlags::DENY_CANCELLATION,
+            start_visualization_data_saving(ctx.clone(), STRATEGY_NAME),
+        );
+
+        spawn_future_ok(
+            "Checking orders activity",
+            SpawnFutureFlags::STOP_BY_TOKEN | SpawnFutureFlags::DENY_CANCELLATION,
+            orders_activity::checking_orders_activity(ctx),
+        );
+
         match engine.run().await {
             ActionAfterGracefulShutdown::Nothing => break,
             ActionAfterGracefulShutdown::Restart => continue,
@@ -37,10 +37,10 @@ async fn main() {
         let engine =
             launch_trading_engine(&engine_config, init_settings.clone(), |settings, ctx| {
                 Box::new(ExampleStrategy::new(
-                    settings.strategy.exchange_account_id(),
-                    settings.strategy.currency_pair(),
-                    settings.strategy.spread,
-                    settings.strategy.max_amount,
+                    settings.exchange_account_id(),
+                    settings.currency_pair(),
+                    settings.spread,
+                    settings.max_amount,
                     ctx,
                 ))
             })
@@ -21,7 +21,7 @@ use mmb_domain::exchanges::symbol::Round;
 use mmb_domain::market::CurrencyPair;
 use mmb_domain::market::{ExchangeAccountId, MarketAccountId, MarketId};
 use mmb_domain::order::snapshot::Amount;
-use mmb_domain::order::snapshot::{OrderRole, OrderSide, OrderSnapshot};
+use mmb_domain::order::snapshot::{OrderSide, OrderSnapshot};
 use mmb_utils::cancellation_token::CancellationToken;
 use serde::{Deserialize, Serialize};
 
@@ -203,16 +203,10 @@ impl ExampleStrategy {
         Some(TradingContextBySide {
             max_amount: self.max_amount,
             estimating: vec![WithExplanation {
-                value: Some(TradeCycle {
-                    order_role: OrderRole::Maker,
-                    strategy_name: Self::strategy_name().to_string(),
-                    disposition: TradeDisposition::new(
-                        self.market_account_id(),
-                        side,
-                        price,
-                        amount,
-                    ),
-                }),
+                value: Some(TradeCycle::maker(
+                    Self::strategy_name().to_string(),
+                    TradeDisposition::new(self.market_account_id(), side, price, amount),
+                )),
                 explanation,
             }],
         })
@@ -250,7 +244,7 @@ impl DispositionStrategy for ExampleStrategy {
         _target_eai: ExchangeAccountId,
         _cancellation_token: CancellationToken,
     ) -> Result<()> {
-        // TODO save order fill info in Database
+        // order fills are saved to the `order_fills` table by the engine
         Ok(())
     }
 
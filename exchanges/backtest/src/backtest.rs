@@ -0,0 +1,328 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use dashmap::DashMap;
+use mmb_core::exchanges::common::send_event;
+use mmb_core::exchanges::general::exchange::BoxExchangeClient;
+use mmb_core::exchanges::general::features::{
+    ExchangeFeatures, OpenOrdersType, OrderFeatures, OrderTradeOption, RestFillsFeatures,
+    RestFillsType, WebSocketOptions,
+};
+use mmb_core::exchanges::simulation::simulated_exchange::SimulatedExchange;
+use mmb_core::exchanges::timeouts::requests_timeout_manager_factory::RequestTimeoutArguments;
+use mmb_core::exchanges::timeouts::timeout_manager::TimeoutManager;
+use mmb_core::exchanges::traits::{
+    ExchangeClientBuilder, ExchangeClientBuilderResult, HandleOrderFilledCb, HandleTradeCb,
+    OrderCancelledCb, OrderCreatedCb,
+};
+use mmb_core::lifecycle::app_lifetime_manager::AppLifetimeManager;
+use mmb_core::settings::ExchangeSettings;
+use mmb_domain::events::{AllowedEventSourceType, ExchangeEvent, TradeId};
+use mmb_domain::exchanges::commission::Commission;
+use mmb_domain::exchanges::symbol::Symbol;
+use mmb_domain::market::{CurrencyCode, CurrencyId, CurrencyPair, ExchangeAccountId, ExchangeId};
+use mmb_domain::order::fill::EventSourceType;
+use mmb_domain::order::pool::OrdersPool;
+use mmb_domain::order::snapshot::{Amount, Price};
+use mmb_domain::order_book::event::{EventType, OrderBookEvent};
+use mmb_utils::DateTime;
+use parking_lot::Mutex;
+use tokio::sync::broadcast;
+
+use crate::market_data::{to_order_book_data, MarketDataSource, RecordedMarketEvent};
+use crate::report::{calculate_profit_loss, BacktestReport};
+
+#[derive(Debug, Clone)]
+pub struct BacktestSettings {
+    pub market_data: MarketDataSource,
+    /// Symbols supported by the simulated exchange
+    pub symbols: Vec<Arc<Symbol>>,
+    pub initial_balances: HashMap<CurrencyCode, Amount>,
+    pub commission: Commission,
+    /// Delay in simulated time between order creation and the moment when order takes part in matching
+    pub latency: Duration,
+    /// Delay before replaying of market data to let strategies subscribe to exchange events
+    pub start_delay: Duration,
+    /// Speed of replaying relative to recorded time. Events are replayed without pauses if `None`
+    pub speed: Option<u32>,
+    /// File for saving json report when replaying is finished
+    pub report_path: Option<PathBuf>,
+    /// Start graceful shutdown of the engine when replaying is finished
+    pub shutdown_on_finish: bool,
+}
+
+impl BacktestSettings {
+    pub fn new(market_data: MarketDataSource, symbols: Vec<Arc<Symbol>>) -> Self {
+        Self {
+            market_data,
+            symbols,
+            initial_balances: HashMap::new(),
+            commission: Commission::default(),
+            latency: Duration::ZERO,
+            start_delay: Duration::from_secs(1),
+            speed: None,
+            report_path: None,
+            shutdown_on_finish: true,
+        }
+    }
+}
+
+/// Exchange client which simulates exchange by replaying recorded market data
+pub struct Backtest {
+    pub id: ExchangeAccountId,
+    pub settings: ExchangeSettings,
+    pub backtest_settings: BacktestSettings,
+    pub order_created_callback: OrderCreatedCb,
+    pub order_cancelled_callback: OrderCancelledCb,
+    pub handle_order_filled_callback: HandleOrderFilledCb,
+    pub handle_trade_callback: HandleTradeCb,
+
+    pub supported_currencies: DashMap<CurrencyId, CurrencyCode>,
+
+    pub(super) events_channel: broadcast::Sender<ExchangeEvent>,
+    pub(super) lifetime_manager: Arc<AppLifetimeManager>,
+    pub(super) simulated_exchange: SimulatedExchange,
+
+    /// Start and finish of replayed market data in simulated time
+    replayed_period: Mutex<(Option<DateTime>, Option<DateTime>)>,
+}
+
+impl Backtest {
+    pub fn new(
+        id: ExchangeAccountId,
+        settings: ExchangeSettings,
+        backtest_settings: BacktestSettings,
+        events_channel: broadcast::Sender<ExchangeEvent>,
+        lifetime_manager: Arc<AppLifetimeManager>,
+    ) -> Self {
+        let simulated_exchange = SimulatedExchange::new(
+            backtest_settings.symbols.clone(),
+            backtest_settings.initial_balances.clone(),
+            backtest_settings.commission.clone(),
+            backtest_settings.latency,
+        );
+
+        Self {
+            id,
+            settings,
+            backtest_settings,
+            order_created_callback: Box::new(|_, _, _| {}),
+            order_cancelled_callback: Box::new(|_, _, _| {}),
+            handle_order_filled_callback: Box::new(|_| {}),
+            handle_trade_callback: Box::new(|_, _, _, _, _, _| {}),
+            supported_currencies: Default::default(),
+            events_channel,
+            lifetime_manager,
+            simulated_exchange,
+            replayed_period: Mutex::new((None, None)),
+        }
+    }
+
+    /// Current time of the simulated clock
+    pub fn now(&self) -> DateTime {
+        self.simulated_exchange.now()
+    }
+
+    /// Report with fills, balances and profit for the replayed period
+    pub fn report(&self) -> BacktestReport {
+        let (started_at, finished_at) = *self.replayed_period.lock();
+        let fills = self.simulated_exchange.fills();
+        let profit_loss = calculate_profit_loss(&fills, &self.simulated_exchange.last_prices());
+
+        BacktestReport {
+            started_at,
+            finished_at,
+            fills,
+            initial_balances: self.backtest_settings.initial_balances.clone(),
+            final_balances: self.simulated_exchange.balances(),
+            profit_loss,
+        }
+    }
+
+    /// Apply recorded event on the simulated exchange and forward it to the engine
+    pub(super) fn handle_market_event(&self, event: RecordedMarketEvent) -> Result<()> {
+        let simulated_events = match &event {
+            RecordedMarketEvent::OrderBookSnapshot {
+                time,
+                currency_pair,
+                asks,
+                bids,
+            } => self.simulated_exchange.apply_order_book_event(
+                *time,
+                *currency_pair,
+                EventType::Snapshot,
+                &to_order_book_data(asks, bids),
+            )?,
+            RecordedMarketEvent::OrderBookUpdate {
+                time,
+                currency_pair,
+                asks,
+                bids,
+            } => self.simulated_exchange.apply_order_book_event(
+                *time,
+                *currency_pair,
+                EventType::Update,
+                &to_order_book_data(asks, bids),
+            )?,
+            RecordedMarketEvent::Trade {
+                time,
+                currency_pair,
+                price,
+                amount,
+                side,
+                ..
+            } => self.simulated_exchange.apply_trade(
+                *time,
+                *currency_pair,
+                *price,
+                *amount,
+                *side,
+            )?,
+        };
+
+        self.forward_market_event(event)?;
+        for fill_event in simulated_events.fills {
+            (self.handle_order_filled_callback)(fill_event);
+        }
+        for (client_order_id, exchange_order_id) in simulated_events.expired_orders {
+            (self.order_cancelled_callback)(
+                client_order_id,
+                exchange_order_id,
+                EventSourceType::WebSocket,
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Mark the end of replaying in the report
+    pub(super) fn finish_replay(&self, started_at: Option<DateTime>) {
+        *self.replayed_period.lock() = (started_at, Some(self.simulated_exchange.now()));
+    }
+
+    fn forward_market_event(&self, event: RecordedMarketEvent) -> Result<()> {
+        match event {
+            RecordedMarketEvent::OrderBookSnapshot { .. }
+            | RecordedMarketEvent::OrderBookUpdate { .. }
+                if !self.settings.subscribe_to_market_data =>
+            {
+                Ok(())
+            }
+            RecordedMarketEvent::OrderBookSnapshot {
+                time,
+                currency_pair,
+                asks,
+                bids,
+            } => self.send_order_book_event(time, currency_pair, EventType::Snapshot, &asks, &bids),
+            RecordedMarketEvent::OrderBookUpdate {
+                time,
+                currency_pair,
+                asks,
+                bids,
+            } => self.send_order_book_event(time, currency_pair, EventType::Update, &asks, &bids),
+            RecordedMarketEvent::Trade {
+                time,
+                currency_pair,
+                trade_id,
+                price,
+                amount,
+                side,
+            } => {
+                (self.handle_trade_callback)(
+                    currency_pair,
+                    TradeId::Number(trade_id),
+                    price,
+                    amount,
+                    side,
+                    time,
+                );
+                Ok(())
+            }
+        }
+    }
+
+    fn send_order_book_event(
+        &self,
+        time: DateTime,
+        currency_pair: CurrencyPair,
+        event_type: EventType,
+        asks: &[(Price, Amount)],
+        bids: &[(Price, Amount)],
+    ) -> Result<()> {
+        // replayed events are created in simulated time, so the engine sees them as fresh
+        let order_book_event = OrderBookEvent::new(
+            time,
+            self.id,
+            currency_pair,
+            time.timestamp_millis().to_string(),
+            event_type,
+            Arc::new(to_order_book_data(asks, bids)),
+        );
+
+        send_event(
+            &self.events_channel,
+            self.lifetime_manager.clone(),
+            self.id,
+            ExchangeEvent::OrderBookEvent(order_book_event),
+        )
+    }
+}
+
+pub struct BacktestBuilder {
+    pub settings: BacktestSettings,
+}
+
+impl BacktestBuilder {
+    pub fn new(settings: BacktestSettings) -> Self {
+        Self { settings }
+    }
+}
+
+impl ExchangeClientBuilder for BacktestBuilder {
+    fn create_exchange_client(
+        &self,
+        exchange_settings: ExchangeSettings,
+        events_channel: broadcast::Sender<ExchangeEvent>,
+        lifetime_manager: Arc<AppLifetimeManager>,
+        _timeout_manager: Arc<TimeoutManager>,
+        _orders: Arc<OrdersPool>,
+    ) -> Result<ExchangeClientBuilderResult> {
+        let exchange_account_id = exchange_settings.exchange_account_id;
+
+        Ok(ExchangeClientBuilderResult {
+            client: Box::new(Backtest::new(
+                exchange_account_id,
+                exchange_settings,
+                self.settings.clone(),
+                events_channel,
+                lifetime_manager,
+            )) as BoxExchangeClient,
+            features: ExchangeFeatures::new(
+                OpenOrdersType::AllCurrencyPair,
+                RestFillsFeatures::new(RestFillsType::None),
+                OrderFeatures {
+                    supports_get_order_info_by_client_order_id: true,
+                    ..OrderFeatures::default()
+                },
+                OrderTradeOption::default(),
+                WebSocketOptions::default(),
+                false,
+                AllowedEventSourceType::All,
+                AllowedEventSourceType::All,
+                AllowedEventSourceType::All,
+            ),
+        })
+    }
+
+    fn get_timeout_arguments(&self) -> RequestTimeoutArguments {
+        // there are no request limits on the simulated exchange
+        RequestTimeoutArguments::from_requests_per_minute(1_000_000)
+    }
+
+    fn get_exchange_id(&self) -> ExchangeId {
+        "Backtest".into()
+    }
+}
@@ -0,0 +1,118 @@
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use mmb_core::exchanges::general::exchange::RequestResult;
+use mmb_core::exchanges::general::order::cancel::CancelOrderResult;
+use mmb_core::exchanges::general::order::create::CreateOrderResult;
+use mmb_core::exchanges::general::order::get_order_trades::OrderTrade;
+use mmb_core::exchanges::traits::{ExchangeClient, ExchangeError};
+use mmb_domain::events::ExchangeBalancesAndPositions;
+use mmb_domain::exchanges::symbol::Symbol;
+use mmb_domain::market::CurrencyPair;
+use mmb_domain::order::fill::EventSourceType;
+use mmb_domain::order::pool::OrderRef;
+use mmb_domain::order::snapshot::{OrderCancelling, OrderInfo, Price};
+use mmb_domain::position::{ActivePosition, ClosedPosition};
+use mmb_utils::DateTime;
+
+use crate::backtest::Backtest;
+
+#[async_trait]
+impl ExchangeClient for Backtest {
+    async fn create_order(&self, order: &OrderRef) -> CreateOrderResult {
+        let result = self.simulated_exchange.create_order(order);
+        // like real exchanges, creation of order is confirmed by event after response to request
+        if let RequestResult::Success(exchange_order_id) = &result.outcome {
+            (self.order_created_callback)(
+                order.client_order_id(),
+                exchange_order_id.clone(),
+                EventSourceType::Rest,
+            );
+        }
+
+        result
+    }
+
+    async fn cancel_order(&self, order: OrderCancelling) -> CancelOrderResult {
+        let result = self.simulated_exchange.cancel_order(&order);
+        // like real exchanges, cancellation of order is confirmed by event after response to request
+        if let RequestResult::Success(client_order_id) = &result.outcome {
+            (self.order_cancelled_callback)(
+                client_order_id.clone(),
+                order.exchange_order_id,
+                EventSourceType::Rest,
+            );
+        }
+
+        result
+    }
+
+    async fn cancel_all_orders(&self, currency_pair: CurrencyPair) -> Result<()> {
+        for (client_order_id, exchange_order_id) in
+            self.simulated_exchange.cancel_all_orders(currency_pair)?
+        {
+            (self.order_cancelled_callback)(
+                client_order_id,
+                exchange_order_id,
+                EventSourceType::Rest,
+            );
+        }
+
+        Ok(())
+    }
+
+    async fn get_open_orders(&self) -> Result<Vec<OrderInfo>> {
+        Ok(self.simulated_exchange.get_open_orders(|_| true))
+    }
+
+    async fn get_open_orders_by_currency_pair(
+        &self,
+        currency_pair: CurrencyPair,
+    ) -> Result<Vec<OrderInfo>> {
+        Ok(self
+            .simulated_exchange
+            .get_open_orders(|x| x.currency_pair == currency_pair))
+    }
+
+    async fn get_order_info(&self, order: &OrderRef) -> Result<OrderInfo, ExchangeError> {
+        self.simulated_exchange.get_order_info(order)
+    }
+
+    async fn close_position(
+        &self,
+        _position: &ActivePosition,
+        _price: Option<Price>,
+    ) -> Result<ClosedPosition> {
+        Err(anyhow!(
+            "Derivative positions are not supported by backtest exchange"
+        ))
+    }
+
+    async fn get_active_positions(&self) -> Result<Vec<ActivePosition>> {
+        Ok(vec![])
+    }
+
+    async fn get_balance(&self) -> Result<ExchangeBalancesAndPositions> {
+        Ok(self.simulated_exchange.get_balance())
+    }
+
+    async fn get_balance_and_positions(&self) -> Result<ExchangeBalancesAndPositions> {
+        self.get_balance().await
+    }
+
+    async fn get_my_trades(
+        &self,
+        symbol: &Symbol,
+        last_date_time: Option<DateTime>,
+    ) -> RequestResult<Vec<OrderTrade>> {
+        RequestResult::Success(
+            self.simulated_exchange
+                .get_my_trades(symbol.currency_pair(), last_date_time),
+        )
+    }
+
+    async fn build_all_symbols(&self) -> Result<Vec<Arc<Symbol>>> {
+        Ok(self.backtest_settings.symbols.clone())
+    }
+}
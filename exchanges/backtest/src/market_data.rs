@@ -0,0 +1,256 @@
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use itertools::Itertools;
+use mmb_database::postgres_db::events::{load_events_by_period, DbEvent};
+use mmb_database::postgres_db::PgPool;
+use mmb_domain::market::{CurrencyPair, ExchangeId};
+use mmb_domain::order::snapshot::{Amount, OrderSide, Price, SortedOrderData};
+use mmb_domain::order_book::order_book_data::OrderBookData;
+use mmb_utils::DateTime;
+use serde::{Deserialize, Serialize};
+
+const LIQUIDITY_ORDER_BOOKS_TABLE_NAME: &str = "liquidity_order_books";
+
+/// Recorded market data event which is replayed by backtest exchange
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum RecordedMarketEvent {
+    /// Full order book state which replaces previous one
+    OrderBookSnapshot {
+        time: DateTime,
+        currency_pair: CurrencyPair,
+        asks: Vec<(Price, Amount)>,
+        bids: Vec<(Price, Amount)>,
+    },
+    /// Changed price levels of order book. Zero amount means removing of price level
+    OrderBookUpdate {
+        time: DateTime,
+        currency_pair: CurrencyPair,
+        asks: Vec<(Price, Amount)>,
+        bids: Vec<(Price, Amount)>,
+    },
+    /// Public trade. `side` is a side of the taker order
+    Trade {
+        time: DateTime,
+        currency_pair: CurrencyPair,
+        trade_id: u64,
+        price: Price,
+        amount: Amount,
+        side: OrderSide,
+    },
+}
+
+impl RecordedMarketEvent {
+    pub fn time(&self) -> DateTime {
+        match self {
+            RecordedMarketEvent::OrderBookSnapshot { time, .. }
+            | RecordedMarketEvent::OrderBookUpdate { time, .. }
+            | RecordedMarketEvent::Trade { time, .. } => *time,
+        }
+    }
+
+    pub fn currency_pair(&self) -> CurrencyPair {
+        match self {
+            RecordedMarketEvent::OrderBookSnapshot { currency_pair, .. }
+            | RecordedMarketEvent::OrderBookUpdate { currency_pair, .. }
+            | RecordedMarketEvent::Trade { currency_pair, .. } => *currency_pair,
+        }
+    }
+}
+
+pub(crate) fn to_order_book_data(
+    asks: &[(Price, Amount)],
+    bids: &[(Price, Amount)],
+) -> OrderBookData {
+    fn to_sorted_data(levels: &[(Price, Amount)]) -> SortedOrderData {
+        levels.iter().copied().collect()
+    }
+
+    OrderBookData::new(to_sorted_data(asks), to_sorted_data(bids))
+}
+
+#[derive(Debug, Clone)]
+pub enum MarketDataSource {
+    /// File with one json serialized `RecordedMarketEvent` per line
+    File(PathBuf),
+    /// Order book snapshots saved in `liquidity_order_books` table for specified exchange
+    Database {
+        database_url: String,
+        exchange_id: ExchangeId,
+        from: DateTime,
+        to: DateTime,
+    },
+}
+
+/// Load recorded market data ordered by event time
+pub async fn load_market_data(source: &MarketDataSource) -> Result<Vec<RecordedMarketEvent>> {
+    let events = match source {
+        MarketDataSource::File(path) => {
+            let content = tokio::fs::read_to_string(path)
+                .await
+                .with_context(|| format!("unable read market data file {}", path.display()))?;
+            parse_market_data(&content)?
+        }
+        MarketDataSource::Database {
+            database_url,
+            exchange_id,
+            from,
+            to,
+        } => {
+            let pool = PgPool::create(database_url, 1)
+                .await
+                .context("unable connect to database for loading market data")?;
+
+            load_events_by_period(&pool, LIQUIDITY_ORDER_BOOKS_TABLE_NAME, *from, *to)
+                .await
+                .context("unable load liquidity order books")?
+                .into_iter()
+                .map(from_liquidity_order_book)
+                .filter_ok(|(event_exchange_id, _)| event_exchange_id == exchange_id)
+                .map_ok(|(_, event)| event)
+                .try_collect()?
+        }
+    };
+
+    // stable sort keeps order of events with the same time
+    Ok(events.into_iter().sorted_by_key(|x| x.time()).collect())
+}
+
+fn parse_market_data(content: &str) -> Result<Vec<RecordedMarketEvent>> {
+    content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| {
+            serde_json::from_str(line)
+                .with_context(|| format!("unable parse market data event at line {}", index + 1))
+        })
+        .collect()
+}
+
+#[derive(Deserialize)]
+struct PriceLevel {
+    price: Price,
+    amount: Amount,
+}
+
+#[derive(Deserialize)]
+struct LiquiditySnapshot {
+    asks: Vec<PriceLevel>,
+    bids: Vec<PriceLevel>,
+}
+
+/// Part of the `liquidity_order_books` event which is needed for replaying
+#[derive(Deserialize)]
+struct LiquidityOrderBook {
+    exchange_id: ExchangeId,
+    currency_pair: CurrencyPair,
+    snapshot: LiquiditySnapshot,
+}
+
+fn from_liquidity_order_book(event: DbEvent) -> Result<(ExchangeId, RecordedMarketEvent)> {
+    let order_book: LiquidityOrderBook = serde_json::from_value(event.json)
+        .with_context(|| format!("unable parse liquidity order book with id {}", event.id))?;
+
+    let to_levels = |levels: Vec<PriceLevel>| {
+        levels
+            .into_iter()
+            .map(|x| (x.price, x.amount))
+            .collect_vec()
+    };
+
+    Ok((
+        order_book.exchange_id,
+        RecordedMarketEvent::OrderBookSnapshot {
+            time: event.insert_time,
+            currency_pair: order_book.currency_pair,
+            asks: to_levels(order_book.snapshot.asks),
+            bids: to_levels(order_book.snapshot.bids),
+        },
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+    use rust_decimal_macros::dec;
+    use serde_json::json;
+
+    #[test]
+    fn parse_market_data_lines() {
+        let content = r#"
+{"type":"OrderBookSnapshot","time":"2022-08-01T00:00:00Z","currency_pair":"btc/usdt","asks":[["101","1"]],"bids":[["99","2"]]}
+
+{"type":"Trade","time":"2022-08-01T00:00:01Z","currency_pair":"btc/usdt","trade_id":1,"price":"100","amount":"0.5","side":"Sell"}
+"#;
+
+        let events = parse_market_data(content).expect("in test");
+
+        let currency_pair = CurrencyPair::from_codes("btc".into(), "usdt".into());
+        assert_eq!(
+            events,
+            vec![
+                RecordedMarketEvent::OrderBookSnapshot {
+                    time: Utc.ymd(2022, 8, 1).and_hms(0, 0, 0),
+                    currency_pair,
+                    asks: vec![(dec!(101), dec!(1))],
+                    bids: vec![(dec!(99), dec!(2))],
+                },
+                RecordedMarketEvent::Trade {
+                    time: Utc.ymd(2022, 8, 1).and_hms(0, 0, 1),
+                    currency_pair,
+                    trade_id: 1,
+                    price: dec!(100),
+                    amount: dec!(0.5),
+                    side: OrderSide::Sell,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_market_data_with_wrong_line() {
+        let content = r#"{"type":"Unknown"}"#;
+
+        let error = parse_market_data(content).expect_err("in test");
+
+        assert_eq!(
+            error.to_string(),
+            "unable parse market data event at line 1"
+        );
+    }
+
+    #[test]
+    fn liquidity_order_book_to_snapshot() {
+        let insert_time = Utc.ymd(2022, 8, 1).and_hms(0, 0, 0);
+        let event = DbEvent {
+            id: 1,
+            insert_time,
+            version: 1,
+            json: json!({
+                "exchange_id": "Binance",
+                "currency_pair": "btc/usdt",
+                "snapshot": {
+                    "asks": [{"price": "101", "amount": "1"}],
+                    "bids": [{"price": "99", "amount": "2"}, {"price": "98", "amount": "3"}],
+                },
+                "orders": [],
+            }),
+        };
+
+        let (exchange_id, event) = from_liquidity_order_book(event).expect("in test");
+
+        assert_eq!(exchange_id, ExchangeId::new("Binance"));
+        assert_eq!(
+            event,
+            RecordedMarketEvent::OrderBookSnapshot {
+                time: insert_time,
+                currency_pair: CurrencyPair::from_codes("btc".into(), "usdt".into()),
+                asks: vec![(dec!(101), dec!(1))],
+                bids: vec![(dec!(99), dec!(2)), (dec!(98), dec!(3))],
+            }
+        );
+    }
+}
@@ -0,0 +1,139 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use mmb_core::exchanges::simulation::simulated_exchange::SimulatedFill;
+use mmb_domain::market::{CurrencyCode, CurrencyPair};
+use mmb_domain::order::snapshot::{Amount, OrderSide, Price};
+use mmb_utils::DateTime;
+use rust_decimal::Decimal;
+use serde::Serialize;
+
+/// Result of trading by market for the whole backtest
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct MarketProfitLoss {
+    /// Change of the base currency balance
+    pub base_amount: Amount,
+    /// Change of the quote currency balance
+    pub quote_amount: Amount,
+    /// Last price of the market by which the base currency is valued
+    pub last_price: Option<Price>,
+    /// Realized and unrealized profit in quote currency after commissions
+    pub profit_loss: Decimal,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct BacktestReport {
+    pub started_at: Option<DateTime>,
+    pub finished_at: Option<DateTime>,
+    pub fills: Vec<SimulatedFill>,
+    pub initial_balances: HashMap<CurrencyCode, Amount>,
+    pub final_balances: HashMap<CurrencyCode, Amount>,
+    pub profit_loss: HashMap<CurrencyPair, MarketProfitLoss>,
+}
+
+impl BacktestReport {
+    pub fn total_profit_loss(&self) -> Decimal {
+        self.profit_loss.values().map(|x| x.profit_loss).sum()
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let json = serde_json::to_string_pretty(self).context("unable serialize report")?;
+        std::fs::write(path, json)
+            .with_context(|| format!("unable write report to {}", path.display()))
+    }
+}
+
+/// Calculate profit and loss by markets where `last_prices` are used
+/// for valuation of the base currency position that is still opened
+pub fn calculate_profit_loss(
+    fills: &[SimulatedFill],
+    last_prices: &HashMap<CurrencyPair, Price>,
+) -> HashMap<CurrencyPair, MarketProfitLoss> {
+    let mut result = HashMap::<CurrencyPair, MarketProfitLoss>::new();
+    for fill in fills {
+        let market = result.entry(fill.currency_pair).or_default();
+        let codes = fill.currency_pair.to_codes();
+
+        let cost = fill.price * fill.amount;
+        let (base_diff, quote_diff) = match fill.side {
+            OrderSide::Buy => (fill.amount, -cost),
+            OrderSide::Sell => (-fill.amount, cost),
+        };
+        market.base_amount += base_diff;
+        market.quote_amount += quote_diff;
+
+        if fill.commission_currency_code == codes.base {
+            market.base_amount -= fill.commission_amount;
+        } else if fill.commission_currency_code == codes.quote {
+            market.quote_amount -= fill.commission_amount;
+        }
+    }
+
+    for (currency_pair, market) in &mut result {
+        market.last_price = last_prices.get(currency_pair).copied();
+        market.profit_loss =
+            market.quote_amount + market.base_amount * market.last_price.unwrap_or_default();
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use mmb_domain::order::snapshot::OrderRole;
+    use mmb_utils::hashmap;
+    use rust_decimal_macros::dec;
+
+    fn fill(side: OrderSide, price: Price, amount: Amount, commission: Amount) -> SimulatedFill {
+        let currency_pair = CurrencyPair::from_codes("btc".into(), "usdt".into());
+        let codes = currency_pair.to_codes();
+        SimulatedFill {
+            time: Utc::now(),
+            trade_id: 1,
+            client_order_id: "client_order_id".into(),
+            exchange_order_id: "exchange_order_id".into(),
+            currency_pair,
+            side,
+            price,
+            amount,
+            role: OrderRole::Maker,
+            commission_currency_code: match side {
+                OrderSide::Buy => codes.base,
+                OrderSide::Sell => codes.quote,
+            },
+            commission_amount: commission,
+        }
+    }
+
+    #[test]
+    fn profit_loss_with_closed_position() {
+        let fills = [
+            fill(OrderSide::Buy, dec!(100), dec!(1), dec!(0)),
+            fill(OrderSide::Sell, dec!(110), dec!(1), dec!(1)),
+        ];
+
+        let profit_loss = calculate_profit_loss(&fills, &HashMap::new());
+
+        let market = profit_loss.values().next().expect("in test");
+        assert_eq!(market.base_amount, dec!(0));
+        assert_eq!(market.quote_amount, dec!(9));
+        assert_eq!(market.profit_loss, dec!(9));
+    }
+
+    #[test]
+    fn profit_loss_with_opened_position() {
+        let currency_pair = CurrencyPair::from_codes("btc".into(), "usdt".into());
+        let fills = [fill(OrderSide::Buy, dec!(100), dec!(2), dec!(0.01))];
+
+        let profit_loss = calculate_profit_loss(&fills, &hashmap![currency_pair => dec!(105)]);
+
+        let market = &profit_loss[&currency_pair];
+        assert_eq!(market.base_amount, dec!(1.99));
+        assert_eq!(market.quote_amount, dec!(-200));
+        assert_eq!(market.last_price, Some(dec!(105)));
+        assert_eq!(market.profit_loss, dec!(8.95));
+    }
+}
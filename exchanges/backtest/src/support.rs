@@ -0,0 +1,170 @@
+use std::any::Any;
+use std::sync::{Arc, Weak};
+
+use anyhow::{anyhow, Context, Result};
+use async_trait::async_trait;
+use dashmap::DashMap;
+use mmb_core::connectivity::WebSocketRole;
+use mmb_core::exchanges::general::exchange::Exchange;
+use mmb_core::exchanges::traits::{
+    HandleOrderFilledCb, HandleTradeCb, OrderCancelledCb, OrderCreatedCb, SendWebsocketMessageCb,
+    Support,
+};
+use mmb_core::infrastructure::spawn_future;
+use mmb_core::settings::ExchangeSettings;
+use mmb_domain::market::{CurrencyCode, CurrencyId, CurrencyPair, SpecificCurrencyPair};
+use mmb_utils::infrastructure::SpawnFutureFlags;
+use mmb_utils::DateTime;
+use url::Url;
+
+use crate::backtest::Backtest;
+use crate::market_data::load_market_data;
+
+#[async_trait]
+impl Support for Backtest {
+    fn as_any(&self) -> &(dyn Any + Send + Sync + 'static) {
+        self
+    }
+
+    async fn initialized(&self, exchange: Arc<Exchange>) {
+        start_replaying_market_data(&exchange);
+    }
+
+    fn on_websocket_message(&self, msg: &str) -> Result<()> {
+        self.log_unknown_message(self.id, msg);
+        Ok(())
+    }
+
+    fn on_connecting(&self) -> Result<()> {
+        Ok(())
+    }
+
+    fn on_disconnected(&self) -> Result<()> {
+        Ok(())
+    }
+
+    fn set_send_websocket_message_callback(&self, _callback: SendWebsocketMessageCb) {}
+
+    fn set_order_created_callback(&mut self, callback: OrderCreatedCb) {
+        self.order_created_callback = callback;
+    }
+
+    fn set_order_cancelled_callback(&mut self, callback: OrderCancelledCb) {
+        self.order_cancelled_callback = callback;
+    }
+
+    fn set_handle_order_filled_callback(&mut self, callback: HandleOrderFilledCb) {
+        self.handle_order_filled_callback = callback;
+    }
+
+    fn set_handle_trade_callback(&mut self, callback: HandleTradeCb) {
+        self.handle_trade_callback = callback;
+    }
+
+    fn set_traded_specific_currencies(&self, _currencies: Vec<SpecificCurrencyPair>) {}
+
+    fn is_websocket_enabled(&self, _role: WebSocketRole) -> bool {
+        // market data and order events are produced by replaying
+        false
+    }
+
+    async fn create_ws_url(&self, role: WebSocketRole) -> Result<Url> {
+        Err(anyhow!("Backtest exchange has no websocket {role:?}"))
+    }
+
+    fn get_specific_currency_pair(&self, currency_pair: CurrencyPair) -> SpecificCurrencyPair {
+        currency_pair.as_str().into()
+    }
+
+    fn get_supported_currencies(&self) -> &DashMap<CurrencyId, CurrencyCode> {
+        &self.supported_currencies
+    }
+
+    fn should_log_message(&self, _message: &str) -> bool {
+        false
+    }
+
+    fn get_settings(&self) -> &ExchangeSettings {
+        &self.settings
+    }
+
+    fn market_data_now(&self) -> DateTime {
+        self.now()
+    }
+}
+
+fn start_replaying_market_data(exchange: &Arc<Exchange>) {
+    spawn_future(
+        "Replay backtest market data",
+        SpawnFutureFlags::STOP_BY_TOKEN,
+        replay_market_data(Arc::downgrade(exchange)),
+    );
+}
+
+fn as_backtest(exchange: &Exchange) -> &Backtest {
+    exchange
+        .exchange_client
+        .as_any()
+        .downcast_ref::<Backtest>()
+        .expect("received non Backtest exchange client in replaying of market data")
+}
+
+async fn replay_market_data(exchange_wk: Weak<Exchange>) -> Result<()> {
+    let settings = {
+        let exchange = exchange_wk
+            .upgrade()
+            .context("exchange is dropped before replaying of market data")?;
+        as_backtest(&exchange).backtest_settings.clone()
+    };
+
+    let events = load_market_data(&settings.market_data).await?;
+    log::info!("Loaded {} market data events for backtest", events.len());
+
+    tokio::time::sleep(settings.start_delay).await;
+
+    let started_at = events.first().map(|x| x.time());
+    let mut previous_time = started_at;
+    for event in events {
+        match (settings.speed, previous_time) {
+            (Some(speed), Some(previous_time)) if speed > 0 => {
+                let pause = (event.time() - previous_time).to_std().unwrap_or_default();
+                tokio::time::sleep(pause / speed).await;
+            }
+            // give other tasks a chance to handle replayed events
+            _ => tokio::task::yield_now().await,
+        }
+        previous_time = Some(event.time());
+
+        let exchange = match exchange_wk.upgrade() {
+            Some(exchange) => exchange,
+            None => return Ok(()),
+        };
+        as_backtest(&exchange).handle_market_event(event)?;
+    }
+
+    let exchange = match exchange_wk.upgrade() {
+        Some(exchange) => exchange,
+        None => return Ok(()),
+    };
+    let backtest = as_backtest(&exchange);
+    backtest.finish_replay(started_at);
+
+    let report = backtest.report();
+    log::info!(
+        "Backtest is finished with {} fills and profit {} in quote currencies",
+        report.fills.len(),
+        report.total_profit_loss()
+    );
+
+    if let Some(report_path) = &settings.report_path {
+        report.save(report_path)?;
+    }
+
+    if settings.shutdown_on_finish {
+        let _ = backtest
+            .lifetime_manager
+            .spawn_graceful_shutdown("Backtest market data is replayed");
+    }
+
+    Ok(())
+}
@@ -3,34 +3,43 @@ use std::fmt::{Display, Formatter};
 use std::sync::Arc;
 use std::time::{Duration, UNIX_EPOCH};
 
-use anyhow::{anyhow, bail, Context, Result};
+use anyhow::{anyhow, bail, ensure, Context, Result};
+use chrono::Utc;
 use dashmap::DashMap;
 use function_name::named;
 use hmac::digest::generic_array;
 use hmac::{Hmac, Mac};
 use itertools::Itertools;
-use mmb_utils::time::{get_current_milliseconds, u64_to_date_time};
+use mmb_utils::time::u64_to_date_time;
 use mmb_utils::DateTime;
 use parking_lot::{Mutex, RwLock};
 use serde_json::Value;
 use sha2::Sha256;
-use tokio::sync::broadcast;
+use tokio::sync::{broadcast, mpsc};
 
+use super::order_book::OrderBookSynchronizer;
 use super::support::{BinanceOrderInfo, BinanceSpotBalances};
-use crate::support::{BinanceAccountInfo, BinanceMarginBalances};
+use crate::support::{
+    BinanceAccountInfo, BinanceAccountUpdate, BinanceMarginBalances, BinancePosition,
+};
+use mmb_core::exchanges::common::send_event;
+use mmb_core::exchanges::exchange_clock::ExchangeClock;
 use mmb_core::exchanges::general::exchange::BoxExchangeClient;
 use mmb_core::exchanges::general::exchange::Exchange;
 use mmb_core::exchanges::general::features::{
-    OrderFeatures, OrderTradeOption, RestFillsFeatures, RestFillsType, WebSocketOptions,
+    BalancePositionOption, OrderFeatures, OrderTradeOption, RestFillsFeatures, RestFillsType,
+    WebSocketOptions,
 };
 use mmb_core::exchanges::general::handlers::handle_order_filled::FillAmount;
 use mmb_core::exchanges::general::handlers::handle_order_filled::FillEvent;
 use mmb_core::exchanges::general::order::get_order_trades::OrderTrade;
+use mmb_core::exchanges::general::request_type::RequestType;
 use mmb_core::exchanges::hosts::Hosts;
 use mmb_core::exchanges::rest_client::{
     ErrorHandler, ErrorHandlerData, RestClient, RestResponse, UriBuilder,
 };
 use mmb_core::exchanges::timeouts::timeout_manager::TimeoutManager;
+use mmb_core::exchanges::traffic::recorder::TrafficRecorder;
 use mmb_core::exchanges::traits::{ExchangeClientBuilder, ExchangeError};
 use mmb_core::exchanges::traits::{
     ExchangeClientBuilderResult, HandleOrderFilledCb, HandleTradeCb, OrderCancelledCb,
@@ -38,12 +47,16 @@ use mmb_core::exchanges::traits::{
 };
 use mmb_core::exchanges::{
     general::features::{ExchangeFeatures, OpenOrdersType},
+    timeouts::request_weight_limit::RequestWeightLimit,
     timeouts::requests_timeout_manager_factory::RequestTimeoutArguments,
 };
 use mmb_core::lifecycle::app_lifetime_manager::AppLifetimeManager;
 use mmb_core::settings::ExchangeSettings;
 use mmb_domain::events::AllowedEventSourceType;
-use mmb_domain::events::{ExchangeBalance, ExchangeBalancesAndPositions, ExchangeEvent, TradeId};
+use mmb_domain::events::{
+    BalanceUpdateEvent, ExchangeBalance, ExchangeBalancesAndPositions, ExchangeEvent,
+    LiquidationPriceEvent, TradeId,
+};
 use mmb_domain::exchanges::symbol::{Precision, Symbol};
 use mmb_domain::market::{CurrencyCode, CurrencyId, CurrencyPair, ExchangeErrorType, ExchangeId};
 use mmb_domain::market::{ExchangeAccountId, SpecificCurrencyPair};
@@ -51,13 +64,19 @@ use mmb_domain::order::fill::{EventSourceType, OrderFillType};
 use mmb_domain::order::pool::{OrderRef, OrdersPool};
 use mmb_domain::order::snapshot::*;
 use mmb_domain::order::snapshot::{Amount, Price};
-use mmb_domain::position::ActivePosition;
+use mmb_domain::position::{ActivePosition, DerivativePosition};
 use mmb_utils::value_to_decimal::GetOrErr;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
 use serde::{Deserialize, Serialize};
 use sha2::digest::generic_array::GenericArray;
 
 const LISTEN_KEY: &str = "listenKey";
 
+const USED_WEIGHT_1M_HEADER: &str = "x-mbx-used-weight-1m";
+const ORDER_COUNT_10S_HEADER: &str = "x-mbx-order-count-10s";
+const ORDER_COUNT_1D_HEADER: &str = "x-mbx-order-count-1d";
+
 #[derive(Default)]
 pub struct ErrorHandlerBinance;
 
@@ -84,6 +103,11 @@ impl ErrorHandler for ErrorHandlerBinance {
             ))
         })?;
 
+        // some futures endpoints (e.g. changing margin type) return code 200 on success
+        if error.code == 200 {
+            return Ok(());
+        }
+
         Err(ExchangeError::new(
             ExchangeErrorType::Unknown,
             error.msg,
@@ -142,9 +166,44 @@ pub struct Binance {
     pub(super) is_reducing_market_data: bool,
 
     pub(super) rest_client: RestClient<ErrorHandlerBinance>,
+    // Server clock for request timestamps
+    pub(super) clock: Arc<ExchangeClock>,
 
     // NOTE: None when websocket is disconnected
     pub(super) listen_key: RwLock<Option<String>>,
+
+    // Last known futures positions. Websocket account updates don't contain liquidation price
+    // and leverage, so they are taken from here
+    pub(super) positions: DashMap<CurrencyPair, DerivativePosition>,
+
+    // Synchronization of order books received from diff depth stream
+    pub(super) order_book_synchronizers: DashMap<CurrencyPair, OrderBookSynchronizer>,
+    pub(super) order_book_synchronization_sender: mpsc::UnboundedSender<CurrencyPair>,
+    pub(super) order_book_synchronization_receiver:
+        Mutex<Option<mpsc::UnboundedReceiver<CurrencyPair>>>,
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+pub enum BinanceMarginType {
+    Isolated,
+    Crossed,
+}
+
+impl BinanceMarginType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BinanceMarginType::Isolated => "ISOLATED",
+            BinanceMarginType::Crossed => "CROSSED",
+        }
+    }
+
+    pub(super) fn from_position_margin_type(margin_type: &str) -> Result<Self> {
+        match margin_type {
+            "isolated" | "ISOLATED" => Ok(BinanceMarginType::Isolated),
+            "cross" | "crossed" | "CROSSED" => Ok(BinanceMarginType::Crossed),
+            _ => bail!("Unknown margin type {margin_type}"),
+        }
+    }
 }
 
 impl Binance {
@@ -179,15 +238,32 @@ impl Binance {
         timeout_manager: Arc<TimeoutManager>,
         is_reducing_market_data: bool,
         empty_response_is_ok: bool,
-    ) -> Self {
+    ) -> Result<Self> {
         let is_reducing_market_data = settings
             .is_reducing_market_data
             .unwrap_or(is_reducing_market_data);
 
-        let hosts = Self::make_hosts(settings.is_margin_trading);
+        let hosts =
+            Self::make_hosts(&settings).context("Unable to make Binance hosts from settings")?;
         let exchange_account_id = settings.exchange_account_id;
+        let rest_client = RestClient::new(ErrorHandlerData::new(
+            empty_response_is_ok,
+            exchange_account_id,
+            ErrorHandlerBinance::default(),
+        ))
+        .with_network_settings(settings.network.clone())
+        .with_http_allowed(hosts.is_rest_over_http())
+        .with_traffic_recorder(TrafficRecorder::for_exchange_account(&settings))
+        .with_response_headers_handler({
+            let timeout_manager = timeout_manager.clone();
+            Box::new(move |headers| {
+                timeout_manager.sync_by_response_headers(exchange_account_id, headers)
+            })
+        });
+        let (order_book_synchronization_sender, order_book_synchronization_receiver) =
+            mpsc::unbounded_channel();
 
-        Self {
+        Ok(Self {
             id,
             order_created_callback: Box::new(|_, _, _| {}),
             order_cancelled_callback: Box::new(|_, _, _| {}),
@@ -206,28 +282,56 @@ impl Binance {
             hosts,
             events_channel,
             lifetime_manager,
-            rest_client: RestClient::new(ErrorHandlerData::new(
-                empty_response_is_ok,
-                exchange_account_id,
-                ErrorHandlerBinance::default(),
-            )),
+            rest_client,
+            clock: Default::default(),
             listen_key: Default::default(),
+            positions: Default::default(),
+            order_book_synchronizers: Default::default(),
+            order_book_synchronization_sender,
+            order_book_synchronization_receiver: Mutex::new(Some(
+                order_book_synchronization_receiver,
+            )),
+        })
+    }
+
+    pub fn make_hosts(settings: &ExchangeSettings) -> Result<Hosts> {
+        let is_margin_trading = settings.is_margin_trading;
+        Hosts::resolve(
+            settings,
+            || Self::mainnet_hosts(is_margin_trading),
+            || Some(Self::testnet_hosts(is_margin_trading)),
+        )
+    }
+
+    pub fn mainnet_hosts(is_margin_trading: bool) -> Hosts {
+        if is_margin_trading {
+            Hosts::new(
+                "wss://fstream.binance.com",
+                "wss://fstream.binance.com",
+                "https://fapi.binance.com",
+            )
+        } else {
+            Hosts::new(
+                "wss://stream.binance.com:9443",
+                "wss://stream.binance.com:9443",
+                "https://api.binance.com",
+            )
         }
     }
 
-    pub fn make_hosts(is_margin_trading: bool) -> Hosts {
+    pub fn testnet_hosts(is_margin_trading: bool) -> Hosts {
         if is_margin_trading {
-            Hosts {
-                web_socket_host: "wss://fstream.binance.com",
-                web_socket2_host: "wss://fstream.binance.com",
-                rest_host: "https://fapi.binance.com",
-            }
+            Hosts::new(
+                "wss://stream.binancefuture.com",
+                "wss://stream.binancefuture.com",
+                "https://testnet.binancefuture.com",
+            )
         } else {
-            Hosts {
-                web_socket_host: "wss://stream.binance.com:9443",
-                web_socket2_host: "wss://stream.binance.com:9443",
-                rest_host: "https://api.binance.com",
-            }
+            Hosts::new(
+                "wss://testnet.binance.vision",
+                "wss://testnet.binance.vision",
+                "https://testnet.binance.vision",
+            )
         }
     }
 
@@ -309,7 +413,7 @@ impl Binance {
     }
 
     pub(super) fn add_authentification(&self, builder: &mut UriBuilder) {
-        let time_stamp = get_current_milliseconds();
+        let time_stamp = self.clock.now().timestamp_millis();
         builder.add_kv("timestamp", time_stamp);
 
         self.write_signature_to_builder(builder);
@@ -395,7 +499,8 @@ impl Binance {
                 // We get notification of rejected orders from the rest responses
             }
             "EXPIRED" => match time_in_force {
-                "GTX" => {
+                // unfilled part of order was expired according to time in force
+                "GTX" | "IOC" | "FOK" | "GTD" => {
                     (self.order_cancelled_callback)(
                         client_order_id.into(),
                         exchange_order_id.into(),
@@ -420,6 +525,142 @@ impl Binance {
         Ok(())
     }
 
+    /// Handle `ACCOUNT_UPDATE` event of futures user data stream.
+    /// The event contains only changed balances and positions
+    pub(super) fn handle_account_update(&self, json_response: &Value) -> Result<()> {
+        let account_update: BinanceAccountUpdate =
+            serde_json::from_value(json_response["a"].clone())
+                .context("Unable to parse ACCOUNT_UPDATE event")?;
+
+        let balances = account_update
+            .balances
+            .iter()
+            .filter_map(|balance| {
+                self.get_currency_code(&balance.asset.as_str().into())
+                    .map(|currency_code| ExchangeBalance {
+                        currency_code,
+                        balance: balance.wallet_balance,
+                    })
+            })
+            .collect_vec();
+
+        let mut positions = Vec::with_capacity(account_update.positions.len());
+        for raw_position in account_update.positions {
+            let currency_pair =
+                match self.get_unified_currency_pair(&raw_position.specific_currency_pair) {
+                    Ok(currency_pair) => currency_pair,
+                    Err(_) => continue,
+                };
+
+            // liquidation price and leverage aren't sent in ACCOUNT_UPDATE, so last known ones are used
+            let (liquidation_price, leverage) = self
+                .positions
+                .get(&currency_pair)
+                .map(|x| (x.liquidation_price, x.leverage))
+                .unwrap_or((dec!(0), dec!(1)));
+            let liquidation_price = match raw_position.position_amount.is_zero() {
+                true => dec!(0),
+                false => liquidation_price,
+            };
+
+            let position = DerivativePosition::new(
+                currency_pair,
+                raw_position.position_amount,
+                Some(get_position_side(
+                    &raw_position.position_side,
+                    raw_position.position_amount,
+                )),
+                raw_position.entry_price,
+                liquidation_price,
+                leverage,
+            );
+            self.positions.insert(currency_pair, position.clone());
+
+            self.send_liquidation_price(&position)?;
+            positions.push(position);
+        }
+
+        self.send_balance_update(balances, positions)
+    }
+
+    /// Handle `ACCOUNT_CONFIG_UPDATE` event of futures user data stream which is sent when leverage is changed
+    pub(super) fn handle_account_config_update(&self, json_response: &Value) -> Result<()> {
+        let config = &json_response["ac"];
+        if config.is_null() {
+            // multi-assets mode changing doesn't affect positions
+            return Ok(());
+        }
+
+        let specific_currency_pair = config["s"]
+            .as_str()
+            .ok_or_else(|| anyhow!("Unable to parse symbol of ACCOUNT_CONFIG_UPDATE"))?;
+        let leverage = config["l"]
+            .as_u64()
+            .ok_or_else(|| anyhow!("Unable to parse leverage of ACCOUNT_CONFIG_UPDATE"))?;
+
+        let currency_pair = self.get_unified_currency_pair(&specific_currency_pair.into())?;
+        let position = {
+            let mut position = self.positions.entry(currency_pair).or_insert_with(|| {
+                DerivativePosition::new(
+                    currency_pair,
+                    dec!(0),
+                    Some(OrderSide::Buy),
+                    dec!(0),
+                    dec!(0),
+                    dec!(1),
+                )
+            });
+            position.leverage = leverage.into();
+            position.clone()
+        };
+
+        self.send_balance_update(Vec::new(), vec![position])
+    }
+
+    fn send_liquidation_price(&self, position: &DerivativePosition) -> Result<()> {
+        let side = match position.side {
+            Some(side) if !position.liquidation_price.is_zero() => side,
+            _ => return Ok(()),
+        };
+
+        let event = LiquidationPriceEvent::new(
+            Utc::now(),
+            self.id,
+            position.currency_pair,
+            position.liquidation_price,
+            position.average_entry_price,
+            side,
+        );
+
+        send_event(
+            &self.events_channel,
+            self.lifetime_manager.clone(),
+            self.id,
+            ExchangeEvent::LiquidationPrice(event),
+        )
+    }
+
+    fn send_balance_update(
+        &self,
+        balances: Vec<ExchangeBalance>,
+        positions: Vec<DerivativePosition>,
+    ) -> Result<()> {
+        let event = BalanceUpdateEvent {
+            exchange_account_id: self.id,
+            balances_and_positions: ExchangeBalancesAndPositions {
+                balances,
+                positions: Some(positions),
+            },
+        };
+
+        send_event(
+            &self.events_channel,
+            self.lifetime_manager.clone(),
+            self.id,
+            ExchangeEvent::BalanceUpdate(event),
+        )
+    }
+
     pub(crate) fn get_currency_code(&self, currency_id: &CurrencyId) -> Option<CurrencyCode> {
         self.supported_currencies
             .get(currency_id)
@@ -544,6 +785,22 @@ impl Binance {
         }
     }
 
+    pub(super) fn get_futures_exchange_balances_and_positions(
+        &self,
+        raw_balances: Vec<BinanceMarginBalances>,
+        raw_positions: &[BinancePosition],
+    ) -> ExchangeBalancesAndPositions {
+        let positions = raw_positions
+            .iter()
+            .filter_map(|x| self.binance_position_to_derivative_position(x))
+            .collect_vec();
+
+        ExchangeBalancesAndPositions {
+            positions: Some(positions),
+            ..self.get_margin_exchange_balances_and_positions(raw_balances)
+        }
+    }
+
     pub(super) fn get_order_id(
         &self,
         response: &RestResponse,
@@ -656,24 +913,28 @@ impl Binance {
         position: &ActivePosition,
         price: Option<Price>,
     ) -> Result<RestResponse, ExchangeError> {
-        let side = match position.derivative.side {
-            Some(side) => side.change_side().as_str(),
-            None => "0", // unknown side
-        };
+        let side = position
+            .derivative
+            .side
+            .context("Unable to close position with unknown side")?
+            .change_side();
+        let specific_currency_pair =
+            self.get_specific_currency_pair(position.derivative.currency_pair);
 
         let mut builder = UriBuilder::from_path("/fapi/v1/order");
-        builder.add_kv("leverage", &position.derivative.leverage);
+        builder.add_kv("symbol", specific_currency_pair);
+        builder.add_kv("side", get_server_order_side(side));
         builder.add_kv("positionSide", "BOTH");
-        builder.add_kv("quantity", &position.derivative.position.abs());
-        builder.add_kv("side", side);
-        builder.add_kv("symbol", &position.derivative.currency_pair);
+        builder.add_kv("quantity", position.derivative.position.abs());
+        builder.add_kv("reduceOnly", "true");
 
         match price {
             Some(price) => {
-                builder.add_kv("type", "MARKET");
-                builder.add_kv("price", &price);
+                builder.add_kv("type", "LIMIT");
+                builder.add_kv("price", price);
+                builder.add_kv("timeInForce", "GTC");
             }
-            None => builder.add_kv("type", "LIMIT"),
+            None => builder.add_kv("type", "MARKET"),
         }
 
         self.add_authentification(&mut builder);
@@ -688,8 +949,14 @@ impl Binance {
     }
 
     #[named]
-    pub(super) async fn request_get_position(&self) -> Result<RestResponse, ExchangeError> {
+    pub(super) async fn request_get_position(
+        &self,
+        currency_pair: Option<CurrencyPair>,
+    ) -> Result<RestResponse, ExchangeError> {
         let mut builder = UriBuilder::from_path("/fapi/v2/positionRisk");
+        if let Some(currency_pair) = currency_pair {
+            builder.add_kv("symbol", self.get_specific_currency_pair(currency_pair));
+        }
         self.add_authentification(&mut builder);
 
         let uri = builder.build_uri(self.hosts.rest_uri_host(), true);
@@ -700,6 +967,137 @@ impl Binance {
             .await
     }
 
+    /// Parse positions from `positionRisk` response and remember them as last known positions
+    pub(super) fn parse_positions(&self, response: &RestResponse) -> Result<Vec<BinancePosition>> {
+        let binance_positions: Vec<BinancePosition> = serde_json::from_str(&response.content)
+            .context("Unable to parse response content for positionRisk request")?;
+
+        for binance_position in &binance_positions {
+            if let Some(position) = self.binance_position_to_derivative_position(binance_position) {
+                self.positions.insert(position.currency_pair, position);
+            }
+        }
+
+        Ok(binance_positions)
+    }
+
+    /// Change initial leverage of futures positions by currency pair.
+    /// Returns leverage accepted by Binance
+    #[named]
+    pub async fn set_leverage(
+        &self,
+        currency_pair: CurrencyPair,
+        leverage: Decimal,
+    ) -> Result<Decimal> {
+        ensure!(
+            self.settings.is_margin_trading,
+            "Leverage can be changed only for futures trading on {}",
+            self.id
+        );
+        self.reserve_request(RequestType::SetLeverage).await;
+
+        let mut builder = UriBuilder::from_path("/fapi/v1/leverage");
+        builder.add_kv("symbol", self.get_specific_currency_pair(currency_pair));
+        builder.add_kv("leverage", leverage);
+        self.add_authentification(&mut builder);
+
+        let (uri, query) = builder.build_uri_and_query(self.hosts.rest_uri_host(), false);
+
+        let log_args = format!("Set leverage {leverage} for {currency_pair}");
+        let api_key = &self.settings.api_key;
+        let response = self
+            .rest_client
+            .post(uri, api_key, query, function_name!(), log_args)
+            .await?;
+
+        #[derive(Deserialize)]
+        struct LeverageResponse {
+            leverage: Decimal,
+        }
+
+        let leverage = serde_json::from_str::<LeverageResponse>(&response.content)
+            .context("Unable to parse response content for set leverage request")?
+            .leverage;
+
+        if let Some(mut position) = self.positions.get_mut(&currency_pair) {
+            position.leverage = leverage;
+        }
+
+        Ok(leverage)
+    }
+
+    /// Change margin type of futures positions by currency pair
+    #[named]
+    pub async fn set_margin_type(
+        &self,
+        currency_pair: CurrencyPair,
+        margin_type: BinanceMarginType,
+    ) -> Result<()> {
+        // -4046 "No need to change margin type."
+        const MARGIN_TYPE_IS_ALREADY_SET: i64 = -4046;
+
+        ensure!(
+            self.settings.is_margin_trading,
+            "Margin type can be changed only for futures trading on {}",
+            self.id
+        );
+        self.reserve_request(RequestType::SetLeverage).await;
+
+        let mut builder = UriBuilder::from_path("/fapi/v1/marginType");
+        builder.add_kv("symbol", self.get_specific_currency_pair(currency_pair));
+        builder.add_kv("marginType", margin_type.as_str());
+        self.add_authentification(&mut builder);
+
+        let (uri, query) = builder.build_uri_and_query(self.hosts.rest_uri_host(), false);
+
+        let log_args = format!("Set margin type {margin_type:?} for {currency_pair}");
+        let api_key = &self.settings.api_key;
+        match self
+            .rest_client
+            .post(uri, api_key, query, function_name!(), log_args)
+            .await
+        {
+            Ok(_) => Ok(()),
+            Err(error) if error.code == Some(MARGIN_TYPE_IS_ALREADY_SET) => Ok(()),
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    /// Current leverage and margin type of futures positions by currency pair
+    pub async fn get_leverage_and_margin_type(
+        &self,
+        currency_pair: CurrencyPair,
+    ) -> Result<(Decimal, BinanceMarginType)> {
+        ensure!(
+            self.settings.is_margin_trading,
+            "Leverage is available only for futures trading on {}",
+            self.id
+        );
+        self.reserve_request(RequestType::GetActivePositions).await;
+
+        let response = self.request_get_position(Some(currency_pair)).await?;
+        let binance_position = self
+            .parse_positions(&response)?
+            .into_iter()
+            .next()
+            .with_context(|| format!("There is no position for {currency_pair} on {}", self.id))?;
+
+        let margin_type =
+            BinanceMarginType::from_position_margin_type(&binance_position.margin_type)?;
+        Ok((binance_position.leverage, margin_type))
+    }
+
+    pub(super) async fn reserve_request(&self, request_type: RequestType) {
+        self.timeout_manager
+            .reserve_when_available(
+                self.settings.exchange_account_id,
+                request_type,
+                None,
+                self.lifetime_manager.stop_token(),
+            )
+            .await;
+    }
+
     #[named]
     pub(super) async fn request_get_balance(&self) -> Result<RestResponse, ExchangeError> {
         let path = self.get_uri_path("/fapi/v2/account", "/api/v3/account");
@@ -848,7 +1246,14 @@ impl Binance {
         &self,
         order: &OrderRef,
     ) -> Result<RestResponse, ExchangeError> {
-        let (header, price) = order.fn_ref(|order| (order.header.clone(), order.price()));
+        let (header, price, stop_loss_price, trailing_stop_delta) = order.fn_ref(|order| {
+            (
+                order.header.clone(),
+                order.price(),
+                order.props.stop_loss_price,
+                order.props.trailing_stop_delta,
+            )
+        });
         let specific_currency_pair = self.get_specific_currency_pair(header.currency_pair);
         let is_margin_trading = self.settings.is_margin_trading;
 
@@ -856,20 +1261,63 @@ impl Binance {
         let mut builder = UriBuilder::from_path(path);
         builder.add_kv("symbol", specific_currency_pair);
         builder.add_kv("side", get_server_order_side(header.side));
-        builder.add_kv("type", get_server_order_type(&header, is_margin_trading));
+        let server_order_type = get_server_order_type(&header, price, is_margin_trading)?;
+        builder.add_kv("type", server_order_type);
         builder.add_kv("quantity", &header.amount);
         builder.add_kv("newClientOrderId", &header.client_order_id);
 
-        if header.order_type != OrderType::Market {
+        let is_limit = is_limit_server_order_type(server_order_type, is_margin_trading);
+        if is_limit {
             builder.add_kv("price", &price);
         }
 
-        if header.order_type != OrderType::Market
-            && header.execution_type != OrderExecutionType::MakerOnly
+        match header.order_type {
+            OrderType::ClosePosition => builder.add_kv("reduceOnly", "true"),
+            OrderType::StopLoss | OrderType::TakeProfit => {
+                if stop_loss_price <= dec!(0) {
+                    return Err(ExchangeError::new(
+                        ExchangeErrorType::InvalidOrder,
+                        format!("Stop price should be positive for {header:?}"),
+                        None,
+                    ));
+                }
+                builder.add_kv("stopPrice", stop_loss_price);
+            }
+            OrderType::TrailingStop => {
+                if trailing_stop_delta <= dec!(0) {
+                    return Err(ExchangeError::new(
+                        ExchangeErrorType::InvalidOrder,
+                        format!("Trailing stop delta should be positive for {header:?}"),
+                        None,
+                    ));
+                }
+
+                match is_margin_trading {
+                    true => {
+                        builder.add_kv("callbackRate", trailing_stop_delta);
+                        if stop_loss_price > dec!(0) {
+                            builder.add_kv("activationPrice", stop_loss_price);
+                        }
+                    }
+                    false => {
+                        // spot trailing delta is specified in basis points
+                        let trailing_delta = (trailing_stop_delta * dec!(100)).round();
+                        builder.add_kv("trailingDelta", trailing_delta);
+                        if stop_loss_price > dec!(0) {
+                            builder.add_kv("stopPrice", stop_loss_price);
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+
+        if let Some(time_in_force) = get_server_time_in_force(&header, is_limit, is_margin_trading)?
         {
-            builder.add_kv("timeInForce", "GTC");
-        } else if header.execution_type == OrderExecutionType::MakerOnly && is_margin_trading {
-            builder.add_kv("timeInForce", "GTX");
+            builder.add_kv("timeInForce", time_in_force);
+        }
+        if let OrderTimeInForce::GoodTillDate(expire_time) = header.time_in_force {
+            builder.add_kv("goodTillDate", expire_time.timestamp_millis());
         }
 
         self.add_authentification(&mut builder);
@@ -883,6 +1331,29 @@ impl Binance {
             .await
     }
 
+    #[named]
+    pub(super) async fn request_server_time(&self) -> Result<RestResponse, ExchangeError> {
+        let path = self.get_uri_path("/fapi/v1/time", "/api/v3/time");
+        let builder = UriBuilder::from_path(path);
+        let uri = builder.build_uri(self.hosts.rest_uri_host(), false);
+
+        let api_key = &self.settings.api_key;
+        self.rest_client
+            .get(uri, api_key, function_name!(), "".to_string())
+            .await
+    }
+
+    pub(super) fn parse_server_time(response: &RestResponse) -> Result<DateTime> {
+        let data: Value = serde_json::from_str(&response.content)
+            .context("Unable to parse server time response for Binance")?;
+
+        let server_time = data["serverTime"]
+            .as_u64()
+            .context("Unable to parse serverTime field for Binance")?;
+
+        Ok(u64_to_date_time(server_time))
+    }
+
     #[named]
     pub(super) async fn request_all_symbols(&self) -> Result<RestResponse, ExchangeError> {
         let path = self.get_uri_path("/fapi/v1/exchangeInfo", "/api/v3/exchangeInfo");
@@ -1028,6 +1499,16 @@ pub(super) fn get_server_order_side(side: OrderSide) -> &'static str {
     }
 }
 
+/// Side of futures position. `BOTH` is used in one-way mode where side is determined by sign of amount
+pub(super) fn get_position_side(position_side: &str, position_amount: Amount) -> OrderSide {
+    match position_side {
+        "LONG" => OrderSide::Buy,
+        "SHORT" => OrderSide::Sell,
+        _ if position_amount.is_sign_negative() => OrderSide::Sell,
+        _ => OrderSide::Buy,
+    }
+}
+
 pub(super) fn get_local_order_side(side: &str) -> OrderSide {
     match side {
         "BUY" => OrderSide::Buy,
@@ -1046,15 +1527,85 @@ fn get_local_order_status(status: &str) -> OrderStatus {
     }
 }
 
-pub(super) fn get_server_order_type(header: &OrderHeader, is_margin_trading: bool) -> &'static str {
+/// Conditional orders with zero price are executed by market when triggered
+pub(super) fn get_server_order_type(
+    header: &OrderHeader,
+    price: Price,
+    is_margin_trading: bool,
+) -> Result<&'static str> {
     if header.execution_type == OrderExecutionType::MakerOnly && !is_margin_trading {
-        return "LIMIT_MAKER";
+        return Ok("LIMIT_MAKER");
+    }
+
+    let is_market_triggered = price.is_zero();
+    match (header.order_type, is_margin_trading) {
+        (OrderType::Limit, _) => Ok("LIMIT"),
+        (OrderType::Market, _) => Ok("MARKET"),
+        (OrderType::ClosePosition, true) => Ok("MARKET"),
+        (OrderType::StopLoss, true) if is_market_triggered => Ok("STOP_MARKET"),
+        (OrderType::StopLoss, true) => Ok("STOP"),
+        (OrderType::StopLoss, false) if is_market_triggered => Ok("STOP_LOSS"),
+        (OrderType::StopLoss, false) => Ok("STOP_LOSS_LIMIT"),
+        (OrderType::TakeProfit, true) if is_market_triggered => Ok("TAKE_PROFIT_MARKET"),
+        (OrderType::TakeProfit, true) => Ok("TAKE_PROFIT"),
+        (OrderType::TakeProfit, false) if is_market_triggered => Ok("TAKE_PROFIT"),
+        (OrderType::TakeProfit, false) => Ok("TAKE_PROFIT_LIMIT"),
+        (OrderType::TrailingStop, true) => Ok("TRAILING_STOP_MARKET"),
+        // spot trailing stops are stop loss orders with trailing delta
+        (OrderType::TrailingStop, false) if is_market_triggered => Ok("STOP_LOSS"),
+        (OrderType::TrailingStop, false) => Ok("STOP_LOSS_LIMIT"),
+        (unexpected_variant, _) => bail!(
+            "Order type {unexpected_variant:?} is not supported by Binance {}",
+            match is_margin_trading {
+                true => "futures",
+                false => "spot",
+            }
+        ),
+    }
+}
+
+/// Returns None for orders which shouldn't have time in force in request
+pub(super) fn get_server_time_in_force(
+    header: &OrderHeader,
+    is_limit: bool,
+    is_margin_trading: bool,
+) -> Result<Option<&'static str>> {
+    let time_in_force = header.time_in_force;
+    if header.execution_type == OrderExecutionType::MakerOnly {
+        ensure!(
+            time_in_force == OrderTimeInForce::GoodTillCancelled,
+            "Maker only order can't have time in force {time_in_force:?}"
+        );
+
+        // spot maker only orders are LIMIT_MAKER orders without time in force
+        return Ok(is_margin_trading.then_some("GTX"));
+    }
+
+    if !is_limit {
+        ensure!(
+            time_in_force == OrderTimeInForce::GoodTillCancelled,
+            "Time in force {time_in_force:?} is supported only for limit orders by Binance"
+        );
+        return Ok(None);
+    }
+
+    match time_in_force {
+        OrderTimeInForce::GoodTillCancelled => Ok(Some("GTC")),
+        OrderTimeInForce::ImmediateOrCancel => Ok(Some("IOC")),
+        OrderTimeInForce::FillOrKill => Ok(Some("FOK")),
+        OrderTimeInForce::GoodTillDate(_) if is_margin_trading => Ok(Some("GTD")),
+        OrderTimeInForce::GoodTillDate(_) => bail!("GTD orders are not supported by Binance spot"),
     }
+}
 
-    match header.order_type {
-        OrderType::Limit => "LIMIT",
-        OrderType::Market => "MARKET",
-        unexpected_variant => panic!("{unexpected_variant:?} are not expected"),
+/// Order types which require price in request
+fn is_limit_server_order_type(server_order_type: &str, is_margin_trading: bool) -> bool {
+    match is_margin_trading {
+        true => matches!(server_order_type, "LIMIT" | "STOP" | "TAKE_PROFIT"),
+        false => matches!(
+            server_order_type,
+            "LIMIT" | "LIMIT_MAKER" | "STOP_LOSS_LIMIT" | "TAKE_PROFIT_LIMIT"
+        ),
     }
 }
 
@@ -1068,11 +1619,30 @@ impl ExchangeClientBuilder for BinanceBuilder {
         lifetime_manager: Arc<AppLifetimeManager>,
         timeout_manager: Arc<TimeoutManager>,
         _orders: Arc<OrdersPool>,
-    ) -> ExchangeClientBuilderResult {
+    ) -> Result<ExchangeClientBuilderResult> {
         let exchange_account_id = exchange_settings.exchange_account_id;
         let empty_response_is_ok = false;
 
-        ExchangeClientBuilderResult {
+        let mut features = ExchangeFeatures::new(
+            OpenOrdersType::AllCurrencyPair,
+            RestFillsFeatures::new(RestFillsType::None),
+            OrderFeatures {
+                supports_get_order_info_by_client_order_id: true,
+                supports_stop_loss_order: true,
+                ..OrderFeatures::default()
+            },
+            OrderTradeOption::default(),
+            WebSocketOptions::default(),
+            empty_response_is_ok,
+            AllowedEventSourceType::All,
+            AllowedEventSourceType::All,
+            AllowedEventSourceType::All,
+        );
+        if exchange_settings.is_margin_trading {
+            features.balance_position_option = BalancePositionOption::SingleRequest;
+        }
+
+        Ok(ExchangeClientBuilderResult {
             client: Box::new(Binance::new(
                 exchange_account_id,
                 exchange_settings,
@@ -1081,26 +1651,36 @@ impl ExchangeClientBuilder for BinanceBuilder {
                 timeout_manager,
                 false,
                 empty_response_is_ok,
-            )) as BoxExchangeClient,
-            features: ExchangeFeatures::new(
-                OpenOrdersType::AllCurrencyPair,
-                RestFillsFeatures::new(RestFillsType::None),
-                OrderFeatures {
-                    supports_get_order_info_by_client_order_id: true,
-                    ..OrderFeatures::default()
-                },
-                OrderTradeOption::default(),
-                WebSocketOptions::default(),
-                empty_response_is_ok,
-                AllowedEventSourceType::All,
-                AllowedEventSourceType::All,
-                AllowedEventSourceType::All,
-            ),
-        }
+            )?) as BoxExchangeClient,
+            features,
+        })
     }
 
     fn get_timeout_arguments(&self) -> RequestTimeoutArguments {
+        // Limits are common for spot and futures, so the lowest ones are used
         RequestTimeoutArguments::from_requests_per_minute(1200)
+            .with_weight_limit(
+                RequestWeightLimit::new("REQUEST_WEIGHT_1M", 1200, chrono::Duration::minutes(1))
+                    .with_weight(RequestType::GetOrderInfo, 4)
+                    .with_weight(RequestType::GetOpenOrders, 40)
+                    .with_weight(RequestType::GetBalance, 20)
+                    .with_weight(RequestType::GetOrderBook, 20)
+                    .with_weight(RequestType::GetMyTrades, 20)
+                    .with_weight(RequestType::GetActivePositions, 5)
+                    .with_used_weight_header(USED_WEIGHT_1M_HEADER),
+            )
+            .with_weight_limit(
+                RequestWeightLimit::new("ORDERS_10S", 50, chrono::Duration::seconds(10))
+                    .with_default_weight(0)
+                    .with_weight(RequestType::CreateOrder, 1)
+                    .with_used_weight_header(ORDER_COUNT_10S_HEADER),
+            )
+            .with_weight_limit(
+                RequestWeightLimit::new("ORDERS_1D", 160_000, chrono::Duration::days(1))
+                    .with_default_weight(0)
+                    .with_weight(RequestType::CreateOrder, 1)
+                    .with_used_weight_header(ORDER_COUNT_1D_HEADER),
+            )
     }
 
     fn get_exchange_id(&self) -> ExchangeId {
@@ -1111,10 +1691,14 @@ impl ExchangeClientBuilder for BinanceBuilder {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use mmb_core::exchanges::exchange_clock::ClockMeasurement;
     use mmb_core::exchanges::timeouts::requests_timeout_manager_factory::RequestsTimeoutManagerFactory;
+    use mmb_core::exchanges::traffic::replay::TrafficSession;
     use mmb_core::lifecycle::launcher::EngineBuildConfig;
+    use mmb_core::settings::ExchangeEnvironment;
     use mmb_utils::cancellation_token::CancellationToken;
     use mmb_utils::hashmap;
+    use serde_json::json;
 
     pub(crate) fn get_timeout_manager(
         exchange_account_id: ExchangeAccountId,
@@ -1153,7 +1737,8 @@ mod tests {
             get_timeout_manager(exchange_account_id),
             false,
             false,
-        );
+        )
+        .expect("in test");
 
         let mut builder = UriBuilder::from_path("/test");
         builder.add_kv("symbol", "LTCBTC");
@@ -1175,4 +1760,340 @@ mod tests {
 
         assert_eq!(signature_value, expected);
     }
+
+    #[test]
+    fn custom_environment_without_hosts_is_not_created() {
+        let exchange_account_id: ExchangeAccountId = "Binance_0".parse().expect("in test");
+        let mut settings =
+            ExchangeSettings::new_short(exchange_account_id, "".into(), "".into(), false);
+        settings.environment = Some(ExchangeEnvironment::Custom);
+
+        let (tx, _) = broadcast::channel(10);
+        let result = Binance::new(
+            exchange_account_id,
+            settings,
+            tx,
+            AppLifetimeManager::new(CancellationToken::default()),
+            get_timeout_manager(exchange_account_id),
+            false,
+            false,
+        );
+
+        assert!(result.is_err());
+    }
+
+    fn create_futures_binance() -> (Binance, broadcast::Receiver<ExchangeEvent>) {
+        let exchange_account_id: ExchangeAccountId = "Binance_0".parse().expect("in test");
+        let settings = ExchangeSettings::new_short(exchange_account_id, "".into(), "".into(), true);
+
+        let (tx, rx) = broadcast::channel(10);
+        let binance = Binance::new(
+            exchange_account_id,
+            settings,
+            tx,
+            AppLifetimeManager::new(CancellationToken::default()),
+            get_timeout_manager(exchange_account_id),
+            false,
+            false,
+        )
+        .expect("in test");
+
+        let currency_pair = CurrencyPair::from_codes("btc".into(), "usdt".into());
+        binance
+            .specific_to_unified
+            .write()
+            .insert("BTCUSDT".into(), currency_pair);
+        binance
+            .unified_to_specific
+            .write()
+            .insert(currency_pair, "BTCUSDT".into());
+        binance
+            .supported_currencies
+            .insert("USDT".into(), "usdt".into());
+
+        (binance, rx)
+    }
+
+    fn order_header(order_type: OrderType) -> Arc<OrderHeader> {
+        OrderHeader::new(
+            "test".into(),
+            Utc::now(),
+            "Binance_0".parse().expect("in test"),
+            CurrencyPair::from_codes("btc".into(), "usdt".into()),
+            order_type,
+            OrderSide::Buy,
+            dec!(1),
+            OrderExecutionType::None,
+            None,
+            None,
+            "test".to_string(),
+        )
+    }
+
+    #[test]
+    fn server_order_type_for_unsupported_order_type_is_error() {
+        let header = order_header(OrderType::Liquidation);
+
+        assert!(get_server_order_type(&header, dec!(0), true).is_err());
+        assert!(get_server_order_type(&header, dec!(0), false).is_err());
+    }
+
+    #[test]
+    fn server_order_type_for_close_position_on_futures() {
+        let header = order_header(OrderType::ClosePosition);
+
+        assert_eq!(
+            get_server_order_type(&header, dec!(0), true).expect("in test"),
+            "MARKET"
+        );
+        assert!(get_server_order_type(&header, dec!(0), false).is_err());
+    }
+
+    #[test]
+    fn server_time_in_force() {
+        let header = order_header(OrderType::Limit);
+        let with_time_in_force = |time_in_force| header.with_time_in_force(time_in_force);
+
+        let cases = [
+            (OrderTimeInForce::GoodTillCancelled, false, Some("GTC")),
+            (OrderTimeInForce::ImmediateOrCancel, false, Some("IOC")),
+            (OrderTimeInForce::FillOrKill, true, Some("FOK")),
+            (
+                OrderTimeInForce::GoodTillDate(Utc::now()),
+                true,
+                Some("GTD"),
+            ),
+        ];
+        for (time_in_force, is_margin_trading, expected) in cases {
+            let header = with_time_in_force(time_in_force);
+            assert_eq!(
+                get_server_time_in_force(&header, true, is_margin_trading).expect("in test"),
+                expected,
+                "{time_in_force:?}"
+            );
+        }
+
+        let gtd = with_time_in_force(OrderTimeInForce::GoodTillDate(Utc::now()));
+        assert!(get_server_time_in_force(&gtd, true, false).is_err());
+
+        let ioc_market =
+            order_header(OrderType::Market).with_time_in_force(OrderTimeInForce::ImmediateOrCancel);
+        assert!(get_server_time_in_force(&ioc_market, false, true).is_err());
+    }
+
+    #[test]
+    fn expired_ioc_order_is_cancelled() {
+        let (mut binance, _rx) = create_futures_binance();
+        let cancelled_orders = Arc::new(Mutex::new(Vec::new()));
+        let cancelled_orders_cloned = cancelled_orders.clone();
+        binance.order_cancelled_callback =
+            Box::new(move |client_order_id, exchange_order_id, _| {
+                cancelled_orders_cloned
+                    .lock()
+                    .push((client_order_id, exchange_order_id))
+            });
+
+        let json_response = json!({
+            "c": "test",
+            "C": "",
+            "i": 42,
+            "x": "EXPIRED",
+            "X": "EXPIRED",
+            "f": "IOC"
+        });
+        binance
+            .handle_order_fill("", json_response)
+            .expect("in test");
+
+        assert_eq!(*cancelled_orders.lock(), vec![("test".into(), "42".into())]);
+    }
+
+    #[test]
+    fn server_order_type_for_conditional_orders() {
+        let cases = [
+            (OrderType::StopLoss, dec!(10), false, "STOP_LOSS_LIMIT"),
+            (OrderType::StopLoss, dec!(0), false, "STOP_LOSS"),
+            (OrderType::StopLoss, dec!(10), true, "STOP"),
+            (OrderType::StopLoss, dec!(0), true, "STOP_MARKET"),
+            (OrderType::TakeProfit, dec!(10), false, "TAKE_PROFIT_LIMIT"),
+            (OrderType::TakeProfit, dec!(0), false, "TAKE_PROFIT"),
+            (OrderType::TakeProfit, dec!(10), true, "TAKE_PROFIT"),
+            (OrderType::TakeProfit, dec!(0), true, "TAKE_PROFIT_MARKET"),
+            (
+                OrderType::TrailingStop,
+                dec!(0),
+                true,
+                "TRAILING_STOP_MARKET",
+            ),
+            (OrderType::TrailingStop, dec!(0), false, "STOP_LOSS"),
+        ];
+
+        for (order_type, price, is_margin_trading, expected) in cases {
+            let header = order_header(order_type);
+            let server_order_type =
+                get_server_order_type(&header, price, is_margin_trading).expect("in test");
+
+            assert_eq!(server_order_type, expected, "{order_type:?} {price}");
+            assert_eq!(
+                is_limit_server_order_type(server_order_type, is_margin_trading),
+                !price.is_zero(),
+                "{order_type:?} {price}"
+            );
+        }
+    }
+
+    #[test]
+    fn parse_positions() {
+        let (binance, _rx) = create_futures_binance();
+        let response = RestResponse {
+            status: hyper::StatusCode::OK,
+            content: r#"[{"symbol":"BTCUSDT","positionAmt":"-0.010","entryPrice":"20000.0","markPrice":"20100.0","unRealizedProfit":"-1.0","liquidationPrice":"29500.5","leverage":"10","maxNotionalValue":"250000","marginType":"isolated","isolatedMargin":"20.0","isAutoAddMargin":"false","positionSide":"BOTH","notional":"-201.0","isolatedWallet":"21.0","updateTime":1660000000000}]"#.to_owned(),
+        };
+
+        let positions = binance.parse_positions(&response).expect("in test");
+        let position = binance
+            .binance_position_to_derivative_position(&positions[0])
+            .expect("in test");
+
+        assert_eq!(position.position, dec!(-0.010));
+        assert_eq!(position.side, Some(OrderSide::Sell));
+        assert_eq!(position.average_entry_price, dec!(20000.0));
+        assert_eq!(position.liquidation_price, dec!(29500.5));
+        assert_eq!(position.leverage, dec!(10));
+        assert_eq!(
+            BinanceMarginType::from_position_margin_type(&positions[0].margin_type)
+                .expect("in test"),
+            BinanceMarginType::Isolated
+        );
+        assert!(binance.positions.contains_key(&position.currency_pair));
+    }
+
+    #[test]
+    fn account_update_sends_balance_and_liquidation_price() {
+        let (binance, mut rx) = create_futures_binance();
+        let currency_pair = CurrencyPair::from_codes("btc".into(), "usdt".into());
+        binance.positions.insert(
+            currency_pair,
+            DerivativePosition::new(
+                currency_pair,
+                dec!(0),
+                Some(OrderSide::Buy),
+                dec!(0),
+                dec!(15000),
+                dec!(20),
+            ),
+        );
+
+        let msg = r#"{"e":"ACCOUNT_UPDATE","E":1564745798939,"T":1564745798938,"a":{"m":"ORDER","B":[{"a":"USDT","wb":"122.12","cw":"100.12","bc":"0"}],"P":[{"s":"BTCUSDT","pa":"0.5","ep":"20000.0","cr":"200","up":"0","mt":"cross","iw":"0","ps":"BOTH"}]}}"#;
+        binance.on_websocket_message(msg).expect("in test");
+
+        match rx.try_recv().expect("in test") {
+            ExchangeEvent::LiquidationPrice(event) => {
+                assert_eq!(event.currency_pair, currency_pair);
+                assert_eq!(event.liq_price, dec!(15000));
+                assert_eq!(event.entry_price, dec!(20000.0));
+                assert_eq!(event.side, OrderSide::Buy);
+            }
+            event => panic!("Unexpected event {event:?}"),
+        }
+
+        match rx.try_recv().expect("in test") {
+            ExchangeEvent::BalanceUpdate(event) => {
+                let balances = event.balances_and_positions.balances;
+                assert_eq!(balances.len(), 1);
+                assert_eq!(balances[0].balance, dec!(122.12));
+
+                let positions = event.balances_and_positions.positions.expect("in test");
+                assert_eq!(positions.len(), 1);
+                assert_eq!(positions[0].position, dec!(0.5));
+                assert_eq!(positions[0].leverage, dec!(20));
+            }
+            event => panic!("Unexpected event {event:?}"),
+        }
+    }
+
+    #[test]
+    fn account_config_update_changes_leverage() {
+        let (binance, mut rx) = create_futures_binance();
+        let currency_pair = CurrencyPair::from_codes("btc".into(), "usdt".into());
+
+        let msg = r#"{"e":"ACCOUNT_CONFIG_UPDATE","E":1611646737479,"T":1611646737476,"ac":{"s":"BTCUSDT","l":25}}"#;
+        binance.on_websocket_message(msg).expect("in test");
+
+        match rx.try_recv().expect("in test") {
+            ExchangeEvent::BalanceUpdate(event) => {
+                let positions = event.balances_and_positions.positions.expect("in test");
+                assert_eq!(positions[0].currency_pair, currency_pair);
+                assert_eq!(positions[0].leverage, dec!(25));
+            }
+            event => panic!("Unexpected event {event:?}"),
+        }
+        assert_eq!(
+            binance
+                .positions
+                .get(&currency_pair)
+                .expect("in test")
+                .leverage,
+            dec!(25)
+        );
+    }
+
+    #[test]
+    fn request_timestamp_is_taken_from_server_clock() {
+        let (binance, _rx) = create_futures_binance();
+        let response = RestResponse {
+            status: hyper::StatusCode::OK,
+            content: r#"{"serverTime":1499827319559}"#.to_owned(),
+        };
+        let server_time = Binance::parse_server_time(&response).expect("in test");
+        assert_eq!(server_time.timestamp_millis(), 1499827319559);
+
+        let offset = chrono::Duration::hours(-1);
+        binance.clock.apply(ClockMeasurement {
+            offset,
+            round_trip_delay: chrono::Duration::zero(),
+        });
+
+        let mut builder = UriBuilder::from_path("/test");
+        let expected_time = (Utc::now() + offset).timestamp_millis();
+        binance.add_authentification(&mut builder);
+
+        let query = String::from_utf8(builder.query().to_vec()).expect("in test");
+        let timestamp: i64 = query
+            .strip_prefix("timestamp=")
+            .and_then(|x| x.split('&').next())
+            .expect("in test")
+            .parse()
+            .expect("in test");
+        assert!((timestamp - expected_time).abs() < 1000);
+    }
+
+    #[test]
+    fn replay_captured_session() {
+        let (binance, mut rx) = create_futures_binance();
+        let captured = r#"
+{"time":"2022-05-01T10:00:00Z","type":"RestResponse","request_id":"67e55044-10b1-426f-9247-bb680e5fe0c8","action_name":"request_server_time","status":200,"content":"{\"serverTime\":1499827319559}"}
+{"time":"2022-05-01T10:00:01Z","type":"WebSocketMessage","role":"Secondary","message":"{\"e\":\"ACCOUNT_CONFIG_UPDATE\",\"E\":1611646737479,\"T\":1611646737476,\"ac\":{\"s\":\"BTCUSDT\",\"l\":25}}"}
+"#;
+        let session = TrafficSession::parse(captured).expect("in test");
+
+        let mut server_times = Vec::new();
+        session
+            .replay(&binance, |action_name, response| {
+                assert_eq!(action_name, "request_server_time");
+                server_times.push(Binance::parse_server_time(response)?);
+                Ok(())
+            })
+            .expect("in test");
+
+        assert_eq!(server_times.len(), 1);
+        assert_eq!(server_times[0].timestamp_millis(), 1499827319559);
+        match rx.try_recv().expect("in test") {
+            ExchangeEvent::BalanceUpdate(event) => {
+                let positions = event.balances_and_positions.positions.expect("in test");
+                assert_eq!(positions[0].leverage, dec!(25));
+            }
+            event => panic!("Unexpected event {event:?}"),
+        }
+    }
 }
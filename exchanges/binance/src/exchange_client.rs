@@ -1,5 +1,5 @@
 use super::binance::Binance;
-use crate::support::{BinanceOrderInfo, BinancePosition};
+use crate::support::{BinanceAccountInfo, BinanceOrderInfo};
 use anyhow::{Context, Result};
 use async_trait::async_trait;
 use function_name::named;
@@ -105,13 +105,14 @@ impl ExchangeClient for Binance {
     }
 
     async fn get_active_positions(&self) -> Result<Vec<ActivePosition>> {
-        let response = self.request_get_position().await?;
-        let binance_positions: Vec<BinancePosition> = serde_json::from_str(&response.content)
-            .expect("Unable to parse response content for get_active_positions_core request");
+        let response = self.request_get_position(None).await?;
+        let binance_positions = self.parse_positions(&response)?;
 
         Ok(binance_positions
-            .into_iter()
-            .map(|x| self.binance_position_to_active_position(x))
+            .iter()
+            .filter(|x| !x.position_amount.is_zero())
+            .filter_map(|x| self.binance_position_to_derivative_position(x))
+            .map(ActivePosition::new)
             .collect_vec())
     }
 
@@ -122,7 +123,26 @@ impl ExchangeClient for Binance {
     }
 
     async fn get_balance_and_positions(&self) -> Result<ExchangeBalancesAndPositions> {
-        unimplemented!("Have not implementation in C# too")
+        if !self.settings.is_margin_trading {
+            return self.get_balance().await;
+        }
+
+        // account information v2 doesn't contain liquidation price of positions,
+        // so they are requested separately
+        let balance_response = self.request_get_balance().await?;
+        let position_response = self.request_get_position(None).await?;
+
+        let binance_account_info: BinanceAccountInfo =
+            serde_json::from_str(&balance_response.content)
+                .context("Unable to parse response content for get_balance request")?;
+        let binance_positions = self.parse_positions(&position_response)?;
+
+        Ok(self.get_futures_exchange_balances_and_positions(
+            binance_account_info
+                .assets
+                .context("Unable to parse margin balances")?,
+            &binance_positions,
+        ))
     }
 
     async fn get_my_trades(
@@ -144,6 +164,11 @@ impl ExchangeClient for Binance {
         let response = &self.request_all_symbols().await?;
         self.parse_all_symbols(response)
     }
+
+    async fn get_server_time(&self) -> Result<DateTime> {
+        let response = self.request_server_time().await?;
+        Self::parse_server_time(&response)
+    }
 }
 
 impl Binance {
@@ -19,4 +19,5 @@
 pub mod binance;
 pub mod exchange_client;
 
+mod order_book;
 mod support;
@@ -0,0 +1,546 @@
+use std::mem;
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use chrono::Utc;
+use function_name::named;
+use mmb_core::exchanges::common::send_event;
+use mmb_core::exchanges::general::exchange::Exchange;
+use mmb_core::exchanges::general::request_type::RequestType;
+use mmb_core::exchanges::rest_client::{RestResponse, UriBuilder};
+use mmb_core::exchanges::traits::{ExchangeError, Support};
+use mmb_core::infrastructure::spawn_future;
+use mmb_domain::events::ExchangeEvent;
+use mmb_domain::market::CurrencyPair;
+use mmb_domain::order::snapshot::{Amount, Price, SortedOrderData};
+use mmb_domain::order_book::event::{EventType, OrderBookEvent};
+use mmb_domain::order_book::order_book_data::OrderBookData;
+use mmb_utils::infrastructure::SpawnFutureFlags;
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::binance::Binance;
+
+/// Max depth of order book snapshot requested for synchronization with diff stream
+const ORDER_BOOK_SNAPSHOT_LIMIT: u32 = 1000;
+
+// Corresponds https://binance-docs.github.io/apidocs/spot/en/#diff-depth-stream
+// and https://binance-docs.github.io/apidocs/futures/en/#diff-book-depth-streams
+#[derive(Debug, Clone, Eq, PartialEq, Deserialize)]
+pub(super) struct BinanceDepthUpdate {
+    #[serde(rename = "U")]
+    pub first_update_id: u64,
+    #[serde(rename = "u")]
+    pub final_update_id: u64,
+    // only futures stream contains final update id of previous event
+    #[serde(rename = "pu", default)]
+    pub previous_final_update_id: Option<u64>,
+    #[serde(rename = "a")]
+    pub asks: Vec<(Price, Amount)>,
+    #[serde(rename = "b")]
+    pub bids: Vec<(Price, Amount)>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(super) struct BinanceDepthSnapshot {
+    pub last_update_id: u64,
+    pub asks: Vec<(Price, Amount)>,
+    pub bids: Vec<(Price, Amount)>,
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub(super) enum DepthUpdateAction {
+    /// Order book isn't synchronized yet and REST snapshot should be requested
+    RequestSnapshot,
+    /// Update is saved until REST snapshot is received
+    Buffered,
+    /// Update is older than current state of order book
+    Skip,
+    Apply(BinanceDepthUpdate),
+}
+
+#[derive(Debug)]
+enum SynchronizationState {
+    NotSynchronized,
+    WaitingSnapshot(Vec<BinanceDepthUpdate>),
+    Synchronized {
+        last_update_id: u64,
+        // first update after snapshot should contain snapshot's `lastUpdateId`
+        is_first_update: bool,
+    },
+}
+
+/// Synchronization of order book diff stream with REST snapshot according to Binance documentation
+#[derive(Debug)]
+pub(super) struct OrderBookSynchronizer {
+    is_margin_trading: bool,
+    state: SynchronizationState,
+}
+
+impl OrderBookSynchronizer {
+    pub fn new(is_margin_trading: bool) -> Self {
+        Self {
+            is_margin_trading,
+            state: SynchronizationState::NotSynchronized,
+        }
+    }
+
+    pub fn on_update(&mut self, update: BinanceDepthUpdate) -> DepthUpdateAction {
+        match &mut self.state {
+            SynchronizationState::NotSynchronized => {
+                self.state = SynchronizationState::WaitingSnapshot(vec![update]);
+                DepthUpdateAction::RequestSnapshot
+            }
+            SynchronizationState::WaitingSnapshot(buffered_updates) => {
+                buffered_updates.push(update);
+                DepthUpdateAction::Buffered
+            }
+            SynchronizationState::Synchronized {
+                last_update_id,
+                is_first_update,
+            } => {
+                let last_update_id = *last_update_id;
+                let is_first_update = *is_first_update;
+                if self.is_outdated(last_update_id, &update) {
+                    return DepthUpdateAction::Skip;
+                }
+
+                if !self.is_next_update(last_update_id, &update, is_first_update) {
+                    log::warn!("Gap in Binance order book diff stream after update {last_update_id}: {update:?}");
+                    self.state = SynchronizationState::WaitingSnapshot(vec![update]);
+                    return DepthUpdateAction::RequestSnapshot;
+                }
+
+                self.state = SynchronizationState::Synchronized {
+                    last_update_id: update.final_update_id,
+                    is_first_update: false,
+                };
+                DepthUpdateAction::Apply(update)
+            }
+        }
+    }
+
+    /// Drop synchronization state and buffer next updates until a new snapshot is received
+    pub fn wait_snapshot(&mut self) {
+        self.state = SynchronizationState::WaitingSnapshot(vec![]);
+    }
+
+    /// Returns buffered updates which should be applied to the snapshot
+    /// or None if snapshot is older than buffered updates and should be requested again
+    pub fn on_snapshot(&mut self, snapshot_last_update_id: u64) -> Option<Vec<BinanceDepthUpdate>> {
+        let buffered_updates = match &mut self.state {
+            SynchronizationState::WaitingSnapshot(buffered_updates) => mem::take(buffered_updates),
+            _ => vec![],
+        };
+
+        let mut last_update_id = snapshot_last_update_id;
+        let mut is_first_update = true;
+        let mut updates = Vec::with_capacity(buffered_updates.len());
+        for update in buffered_updates {
+            if self.is_outdated(last_update_id, &update) {
+                continue;
+            }
+
+            if !self.is_next_update(last_update_id, &update, is_first_update) {
+                log::warn!("Binance order book snapshot {snapshot_last_update_id} doesn't match buffered update {update:?}");
+                self.state = SynchronizationState::WaitingSnapshot(vec![]);
+                return None;
+            }
+
+            last_update_id = update.final_update_id;
+            is_first_update = false;
+            updates.push(update);
+        }
+
+        self.state = SynchronizationState::Synchronized {
+            last_update_id,
+            is_first_update,
+        };
+        Some(updates)
+    }
+
+    fn is_outdated(&self, last_update_id: u64, update: &BinanceDepthUpdate) -> bool {
+        match self.is_margin_trading {
+            true => update.final_update_id < last_update_id,
+            false => update.final_update_id <= last_update_id,
+        }
+    }
+
+    fn is_next_update(
+        &self,
+        last_update_id: u64,
+        update: &BinanceDepthUpdate,
+        is_first_update: bool,
+    ) -> bool {
+        match (self.is_margin_trading, is_first_update) {
+            (true, true) => {
+                update.first_update_id <= last_update_id && last_update_id <= update.final_update_id
+            }
+            (false, true) => {
+                update.first_update_id <= last_update_id + 1
+                    && last_update_id < update.final_update_id
+            }
+            (true, false) => update.previous_final_update_id == Some(last_update_id),
+            (false, false) => update.first_update_id == last_update_id + 1,
+        }
+    }
+}
+
+impl Binance {
+    /// Handle event of `<symbol>@depth` diff stream
+    pub(super) fn handle_order_book_update(
+        &self,
+        currency_pair: CurrencyPair,
+        data: &Value,
+    ) -> Result<()> {
+        if !self.subscribe_to_market_data {
+            return Ok(());
+        }
+
+        let update: BinanceDepthUpdate = serde_json::from_value(data.clone())
+            .context("Unable to parse Binance order book update")?;
+
+        let action = self
+            .order_book_synchronizers
+            .entry(currency_pair)
+            .or_insert_with(|| OrderBookSynchronizer::new(self.settings.is_margin_trading))
+            .on_update(update);
+
+        match action {
+            DepthUpdateAction::RequestSnapshot => {
+                self.request_order_book_synchronization(currency_pair)
+            }
+            DepthUpdateAction::Buffered | DepthUpdateAction::Skip => Ok(()),
+            DepthUpdateAction::Apply(update) => self.send_order_book_event(
+                currency_pair,
+                update.final_update_id,
+                EventType::Update,
+                to_order_book_data(update.asks, update.bids),
+            ),
+        }
+    }
+
+    pub(super) fn handle_order_book_diff_snapshot(
+        &self,
+        currency_pair: CurrencyPair,
+        snapshot: BinanceDepthSnapshot,
+    ) -> Result<()> {
+        let updates = self
+            .order_book_synchronizers
+            .entry(currency_pair)
+            .or_insert_with(|| OrderBookSynchronizer::new(self.settings.is_margin_trading))
+            .on_snapshot(snapshot.last_update_id);
+
+        let updates = match updates {
+            Some(updates) => updates,
+            None => return self.request_order_book_synchronization(currency_pair),
+        };
+
+        let event_id = updates
+            .last()
+            .map(|x| x.final_update_id)
+            .unwrap_or(snapshot.last_update_id);
+
+        let mut order_book_data = to_order_book_data(snapshot.asks, snapshot.bids);
+        order_book_data.update(
+            updates
+                .into_iter()
+                .map(|x| to_order_book_data(x.asks, x.bids))
+                .collect(),
+        );
+
+        self.send_order_book_event(
+            currency_pair,
+            event_id,
+            EventType::Snapshot,
+            order_book_data,
+        )
+    }
+
+    /// Forget synchronization state of order books, so they will be synchronized again
+    /// with next received updates
+    pub(super) fn reset_order_book_synchronization(&self) {
+        self.order_book_synchronizers.clear();
+    }
+
+    /// Request a new snapshot for order book which local copy became inconsistent
+    pub(super) fn resynchronize_order_book(&self, currency_pair: CurrencyPair) -> Result<()> {
+        self.order_book_synchronizers
+            .entry(currency_pair)
+            .or_insert_with(|| OrderBookSynchronizer::new(self.settings.is_margin_trading))
+            .wait_snapshot();
+
+        self.request_order_book_synchronization(currency_pair)
+    }
+
+    pub(super) async fn synchronize_order_book(&self, currency_pair: CurrencyPair) -> Result<()> {
+        self.reserve_request(RequestType::GetOrderBook).await;
+
+        let response = self.request_order_book_snapshot(currency_pair).await?;
+        let snapshot: BinanceDepthSnapshot = serde_json::from_str(&response.content)
+            .context("Unable to parse Binance order book snapshot")?;
+
+        self.handle_order_book_diff_snapshot(currency_pair, snapshot)
+    }
+
+    #[named]
+    async fn request_order_book_snapshot(
+        &self,
+        currency_pair: CurrencyPair,
+    ) -> Result<RestResponse, ExchangeError> {
+        let path = self.get_uri_path("/fapi/v1/depth", "/api/v3/depth");
+        let mut builder = UriBuilder::from_path(path);
+        builder.add_kv("symbol", self.get_specific_currency_pair(currency_pair));
+        builder.add_kv("limit", ORDER_BOOK_SNAPSHOT_LIMIT);
+        let uri = builder.build_uri(self.hosts.rest_uri_host(), true);
+
+        let api_key = &self.settings.api_key;
+        self.rest_client
+            .get(uri, api_key, function_name!(), "".to_string())
+            .await
+    }
+
+    fn request_order_book_synchronization(&self, currency_pair: CurrencyPair) -> Result<()> {
+        self.order_book_synchronization_sender
+            .send(currency_pair)
+            .context("Unable to request Binance order book synchronization")
+    }
+
+    fn send_order_book_event(
+        &self,
+        currency_pair: CurrencyPair,
+        update_id: u64,
+        event_type: EventType,
+        order_book_data: OrderBookData,
+    ) -> Result<()> {
+        let order_book_event = OrderBookEvent::new(
+            Utc::now(),
+            self.id,
+            currency_pair,
+            update_id.to_string(),
+            event_type,
+            Arc::new(order_book_data),
+        );
+
+        send_event(
+            &self.events_channel,
+            self.lifetime_manager.clone(),
+            self.id,
+            ExchangeEvent::OrderBookEvent(order_book_event),
+        )
+    }
+}
+
+/// Request REST snapshots for order books which diff streams should be synchronized
+pub(super) fn start_order_book_synchronization(exchange: &Arc<Exchange>, binance: &Binance) {
+    let mut receiver = match binance.order_book_synchronization_receiver.lock().take() {
+        Some(receiver) => receiver,
+        None => {
+            log::error!("Binance order book synchronization is already started");
+            return;
+        }
+    };
+
+    let exchange_wk = Arc::downgrade(exchange);
+    let stop_token = exchange.stop_token.clone();
+    let action = async move {
+        loop {
+            let currency_pair = tokio::select! {
+                currency_pair = receiver.recv() => match currency_pair {
+                    Some(currency_pair) => currency_pair,
+                    None => return Ok(()),
+                },
+                _ = stop_token.when_cancelled() => return Ok(()),
+            };
+
+            let exchange = match exchange_wk.upgrade() {
+                None => return Ok(()),
+                Some(v) => v,
+            };
+
+            let binance = exchange
+                .exchange_client
+                .as_any()
+                .downcast_ref::<Binance>()
+                .expect("received non Binance exchange client in order book synchronization");
+
+            if let Err(err) = binance.synchronize_order_book(currency_pair).await {
+                log::error!("Failed to synchronize Binance order book {currency_pair}: {err:?}");
+                // synchronization will be started again by next update
+                let _ = binance.order_book_synchronizers.remove(&currency_pair);
+            }
+        }
+    };
+    spawn_future(
+        "Synchronize Binance order books",
+        SpawnFutureFlags::STOP_BY_TOKEN | SpawnFutureFlags::DENY_CANCELLATION,
+        action,
+    );
+}
+
+fn to_order_book_data(asks: Vec<(Price, Amount)>, bids: Vec<(Price, Amount)>) -> OrderBookData {
+    OrderBookData::new(
+        asks.into_iter().collect::<SortedOrderData>(),
+        bids.into_iter().collect::<SortedOrderData>(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn update(first_update_id: u64, final_update_id: u64) -> BinanceDepthUpdate {
+        BinanceDepthUpdate {
+            first_update_id,
+            final_update_id,
+            previous_final_update_id: None,
+            asks: vec![(dec!(2), dec!(1))],
+            bids: vec![],
+        }
+    }
+
+    fn futures_update(
+        first_update_id: u64,
+        final_update_id: u64,
+        previous_final_update_id: u64,
+    ) -> BinanceDepthUpdate {
+        BinanceDepthUpdate {
+            previous_final_update_id: Some(previous_final_update_id),
+            ..update(first_update_id, final_update_id)
+        }
+    }
+
+    #[test]
+    fn parse_depth_update() {
+        let data = serde_json::json!({
+            "e": "depthUpdate",
+            "E": 123456789,
+            "s": "BNBBTC",
+            "U": 157,
+            "u": 160,
+            "b": [["0.0024", "10"]],
+            "a": [["0.0026", "100"]]
+        });
+
+        let update: BinanceDepthUpdate = serde_json::from_value(data).expect("in test");
+
+        assert_eq!(
+            update,
+            BinanceDepthUpdate {
+                first_update_id: 157,
+                final_update_id: 160,
+                previous_final_update_id: None,
+                asks: vec![(dec!(0.0026), dec!(100))],
+                bids: vec![(dec!(0.0024), dec!(10))],
+            }
+        );
+    }
+
+    #[test]
+    fn spot_updates_are_aligned_with_snapshot() {
+        let mut synchronizer = OrderBookSynchronizer::new(false);
+
+        assert_eq!(
+            synchronizer.on_update(update(1, 5)),
+            DepthUpdateAction::RequestSnapshot
+        );
+        assert_eq!(
+            synchronizer.on_update(update(6, 10)),
+            DepthUpdateAction::Buffered
+        );
+        assert_eq!(
+            synchronizer.on_update(update(11, 15)),
+            DepthUpdateAction::Buffered
+        );
+
+        let updates = synchronizer.on_snapshot(8).expect("in test");
+        assert_eq!(updates, vec![update(6, 10), update(11, 15)]);
+
+        assert_eq!(
+            synchronizer.on_update(update(12, 15)),
+            DepthUpdateAction::Skip
+        );
+        assert_eq!(
+            synchronizer.on_update(update(16, 20)),
+            DepthUpdateAction::Apply(update(16, 20))
+        );
+    }
+
+    #[test]
+    fn first_update_after_snapshot_contains_snapshot_id() {
+        let mut synchronizer = OrderBookSynchronizer::new(false);
+
+        assert_eq!(
+            synchronizer.on_update(update(1, 5)),
+            DepthUpdateAction::RequestSnapshot
+        );
+        let updates = synchronizer.on_snapshot(5).expect("in test");
+        assert!(updates.is_empty());
+
+        assert_eq!(
+            synchronizer.on_update(update(4, 8)),
+            DepthUpdateAction::Apply(update(4, 8))
+        );
+    }
+
+    #[test]
+    fn outdated_snapshot_is_requested_again() {
+        let mut synchronizer = OrderBookSynchronizer::new(false);
+
+        assert_eq!(
+            synchronizer.on_update(update(10, 15)),
+            DepthUpdateAction::RequestSnapshot
+        );
+        assert_eq!(synchronizer.on_snapshot(5), None);
+
+        assert_eq!(
+            synchronizer.on_update(update(16, 20)),
+            DepthUpdateAction::Buffered
+        );
+        let updates = synchronizer.on_snapshot(17).expect("in test");
+        assert_eq!(updates, vec![update(16, 20)]);
+    }
+
+    #[test]
+    fn gap_in_futures_updates_requires_resynchronization() {
+        let mut synchronizer = OrderBookSynchronizer::new(true);
+
+        assert_eq!(
+            synchronizer.on_update(futures_update(1, 5, 0)),
+            DepthUpdateAction::RequestSnapshot
+        );
+        let updates = synchronizer.on_snapshot(3).expect("in test");
+        assert_eq!(updates, vec![futures_update(1, 5, 0)]);
+
+        assert_eq!(
+            synchronizer.on_update(futures_update(6, 10, 5)),
+            DepthUpdateAction::Apply(futures_update(6, 10, 5))
+        );
+        assert_eq!(
+            synchronizer.on_update(futures_update(15, 20, 14)),
+            DepthUpdateAction::RequestSnapshot
+        );
+        assert_eq!(
+            synchronizer.on_update(futures_update(21, 25, 20)),
+            DepthUpdateAction::Buffered
+        );
+    }
+
+    #[test]
+    fn synchronized_order_book_waits_snapshot_after_integrity_violation() {
+        let mut synchronizer = OrderBookSynchronizer::new(false);
+
+        synchronizer.on_update(update(1, 5));
+        synchronizer.on_snapshot(3).expect("in test");
+        synchronizer.wait_snapshot();
+
+        assert_eq!(
+            synchronizer.on_update(update(6, 10)),
+            DepthUpdateAction::Buffered
+        );
+        let updates = synchronizer.on_snapshot(7).expect("in test");
+        assert_eq!(updates, vec![update(6, 10)]);
+    }
+}
@@ -1,4 +1,4 @@
-use mmb_domain::position::{ActivePosition, DerivativePosition};
+use mmb_domain::position::DerivativePosition;
 use mmb_utils::infrastructure::{SpawnFutureFlags, WithExpect};
 use std::any::Any;
 
@@ -9,22 +9,23 @@ use dashmap::DashMap;
 use itertools::Itertools;
 use mmb_domain::order::snapshot::{Amount, Price};
 use rust_decimal::Decimal;
-use rust_decimal_macros::dec;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::sync::Arc;
 use std::time::Duration;
 use url::Url;
 
-use super::binance::Binance;
+use super::binance::{get_position_side, Binance};
+use super::order_book::start_order_book_synchronization;
 use mmb_core::connectivity::WebSocketRole;
 use mmb_core::exchanges::common::send_event;
+use mmb_core::exchanges::exchange_clock::ExchangeClock;
 use mmb_core::exchanges::general::exchange::Exchange;
 use mmb_core::exchanges::traits::Support;
 use mmb_core::exchanges::traits::{
     HandleOrderFilledCb, HandleTradeCb, OrderCancelledCb, OrderCreatedCb, SendWebsocketMessageCb,
 };
-use mmb_core::infrastructure::spawn_by_timer;
+use mmb_core::infrastructure::spawn_by_timer_with_token;
 use mmb_core::settings::ExchangeSettings;
 use mmb_domain::events::{ExchangeEvent, TradeId};
 use mmb_domain::market::{CurrencyCode, CurrencyPair};
@@ -84,17 +85,48 @@ pub struct BinanceMarginBalances {
     pub update_time: Decimal,   // last update time
 }
 
+// Corresponds https://binance-docs.github.io/apidocs/futures/en/#position-information-v2-user_data
 #[derive(Debug, Eq, PartialEq, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub(super) struct BinancePosition {
     #[serde(rename = "symbol")]
     pub specific_currency_pair: SpecificCurrencyPair,
-    #[serde(rename = "PositionAmt")]
+    #[serde(rename = "positionAmt")]
     pub position_amount: Amount,
-    #[serde(rename = "LiquidationPrice")]
+    pub entry_price: Price,
     pub liquidation_price: Price,
     pub leverage: Decimal,
-    #[serde(rename = "PositionSide")]
-    pub position_side: Decimal,
+    pub margin_type: String,
+    pub position_side: String,
+}
+
+// Corresponds https://binance-docs.github.io/apidocs/futures/en/#event-balance-and-position-update
+#[derive(Debug, Eq, PartialEq, Clone, Deserialize)]
+pub(super) struct BinanceAccountUpdate {
+    #[serde(rename = "B", default)]
+    pub balances: Vec<BinanceAccountUpdateBalance>,
+    #[serde(rename = "P", default)]
+    pub positions: Vec<BinanceAccountUpdatePosition>,
+}
+
+#[derive(Debug, Eq, PartialEq, Clone, Deserialize)]
+pub(super) struct BinanceAccountUpdateBalance {
+    #[serde(rename = "a")]
+    pub asset: String,
+    #[serde(rename = "wb")]
+    pub wallet_balance: Decimal,
+}
+
+#[derive(Debug, Eq, PartialEq, Clone, Deserialize)]
+pub(super) struct BinanceAccountUpdatePosition {
+    #[serde(rename = "s")]
+    pub specific_currency_pair: SpecificCurrencyPair,
+    #[serde(rename = "pa")]
+    pub position_amount: Amount,
+    #[serde(rename = "ep")]
+    pub entry_price: Price,
+    #[serde(rename = "ps")]
+    pub position_side: String,
 }
 
 #[async_trait]
@@ -107,6 +139,7 @@ impl Support for Binance {
         self.initialize_working_currencies(&exchange);
 
         start_updating_listen_key(&exchange);
+        start_order_book_synchronization(&exchange, self);
     }
 
     fn on_websocket_message(&self, msg: &str) -> Result<()> {
@@ -132,6 +165,13 @@ impl Support for Binance {
                     self.process_snapshot_update(currency_pair, data)?;
                     return Ok(());
                 }
+
+                // diff depth stream `<symbol>@depth` or `<symbol>@depth@100ms`
+                let channel = &stream[byte_index + 1..];
+                if channel == "depth" || channel.starts_with("depth@") {
+                    self.handle_order_book_update(currency_pair, data)?;
+                    return Ok(());
+                }
             }
 
             return Ok(());
@@ -146,6 +186,10 @@ impl Support for Binance {
         } else if event_type == "ORDER_TRADE_UPDATE" {
             let json_response = data["o"].take();
             self.handle_order_fill(msg, json_response)?;
+        } else if event_type == "ACCOUNT_UPDATE" {
+            self.handle_account_update(&data)?;
+        } else if event_type == "ACCOUNT_CONFIG_UPDATE" {
+            self.handle_account_config_update(&data)?;
         } else {
             self.log_unknown_message(self.id, msg);
         }
@@ -163,9 +207,24 @@ impl Support for Binance {
                     .insert(*currency_pair, TradeId::Number(0));
             });
 
+        // updates could be missed while websocket was disconnected
+        self.reset_order_book_synchronization();
+
         Ok(())
     }
 
+    fn can_resync_order_book(&self) -> bool {
+        self.subscribe_to_market_data
+    }
+
+    fn on_order_book_integrity_violated(&self, currency_pair: CurrencyPair) -> Result<()> {
+        if !self.subscribe_to_market_data {
+            return Ok(());
+        }
+
+        self.resynchronize_order_book(currency_pair)
+    }
+
     fn on_disconnected(&self) -> Result<()> {
         *self.listen_key.write() = None;
 
@@ -242,6 +301,10 @@ impl Support for Binance {
     fn get_settings(&self) -> &ExchangeSettings {
         &self.settings
     }
+
+    fn get_clock(&self) -> Option<Arc<ExchangeClock>> {
+        Some(self.clock.clone())
+    }
 }
 
 impl Binance {
@@ -394,43 +457,38 @@ impl Binance {
         Ok(ws_path)
     }
 
-    pub(super) fn binance_position_to_active_position(
+    /// Returns None for currency pairs which are not supported
+    pub(super) fn binance_position_to_derivative_position(
         &self,
-        binance_position: BinancePosition,
-    ) -> ActivePosition {
+        binance_position: &BinancePosition,
+    ) -> Option<DerivativePosition> {
         let currency_pair = self
             .get_unified_currency_pair(&binance_position.specific_currency_pair)
-            .with_expect(|| {
-                let specific_currency_pair = binance_position.specific_currency_pair;
-                format!("Failed to get_unified_currency_pair for {specific_currency_pair:?}")
-            });
+            .ok()?;
 
-        let side = match binance_position.position_side > dec!(0) {
-            true => OrderSide::Buy,
-            false => OrderSide::Sell,
-        };
-
-        let derivative_position = DerivativePosition::new(
+        Some(DerivativePosition::new(
             currency_pair,
             binance_position.position_amount,
-            Some(side),
-            dec!(0),
+            Some(get_position_side(
+                &binance_position.position_side,
+                binance_position.position_amount,
+            )),
+            binance_position.entry_price,
             binance_position.liquidation_price,
             binance_position.leverage,
-        );
-
-        ActivePosition::new(derivative_position)
+        ))
     }
 }
 
 fn start_updating_listen_key(exchange: &Arc<Exchange>) {
     let exchange_wk = Arc::downgrade(exchange);
     let period = Duration::from_secs(20 * 60);
-    spawn_by_timer(
+    spawn_by_timer_with_token(
         "Update listen key",
         period,
         period,
         SpawnFutureFlags::STOP_BY_TOKEN | SpawnFutureFlags::DENY_CANCELLATION,
+        exchange.stop_token.clone(),
         move || {
             let exchange_wk = exchange_wk.clone();
             async move {
@@ -173,15 +173,18 @@ impl BinanceBuilder {
 
         settings.websocket_channels = vec!["depth".into(), "trade".into()];
 
-        let binance = Box::new(Binance::new(
-            exchange_account_id,
-            settings.clone(),
-            tx.clone(),
-            lifetime_manager.clone(),
-            get_timeout_manager(exchange_account_id),
-            false,
-            false,
-        ));
+        let binance = Box::new(
+            Binance::new(
+                exchange_account_id,
+                settings.clone(),
+                tx.clone(),
+                lifetime_manager.clone(),
+                get_timeout_manager(exchange_account_id),
+                false,
+                false,
+            )
+            .expect("in test"),
+        );
 
         let hosts = binance.hosts.clone();
 
@@ -34,7 +34,7 @@ use mmb_domain::market::CurrencyPair;
 use mmb_domain::market::ExchangeAccountId;
 use mmb_domain::order::snapshot::Amount;
 
-#[derive(Default, Clone, Debug, Deserialize, Serialize)]
+#[derive(Default, Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
 pub struct TestStrategySettings {}
 
 impl BaseStrategySettings for TestStrategySettings {
@@ -121,7 +121,7 @@ async fn orders_cancelled() {
         let _ = exchange.cancel_all_orders(test_currency_pair).await;
         let price = get_default_price(
             get_specific_currency_pair_for_tests(&exchange, test_currency_pair),
-            &Binance::make_hosts(is_margin_trading),
+            &Binance::make_hosts(&settings.core.exchanges[0]).expect("in test"),
             &api_key,
             exchange_account_id,
             is_margin_trading,
@@ -137,7 +137,7 @@ async fn orders_cancelled() {
 
         let amount = get_min_amount(
             get_specific_currency_pair_for_tests(&exchange, test_currency_pair),
-            &Binance::make_hosts(is_margin_trading),
+            &Binance::make_hosts(&settings.core.exchanges[0]).expect("in test"),
             &api_key,
             price,
             &symbol,
@@ -160,7 +160,7 @@ async fn orders_cancelled() {
     } else {
         panic!(
             "Incorrect currency pair setting enum type: {:?}",
-            settings.strategy.currency_pair()
+            settings.strategies[0].currency_pair()
         );
     }
 
@@ -53,6 +53,7 @@ use mmb_core::exchanges::general::features::{
 use mmb_core::exchanges::rest_client::{ErrorHandlerData, ErrorHandlerEmpty, RestClient};
 use mmb_core::exchanges::timeouts::requests_timeout_manager_factory::RequestTimeoutArguments;
 use mmb_core::exchanges::timeouts::timeout_manager::TimeoutManager;
+use mmb_core::exchanges::traffic::recorder::TrafficRecorder;
 use mmb_core::exchanges::traits::{
     ExchangeClient, ExchangeClientBuilder, ExchangeClientBuilderResult, ExchangeError,
     HandleOrderFilledCb, HandleTradeCb, OrderCancelledCb, OrderCreatedCb,
@@ -67,8 +68,8 @@ use mmb_domain::market::{
 };
 use mmb_domain::order::pool::{OrderRef, OrdersPool};
 use mmb_domain::order::snapshot::{
-    ClientOrderId, ExchangeOrderId, OrderCancelling, OrderInfo, OrderInfoExtensionData, OrderSide,
-    OrderStatus, OrderType,
+    ClientOrderId, ExchangeOrderId, OrderCancelling, OrderExecutionType, OrderHeader, OrderInfo,
+    OrderInfoExtensionData, OrderSide, OrderStatus, OrderTimeInForce, OrderType,
 };
 use mmb_utils::infrastructure::WithExpect;
 
@@ -131,6 +132,30 @@ pub fn downcast_mut_to_serum_extension_data(
         .expect("Failed to complete downcast to SerumExtensionData type")
 }
 
+fn get_serum_order_type(header: &OrderHeader) -> Result<serum_dex::matching::OrderType> {
+    if header.order_type != OrderType::Limit {
+        bail!(
+            "Order type {:?} is not supported by Serum for order {}",
+            header.order_type,
+            header.client_order_id
+        );
+    }
+
+    let is_maker_only = header.execution_type == OrderExecutionType::MakerOnly;
+    match (header.time_in_force, is_maker_only) {
+        (OrderTimeInForce::GoodTillCancelled, false) => Ok(serum_dex::matching::OrderType::Limit),
+        (OrderTimeInForce::GoodTillCancelled, true) => Ok(serum_dex::matching::OrderType::PostOnly),
+        (OrderTimeInForce::ImmediateOrCancel, false) => {
+            Ok(serum_dex::matching::OrderType::ImmediateOrCancel)
+        }
+        (time_in_force, _) => bail!(
+            "Time in force {time_in_force:?} is not supported by Serum for {:?} order {}",
+            header.execution_type,
+            header.client_order_id
+        ),
+    }
+}
+
 pub struct Serum {
     pub id: ExchangeAccountId,
     pub settings: ExchangeSettings,
@@ -166,6 +191,13 @@ impl Serum {
     ) -> Self {
         let payer = Keypair::from_base58_string(&settings.secret_key);
         let exchange_account_id = settings.exchange_account_id;
+        let rest_client = RestClient::new(ErrorHandlerData::new(
+            empty_response_is_ok,
+            exchange_account_id,
+            ErrorHandlerEmpty::default(),
+        ))
+        .with_network_settings(settings.network.clone())
+        .with_traffic_recorder(TrafficRecorder::for_exchange_account(&settings));
 
         Self {
             id,
@@ -179,11 +211,7 @@ impl Serum {
             unified_to_specific: Default::default(),
             supported_currencies: Default::default(),
             traded_specific_currencies: Default::default(),
-            rest_client: RestClient::new(ErrorHandlerData::new(
-                empty_response_is_ok,
-                exchange_account_id,
-                ErrorHandlerEmpty::default(),
-            )),
+            rest_client,
             rpc_client: Arc::new(SolanaClient::new(&network_type)),
             markets_data: Default::default(),
             network_type,
@@ -326,10 +354,7 @@ impl Serum {
                 },
             )?,
             self_trade_behavior: serum_dex::instruction::SelfTradeBehavior::DecrementTake,
-            order_type: match header.order_type {
-                OrderType::Limit => serum_dex::matching::OrderType::Limit,
-                _ => unimplemented!(),
-            },
+            order_type: get_serum_order_type(&header)?,
             client_order_id,
             limit: u16::MAX,
         };
@@ -936,18 +961,20 @@ impl ExchangeClientBuilder for SerumBuilder {
         lifetime_manager: Arc<AppLifetimeManager>,
         _timeout_manager: Arc<TimeoutManager>,
         orders: Arc<OrdersPool>,
-    ) -> ExchangeClientBuilderResult {
+    ) -> Result<ExchangeClientBuilderResult> {
         let exchange_account_id = exchange_settings.exchange_account_id;
         let empty_response_is_ok = false;
+        let network_type = NetworkType::from_settings(&exchange_settings)
+            .context("Unable to make Serum network from settings")?;
 
-        ExchangeClientBuilderResult {
+        Ok(ExchangeClientBuilderResult {
             client: Box::new(Serum::new(
                 exchange_account_id,
                 exchange_settings,
                 events_channel,
                 lifetime_manager,
                 orders,
-                NetworkType::Mainnet,
+                network_type,
                 empty_response_is_ok,
             )) as BoxExchangeClient,
             features: ExchangeFeatures::new(
@@ -964,7 +991,7 @@ impl ExchangeClientBuilder for SerumBuilder {
                 AllowedEventSourceType::All,
                 AllowedEventSourceType::All,
             ),
-        }
+        })
     }
 
     fn get_timeout_arguments(&self) -> RequestTimeoutArguments {
@@ -28,6 +28,8 @@ use solana_sdk::transaction::Transaction;
 use tokio::join;
 
 use mmb_core::connectivity::WebSocketRole;
+use mmb_core::exchanges::hosts::Hosts;
+use mmb_core::settings::{ExchangeEnvironment, ExchangeSettings};
 use mmb_core::exchanges::traits::SendWebsocketMessageCb;
 use mmb_domain::market::CurrencyPair;
 use mmb_utils::{impl_u64_id, time::get_atomic_current_secs};
@@ -59,13 +61,46 @@ impl SolanaHosts {
 
 pub enum NetworkType {
     Mainnet,
+    Devnet,
     Custom(SolanaHosts),
 }
 
 impl NetworkType {
+    /// Network for environment of exchange account with host overrides from settings.
+    /// Testnet environment corresponds to Solana devnet where Serum test markets are deployed
+    pub fn from_settings(settings: &ExchangeSettings) -> Result<NetworkType> {
+        let environment = settings.environment.unwrap_or_default();
+        let network_type = match environment {
+            ExchangeEnvironment::Mainnet => NetworkType::Mainnet,
+            ExchangeEnvironment::Testnet => NetworkType::Devnet,
+            ExchangeEnvironment::Custom => NetworkType::Mainnet,
+        };
+        if environment != ExchangeEnvironment::Custom && settings.hosts.is_none() {
+            return Ok(network_type);
+        }
+
+        let hosts = Hosts::resolve(
+            settings,
+            || network_type.hosts(),
+            || Some(NetworkType::Devnet.hosts()),
+        )?;
+
+        Ok(NetworkType::Custom(SolanaHosts::new(
+            hosts.rest_host,
+            hosts.web_socket_host,
+            network_type.market_list_url().to_owned(),
+            None,
+        )))
+    }
+
+    fn hosts(&self) -> Hosts {
+        Hosts::new(self.ws(), self.ws(), self.url())
+    }
+
     pub fn url(&self) -> &str {
         match self {
             NetworkType::Mainnet => "https://api.mainnet-beta.solana.com",
+            NetworkType::Devnet => "https://api.devnet.solana.com",
             NetworkType::Custom(network_opts) => &network_opts.url,
         }
     }
@@ -73,6 +108,7 @@ impl NetworkType {
     pub fn ws(&self) -> &str {
         match self {
             NetworkType::Mainnet => "ws://api.mainnet-beta.solana.com/",
+            NetworkType::Devnet => "ws://api.devnet.solana.com/",
             NetworkType::Custom(network_opts) => &network_opts.ws,
         }
     }
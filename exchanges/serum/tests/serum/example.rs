@@ -43,41 +43,41 @@ async fn example() {
     loop {
         let engine =
             launch_trading_engine(&engine_config, init_settings.clone(), |settings, ctx| {
-                spawn_future_ok(
-                    "Events logging",
-                    SpawnFutureFlags::STOP_BY_TOKEN | SpawnFutureFlags::DENY_CANCELLATION,
-                    {
-                        let ctx = ctx.clone();
-                        async move {
-                            let mut events_rx = ctx.clone().get_events_channel();
-                            loop {
-                                let event_res = events_rx.recv().await;
-                                match event_res {
-                                    Ok(event) => {
-                                        println!("Event has been received: {:?}", event);
-                                    }
-                                    Err(err) => {
-                                        println!("Error occurred: {:?}", err);
-                                        break;
-                                    }
-                                };
-                            }
-                        }
-                        .boxed()
-                    },
-                );
-
                 Box::new(ExampleStrategy::new(
-                    settings.strategy.exchange_account_id(),
-                    settings.strategy.currency_pair(),
-                    settings.strategy.spread,
-                    settings.strategy.max_amount,
+                    settings.exchange_account_id(),
+                    settings.currency_pair(),
+                    settings.spread,
+                    settings.max_amount,
                     ctx,
                 ))
             })
             .await
             .expect("Failed to launch trading engine");
 
+        spawn_future_ok(
+            "Events logging",
+            SpawnFutureFlags::STOP_BY_TOKEN | SpawnFutureFlags::DENY_CANCELLATION,
+            {
+                let ctx = engine.context();
+                async move {
+                    let mut events_rx = ctx.get_events_channel();
+                    loop {
+                        let event_res = events_rx.recv().await;
+                        match event_res {
+                            Ok(event) => {
+                                println!("Event has been received: {:?}", event);
+                            }
+                            Err(err) => {
+                                println!("Error occurred: {:?}", err);
+                                break;
+                            }
+                        };
+                    }
+                }
+                .boxed()
+            },
+        );
+
         match engine.run().await {
             ActionAfterGracefulShutdown::Nothing => break,
             ActionAfterGracefulShutdown::Restart => continue,
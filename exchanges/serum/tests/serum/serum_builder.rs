@@ -154,12 +154,12 @@ impl ExchangeClientBuilder for ExchangeSerumBuilder {
         lifetime_manager: Arc<AppLifetimeManager>,
         _timeout_manager: Arc<TimeoutManager>,
         orders: Arc<OrdersPool>,
-    ) -> ExchangeClientBuilderResult {
+    ) -> Result<ExchangeClientBuilderResult> {
         let exchange_account_id = exchange_settings.exchange_account_id;
         let empty_response_is_ok = false;
 
         let network_type = get_network_type().expect("Get network type");
-        ExchangeClientBuilderResult {
+        Ok(ExchangeClientBuilderResult {
             client: Box::new(Serum::new(
                 exchange_account_id,
                 exchange_settings,
@@ -183,7 +183,7 @@ impl ExchangeClientBuilder for ExchangeSerumBuilder {
                 AllowedEventSourceType::All,
                 AllowedEventSourceType::All,
             ),
-        }
+        })
     }
 
     fn get_timeout_arguments(&self) -> RequestTimeoutArguments {
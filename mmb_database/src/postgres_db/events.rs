@@ -9,7 +9,7 @@ use serde_json::Value as JsonValue;
 use std::fmt::{Display, Formatter};
 use tokio_postgres::binary_copy::BinaryCopyInWriter;
 use tokio_postgres::types::Type;
-use tokio_postgres::{NoTls, Statement};
+use tokio_postgres::{NoTls, Row, Statement};
 pub type TableName = &'static str;
 pub type TableNameRef<'a> = &'a str;
 
@@ -159,9 +159,120 @@ pub async fn save_events_one_by_one(
     (Ok(()), failed_events)
 }
 
+/// Load the most recently inserted event from specified table
+pub async fn load_last_event(pool: &PgPool, table_name: &str) -> Result<Option<DbEvent>> {
+    let sql =
+        format!("SELECT id, insert_time, version, json FROM {table_name} ORDER BY id DESC LIMIT 1");
+
+    let row = pool
+        .0
+        .get()
+        .await
+        .context("getting db connection from pool")?
+        .query_opt(&sql, &[])
+        .await
+        .context("from `load_last_event` on query")?;
+
+    Ok(row.map(|row| to_db_event(&row)))
+}
+
+/// Load the most recently inserted event for every distinct value of the json field by `key_path`
+/// among events inserted since `from`.
+/// `key_path` is a postgres text array path, e.g. `{order, header, client_order_id}`
+pub async fn load_last_events_by_key(
+    pool: &PgPool,
+    table_name: &str,
+    key_path: &str,
+    from: DateTime<Utc>,
+) -> Result<Vec<DbEvent>> {
+    let sql = format!(
+        "SELECT DISTINCT ON (json #>> '{key_path}') id, insert_time, version, json FROM {table_name} \
+         WHERE insert_time >= $1 \
+         ORDER BY json #>> '{key_path}', id DESC"
+    );
+
+    let rows = pool
+        .0
+        .get()
+        .await
+        .context("getting db connection from pool")?
+        .query(&sql, &[&from])
+        .await
+        .context("from `load_last_events_by_key` on query")?;
+
+    Ok(rows.iter().map(to_db_event).collect())
+}
+
+/// Load the latest event for every distinct value of the json field by `key_path` among events
+/// with a timestamp by json field `time_path` in the interval `[from, to]`.
+/// Events are ordered by the timestamp the event happened at, not by the time it was saved.
+/// Table should have index on `(json #>> key_path, json_timestamptz(json #>> time_path))`
+/// (`json_timestamptz` is created by migrations) to avoid full scan
+pub async fn load_last_events_by_key_before(
+    pool: &PgPool,
+    table_name: &str,
+    key_path: &str,
+    time_path: &str,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Result<Vec<DbEvent>> {
+    let sql = format!(
+        "SELECT DISTINCT ON (json #>> '{key_path}') id, insert_time, version, json FROM {table_name} \
+         WHERE json_timestamptz(json #>> '{time_path}') BETWEEN $1 AND $2 \
+         ORDER BY json #>> '{key_path}', json_timestamptz(json #>> '{time_path}') DESC, id DESC"
+    );
+
+    let rows = pool
+        .0
+        .get()
+        .await
+        .context("getting db connection from pool")?
+        .query(&sql, &[&from, &to])
+        .await
+        .context("from `load_last_events_by_key_before` on query")?;
+
+    Ok(rows.iter().map(to_db_event).collect())
+}
+
+/// Load events inserted in the half-open interval `[from, to)` ordered by insertion
+pub async fn load_events_by_period(
+    pool: &PgPool,
+    table_name: &str,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Result<Vec<DbEvent>> {
+    let sql = format!(
+        "SELECT id, insert_time, version, json FROM {table_name} WHERE insert_time >= $1 AND insert_time < $2 ORDER BY id"
+    );
+
+    let rows = pool
+        .0
+        .get()
+        .await
+        .context("getting db connection from pool")?
+        .query(&sql, &[&from, &to])
+        .await
+        .context("from `load_events_by_period` on query")?;
+
+    Ok(rows.iter().map(to_db_event).collect())
+}
+
+fn to_db_event(row: &Row) -> DbEvent {
+    let id: i64 = row.get("id");
+    DbEvent {
+        id: id as u64,
+        insert_time: row.get("insert_time"),
+        version: row.get("version"),
+        json: row.get("json"),
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::postgres_db::events::{save_events_batch, save_events_one_by_one, InsertEvent};
+    use crate::postgres_db::events::{
+        load_events_by_period, load_last_event, load_last_events_by_key,
+        load_last_events_by_key_before, save_events_batch, save_events_one_by_one, InsertEvent,
+    };
     use crate::postgres_db::tests::{get_database_url, PgPoolMutex};
     use serde_json::json;
 
@@ -253,4 +364,144 @@ mod tests {
         assert_eq!(version, 1);
         assert_eq!(json, expected_json);
     }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn load_last_events() {
+        let pool_mutex = init_test().await;
+
+        // arrange
+        let items =
+            [("Ivan", 1), ("Petr", 1), ("Ivan", 2)].map(|(first_name, number)| InsertEvent {
+                version: 1,
+                json: json!({
+                    "first_name": first_name,
+                    "number": number,
+                }),
+            });
+        save_events_batch(&pool_mutex.pool, TABLE_NAME, &items)
+            .await
+            .expect("in test");
+
+        // act
+        let last_event = load_last_event(&pool_mutex.pool, TABLE_NAME)
+            .await
+            .expect("in test");
+        let last_events_by_name = load_last_events_by_key(
+            &pool_mutex.pool,
+            TABLE_NAME,
+            "{first_name}",
+            chrono::Utc::now() - chrono::Duration::hours(1),
+        )
+        .await
+        .expect("in test");
+
+        // assert
+        assert_eq!(
+            last_event.expect("in test").json,
+            json!({"first_name": "Ivan", "number": 2})
+        );
+
+        let jsons = last_events_by_name
+            .into_iter()
+            .map(|x| x.json)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            jsons,
+            vec![
+                json!({"first_name": "Ivan", "number": 2}),
+                json!({"first_name": "Petr", "number": 1})
+            ]
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn load_last_events_before_time() {
+        let pool_mutex = init_test().await;
+
+        // arrange
+        let now = chrono::Utc::now();
+        let minutes_ago = |minutes| now - chrono::Duration::minutes(minutes);
+        // events are inserted not in order of their init time
+        let items = [
+            ("Ivan", 1, minutes_ago(2)),
+            ("Ivan", 2, minutes_ago(5)),
+            ("Ivan", 3, minutes_ago(-1)),
+            ("Petr", 1, minutes_ago(3)),
+            ("Sidr", 1, minutes_ago(20)),
+        ]
+        .map(|(first_name, number, init_time)| InsertEvent {
+            version: 1,
+            json: json!({
+                "first_name": first_name,
+                "number": number,
+                "init_time": init_time,
+            }),
+        });
+        save_events_batch(&pool_mutex.pool, TABLE_NAME, &items)
+            .await
+            .expect("in test");
+
+        // act
+        let last_events_by_name = load_last_events_by_key_before(
+            &pool_mutex.pool,
+            TABLE_NAME,
+            "{first_name}",
+            "{init_time}",
+            minutes_ago(10),
+            now,
+        )
+        .await
+        .expect("in test");
+
+        // assert
+        let numbers = last_events_by_name
+            .into_iter()
+            .map(|x| (x.json["first_name"].clone(), x.json["number"].clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            numbers,
+            vec![(json!("Ivan"), json!(1)), (json!("Petr"), json!(1))]
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn load_events_in_period() {
+        let pool_mutex = init_test().await;
+
+        // arrange
+        let items = [1, 2].map(|number| InsertEvent {
+            version: 1,
+            json: json!({ "number": number }),
+        });
+        save_events_batch(&pool_mutex.pool, TABLE_NAME, &items)
+            .await
+            .expect("in test");
+        let now = chrono::Utc::now();
+
+        // act
+        let events_in_period = load_events_by_period(
+            &pool_mutex.pool,
+            TABLE_NAME,
+            now - chrono::Duration::minutes(1),
+            now + chrono::Duration::minutes(1),
+        )
+        .await
+        .expect("in test");
+        let events_in_future = load_events_by_period(
+            &pool_mutex.pool,
+            TABLE_NAME,
+            now + chrono::Duration::minutes(1),
+            now + chrono::Duration::minutes(2),
+        )
+        .await
+        .expect("in test");
+
+        // assert
+        let jsons = events_in_period
+            .into_iter()
+            .map(|x| x.json)
+            .collect::<Vec<_>>();
+        assert_eq!(jsons, vec![json!({ "number": 1 }), json!({ "number": 2 })]);
+        assert!(events_in_future.is_empty());
+    }
 }
@@ -9,3 +9,6 @@ CREATE TABLE TABLE_NAME(
 CREATE INDEX IF NOT EXISTS TABLE_NAME_insert_time_idx ON TABLE_NAME USING btree (insert_time);
 CREATE INDEX IF NOT EXISTS TABLE_NAME_exchange_id_idx ON TABLE_NAME USING btree (((json ->> 'exchange_id')::text));
 CREATE INDEX IF NOT EXISTS TABLE_NAME_currency_pair_idx ON TABLE_NAME USING btree (((json ->> 'currency_pair')::text));
+
+CREATE OR REPLACE FUNCTION json_timestamptz(value text) RETURNS timestamptz AS $$ SELECT value::timestamptz $$ LANGUAGE sql IMMUTABLE;
+CREATE INDEX IF NOT EXISTS TABLE_NAME_first_name_init_time_idx ON TABLE_NAME USING btree (((json #>> '{first_name}')::text), json_timestamptz(json #>> '{init_time}'));
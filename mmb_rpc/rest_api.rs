@@ -1,4 +1,4 @@
-use jsonrpc_core::{Error, Result};
+use jsonrpc_core::{BoxFuture, Error, Result};
 use jsonrpc_derive::rpc;
 
 #[cfg(unix)]
@@ -22,12 +22,68 @@ pub trait MmbRpc {
 
     #[rpc(name = "stats")]
     fn stats(&self) -> Result<String>;
+
+    /// Block trading on exchange account until `resume` is called
+    #[rpc(name = "pause")]
+    fn pause(&self, exchange_account_id: String) -> Result<String>;
+
+    #[rpc(name = "resume")]
+    fn resume(&self, exchange_account_id: String) -> Result<String>;
+
+    /// Active exchange blockers in JSON format
+    #[rpc(name = "blockers")]
+    fn blockers(&self) -> Result<String>;
+
+    /// Pause trading on all exchange accounts, cancel all open orders and close all positions
+    #[rpc(name = "kill_switch")]
+    fn kill_switch(&self) -> Result<String>;
+
+    /// Not finished orders of all exchange accounts with fills and status history in JSON format
+    #[rpc(name = "orders")]
+    fn orders(&self) -> Result<String>;
+
+    /// Balances with reservations and virtual diffs in JSON format
+    #[rpc(name = "balances")]
+    fn balances(&self) -> Result<String>;
+
+    /// Derivative positions received from exchanges with the last balances update in JSON format
+    #[rpc(name = "positions")]
+    fn positions(&self) -> Result<String>;
+
+    /// Top of order book per market in JSON format
+    #[rpc(name = "order_book_top")]
+    fn order_book_top(&self) -> Result<String>;
+
+    #[rpc(name = "cancel_order")]
+    fn cancel_order(
+        &self,
+        exchange_account_id: String,
+        client_order_id: String,
+    ) -> BoxFuture<Result<String>>;
+
+    /// Connect exchange account described by `ExchangeSettings` in toml format with credentials inline.
+    /// The account isn't saved to config
+    #[rpc(name = "add_exchange_account")]
+    fn add_exchange_account(&self, settings: String) -> BoxFuture<Result<String>>;
+
+    /// Cancel orders of exchange account, wait until they are finished and disconnect the account.
+    /// Strategy instances trading on the account should be removed from config before
+    #[rpc(name = "remove_exchange_account")]
+    fn remove_exchange_account(&self, exchange_account_id: String) -> BoxFuture<Result<String>>;
 }
 
 pub enum ErrorCode {
     StopperIsNone = 1,
     UnableToSendSignal = 2,
     FailedToSaveNewConfig = 3,
+    UnknownExchangeAccount = 4,
+    FailedToSerializeResponse = 5,
+    UnknownOrder = 6,
+    FailedToCancelOrder = 7,
+    FailedToApplyNewConfig = 8,
+    FailedToAddExchangeAccount = 9,
+    FailedToRemoveExchangeAccount = 10,
+    PositionsAreNotReceived = 11,
 }
 
 pub fn server_side_error(code: ErrorCode) -> Error {
@@ -35,6 +91,14 @@ pub fn server_side_error(code: ErrorCode) -> Error {
         ErrorCode::StopperIsNone => "Server stopper is none",
         ErrorCode::UnableToSendSignal => "Unable to send signal",
         ErrorCode::FailedToSaveNewConfig => "Failed to save new config",
+        ErrorCode::UnknownExchangeAccount => "Unknown exchange account",
+        ErrorCode::FailedToSerializeResponse => "Failed to serialize response",
+        ErrorCode::UnknownOrder => "Unknown order",
+        ErrorCode::FailedToCancelOrder => "Failed to cancel order",
+        ErrorCode::FailedToApplyNewConfig => "Failed to apply new config",
+        ErrorCode::FailedToAddExchangeAccount => "Failed to add exchange account",
+        ErrorCode::FailedToRemoveExchangeAccount => "Failed to remove exchange account",
+        ErrorCode::PositionsAreNotReceived => "Positions aren't received from exchange yet",
     };
     log::error!("Rest API error: {}", reason);
     Error::new(jsonrpc_core::ErrorCode::ServerError(code as i64))